@@ -1,14 +1,21 @@
 use anchor_client::{Client, Cluster};
 use anyhow::Result;
-use gamma::states::USER_POOL_LIQUIDITY_SEED;
+use gamma::states::{
+    RewardInfo, LP_PRICE_HISTORY_SEED, MINT_ENTRY_SEED, MINT_REGISTRY_SEED, POOL_STATISTICS_SEED,
+    ROLES_SEED, USER_POOL_LIQUIDITY_SEED,
+};
 use solana_sdk::signer::Signer;
-use solana_sdk::{instruction::Instruction, pubkey::Pubkey, system_program, sysvar};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
 
 use gamma::accounts as gamma_accounts;
 use gamma::instruction as gamma_instructions;
 use gamma::{
     states::{AMM_CONFIG_SEED, OBSERVATION_SEED, POOL_LP_MINT_SEED, POOL_SEED, POOL_VAULT_SEED},
-    AUTH_SEED,
+    AUTH_SEED, REWARD_VAULT_SEED,
 };
 use std::rc::Rc;
 
@@ -40,6 +47,7 @@ pub fn create_config_instr(
         .accounts(gamma_accounts::CreateAmmConfig {
             owner: payer_pubkey,
             amm_config: amm_config_key,
+            roles: Pubkey::find_program_address(&[ROLES_SEED.as_bytes()], &program.id()).0,
             system_program: system_program::id(),
         })
         .args(gamma_instructions::CreateAmmConfig {
@@ -50,6 +58,8 @@ pub fn create_config_instr(
             create_pool_fee,
             // 5 days
             max_open_time: 5 * 86400,
+            protocol_owner: payer_pubkey,
+            fund_owner: payer_pubkey,
         })
         .instructions()?;
     Ok(instructions)
@@ -132,6 +142,26 @@ pub fn initialize_pool_instr(
     )
     .0;
 
+    // Mint registry accounts, only read when the config has a mint registry
+    let (mint_registry, __bump) = Pubkey::find_program_address(
+        &[
+            MINT_REGISTRY_SEED.as_bytes(),
+            amm_config_key.to_bytes().as_ref(),
+        ],
+        &program.id(),
+    );
+    let mint_entries = [token_0_mint, token_1_mint].map(|mint| {
+        Pubkey::find_program_address(
+            &[
+                MINT_ENTRY_SEED.as_bytes(),
+                amm_config_key.to_bytes().as_ref(),
+                mint.to_bytes().as_ref(),
+            ],
+            &program.id(),
+        )
+        .0
+    });
+
     let instructions = program
         .request()
         .accounts(gamma_accounts::Initialize {
@@ -159,6 +189,11 @@ pub fn initialize_pool_instr(
             system_program: system_program::id(),
             rent: sysvar::rent::id(),
         })
+        .accounts(vec![
+            AccountMeta::new_readonly(mint_registry, false),
+            AccountMeta::new_readonly(mint_entries[0], false),
+            AccountMeta::new_readonly(mint_entries[1], false),
+        ])
         .args(gamma_instructions::Initialize {
             init_amount_0,
             init_amount_1,
@@ -191,6 +226,7 @@ pub fn create_referral_project_instr(
             admin: signer,
             amm_config,
             project,
+            roles: Pubkey::find_program_address(&[ROLES_SEED.as_bytes()], &gamma::ID).0,
             system_program: system_program::ID,
             referral_program,
         },
@@ -310,7 +346,7 @@ pub fn withdraw_instr(
             // lp_mint: token_lp_mint,
             memo_program: spl_memo::id(),
             instruction_sysvar_account: sysvar::instructions::ID,
-            kamino_program: KAMINO_ID
+            kamino_program: KAMINO_ID,
         })
         .args(gamma_instructions::Withdraw {
             lp_token_amount,
@@ -321,6 +357,18 @@ pub fn withdraw_instr(
     Ok(instructions)
 }
 
+/// Remaining accounts of a swap without segmenter or referral, the statistics account of the pool
+/// is passed so the swap is recorded once it is created
+fn swap_remaining_accounts(program_id: Pubkey, pool_id: Pubkey) -> Vec<AccountMeta> {
+    let (pool_statistics, __bump) = Pubkey::find_program_address(
+        &[POOL_STATISTICS_SEED.as_bytes(), pool_id.to_bytes().as_ref()],
+        &program_id,
+    );
+    let mut remaining_accounts = vec![AccountMeta::new_readonly(program_id, false); 4];
+    remaining_accounts.push(AccountMeta::new(pool_statistics, false));
+    remaining_accounts
+}
+
 pub fn swap_base_input_instr(
     config: &ClientConfig,
     pool_id: Pubkey,
@@ -362,6 +410,7 @@ pub fn swap_base_input_instr(
             output_token_mint,
             observation_state: observation_account,
         })
+        .accounts(swap_remaining_accounts(program.id(), pool_id))
         .args(gamma_instructions::SwapBaseInput {
             amount_in,
             minimum_amount_out,
@@ -411,6 +460,7 @@ pub fn swap_base_output_instr(
             output_token_mint,
             observation_state: observation_account,
         })
+        .accounts(swap_remaining_accounts(program.id(), pool_id))
         .args(gamma_instructions::SwapBaseOutput {
             max_amount_in,
             amount_out,
@@ -446,11 +496,195 @@ pub fn init_user_pool_liquidity_instr(
             user: user_pubkey,
             pool_state: pool_id,
             user_pool_liquidity,
+            partner: None,
             system_program: system_program::id(),
         })
-        .args(gamma_instructions::InitUserPoolLiquidity {
-            partner: None,
+        .args(gamma_instructions::InitUserPoolLiquidity { partner: None })
+        .instructions()?;
+    Ok(instructions)
+}
+
+pub fn snapshot_lp_price_instr(config: &ClientConfig, pool_id: Pubkey) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.payer_path)?;
+    let payer_pubkey = payer.pubkey();
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.gamma_program)?;
+
+    let (lp_price_history, __bump) = Pubkey::find_program_address(
+        &[
+            LP_PRICE_HISTORY_SEED.as_bytes(),
+            pool_id.to_bytes().as_ref(),
+        ],
+        &program.id(),
+    );
+    let instructions = program
+        .request()
+        .accounts(gamma_accounts::SnapshotLpPrice {
+            payer: payer_pubkey,
+            pool_state: pool_id,
+            lp_price_history,
+            system_program: system_program::id(),
+        })
+        .args(gamma_instructions::SnapshotLpPrice {})
+        .instructions()?;
+    Ok(instructions)
+}
+
+pub fn init_pool_statistics_instr(
+    config: &ClientConfig,
+    pool_id: Pubkey,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.payer_path)?;
+    let payer_pubkey = payer.pubkey();
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.gamma_program)?;
+
+    let (pool_statistics, __bump) = Pubkey::find_program_address(
+        &[POOL_STATISTICS_SEED.as_bytes(), pool_id.to_bytes().as_ref()],
+        &program.id(),
+    );
+    let instructions = program
+        .request()
+        .accounts(gamma_accounts::InitPoolStatistics {
+            payer: payer_pubkey,
+            pool_state: pool_id,
+            pool_statistics,
+            system_program: system_program::id(),
+        })
+        .args(gamma_instructions::InitPoolStatistics {})
+        .instructions()?;
+    Ok(instructions)
+}
+
+pub fn resize_pool_state_instr(config: &ClientConfig, pool_id: Pubkey) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.payer_path)?;
+    let payer_pubkey = payer.pubkey();
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.gamma_program)?;
+
+    let instructions = program
+        .request()
+        .accounts(gamma_accounts::ResizePoolState {
+            payer: payer_pubkey,
+            pool_state: pool_id,
+            system_program: system_program::id(),
+        })
+        .args(gamma_instructions::ResizePoolState {})
+        .instructions()?;
+    Ok(instructions)
+}
+
+pub fn resize_user_pool_liquidity_instr(
+    config: &ClientConfig,
+    user_pool_liquidity: Pubkey,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.payer_path)?;
+    let payer_pubkey = payer.pubkey();
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.gamma_program)?;
+
+    let instructions = program
+        .request()
+        .accounts(gamma_accounts::ResizeUserPoolLiquidity {
+            payer: payer_pubkey,
+            user_pool_liquidity,
+            system_program: system_program::id(),
+        })
+        .args(gamma_instructions::ResizeUserPoolLiquidity {})
+        .instructions()?;
+    Ok(instructions)
+}
+
+/// Each RewardInfo of the pool followed by its vault, its mint and the token account and wallet of
+/// its creator, sorted by RewardInfo address. `reward_infos` pairs each RewardInfo with the token
+/// program of its mint
+fn close_pool_reward_accounts(
+    program_id: Pubkey,
+    mut reward_infos: Vec<(Pubkey, RewardInfo, Pubkey)>,
+) -> Vec<AccountMeta> {
+    reward_infos.sort_by_key(|(reward_info_key, _, _)| *reward_info_key);
+    reward_infos
+        .into_iter()
+        .flat_map(|(reward_info_key, reward_info, reward_token_program)| {
+            let (reward_vault, __bump) = Pubkey::find_program_address(
+                &[
+                    REWARD_VAULT_SEED.as_bytes(),
+                    reward_info_key.to_bytes().as_ref(),
+                ],
+                &program_id,
+            );
+            [
+                AccountMeta::new_readonly(reward_info_key, false),
+                AccountMeta::new(reward_vault, false),
+                AccountMeta::new(reward_info.mint, false),
+                AccountMeta::new(
+                    spl_associated_token_account::get_associated_token_address_with_program_id(
+                        &reward_info.rewarded_by,
+                        &reward_info.mint,
+                        &reward_token_program,
+                    ),
+                    false,
+                ),
+                AccountMeta::new(reward_info.rewarded_by, false),
+            ]
+        })
+        .collect()
+}
+
+pub fn close_pool_instr(
+    config: &ClientConfig,
+    pool_id: Pubkey,
+    amm_config: Pubkey,
+    observation_state: Pubkey,
+    token_0_vault: Pubkey,
+    token_1_vault: Pubkey,
+    token_0_mint: Pubkey,
+    token_1_mint: Pubkey,
+    protocol_owner_token_0: Pubkey,
+    protocol_owner_token_1: Pubkey,
+    rent_receiver: Pubkey,
+    lp_mint: Option<Pubkey>,
+    reward_infos: Vec<(Pubkey, RewardInfo, Pubkey)>,
+) -> Result<Vec<Instruction>> {
+    let payer = read_keypair_file(&config.payer_path)?;
+    let url = Cluster::Custom(config.http_url.clone(), config.ws_url.clone());
+    // Client.
+    let client = Client::new(url, Rc::new(payer));
+    let program = client.program(config.gamma_program)?;
+
+    let (authority, __bump) = Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &program.id());
+    let instructions = program
+        .request()
+        .accounts(gamma_accounts::ClosePool {
+            owner: program.payer(),
+            authority,
+            pool_state: pool_id,
+            amm_config,
+            observation_state,
+            token_0_vault,
+            token_1_vault,
+            vault_0_mint: token_0_mint,
+            vault_1_mint: token_1_mint,
+            protocol_owner_token_0,
+            protocol_owner_token_1,
+            rent_receiver,
+            lp_mint,
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+            roles: Pubkey::find_program_address(&[ROLES_SEED.as_bytes()], &program.id()).0,
         })
+        .accounts(close_pool_reward_accounts(program.id(), reward_infos))
+        .args(gamma_instructions::ClosePool {})
         .instructions()?;
     Ok(instructions)
 }
@@ -143,6 +143,63 @@ pub fn handle_program_log(
             LpChangeEvent::DISCRIMINATOR => {
                 println!("{:#?}", decode_event::<LpChangeEvent>(&mut slice)?);
             }
+            CreateAmmConfigEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<CreateAmmConfigEvent>(&mut slice)?);
+            }
+            CollectFeeEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<CollectFeeEvent>(&mut slice)?);
+            }
+            RoleTransferEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<RoleTransferEvent>(&mut slice)?);
+            }
+            ConfigUpdateEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<ConfigUpdateEvent>(&mut slice)?);
+            }
+            PoolUpdateEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<PoolUpdateEvent>(&mut slice)?);
+            }
+            QueuedUpdateEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<QueuedUpdateEvent>(&mut slice)?);
+            }
+            CapabilitiesUpdateEvent::DISCRIMINATOR => {
+                println!(
+                    "{:#?}",
+                    decode_event::<CapabilitiesUpdateEvent>(&mut slice)?
+                );
+            }
+            ClosePoolEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<ClosePoolEvent>(&mut slice)?);
+            }
+            RebindPoolConfigEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<RebindPoolConfigEvent>(&mut slice)?);
+            }
+            MintRegistryModeEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<MintRegistryModeEvent>(&mut slice)?);
+            }
+            MintEntryEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<MintEntryEvent>(&mut slice)?);
+            }
+            LpWrapEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<LpWrapEvent>(&mut slice)?);
+            }
+            LiquidityTransferEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<LiquidityTransferEvent>(&mut slice)?);
+            }
+            LpLockEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<LpLockEvent>(&mut slice)?);
+            }
+            PositionOperatorEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<PositionOperatorEvent>(&mut slice)?);
+            }
+            PartnerEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<PartnerEvent>(&mut slice)?);
+            }
+            PartnerFeesClaimEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<PartnerFeesClaimEvent>(&mut slice)?);
+            }
+            PositionPartnerEvent::DISCRIMINATOR => {
+                println!("{:#?}", decode_event::<PositionPartnerEvent>(&mut slice)?);
+            }
             _ => {
                 println!("unknow event: {}", l);
             }
@@ -347,6 +404,18 @@ pub fn handle_program_instruction(
             }
             println!("{:#?}", UpdateAmmConfig::from(ix));
         }
+        instruction::ApplyAmmConfigUpdate::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::ApplyAmmConfigUpdate>(&mut ix_data).unwrap();
+            println!("{:#?}", ix.update);
+        }
+        instruction::ApplyPoolUpdate::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::ApplyPoolUpdate>(&mut ix_data).unwrap();
+            println!("{:#?}", ix.update);
+        }
+        instruction::QueueUpdate::DISCRIMINATOR => {
+            let ix = decode_instruction::<instruction::QueueUpdate>(&mut ix_data).unwrap();
+            println!("id: {}, {:#?}", ix.id, ix.action);
+        }
         instruction::Initialize::DISCRIMINATOR => {
             let ix = decode_instruction::<instruction::Initialize>(&mut ix_data).unwrap();
             #[derive(Debug)]
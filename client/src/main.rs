@@ -4,10 +4,16 @@ use anyhow::{format_err, Result};
 use arrayref::array_ref;
 use clap::{Parser, Subcommand};
 use dotenv::dotenv;
-use solana_client::{rpc_client::RpcClient, rpc_config::RpcTransactionConfig};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
+    instruction::Instruction,
     signature::{Keypair, Signature, Signer},
     transaction::Transaction,
 };
@@ -87,6 +93,57 @@ fn read_keypair_file(s: &str) -> Result<Keypair> {
     solana_sdk::signature::read_keypair_file(s)
         .map_err(|_| format_err!("failed to read keypair from {}", s))
 }
+
+/// Resizes the user's position in the same transaction when it still has an older layout,
+/// so deposits and withdrawals don't wait for `resize-accounts`
+fn resize_outdated_position_instr(
+    rpc_client: &RpcClient,
+    config: &ClientConfig,
+    pool_id: Pubkey,
+    user: Pubkey,
+) -> Result<Vec<Instruction>> {
+    let (user_pool_liquidity, __bump) = Pubkey::find_program_address(
+        &[
+            gamma::states::USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_id.to_bytes().as_ref(),
+            user.to_bytes().as_ref(),
+        ],
+        &config.gamma_program,
+    );
+    match rpc_client.get_account(&user_pool_liquidity) {
+        Ok(account) if account.data.len() < gamma::states::UserPoolLiquidity::LEN => {
+            resize_user_pool_liquidity_instr(config, user_pool_liquidity)
+        }
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// Loads a pool, an older layout reads the fields it doesn't have yet as zero,
+/// the same values `resize_pool_state` gives them
+fn load_pool_state(rpc_client: &RpcClient, pool_id: Pubkey) -> Result<gamma::states::PoolState> {
+    let mut data = rpc_client.get_account_data(&pool_id)?;
+    if data.len() < gamma::states::PoolState::LEN {
+        data.resize(gamma::states::PoolState::LEN, 0);
+    }
+    let pool_state: gamma::states::PoolState =
+        anchor_lang::AccountDeserialize::try_deserialize(&mut data.as_slice())?;
+    Ok(pool_state)
+}
+
+/// Resizes the pool in the same transaction when it still has an older layout,
+/// so swaps, deposits and withdrawals don't wait for `resize-accounts`
+fn resize_outdated_pool_instr(
+    rpc_client: &RpcClient,
+    config: &ClientConfig,
+    pool_id: Pubkey,
+) -> Result<Vec<Instruction>> {
+    match rpc_client.get_account(&pool_id) {
+        Ok(account) if account.data.len() < gamma::states::PoolState::LEN => {
+            resize_pool_state_instr(config, pool_id)
+        }
+        _ => Ok(Vec::new()),
+    }
+}
 #[derive(Parser, Debug)]
 #[clap(name = "gamma-cli")]
 pub struct Opts {
@@ -147,6 +204,10 @@ pub enum GammaCommands {
     InitUserPoolLiquidity {
         pool_id: Pubkey,
     },
+    UserPoolLiquidity {
+        pool_id: Pubkey,
+        user: Option<Pubkey>,
+    },
     Deposit {
         pool_id: Pubkey,
         lp_token_amount: u64,
@@ -174,6 +235,24 @@ pub enum GammaCommands {
     DecodeTxLog {
         tx_id: String,
     },
+    SnapshotLpPrice {
+        pool_id: Pubkey,
+    },
+    LpPriceHistory {
+        pool_id: Pubkey,
+    },
+    InitPoolStatistics {
+        pool_id: Pubkey,
+    },
+    PoolStatistics {
+        pool_id: Pubkey,
+    },
+    ClosePool {
+        pool_id: Pubkey,
+    },
+    /// Grow every PoolState and UserPoolLiquidity created with an older layout, to be run right
+    /// after upgrading the program since those accounts can't be used until resized
+    ResizeAccounts,
     TestSwaps {
         user_keypair: String,
     },
@@ -308,11 +387,78 @@ fn main() -> Result<()> {
             let signature = send_txn(&rpc_client, &txn, true)?;
             println!("{}", signature);
         }
+        GammaCommands::UserPoolLiquidity { pool_id, user } => {
+            let pool_state: gamma::states::PoolState = program.account(pool_id)?;
+            let (user_pool_liquidity_key, __bump) = Pubkey::find_program_address(
+                &[
+                    gamma::states::USER_POOL_LIQUIDITY_SEED.as_bytes(),
+                    pool_id.to_bytes().as_ref(),
+                    user.unwrap_or(payer.pubkey()).to_bytes().as_ref(),
+                ],
+                &pool_config.gamma_program,
+            );
+            let user_pool_liquidity: gamma::states::UserPoolLiquidity =
+                program.account(user_pool_liquidity_key)?;
+            let (fees_earned_token_0, fees_earned_token_1) =
+                user_pool_liquidity.fees_earned(&pool_state)?;
+            let (total_token_0_amount, total_token_1_amount) =
+                pool_state.vault_amount_without_fee()?;
+            let position = gamma::curve::CurveCalculator::lp_tokens_to_trading_tokens(
+                user_pool_liquidity.lp_tokens_owned,
+                u128::from(pool_state.lp_supply),
+                u128::from(total_token_0_amount),
+                u128::from(total_token_1_amount),
+                gamma::curve::RoundDirection::Floor,
+            )
+            .ok_or(gamma::error::GammaError::ZeroTradingTokens)
+            .unwrap();
+            println!(
+                "lp_tokens_owned:{}, lp_tokens_locked:{}, token_0_amount:{}, token_1_amount:{}",
+                user_pool_liquidity.lp_tokens_owned,
+                user_pool_liquidity.lp_tokens_locked,
+                position.token_0_amount,
+                position.token_1_amount
+            );
+            println!(
+                "token_0_deposited:{}, token_1_deposited:{}, token_0_withdrawn:{}, token_1_withdrawn:{}",
+                user_pool_liquidity.token_0_deposited,
+                user_pool_liquidity.token_1_deposited,
+                user_pool_liquidity.token_0_withdrawn,
+                user_pool_liquidity.token_1_withdrawn
+            );
+            println!(
+                "fees_earned_token_0:{}, fees_earned_token_1:{}",
+                fees_earned_token_0, fees_earned_token_1
+            );
+            // Valued in token_1 at the current price of the pool. Holding the net deposits
+            // instead of providing liquidity is the reference, the difference is the
+            // earned fees minus the impermanent loss.
+            let price = total_token_1_amount as f64 / total_token_0_amount as f64;
+            let value =
+                |token_0_amount: f64, token_1_amount: f64| token_0_amount * price + token_1_amount;
+            let position_value = value(
+                position.token_0_amount as f64,
+                position.token_1_amount as f64,
+            );
+            let hold_value = value(
+                user_pool_liquidity.token_0_deposited as f64
+                    - user_pool_liquidity.token_0_withdrawn as f64,
+                user_pool_liquidity.token_1_deposited as f64
+                    - user_pool_liquidity.token_1_withdrawn as f64,
+            );
+            let fees_value = value(fees_earned_token_0 as f64, fees_earned_token_1 as f64);
+            println!(
+                "pnl_vs_hold_in_token_1:{}, fees_in_token_1:{}, impermanent_loss_in_token_1:{}",
+                position_value - hold_value,
+                fees_value,
+                position_value - hold_value - fees_value
+            );
+        }
         GammaCommands::Deposit {
             pool_id,
             lp_token_amount,
         } => {
-            let pool_state: gamma::states::PoolState = program.account(pool_id)?;
+            let pool_state: gamma::states::PoolState = load_pool_state(&rpc_client, pool_id)?;
             // load account
             let load_pubkeys = vec![pool_state.token_0_vault, pool_state.token_1_vault];
             let rsps = rpc_client.get_multiple_accounts(&load_pubkeys)?;
@@ -369,6 +515,11 @@ fn main() -> Result<()> {
                 amount_0_max, amount_1_max
             );
             let mut instructions = Vec::new();
+            instructions.extend(resize_outdated_pool_instr(
+                &rpc_client,
+                &pool_config,
+                pool_id,
+            )?);
             // let create_user_lp_token_instr = create_ata_token_account_instr(
             //     &pool_config,
             //     spl_token::id(),
@@ -384,6 +535,12 @@ fn main() -> Result<()> {
                 &payer.pubkey(),
                 &pool_state.token_1_mint,
             );
+            instructions.extend(resize_outdated_position_instr(
+                &rpc_client,
+                &pool_config,
+                pool_id,
+                payer.pubkey(),
+            )?);
             let deposit_instr = deposit_instr(
                 &pool_config,
                 pool_id,
@@ -418,7 +575,7 @@ fn main() -> Result<()> {
             pool_id,
             lp_token_amount,
         } => {
-            let pool_state: gamma::states::PoolState = program.account(pool_id)?;
+            let pool_state: gamma::states::PoolState = load_pool_state(&rpc_client, pool_id)?;
             // load account
             let load_pubkeys = vec![pool_state.token_0_vault, pool_state.token_1_vault];
             let rsps = rpc_client.get_multiple_accounts(&load_pubkeys)?;
@@ -476,6 +633,11 @@ fn main() -> Result<()> {
                 amount_0_min, amount_1_min
             );
             let mut instructions = Vec::new();
+            instructions.extend(resize_outdated_pool_instr(
+                &rpc_client,
+                &pool_config,
+                pool_id,
+            )?);
             let create_user_token_0_instr = create_ata_token_account_instr(
                 &pool_config,
                 spl_token::id(),
@@ -490,6 +652,12 @@ fn main() -> Result<()> {
                 &payer.pubkey(),
             )?;
             instructions.extend(create_user_token_1_instr);
+            instructions.extend(resize_outdated_position_instr(
+                &rpc_client,
+                &pool_config,
+                pool_id,
+                payer.pubkey(),
+            )?);
             let withdraw_instr = withdraw_instr(
                 &pool_config,
                 pool_id,
@@ -527,7 +695,7 @@ fn main() -> Result<()> {
             user_input_token,
             user_input_amount,
         } => {
-            let pool_state: gamma::states::PoolState = program.account(pool_id)?;
+            let pool_state: gamma::states::PoolState = load_pool_state(&rpc_client, pool_id)?;
             // load account
             let load_pubkeys = vec![
                 pool_state.amm_config,
@@ -650,6 +818,11 @@ fn main() -> Result<()> {
                 amount_with_slippage(amount_received, pool_config.slippage, false);
 
             let mut instructions = Vec::new();
+            instructions.extend(resize_outdated_pool_instr(
+                &rpc_client,
+                &pool_config,
+                pool_id,
+            )?);
             let create_user_output_token_instr = create_ata_token_account_instr(
                 &pool_config,
                 spl_token::id(),
@@ -690,7 +863,7 @@ fn main() -> Result<()> {
             user_input_token,
             amount_out_less_fee,
         } => {
-            let pool_state: gamma::states::PoolState = program.account(pool_id)?;
+            let pool_state: gamma::states::PoolState = load_pool_state(&rpc_client, pool_id)?;
             // load account
             let load_pubkeys = vec![
                 pool_state.amm_config,
@@ -813,6 +986,11 @@ fn main() -> Result<()> {
             let max_amount_in =
                 amount_with_slippage(input_transfer_amount, pool_config.slippage, true);
             let mut instructions = Vec::new();
+            instructions.extend(resize_outdated_pool_instr(
+                &rpc_client,
+                &pool_config,
+                pool_id,
+            )?);
             let create_user_output_token_instr = create_ata_token_account_instr(
                 &pool_config,
                 spl_token::id(),
@@ -882,6 +1060,248 @@ fn main() -> Result<()> {
             // decode logs
             parse_program_event(&pool_config.gamma_program.to_string(), meta.clone())?;
         }
+        GammaCommands::SnapshotLpPrice { pool_id } => {
+            let snapshot_lp_price_instr = snapshot_lp_price_instr(&pool_config, pool_id)?;
+            let signers = vec![&payer];
+            let recent_hash = rpc_client.get_latest_blockhash()?;
+            let txn = Transaction::new_signed_with_payer(
+                &snapshot_lp_price_instr,
+                Some(&payer.pubkey()),
+                &signers,
+                recent_hash,
+            );
+            let signature = send_txn(&rpc_client, &txn, true)?;
+            println!("{}", signature);
+        }
+        GammaCommands::LpPriceHistory { pool_id } => {
+            let (lp_price_history_key, __bump) = Pubkey::find_program_address(
+                &[
+                    gamma::states::LP_PRICE_HISTORY_SEED.as_bytes(),
+                    pool_id.to_bytes().as_ref(),
+                ],
+                &pool_config.gamma_program,
+            );
+            let lp_price_history: gamma::states::LpPriceHistory =
+                program.account(lp_price_history_key)?;
+            let snapshots = lp_price_history.ordered_snapshots();
+            let q32 = gamma::states::Q32 as f64;
+            for snapshot in snapshots.iter() {
+                println!(
+                    "epoch:{}, block_timestamp:{}, token_0_per_lp:{}, token_1_per_lp:{}",
+                    { snapshot.epoch },
+                    { snapshot.block_timestamp },
+                    snapshot.token_0_per_lp_x32 as f64 / q32,
+                    snapshot.token_1_per_lp_x32 as f64 / q32,
+                );
+            }
+            // sqrt(token_0_per_lp * token_1_per_lp) only grows with fees and kamino profits,
+            // independently of the price of the pool.
+            if let (Some(oldest), Some(newest)) = (snapshots.first(), snapshots.last()) {
+                let elapsed = newest.block_timestamp.saturating_sub(oldest.block_timestamp);
+                if elapsed > 0 {
+                    let oldest_value = (oldest.token_0_per_lp_x32 as f64 / q32
+                        * (oldest.token_1_per_lp_x32 as f64 / q32))
+                        .sqrt();
+                    let newest_value = (newest.token_0_per_lp_x32 as f64 / q32
+                        * (newest.token_1_per_lp_x32 as f64 / q32))
+                        .sqrt();
+                    let apy = (newest_value / oldest_value)
+                        .powf(365.0 * 86400.0 / elapsed as f64)
+                        - 1.0;
+                    println!("apy:{:.4}%", apy * 100.0);
+                }
+            }
+        }
+        GammaCommands::InitPoolStatistics { pool_id } => {
+            let init_pool_statistics_instr = init_pool_statistics_instr(&pool_config, pool_id)?;
+            let signers = vec![&payer];
+            let recent_hash = rpc_client.get_latest_blockhash()?;
+            let txn = Transaction::new_signed_with_payer(
+                &init_pool_statistics_instr,
+                Some(&payer.pubkey()),
+                &signers,
+                recent_hash,
+            );
+            let signature = send_txn(&rpc_client, &txn, true)?;
+            println!("{}", signature);
+        }
+        GammaCommands::ClosePool { pool_id } => {
+            let pool_state: gamma::states::PoolState = program.account(pool_id)?;
+            // The RewardInfo accounts start with the pool they reward
+            let discriminator =
+                <gamma::states::RewardInfo as anchor_lang::Discriminator>::DISCRIMINATOR;
+            let reward_infos = rpc_client
+                .get_program_accounts_with_config(
+                    &pool_config.gamma_program,
+                    RpcProgramAccountsConfig {
+                        filters: Some(vec![
+                            RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, discriminator.to_vec())),
+                            RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                                8,
+                                pool_id.to_bytes().to_vec(),
+                            )),
+                        ]),
+                        account_config: RpcAccountInfoConfig {
+                            encoding: Some(UiAccountEncoding::Base64),
+                            ..RpcAccountInfoConfig::default()
+                        },
+                        ..RpcProgramAccountsConfig::default()
+                    },
+                )?
+                .into_iter()
+                .map(|(key, account)| {
+                    let reward_info: gamma::states::RewardInfo =
+                        anchor_lang::AccountDeserialize::try_deserialize(
+                            &mut account.data.as_ref(),
+                        )?;
+                    let reward_token_program = rpc_client.get_account(&reward_info.mint)?.owner;
+                    Ok((key, reward_info, reward_token_program))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let amm_config: gamma::states::AmmConfig = program.account(pool_state.amm_config)?;
+            let protocol_owner_token_0 =
+                spl_associated_token_account::get_associated_token_address_with_program_id(
+                    &amm_config.protocol_owner,
+                    &pool_state.token_0_mint,
+                    &pool_state.token_0_program,
+                );
+            let protocol_owner_token_1 =
+                spl_associated_token_account::get_associated_token_address_with_program_id(
+                    &amm_config.protocol_owner,
+                    &pool_state.token_1_mint,
+                    &pool_state.token_1_program,
+                );
+            let mut instructions = Vec::new();
+            instructions.extend(create_ata_token_account_instr(
+                &pool_config,
+                pool_state.token_0_program,
+                &pool_state.token_0_mint,
+                &amm_config.protocol_owner,
+            )?);
+            instructions.extend(create_ata_token_account_instr(
+                &pool_config,
+                pool_state.token_1_program,
+                &pool_state.token_1_mint,
+                &amm_config.protocol_owner,
+            )?);
+            // What is left in the reward vaults goes back to the reward creators
+            for (_, reward_info, reward_token_program) in reward_infos.iter() {
+                instructions.extend(create_ata_token_account_instr(
+                    &pool_config,
+                    *reward_token_program,
+                    &reward_info.mint,
+                    &reward_info.rewarded_by,
+                )?);
+            }
+            instructions.extend(close_pool_instr(
+                &pool_config,
+                pool_id,
+                pool_state.amm_config,
+                pool_state.observation_key,
+                pool_state.token_0_vault,
+                pool_state.token_1_vault,
+                pool_state.token_0_mint,
+                pool_state.token_1_mint,
+                protocol_owner_token_0,
+                protocol_owner_token_1,
+                pool_state.pool_creator,
+                (pool_state.lp_mint_enabled == 1).then_some(pool_state.lp_mint),
+                reward_infos,
+            )?);
+            let signers = vec![&payer];
+            let recent_hash = rpc_client.get_latest_blockhash()?;
+            let txn = Transaction::new_signed_with_payer(
+                &instructions,
+                Some(&payer.pubkey()),
+                &signers,
+                recent_hash,
+            );
+            let signature = send_txn(&rpc_client, &txn, true)?;
+            println!("{}", signature);
+        }
+        GammaCommands::PoolStatistics { pool_id } => {
+            let (pool_statistics_key, __bump) = Pubkey::find_program_address(
+                &[
+                    gamma::states::POOL_STATISTICS_SEED.as_bytes(),
+                    pool_id.to_bytes().as_ref(),
+                ],
+                &pool_config.gamma_program,
+            );
+            let pool_statistics: gamma::states::PoolStatistics =
+                program.account(pool_statistics_key)?;
+            for bucket in pool_statistics.ordered_buckets() {
+                println!(
+                    "epoch:{}, start_timestamp:{}, swap_count:{}, volume_token_0:{}, volume_token_1:{}, protocol_fees_token_0:{}, protocol_fees_token_1:{}, fund_fees_token_0:{}, fund_fees_token_1:{}, lp_fees_token_0:{}, lp_fees_token_1:{}, max_dynamic_fee_rate:{}",
+                    { bucket.epoch },
+                    { bucket.start_timestamp },
+                    { bucket.swap_count },
+                    { bucket.volume_token_0 },
+                    { bucket.volume_token_1 },
+                    { bucket.protocol_fees_token_0 },
+                    { bucket.protocol_fees_token_1 },
+                    { bucket.fund_fees_token_0 },
+                    { bucket.fund_fees_token_1 },
+                    { bucket.lp_fees_token_0 },
+                    { bucket.lp_fees_token_1 },
+                    { bucket.max_dynamic_fee_rate },
+                );
+            }
+        }
+        GammaCommands::ResizeAccounts => {
+            let outdated_accounts = |discriminator: [u8; 8], len: usize| -> Result<Vec<Pubkey>> {
+                let accounts = rpc_client.get_program_accounts_with_config(
+                    &pool_config.gamma_program,
+                    RpcProgramAccountsConfig {
+                        filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                            0,
+                            discriminator.to_vec(),
+                        ))]),
+                        account_config: RpcAccountInfoConfig {
+                            encoding: Some(UiAccountEncoding::Base64),
+                            ..RpcAccountInfoConfig::default()
+                        },
+                        ..RpcProgramAccountsConfig::default()
+                    },
+                )?;
+                Ok(accounts
+                    .into_iter()
+                    .filter(|(_, account)| account.data.len() < len)
+                    .map(|(key, _)| key)
+                    .collect())
+            };
+            let pools = outdated_accounts(
+                <gamma::states::PoolState as anchor_lang::Discriminator>::DISCRIMINATOR,
+                gamma::states::PoolState::LEN,
+            )?;
+            let positions = outdated_accounts(
+                <gamma::states::UserPoolLiquidity as anchor_lang::Discriminator>::DISCRIMINATOR,
+                gamma::states::UserPoolLiquidity::LEN,
+            )?;
+            println!("pools:{}, positions:{}", pools.len(), positions.len());
+
+            let mut instructions = Vec::new();
+            for pool_id in pools {
+                instructions.extend(resize_pool_state_instr(&pool_config, pool_id)?);
+            }
+            for user_pool_liquidity in positions {
+                instructions.extend(resize_user_pool_liquidity_instr(
+                    &pool_config,
+                    user_pool_liquidity,
+                )?);
+            }
+            let signers = vec![&payer];
+            for chunk in instructions.chunks(8) {
+                let recent_hash = rpc_client.get_latest_blockhash()?;
+                let txn = Transaction::new_signed_with_payer(
+                    chunk,
+                    Some(&payer.pubkey()),
+                    &signers,
+                    recent_hash,
+                );
+                let signature = send_txn(&rpc_client, &txn, true)?;
+                println!("{}", signature);
+            }
+        }
         GammaCommands::TestSwaps { user_keypair } => {
             run_swap_test(&pool_config, user_keypair)?;
         }
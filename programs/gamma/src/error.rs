@@ -48,4 +48,47 @@ pub enum GammaError {
 
     #[msg("Invalid reward time")]
     InvalidRewardTime,
+
+    #[msg("Not enough oracle observations in the TWAP window")]
+    InsufficientObservations,
+    #[msg("No pending role transfer")]
+    NoPendingRoleTransfer,
+    #[msg("Update is timelocked and has to be queued")]
+    TimelockRequired,
+    #[msg("Queued update is not executable yet")]
+    TimelockNotElapsed,
+    #[msg("Roles account has no free member slot")]
+    RolesFull,
+    #[msg("Pool still holds liquidity, fees or kamino deposits")]
+    PoolNotEmpty,
+    #[msg("Mint is not allowed by the mint registry of the config")]
+    MintNotAllowed,
+    #[msg("Mints with a permanent delegate are not supported")]
+    PermanentDelegateNotSupported,
+    #[msg("Non-transferable mints are not supported")]
+    NonTransferableNotSupported,
+    #[msg("LP mint is not enabled for the pool")]
+    LpMintNotEnabled,
+    #[msg("LP tokens are locked")]
+    LpLocked,
+    #[msg("Invalid unlock schedule")]
+    InvalidUnlockSchedule,
+    #[msg("Signer is not an approved operator of the position")]
+    OperatorNotApproved,
+    #[msg("Position still holds LP tokens")]
+    PositionNotEmpty,
+    #[msg("Rewards of the pool are still running or unclaimed")]
+    UnclaimedRewards,
+    #[msg("PoolPartner account of the partner of the position is missing")]
+    PoolPartnerMissing,
+    #[msg("Remaining account 4 of the swap is not the pool statistics account of the pool or is missing")]
+    InvalidPoolStatistics,
+    #[msg("Pool is reduce-only, the swap moves the price away from the TWAP")]
+    ReduceOnlySwap,
+    #[msg("Vault is frozen, the freeze authority of the mint has to sign to thaw it")]
+    FrozenVault,
+    #[msg("A role transfer is already pending, cancel it first")]
+    RoleTransferPending,
+    #[msg("Rewards of the pool are not counted yet, they have to be migrated first")]
+    RewardInfoCountNotMigrated,
 }
@@ -218,7 +218,7 @@ impl DynamicFee {
     ///
     /// # Returns
     /// A tuple of (min_price, max_price, twap_price) observed within the window
-    fn get_price_range(
+    pub fn get_price_range(
         observation_state: &ObservationState,
         current_time: u64,
         window: u64,
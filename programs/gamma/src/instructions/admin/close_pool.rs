@@ -0,0 +1,378 @@
+use crate::error::GammaError;
+use crate::states::*;
+use crate::utils::*;
+use crate::{LOCK_LP_AMOUNT, REWARD_VAULT_SEED};
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::Mint;
+use anchor_spl::token_interface::Token2022;
+use anchor_spl::token_interface::TokenAccount;
+
+#[derive(Accounts)]
+pub struct ClosePool<'info> {
+    /// The pool creator or a key holding the config creator capability
+    pub owner: Signer<'info>,
+
+    /// CHECK: pool vault authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// CHECK: the pool to close, loaded in the handler. It is left as an empty account owned by the
+    /// program, so the pool address can't be initialized again and the accounts derived from it
+    /// can't attach to a new pool
+    #[account(mut, owner = crate::id())]
+    pub pool_state: UncheckedAccount<'info>,
+
+    /// Amm config account stores the protocol owner
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// The oracle observations of the pool
+    #[account(mut, close = rent_receiver)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// The address that holds pool tokens for token_0
+    #[account(mut)]
+    pub token_0_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_1
+    #[account(mut)]
+    pub token_1_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of token_0 vault, writable to harvest withheld transfer fees
+    #[account(
+        mut,
+        address = token_0_vault.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token_1 vault, writable to harvest withheld transfer fees
+    #[account(
+        mut,
+        address = token_1_vault.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The protocol owner token_0 account receiving the vault dust
+    #[account(
+        mut,
+        token::mint = vault_0_mint,
+        token::authority = amm_config.protocol_owner,
+    )]
+    pub protocol_owner_token_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The protocol owner token_1 account receiving the vault dust
+    #[account(
+        mut,
+        token::mint = vault_1_mint,
+        token::authority = amm_config.protocol_owner,
+    )]
+    pub protocol_owner_token_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: the pool creator, who paid for the accounts and gets their rent back
+    #[account(mut)]
+    pub rent_receiver: UncheckedAccount<'info>,
+
+    /// CHECK: the LP mint of the pool, required when it was created with `init_lp_mint`
+    #[account(mut)]
+    pub lp_mint: Option<UncheckedAccount<'info>>,
+
+    /// The SPL program to perform token transfers
+    pub token_program: Program<'info, Token>,
+
+    /// The SPL program 2022 to perform token transfers
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// CHECK: roles account granting capabilities, read by `Roles::load`. The hard-coded admin
+    /// keys are only trusted until it is created
+    #[account(seeds = [ROLES_SEED.as_bytes()], bump)]
+    pub roles: UncheckedAccount<'info>,
+}
+
+/// Remaining accounts: for each of the `reward_info_count` rewards of the pool sorted by RewardInfo
+/// address, `REWARD_ACCOUNTS_LEN` accounts: the RewardInfo, its vault, the reward mint, a token
+/// account and the wallet of the reward creator. Then the transfer hook accounts
+pub fn close_pool<'c, 'info>(ctx: Context<'_, '_, 'c, 'info, ClosePool<'info>>) -> Result<()>
+where
+    'c: 'info,
+{
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state_info = ctx.accounts.pool_state.to_account_info();
+    require_gte!(
+        pool_state_info.data_len(),
+        PoolState::LEN,
+        GammaError::InvalidInput
+    );
+    let pool_state = PoolState::try_deserialize(&mut &pool_state_info.try_borrow_data()?[..])?;
+    let owner = ctx.accounts.owner.key();
+    require!(
+        owner == pool_state.pool_creator
+            || Roles::is_granted(
+                Roles::load(&ctx.accounts.roles)?.as_ref(),
+                owner,
+                Capability::ConfigCreator
+            ),
+        GammaError::InvalidOwner
+    );
+    require_keys_eq!(ctx.accounts.amm_config.key(), pool_state.amm_config);
+    require_keys_eq!(
+        ctx.accounts.observation_state.key(),
+        pool_state.observation_key
+    );
+    require_keys_eq!(
+        ctx.accounts.token_0_vault.key(),
+        pool_state.token_0_vault,
+        GammaError::InvalidVault
+    );
+    require_keys_eq!(
+        ctx.accounts.token_1_vault.key(),
+        pool_state.token_1_vault,
+        GammaError::InvalidVault
+    );
+    require_keys_eq!(ctx.accounts.rent_receiver.key(), pool_state.pool_creator);
+    // Only the liquidity locked at initialization may remain, with every fee collected
+    // and nothing deployed to kamino
+    require!(
+        pool_state.lp_supply == LOCK_LP_AMOUNT
+            && pool_state.protocol_fees_token_0 == 0
+            && pool_state.protocol_fees_token_1 == 0
+            && pool_state.fund_fees_token_0 == 0
+            && pool_state.fund_fees_token_1 == 0
+            && pool_state.token_0_amount_in_kamino == 0
+            && pool_state.token_1_amount_in_kamino == 0,
+        GammaError::PoolNotEmpty
+    );
+
+    // The rewards of a pool created before they were counted are not all known
+    require_eq!(
+        pool_state.reward_info_count_migrated,
+        1,
+        GammaError::RewardInfoCountNotMigrated
+    );
+    let reward_accounts_len = usize::try_from(pool_state.reward_info_count)
+        .map_err(|_| GammaError::MathOverflow)?
+        .checked_mul(REWARD_ACCOUNTS_LEN)
+        .ok_or(GammaError::MathOverflow)?;
+    require_gte!(
+        ctx.remaining_accounts.len(),
+        reward_accounts_len,
+        GammaError::UnclaimedRewards
+    );
+    let (reward_accounts, remaining_accounts) =
+        ctx.remaining_accounts.split_at(reward_accounts_len);
+
+    let auth_bump = pool_state.auth_bump;
+    let signer_seeds: &[&[&[u8]]] = &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]];
+    sweep_reward_vaults(
+        pool_id,
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.token_program_2022.to_account_info(),
+        reward_accounts,
+        remaining_accounts,
+        signer_seeds,
+    )?;
+
+    let dust_token_0 = ctx.accounts.token_0_vault.amount;
+    let dust_token_1 = ctx.accounts.token_1_vault.amount;
+    let token_0_program =
+        if ctx.accounts.vault_0_mint.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        };
+    let token_1_program =
+        if ctx.accounts.vault_1_mint.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        };
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_0_vault.to_account_info(),
+        ctx.accounts.protocol_owner_token_0.to_account_info(),
+        ctx.accounts.vault_0_mint.to_account_info(),
+        token_0_program.clone(),
+        dust_token_0,
+        ctx.accounts.vault_0_mint.decimals,
+        remaining_accounts,
+        signer_seeds,
+    )?;
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_1_vault.to_account_info(),
+        ctx.accounts.protocol_owner_token_1.to_account_info(),
+        ctx.accounts.vault_1_mint.to_account_info(),
+        token_1_program.clone(),
+        dust_token_1,
+        ctx.accounts.vault_1_mint.decimals,
+        remaining_accounts,
+        signer_seeds,
+    )?;
+
+    harvest_withheld_fees(
+        token_0_program.clone(),
+        ctx.accounts.vault_0_mint.to_account_info(),
+        ctx.accounts.token_0_vault.to_account_info(),
+    )?;
+    harvest_withheld_fees(
+        token_1_program.clone(),
+        ctx.accounts.vault_1_mint.to_account_info(),
+        ctx.accounts.token_1_vault.to_account_info(),
+    )?;
+
+    close_token_account(
+        ctx.accounts.authority.to_account_info(),
+        token_0_program,
+        ctx.accounts.token_0_vault.to_account_info(),
+        ctx.accounts.rent_receiver.to_account_info(),
+        signer_seeds,
+    )?;
+    close_token_account(
+        ctx.accounts.authority.to_account_info(),
+        token_1_program,
+        ctx.accounts.token_1_vault.to_account_info(),
+        ctx.accounts.rent_receiver.to_account_info(),
+        signer_seeds,
+    )?;
+
+    // The LP supply is back to the locked liquidity, so no LP token is left in circulation
+    if pool_state.lp_mint_enabled == 1 {
+        let lp_mint = ctx
+            .accounts
+            .lp_mint
+            .as_ref()
+            .ok_or(GammaError::InvalidInput)?;
+        require_keys_eq!(lp_mint.key(), pool_state.lp_mint, GammaError::InvalidInput);
+        close_token_account(
+            ctx.accounts.authority.to_account_info(),
+            ctx.accounts.token_program_2022.to_account_info(),
+            lp_mint.to_account_info(),
+            ctx.accounts.rent_receiver.to_account_info(),
+            signer_seeds,
+        )?;
+    }
+
+    // Keep the pool address taken with the rent of an empty account
+    let released_lamports = pool_state_info
+        .lamports()
+        .checked_sub(Rent::get()?.minimum_balance(0))
+        .ok_or(GammaError::MathOverflow)?;
+    **pool_state_info.try_borrow_mut_lamports()? -= released_lamports;
+    **ctx.accounts.rent_receiver.try_borrow_mut_lamports()? += released_lamports;
+    pool_state_info.realloc(0, false)?;
+
+    emit!(ClosePoolEvent {
+        pool_id,
+        authority: ctx.accounts.owner.key(),
+        rent_receiver: ctx.accounts.rent_receiver.key(),
+        dust_token_0,
+        dust_token_1,
+    });
+
+    Ok(())
+}
+
+/// Accounts passed for each reward of the pool
+const REWARD_ACCOUNTS_LEN: usize = 5;
+
+/// Every reward of the pool must have ended. The vaults are never emptied by the claims: the
+/// rewards of the locked LP nobody owns, the rounding of the claims and the rewards missed by
+/// wrapped LP stay in them. Rewards can't be claimed once the pool is closed, so what is left goes
+/// back to the reward creator together with the rent of the vault
+fn sweep_reward_vaults<'info>(
+    pool_id: Pubkey,
+    authority: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    token_program_2022: AccountInfo<'info>,
+    reward_accounts: &'info [AccountInfo<'info>],
+    transfer_extra_accounts: &'info [AccountInfo<'info>],
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    let mut previous_reward_info = Pubkey::default();
+    for accounts in reward_accounts.chunks(REWARD_ACCOUNTS_LEN) {
+        let reward_info = Account::<RewardInfo>::try_from(&accounts[0])?;
+        require_keys_eq!(reward_info.pool, pool_id, GammaError::InvalidInput);
+        // Sorted so that each RewardInfo of the pool is counted once
+        require!(
+            reward_info.key() > previous_reward_info,
+            GammaError::InvalidInput
+        );
+        previous_reward_info = reward_info.key();
+        require_gte!(
+            current_time,
+            reward_info.end_rewards_at,
+            GammaError::UnclaimedRewards
+        );
+
+        let reward_vault = InterfaceAccount::<TokenAccount>::try_from(&accounts[1])?;
+        let (reward_vault_key, __bump) = Pubkey::find_program_address(
+            &[REWARD_VAULT_SEED.as_bytes(), reward_info.key().as_ref()],
+            &crate::id(),
+        );
+        require_keys_eq!(
+            reward_vault.key(),
+            reward_vault_key,
+            GammaError::InvalidVault
+        );
+        let reward_mint = InterfaceAccount::<Mint>::try_from(&accounts[2])?;
+        require_keys_eq!(
+            reward_mint.key(),
+            reward_info.mint,
+            GammaError::InvalidInput
+        );
+        let creator_token_account = InterfaceAccount::<TokenAccount>::try_from(&accounts[3])?;
+        require_keys_eq!(
+            creator_token_account.mint,
+            reward_info.mint,
+            GammaError::InvalidInput
+        );
+        require_keys_eq!(
+            creator_token_account.owner,
+            reward_info.rewarded_by,
+            GammaError::InvalidInput
+        );
+        let rewarded_by = &accounts[4];
+        require_keys_eq!(
+            rewarded_by.key(),
+            reward_info.rewarded_by,
+            GammaError::InvalidInput
+        );
+
+        let reward_token_program = if *accounts[2].owner == token_program.key() {
+            token_program.clone()
+        } else {
+            token_program_2022.clone()
+        };
+        transfer_from_pool_vault_to_user(
+            authority.clone(),
+            accounts[1].clone(),
+            accounts[3].clone(),
+            accounts[2].clone(),
+            reward_token_program.clone(),
+            reward_vault.amount,
+            reward_mint.decimals,
+            transfer_extra_accounts,
+            signer_seeds,
+        )?;
+        harvest_withheld_fees(
+            reward_token_program.clone(),
+            accounts[2].clone(),
+            accounts[1].clone(),
+        )?;
+        close_token_account(
+            authority.clone(),
+            reward_token_program,
+            accounts[1].clone(),
+            rewarded_by.clone(),
+            signer_seeds,
+        )?;
+    }
+    Ok(())
+}
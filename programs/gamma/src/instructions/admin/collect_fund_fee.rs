@@ -1,6 +1,6 @@
 use crate::{
     error::GammaError,
-    states::{AmmConfig, PoolState},
+    states::{AmmConfig, CollectFeeEvent, PoolState},
     utils::transfer_from_pool_vault_to_user,
 };
 use anchor_lang::prelude::*;
@@ -9,8 +9,7 @@ use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
 
 #[derive(Accounts)]
 pub struct CollectFundFee<'info> {
-    /// Only admin or fund_owner can collect fee now
-    #[account(constraint = (owner.key() == amm_config.fund_owner || owner.key() == crate::admin::id()) @ GammaError::InvalidOwner)]
+    /// The fund owner of the config or a key holding the fee collector capability
     pub owner: Signer<'info>,
 
     /// CHECK: pool vault authority
@@ -69,13 +68,28 @@ pub struct CollectFundFee<'info> {
 
     /// The SPL program 2022 to perform token transfers
     pub token_program_2022: Program<'info, Token2022>,
+
+    /// CHECK: roles account granting capabilities, read by `Roles::load`. The hard-coded admin
+    /// keys are only trusted until it is created
+    #[account(seeds = [ROLES_SEED.as_bytes()], bump)]
+    pub roles: UncheckedAccount<'info>,
 }
 
-pub fn collect_fund_fee(
-    ctx: Context<CollectFundFee>,
+pub fn collect_fund_fee<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, CollectFundFee<'info>>,
     amount_0_requested: u64,
     amount_1_requested: u64,
 ) -> Result<()> {
+    let owner = ctx.accounts.owner.key();
+    require!(
+        owner == ctx.accounts.amm_config.fund_owner
+            || Roles::is_granted(
+                Roles::load(&ctx.accounts.roles)?.as_ref(),
+                owner,
+                Capability::FeeCollector
+            ),
+        GammaError::InvalidOwner
+    );
     let amount_0: u64;
     let amount_1: u64;
     let auth_bump: u8;
@@ -94,6 +108,18 @@ pub fn collect_fund_fee(
             .ok_or(GammaError::MathOverflow)?;
         auth_bump = pool_state.auth_bump;
         pool_state.recent_epoch = Clock::get()?.epoch;
+
+        emit!(CollectFeeEvent {
+            pool_id: ctx.accounts.pool_state.key(),
+            authority: ctx.accounts.owner.key(),
+            recipient_token_0_account: ctx.accounts.recipient_token_0_account.key(),
+            recipient_token_1_account: ctx.accounts.recipient_token_1_account.key(),
+            amount_0,
+            amount_1,
+            remaining_fees_token_0: pool_state.fund_fees_token_0,
+            remaining_fees_token_1: pool_state.fund_fees_token_1,
+            fee_type: 1,
+        });
     }
     transfer_from_pool_vault_to_user(
         ctx.accounts.authority.to_account_info(),
@@ -107,6 +133,7 @@ pub fn collect_fund_fee(
         },
         amount_0,
         ctx.accounts.vault_0_mint.decimals,
+        ctx.remaining_accounts,
         &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
     )?;
 
@@ -122,6 +149,7 @@ pub fn collect_fund_fee(
         },
         amount_1,
         ctx.accounts.vault_1_mint.decimals,
+        ctx.remaining_accounts,
         &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
     )?;
 
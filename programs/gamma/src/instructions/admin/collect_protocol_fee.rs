@@ -9,8 +9,7 @@ use anchor_spl::token_interface::TokenAccount;
 
 #[derive(Accounts)]
 pub struct CollectProtocolFee<'info> {
-    /// Only admin or owner can collect fee now
-    #[account(constraint = (owner.key() == amm_config.protocol_owner || owner.key() == crate::admin::id()) @ GammaError::InvalidOwner)]
+    /// The protocol owner of the config or a key holding the fee collector capability
     pub owner: Signer<'info>,
 
     /// CHECK: pool vault authority
@@ -69,21 +68,38 @@ pub struct CollectProtocolFee<'info> {
 
     /// The SPL program 2022 to perform token transfers
     pub token_program_2022: Program<'info, Token2022>,
+
+    /// CHECK: roles account granting capabilities, read by `Roles::load`. The hard-coded admin
+    /// keys are only trusted until it is created
+    #[account(seeds = [ROLES_SEED.as_bytes()], bump)]
+    pub roles: UncheckedAccount<'info>,
 }
 
-pub fn collect_protocol_fee(
-    ctx: Context<CollectProtocolFee>,
+pub fn collect_protocol_fee<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, CollectProtocolFee<'info>>,
     amount_0_requested: u64,
     amount_1_requested: u64,
 ) -> Result<()> {
+    let owner = ctx.accounts.owner.key();
+    require!(
+        owner == ctx.accounts.amm_config.protocol_owner
+            || Roles::is_granted(
+                Roles::load(&ctx.accounts.roles)?.as_ref(),
+                owner,
+                Capability::FeeCollector
+            ),
+        GammaError::InvalidOwner
+    );
     let amount_0: u64;
     let amount_1: u64;
     let auth_bump: u8;
     {
         let mut pool_state = ctx.accounts.pool_state.load_mut()?;
 
-        amount_0 = amount_0_requested.min(pool_state.protocol_fees_token_0);
-        amount_1 = amount_1_requested.min(pool_state.protocol_fees_token_1);
+        // The fees set aside for the partners stay in the pool until `claim_partner_fees`
+        let (collectable_token_0, collectable_token_1) = pool_state.collectable_protocol_fees()?;
+        amount_0 = amount_0_requested.min(collectable_token_0);
+        amount_1 = amount_1_requested.min(collectable_token_1);
 
         pool_state.protocol_fees_token_0 = pool_state
             .protocol_fees_token_0
@@ -96,6 +112,18 @@ pub fn collect_protocol_fee(
 
         auth_bump = pool_state.auth_bump;
         pool_state.recent_epoch = Clock::get()?.epoch;
+
+        emit!(CollectFeeEvent {
+            pool_id: ctx.accounts.pool_state.key(),
+            authority: ctx.accounts.owner.key(),
+            recipient_token_0_account: ctx.accounts.recipient_token_0_account.key(),
+            recipient_token_1_account: ctx.accounts.recipient_token_1_account.key(),
+            amount_0,
+            amount_1,
+            remaining_fees_token_0: pool_state.protocol_fees_token_0,
+            remaining_fees_token_1: pool_state.protocol_fees_token_1,
+            fee_type: 0,
+        });
     }
     transfer_from_pool_vault_to_user(
         ctx.accounts.authority.to_account_info(),
@@ -109,6 +137,7 @@ pub fn collect_protocol_fee(
         },
         amount_0,
         ctx.accounts.vault_0_mint.decimals,
+        ctx.remaining_accounts,
         &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
     )?;
 
@@ -124,6 +153,7 @@ pub fn collect_protocol_fee(
         },
         amount_1,
         ctx.accounts.vault_1_mint.decimals,
+        ctx.remaining_accounts,
         &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
     )?;
 
@@ -2,18 +2,18 @@ use std::ops::DerefMut;
 
 use crate::{
     error::GammaError,
-    states::{validate_config_rates, AmmConfig, AMM_CONFIG_SEED},
+    states::{
+        validate_config_rates, AmmConfig, Capability, CreateAmmConfigEvent, Roles, AMM_CONFIG_SEED,
+        ROLES_SEED,
+    },
 };
 use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
 #[instruction(index: u16)]
 pub struct CreateAmmConfig<'info> {
-    /// Address to be set as protocol owner.
-    #[account(
-        mut,
-        address = crate::admin::id() @ GammaError::InvalidOwner
-    )]
+    /// The admin or a config creator
+    #[account(mut)]
     pub owner: Signer<'info>,
 
     /// Initialize AmmConfig state account to store protocol owner address and fee rates
@@ -29,6 +29,11 @@ pub struct CreateAmmConfig<'info> {
     )]
     pub amm_config: Account<'info, AmmConfig>,
 
+    /// CHECK: roles account granting capabilities, read by `Roles::load`. The hard-coded admin
+    /// keys are only trusted until it is created
+    #[account(seeds = [ROLES_SEED.as_bytes()], bump)]
+    pub roles: UncheckedAccount<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -40,7 +45,26 @@ pub fn create_amm_config(
     fund_fee_rate: u64,
     create_pool_fee: u64,
     max_open_time: u64,
+    protocol_owner: Pubkey,
+    fund_owner: Pubkey,
 ) -> Result<()> {
+    let roles = Roles::load(&ctx.accounts.roles)?;
+    let owner = ctx.accounts.owner.key();
+    require!(
+        Roles::is_granted(roles.as_ref(), owner, Capability::ConfigCreator),
+        GammaError::InvalidOwner
+    );
+    // A role admin hands the fees to any key, a config creator only to the fee collectors
+    if !Roles::is_granted(roles.as_ref(), owner, Capability::RoleAdmin) {
+        for fee_owner in [protocol_owner, fund_owner] {
+            require!(
+                Roles::is_granted(roles.as_ref(), fee_owner, Capability::FeeCollector),
+                GammaError::InvalidOwner
+            );
+        }
+    }
+    require_keys_neq!(protocol_owner, Pubkey::default(), GammaError::InvalidInput);
+    require_keys_neq!(fund_owner, Pubkey::default(), GammaError::InvalidInput);
     let amm_config = ctx.accounts.amm_config.deref_mut();
     amm_config.bump = ctx.bumps.amm_config;
     amm_config.disable_create_pool = false;
@@ -49,12 +73,25 @@ pub fn create_amm_config(
     amm_config.protocol_fee_rate = protocol_fee_rate;
     amm_config.fund_fee_rate = fund_fee_rate;
     amm_config.create_pool_fee = create_pool_fee;
-    amm_config.protocol_owner = ctx.accounts.owner.key();
-    amm_config.fund_owner = ctx.accounts.owner.key();
+    amm_config.protocol_owner = protocol_owner;
+    amm_config.fund_owner = fund_owner;
     amm_config.referral_project = Pubkey::default();
     amm_config.max_open_time = max_open_time;
 
     validate_config_rates(amm_config)?;
 
+    emit!(CreateAmmConfigEvent {
+        amm_config: ctx.accounts.amm_config.key(),
+        authority: ctx.accounts.owner.key(),
+        index,
+        trade_fee_rate,
+        protocol_fee_rate,
+        fund_fee_rate,
+        create_pool_fee,
+        max_open_time,
+        protocol_owner,
+        fund_owner,
+    });
+
     Ok(())
 }
@@ -1,6 +1,8 @@
 use crate::{
     error::GammaError,
-    states::{AmmConfig, AMM_CONFIG_SEED},
+    states::{
+        AmmConfig, Capability, ConfigUpdateEvent, ParamValue, Roles, AMM_CONFIG_SEED, ROLES_SEED,
+    },
 };
 use anchor_lang::prelude::*;
 use referral::cpi::accounts::InitializeProject;
@@ -11,8 +13,7 @@ use referral::InitializeProjectParams;
 #[derive(Accounts)]
 #[instruction(index: u16)]
 pub struct CreateReferralProject<'info> {
-    /// Admin signer for this operation
-    #[account(constraint = [crate::admin::ID, amm_config.secondary_admin].contains(&owner.key()) @ GammaError::InvalidOwner)]
+    /// A config creator, or the admin or the secondary admin until the roles account is created
     pub admin: Signer<'info>,
 
     /// CHECK: Address to be set as protocol owner
@@ -30,6 +31,11 @@ pub struct CreateReferralProject<'info> {
     #[account(mut)]
     pub project: UncheckedAccount<'info>,
 
+    /// CHECK: roles account granting capabilities, read by `Roles::load`. The hard-coded admin
+    /// keys are only trusted until it is created
+    #[account(seeds = [ROLES_SEED.as_bytes()], bump)]
+    pub roles: UncheckedAccount<'info>,
+
     pub system_program: Program<'info, System>,
     pub referral_program: Program<'info, Referral>,
 }
@@ -39,9 +45,26 @@ pub fn create_referral_project(
     name: String,
     default_share_bps: u16,
 ) -> Result<()> {
+    let admin = ctx.accounts.admin.key();
+    let roles = Roles::load(&ctx.accounts.roles)?;
+    // The secondary admin is only trusted until the roles account is created, as the admin
+    require!(
+        (roles.is_none() && admin == ctx.accounts.amm_config.secondary_admin)
+            || Roles::is_granted(roles.as_ref(), admin, Capability::ConfigCreator),
+        GammaError::InvalidOwner
+    );
     let config = &mut ctx.accounts.amm_config;
+    let old_referral_project = config.referral_project;
     config.referral_project = ctx.accounts.project.key();
 
+    emit!(ConfigUpdateEvent {
+        amm_config: config.key(),
+        authority: admin,
+        param: "referral_project".to_string(),
+        old_value: ParamValue::Pubkey(old_referral_project),
+        new_value: ParamValue::Pubkey(config.referral_project),
+    });
+
     let seeds = &[
         AMM_CONFIG_SEED.as_bytes(),
         &config.index.to_be_bytes(),
@@ -0,0 +1,67 @@
+use crate::error::GammaError;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct MigrateRewardInfoCount<'info> {
+    /// A key holding the rewards operator capability
+    pub authority: Signer<'info>,
+
+    /// The pool created before its rewards were counted
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// CHECK: roles account granting capabilities, read by `Roles::load`. The hard-coded admin
+    /// keys are only trusted until it is created
+    #[account(seeds = [ROLES_SEED.as_bytes()], bump)]
+    pub roles: UncheckedAccount<'info>,
+}
+
+/// Counts the rewards of a pool created before `reward_info_count` was tracked, it can only be
+/// done once. Remaining accounts: every RewardInfo of the pool sorted by address, including the
+/// ones created since the pool was resized.
+pub fn migrate_reward_info_count<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, MigrateRewardInfoCount<'info>>,
+) -> Result<()>
+where
+    'c: 'info,
+{
+    require!(
+        Roles::is_granted(
+            Roles::load(&ctx.accounts.roles)?.as_ref(),
+            ctx.accounts.authority.key(),
+            Capability::RewardsOperator
+        ),
+        GammaError::InvalidOwner
+    );
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    require_eq!(
+        pool_state.reward_info_count_migrated,
+        0,
+        GammaError::InvalidInput
+    );
+
+    let mut previous_reward_info = Pubkey::default();
+    for reward_info_info in ctx.remaining_accounts.iter() {
+        let reward_info = Account::<RewardInfo>::try_from(reward_info_info)?;
+        require_keys_eq!(reward_info.pool, pool_id, GammaError::InvalidInput);
+        // Sorted so that each RewardInfo of the pool is counted once
+        require!(
+            reward_info.key() > previous_reward_info,
+            GammaError::InvalidInput
+        );
+        previous_reward_info = reward_info.key();
+    }
+    let reward_info_count =
+        u64::try_from(ctx.remaining_accounts.len()).map_err(|_| GammaError::MathOverflow)?;
+    // The rewards created since the resize are already counted
+    require_gte!(
+        reward_info_count,
+        pool_state.reward_info_count,
+        GammaError::InvalidInput
+    );
+    pool_state.reward_info_count = reward_info_count;
+    pool_state.reward_info_count_migrated = 1;
+    Ok(())
+}
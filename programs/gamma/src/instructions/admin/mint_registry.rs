@@ -0,0 +1,199 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::GammaError,
+    states::{
+        AmmConfig, Capability, MintEntry, MintEntryEvent, MintEntryStatus, MintRegistry,
+        MintRegistryMode, MintRegistryModeEvent, Roles, MINT_ENTRY_SEED, MINT_REGISTRY_SEED,
+        ROLES_SEED,
+    },
+};
+
+fn check_registry_authority(authority: Pubkey, roles: Option<&Roles>) -> Result<()> {
+    require!(
+        Roles::is_granted(roles, authority, Capability::ConfigCreator),
+        GammaError::InvalidOwner
+    );
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitMintRegistry<'info> {
+    /// The admin or a config creator
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// The config the registry applies to
+    #[account(mut)]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(
+        init,
+        seeds = [
+            MINT_REGISTRY_SEED.as_bytes(),
+            amm_config.key().as_ref(),
+        ],
+        bump,
+        payer = authority,
+        space = MintRegistry::LEN,
+    )]
+    pub mint_registry: Account<'info, MintRegistry>,
+
+    /// CHECK: roles account granting capabilities, read by `Roles::load`. The hard-coded admin
+    /// keys are only trusted until it is created
+    #[account(seeds = [ROLES_SEED.as_bytes()], bump)]
+    pub roles: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init_mint_registry(ctx: Context<InitMintRegistry>, mode: u8) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    check_registry_authority(authority, Roles::load(&ctx.accounts.roles)?.as_ref())?;
+    MintRegistryMode::new(mode)?;
+
+    let mint_registry = &mut ctx.accounts.mint_registry;
+    mint_registry.bump = ctx.bumps.mint_registry;
+    mint_registry.amm_config = ctx.accounts.amm_config.key();
+    mint_registry.mode = mode;
+    ctx.accounts.amm_config.mint_registry_enabled = true;
+
+    emit!(MintRegistryModeEvent {
+        amm_config: ctx.accounts.amm_config.key(),
+        authority,
+        old_mode: MintRegistryMode::Disabled as u8,
+        new_mode: mode,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetMintRegistryMode<'info> {
+    /// The admin or a config creator
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            MINT_REGISTRY_SEED.as_bytes(),
+            mint_registry.amm_config.as_ref(),
+        ],
+        bump = mint_registry.bump,
+    )]
+    pub mint_registry: Account<'info, MintRegistry>,
+
+    /// CHECK: roles account granting capabilities, read by `Roles::load`. The hard-coded admin
+    /// keys are only trusted until it is created
+    #[account(seeds = [ROLES_SEED.as_bytes()], bump)]
+    pub roles: UncheckedAccount<'info>,
+}
+
+pub fn set_mint_registry_mode(ctx: Context<SetMintRegistryMode>, mode: u8) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    check_registry_authority(authority, Roles::load(&ctx.accounts.roles)?.as_ref())?;
+    MintRegistryMode::new(mode)?;
+
+    let mint_registry = &mut ctx.accounts.mint_registry;
+    let old_mode = mint_registry.mode;
+    mint_registry.mode = mode;
+
+    emit!(MintRegistryModeEvent {
+        amm_config: mint_registry.amm_config,
+        authority,
+        old_mode,
+        new_mode: mode,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(mint: Pubkey)]
+pub struct SetMintEntry<'info> {
+    /// The admin or a config creator
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// The config the entry applies to
+    pub amm_config: Account<'info, AmmConfig>,
+
+    #[account(
+        init_if_needed,
+        seeds = [
+            MINT_ENTRY_SEED.as_bytes(),
+            amm_config.key().as_ref(),
+            mint.as_ref(),
+        ],
+        bump,
+        payer = authority,
+        space = MintEntry::LEN,
+    )]
+    pub mint_entry: Account<'info, MintEntry>,
+
+    /// CHECK: roles account granting capabilities, read by `Roles::load`. The hard-coded admin
+    /// keys are only trusted until it is created
+    #[account(seeds = [ROLES_SEED.as_bytes()], bump)]
+    pub roles: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn set_mint_entry(ctx: Context<SetMintEntry>, mint: Pubkey, status: u8) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    check_registry_authority(authority, Roles::load(&ctx.accounts.roles)?.as_ref())?;
+    MintEntryStatus::new(status)?;
+
+    let mint_entry = &mut ctx.accounts.mint_entry;
+    let old_status = mint_entry.status;
+    mint_entry.bump = ctx.bumps.mint_entry;
+    mint_entry.amm_config = ctx.accounts.amm_config.key();
+    mint_entry.mint = mint;
+    mint_entry.status = status;
+
+    emit!(MintEntryEvent {
+        amm_config: mint_entry.amm_config,
+        mint,
+        authority,
+        old_status,
+        new_status: status,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RemoveMintEntry<'info> {
+    /// The admin or a config creator, receives the rent of the entry
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            MINT_ENTRY_SEED.as_bytes(),
+            mint_entry.amm_config.as_ref(),
+            mint_entry.mint.as_ref(),
+        ],
+        bump = mint_entry.bump,
+        close = authority,
+    )]
+    pub mint_entry: Account<'info, MintEntry>,
+
+    /// CHECK: roles account granting capabilities, read by `Roles::load`. The hard-coded admin
+    /// keys are only trusted until it is created
+    #[account(seeds = [ROLES_SEED.as_bytes()], bump)]
+    pub roles: UncheckedAccount<'info>,
+}
+
+pub fn remove_mint_entry(ctx: Context<RemoveMintEntry>) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    check_registry_authority(authority, Roles::load(&ctx.accounts.roles)?.as_ref())?;
+
+    let mint_entry = &ctx.accounts.mint_entry;
+    emit!(MintEntryEvent {
+        amm_config: mint_entry.amm_config,
+        mint: mint_entry.mint,
+        authority,
+        old_status: mint_entry.status,
+        new_status: 0,
+    });
+    Ok(())
+}
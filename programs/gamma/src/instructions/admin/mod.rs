@@ -1,13 +1,29 @@
+pub mod close_pool;
 pub mod collect_fund_fee;
 pub mod collect_protocol_fee;
 pub mod create_config;
 pub mod create_referral_project;
+pub mod migrate_reward_info_count;
+pub mod mint_registry;
+pub mod partner;
+pub mod queued_update;
+pub mod rebind_pool_config;
+pub mod roles;
+pub mod transfer_role;
 pub mod update_config;
 pub mod update_pool;
 
+pub use close_pool::*;
 pub use collect_fund_fee::*;
 pub use collect_protocol_fee::*;
 pub use create_config::*;
 pub use create_referral_project::*;
+pub use migrate_reward_info_count::*;
+pub use mint_registry::*;
+pub use partner::*;
+pub use queued_update::*;
+pub use rebind_pool_config::*;
+pub use roles::*;
+pub use transfer_role::*;
 pub use update_config::*;
 pub use update_pool::*;
@@ -0,0 +1,133 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::GammaError,
+    fees::FEE_RATE_DENOMINATOR_VALUE,
+    states::{
+        Capability, Partner, PartnerEvent, Roles, PARTNER_NAME_MAX_LEN, PARTNER_SEED, ROLES_SEED,
+    },
+};
+
+fn check_partner_params(name: &str, revenue_share_rate: u64) -> Result<()> {
+    require!(
+        !name.is_empty() && name.len() <= PARTNER_NAME_MAX_LEN,
+        GammaError::InvalidInput
+    );
+    require_gte!(
+        FEE_RATE_DENOMINATOR_VALUE,
+        revenue_share_rate,
+        GammaError::InvalidInput
+    );
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct CreatePartner<'info> {
+    /// The admin or a partner manager
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [
+            PARTNER_SEED.as_bytes(),
+            id.to_le_bytes().as_ref(),
+        ],
+        bump,
+        payer = authority,
+        space = Partner::LEN,
+    )]
+    pub partner: Account<'info, Partner>,
+
+    /// CHECK: roles account granting capabilities, read by `Roles::load`. The hard-coded admin
+    /// keys are only trusted until it is created
+    #[account(seeds = [ROLES_SEED.as_bytes()], bump)]
+    pub roles: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn create_partner(
+    ctx: Context<CreatePartner>,
+    id: u64,
+    name: String,
+    payout_wallet: Pubkey,
+    revenue_share_rate: u64,
+) -> Result<()> {
+    require!(
+        Roles::is_granted(
+            Roles::load(&ctx.accounts.roles)?.as_ref(),
+            ctx.accounts.authority.key(),
+            Capability::PartnerManager
+        ),
+        GammaError::InvalidOwner
+    );
+    check_partner_params(&name, revenue_share_rate)?;
+
+    let partner = &mut ctx.accounts.partner;
+    partner.bump = ctx.bumps.partner;
+    partner.id = id;
+    partner.name = name;
+    partner.payout_wallet = payout_wallet;
+    partner.revenue_share_rate = revenue_share_rate;
+
+    emit!(PartnerEvent {
+        partner_id: id,
+        name: partner.name.clone(),
+        payout_wallet,
+        revenue_share_rate,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdatePartner<'info> {
+    /// The admin or a partner manager
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            PARTNER_SEED.as_bytes(),
+            partner.id.to_le_bytes().as_ref(),
+        ],
+        bump = partner.bump,
+    )]
+    pub partner: Account<'info, Partner>,
+
+    /// CHECK: roles account granting capabilities, read by `Roles::load`. The hard-coded admin
+    /// keys are only trusted until it is created
+    #[account(seeds = [ROLES_SEED.as_bytes()], bump)]
+    pub roles: UncheckedAccount<'info>,
+}
+
+pub fn update_partner(
+    ctx: Context<UpdatePartner>,
+    name: String,
+    payout_wallet: Pubkey,
+    revenue_share_rate: u64,
+) -> Result<()> {
+    require!(
+        Roles::is_granted(
+            Roles::load(&ctx.accounts.roles)?.as_ref(),
+            ctx.accounts.authority.key(),
+            Capability::PartnerManager
+        ),
+        GammaError::InvalidOwner
+    );
+    check_partner_params(&name, revenue_share_rate)?;
+
+    let partner = &mut ctx.accounts.partner;
+    partner.name = name;
+    partner.payout_wallet = payout_wallet;
+    partner.revenue_share_rate = revenue_share_rate;
+
+    emit!(PartnerEvent {
+        partner_id: partner.id,
+        name: partner.name.clone(),
+        payout_wallet,
+        revenue_share_rate,
+    });
+    Ok(())
+}
@@ -0,0 +1,258 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::GammaError,
+    instructions::{
+        check_authority, check_config_authority, execute_amm_config_update, execute_pool_update,
+    },
+    states::{
+        AmmConfig, PoolState, QueuedAction, QueuedUpdate, QueuedUpdateEvent, Roles,
+        QUEUED_UPDATE_SEED, ROLES_SEED,
+    },
+};
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct QueueUpdate<'info> {
+    /// The admin, or a key holding the capability the update requires
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// The amm config the update belongs to
+    pub amm_config: Account<'info, AmmConfig>,
+
+    /// The pool a `QueuedAction::Pool` applies to
+    pub pool_state: Option<AccountLoader<'info, PoolState>>,
+
+    #[account(
+        init,
+        seeds = [
+            QUEUED_UPDATE_SEED.as_bytes(),
+            amm_config.key().as_ref(),
+            &id.to_le_bytes(),
+        ],
+        bump,
+        payer = authority,
+        space = QueuedUpdate::LEN,
+    )]
+    pub queued_update: Account<'info, QueuedUpdate>,
+
+    /// CHECK: roles account granting capabilities, read by `Roles::load`. The hard-coded admin
+    /// keys are only trusted until it is created
+    #[account(seeds = [ROLES_SEED.as_bytes()], bump)]
+    pub roles: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn queue_update(ctx: Context<QueueUpdate>, id: u64, action: QueuedAction) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    let amm_config = &ctx.accounts.amm_config;
+    let pool_id = match &action {
+        QueuedAction::Config(update) => {
+            require!(
+                check_config_authority(
+                    authority,
+                    Roles::load(&ctx.accounts.roles)?.as_ref(),
+                    update
+                ),
+                GammaError::InvalidOwner
+            );
+            Pubkey::default()
+        }
+        QueuedAction::Pool(update) => {
+            let pool_state_loader = ctx
+                .accounts
+                .pool_state
+                .as_ref()
+                .ok_or(GammaError::InvalidInput)?;
+            require_keys_eq!(
+                pool_state_loader.load()?.amm_config,
+                amm_config.key(),
+                GammaError::InvalidInput
+            );
+            require!(
+                check_authority(
+                    authority,
+                    amm_config,
+                    Roles::load(&ctx.accounts.roles)?.as_ref(),
+                    update
+                ),
+                GammaError::InvalidOwner
+            );
+            pool_state_loader.key()
+        }
+    };
+
+    let block_timestamp = Clock::get()?.unix_timestamp as u64;
+    let eta = block_timestamp
+        .checked_add(amm_config.timelock_delay)
+        .ok_or(GammaError::MathOverflow)?;
+
+    let queued_update = &mut ctx.accounts.queued_update;
+    queued_update.bump = ctx.bumps.queued_update;
+    queued_update.id = id;
+    queued_update.amm_config = amm_config.key();
+    queued_update.pool_state = pool_id;
+    queued_update.proposer = authority;
+    queued_update.eta = eta;
+    queued_update.action = action.clone();
+
+    emit!(QueuedUpdateEvent {
+        queued_update: queued_update.key(),
+        amm_config: amm_config.key(),
+        pool_state: pool_id,
+        authority,
+        action,
+        eta,
+        status: 0,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ExecuteQueuedUpdate<'info> {
+    /// Anyone can execute an update once its eta has passed
+    pub executor: Signer<'info>,
+
+    /// CHECK: receives the rent of the queued update
+    #[account(mut, address = queued_update.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        close = proposer,
+        has_one = amm_config,
+    )]
+    pub queued_update: Account<'info, QueuedUpdate>,
+
+    #[account(mut)]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    /// The pool a `QueuedAction::Pool` applies to
+    #[account(mut)]
+    pub pool_state: Option<AccountLoader<'info, PoolState>>,
+
+    /// CHECK: roles account granting capabilities, read by `Roles::load`. The hard-coded admin
+    /// keys are only trusted until it is created
+    #[account(seeds = [ROLES_SEED.as_bytes()], bump)]
+    pub roles: UncheckedAccount<'info>,
+}
+
+pub fn execute_queued_update(ctx: Context<ExecuteQueuedUpdate>) -> Result<()> {
+    let block_timestamp = Clock::get()?.unix_timestamp as u64;
+    let queued_update = &ctx.accounts.queued_update;
+    require_gte!(
+        block_timestamp,
+        queued_update.eta,
+        GammaError::TimelockNotElapsed
+    );
+
+    // Events of the applied update carry the proposer, who authorized the change
+    let proposer = queued_update.proposer;
+    match queued_update.action.clone() {
+        QueuedAction::Config(update) => {
+            // The proposer may have lost its capability while the update was queued
+            require!(
+                check_config_authority(
+                    proposer,
+                    Roles::load(&ctx.accounts.roles)?.as_ref(),
+                    &update
+                ),
+                GammaError::InvalidOwner
+            );
+            execute_amm_config_update(&mut ctx.accounts.amm_config, proposer, update)?;
+        }
+        QueuedAction::Pool(update) => {
+            let pool_state_loader = ctx
+                .accounts
+                .pool_state
+                .as_ref()
+                .ok_or(GammaError::InvalidInput)?;
+            require_keys_eq!(
+                pool_state_loader.key(),
+                queued_update.pool_state,
+                GammaError::InvalidInput
+            );
+            let mut pool_state = pool_state_loader.load_mut()?;
+            // The pool may have been rebound to another config while the update was queued
+            require_keys_eq!(
+                pool_state.amm_config,
+                ctx.accounts.amm_config.key(),
+                GammaError::InvalidInput
+            );
+            // The proposer may have lost its capability while the update was queued
+            require!(
+                check_authority(
+                    proposer,
+                    &ctx.accounts.amm_config,
+                    Roles::load(&ctx.accounts.roles)?.as_ref(),
+                    &update
+                ),
+                GammaError::InvalidOwner
+            );
+            execute_pool_update(pool_state_loader.key(), &mut pool_state, proposer, update)?;
+        }
+    }
+
+    emit!(QueuedUpdateEvent {
+        queued_update: queued_update.key(),
+        amm_config: queued_update.amm_config,
+        pool_state: queued_update.pool_state,
+        authority: ctx.accounts.executor.key(),
+        action: queued_update.action.clone(),
+        eta: queued_update.eta,
+        status: 1,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelQueuedUpdate<'info> {
+    /// The proposer of the update, or a key holding the capability the update requires
+    pub authority: Signer<'info>,
+
+    /// CHECK: receives the rent of the queued update
+    #[account(mut, address = queued_update.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+
+    #[account(mut, close = proposer)]
+    pub queued_update: Account<'info, QueuedUpdate>,
+
+    /// CHECK: roles account granting capabilities, read by `Roles::load`. The hard-coded admin
+    /// keys are only trusted until it is created
+    #[account(seeds = [ROLES_SEED.as_bytes()], bump)]
+    pub roles: UncheckedAccount<'info>,
+}
+
+pub fn cancel_queued_update(ctx: Context<CancelQueuedUpdate>) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    let queued_update = &ctx.accounts.queued_update;
+    let required_capability = match &queued_update.action {
+        QueuedAction::Config(update) => update.required_capability(),
+        QueuedAction::Pool(update) => update.required_capability(),
+    };
+    require!(
+        authority == queued_update.proposer
+            || Roles::is_granted(
+                Roles::load(&ctx.accounts.roles)?.as_ref(),
+                authority,
+                required_capability
+            ),
+        GammaError::InvalidOwner
+    );
+
+    emit!(QueuedUpdateEvent {
+        queued_update: queued_update.key(),
+        amm_config: queued_update.amm_config,
+        pool_state: queued_update.pool_state,
+        authority,
+        action: queued_update.action.clone(),
+        eta: queued_update.eta,
+        status: 2,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,266 @@
+use crate::error::GammaError;
+use crate::states::*;
+use crate::utils::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::Mint;
+use anchor_spl::token_interface::Token2022;
+use anchor_spl::token_interface::TokenAccount;
+
+#[derive(Accounts)]
+pub struct RebindPoolConfig<'info> {
+    /// A key holding the fee manager capability
+    pub owner: Signer<'info>,
+
+    /// CHECK: pool vault authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// The pool to rebind, its address keeps the seeds of the old config
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The config the pool currently belongs to
+    #[account(address = pool_state.load()?.amm_config)]
+    pub old_amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// The config the pool moves to
+    #[account(
+        constraint = new_amm_config.key() != old_amm_config.key() @ GammaError::InvalidInput
+    )]
+    pub new_amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// The address that holds pool tokens for token_0
+    #[account(
+        mut,
+        constraint = token_0_vault.key() == pool_state.load()?.token_0_vault
+    )]
+    pub token_0_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_1
+    #[account(
+        mut,
+        constraint = token_1_vault.key() == pool_state.load()?.token_1_vault
+    )]
+    pub token_1_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of token_0 vault
+    #[account(
+        address = token_0_vault.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token_1 vault
+    #[account(
+        address = token_1_vault.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Receives the token_0 protocol fees accrued under the old config
+    #[account(
+        mut,
+        token::mint = vault_0_mint,
+        token::authority = old_amm_config.protocol_owner,
+    )]
+    pub protocol_owner_token_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives the token_1 protocol fees accrued under the old config
+    #[account(
+        mut,
+        token::mint = vault_1_mint,
+        token::authority = old_amm_config.protocol_owner,
+    )]
+    pub protocol_owner_token_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives the token_0 fund fees accrued under the old config
+    #[account(
+        mut,
+        token::mint = vault_0_mint,
+        token::authority = old_amm_config.fund_owner,
+    )]
+    pub fund_owner_token_0: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives the token_1 fund fees accrued under the old config
+    #[account(
+        mut,
+        token::mint = vault_1_mint,
+        token::authority = old_amm_config.fund_owner,
+    )]
+    pub fund_owner_token_1: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The SPL program to perform token transfers
+    pub token_program: Program<'info, Token>,
+
+    /// The SPL program 2022 to perform token transfers
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// CHECK: roles account granting capabilities, read by `Roles::load`. The hard-coded admin
+    /// keys are only trusted until it is created
+    #[account(seeds = [ROLES_SEED.as_bytes()], bump)]
+    pub roles: UncheckedAccount<'info>,
+}
+
+pub fn rebind_pool_config<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, RebindPoolConfig<'info>>,
+) -> Result<()> {
+    require!(
+        Roles::is_granted(
+            Roles::load(&ctx.accounts.roles)?.as_ref(),
+            ctx.accounts.owner.key(),
+            Capability::FeeManager
+        ),
+        GammaError::InvalidOwner
+    );
+    let old_amm_config = &ctx.accounts.old_amm_config;
+    let new_amm_config = &ctx.accounts.new_amm_config;
+    // Under a timelock, fee increases must not take effect through an immediate rebind, nor
+    // through a config with a shorter delay the pool could be rebound from right away
+    require!(
+        old_amm_config.timelock_delay == 0
+            || (new_amm_config.trade_fee_rate <= old_amm_config.trade_fee_rate
+                && new_amm_config.protocol_fee_rate <= old_amm_config.protocol_fee_rate
+                && new_amm_config.fund_fee_rate <= old_amm_config.fund_fee_rate
+                && new_amm_config.timelock_delay >= old_amm_config.timelock_delay),
+        GammaError::TimelockRequired
+    );
+    // The remaining accounts start with the registry accounts when the new config has a mint
+    // registry, the token transfers get the ones after them
+    let registry_accounts_len = if new_amm_config.mint_registry_enabled {
+        MINT_REGISTRY_ACCOUNTS_LEN
+    } else {
+        0
+    };
+    require!(
+        ctx.remaining_accounts.len() >= registry_accounts_len,
+        GammaError::InvalidInput
+    );
+    let (registry_accounts, transfer_extra_accounts) =
+        ctx.remaining_accounts.split_at(registry_accounts_len);
+
+    // The mints have to pass the registry of the new config, as they would for a new pool
+    let verified = if new_amm_config.mint_registry_enabled {
+        Some(check_pool_mints(
+            new_amm_config.key(),
+            registry_accounts,
+            ctx.accounts.vault_0_mint.key(),
+            ctx.accounts.vault_1_mint.key(),
+        )?)
+    } else {
+        None
+    };
+
+    let pool_id = ctx.accounts.pool_state.key();
+    let authority = ctx.accounts.owner.key();
+    let protocol_fees_token_0: u64;
+    let protocol_fees_token_1: u64;
+    let fund_fees_token_0: u64;
+    let fund_fees_token_1: u64;
+    let partner_fees_token_0: u64;
+    let partner_fees_token_1: u64;
+    let auth_bump: u8;
+    {
+        let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+
+        // Settle the fees accrued under the old config before moving, the ones set aside for
+        // the partners stay in the pool
+        (protocol_fees_token_0, protocol_fees_token_1) = pool_state.collectable_protocol_fees()?;
+        fund_fees_token_0 = pool_state.fund_fees_token_0;
+        fund_fees_token_1 = pool_state.fund_fees_token_1;
+        partner_fees_token_0 = pool_state.partner_fees_token_0;
+        partner_fees_token_1 = pool_state.partner_fees_token_1;
+        pool_state.protocol_fees_token_0 = partner_fees_token_0;
+        pool_state.protocol_fees_token_1 = partner_fees_token_1;
+        pool_state.fund_fees_token_0 = 0;
+        pool_state.fund_fees_token_1 = 0;
+
+        pool_state.amm_config = new_amm_config.key();
+        if let Some(verified) = verified {
+            pool_state.verified = verified as u8;
+        }
+        auth_bump = pool_state.auth_bump;
+        pool_state.recent_epoch = Clock::get()?.epoch;
+    }
+
+    emit!(CollectFeeEvent {
+        pool_id,
+        authority,
+        recipient_token_0_account: ctx.accounts.protocol_owner_token_0.key(),
+        recipient_token_1_account: ctx.accounts.protocol_owner_token_1.key(),
+        amount_0: protocol_fees_token_0,
+        amount_1: protocol_fees_token_1,
+        remaining_fees_token_0: partner_fees_token_0,
+        remaining_fees_token_1: partner_fees_token_1,
+        fee_type: 0,
+    });
+    emit!(CollectFeeEvent {
+        pool_id,
+        authority,
+        recipient_token_0_account: ctx.accounts.fund_owner_token_0.key(),
+        recipient_token_1_account: ctx.accounts.fund_owner_token_1.key(),
+        amount_0: fund_fees_token_0,
+        amount_1: fund_fees_token_1,
+        remaining_fees_token_0: 0,
+        remaining_fees_token_1: 0,
+        fee_type: 1,
+    });
+    emit!(RebindPoolConfigEvent {
+        pool_id,
+        authority,
+        old_amm_config: old_amm_config.key(),
+        new_amm_config: new_amm_config.key(),
+    });
+
+    let signer_seeds: &[&[&[u8]]] = &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]];
+    let token_0_program =
+        if ctx.accounts.vault_0_mint.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        };
+    let token_1_program =
+        if ctx.accounts.vault_1_mint.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        };
+
+    for (recipient, amount) in [
+        (&ctx.accounts.protocol_owner_token_0, protocol_fees_token_0),
+        (&ctx.accounts.fund_owner_token_0, fund_fees_token_0),
+    ] {
+        transfer_from_pool_vault_to_user(
+            ctx.accounts.authority.to_account_info(),
+            ctx.accounts.token_0_vault.to_account_info(),
+            recipient.to_account_info(),
+            ctx.accounts.vault_0_mint.to_account_info(),
+            token_0_program.clone(),
+            amount,
+            ctx.accounts.vault_0_mint.decimals,
+            transfer_extra_accounts,
+            signer_seeds,
+        )?;
+    }
+    for (recipient, amount) in [
+        (&ctx.accounts.protocol_owner_token_1, protocol_fees_token_1),
+        (&ctx.accounts.fund_owner_token_1, fund_fees_token_1),
+    ] {
+        transfer_from_pool_vault_to_user(
+            ctx.accounts.authority.to_account_info(),
+            ctx.accounts.token_1_vault.to_account_info(),
+            recipient.to_account_info(),
+            ctx.accounts.vault_1_mint.to_account_info(),
+            token_1_program.clone(),
+            amount,
+            ctx.accounts.vault_1_mint.decimals,
+            transfer_extra_accounts,
+            signer_seeds,
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::GammaError,
+    states::{Capability, CapabilitiesUpdateEvent, Roles, ROLES_SEED},
+};
+
+#[derive(Accounts)]
+pub struct InitRoles<'info> {
+    #[account(
+        mut,
+        address = crate::admin::id() @ GammaError::InvalidOwner
+    )]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [ROLES_SEED.as_bytes()],
+        bump,
+        payer = owner,
+        space = Roles::LEN
+    )]
+    pub roles: Account<'info, Roles>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init_roles(ctx: Context<InitRoles>) -> Result<()> {
+    let roles = &mut ctx.accounts.roles;
+    roles.bump = ctx.bumps.roles;
+    // The hard-coded keys stop being trusted once the account exists, they start with the
+    // capabilities they had and can be revoked from there
+    roles.set_capabilities(crate::admin::id(), Capability::ALL_MASK)?;
+    roles.set_capabilities(
+        crate::CALCULATE_REWARDS_ADMIN,
+        Capability::RewardsOperator.mask(),
+    )?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetCapabilities<'info> {
+    /// The admin or a key holding the role admin capability
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [ROLES_SEED.as_bytes()],
+        bump = roles.bump,
+    )]
+    pub roles: Account<'info, Roles>,
+}
+
+pub fn set_capabilities(
+    ctx: Context<SetCapabilities>,
+    member: Pubkey,
+    capabilities: u32,
+) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    require!(
+        Roles::is_granted(Some(&*ctx.accounts.roles), authority, Capability::RoleAdmin),
+        GammaError::InvalidOwner
+    );
+
+    let old_capabilities = ctx.accounts.roles.set_capabilities(member, capabilities)?;
+    // Otherwise the roles account could never be changed again
+    require!(
+        ctx.accounts.roles.has_role_admin(),
+        GammaError::InvalidInput
+    );
+
+    emit!(CapabilitiesUpdateEvent {
+        member,
+        authority,
+        old_capabilities,
+        new_capabilities: capabilities,
+    });
+
+    Ok(())
+}
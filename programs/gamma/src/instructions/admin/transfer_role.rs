@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::GammaError,
+    states::{
+        AmmConfig, Capability, ConfigRole, ConfigUpdateEvent, ParamValue, RoleTransferEvent, Roles,
+        ROLES_SEED,
+    },
+};
+
+#[derive(Accounts)]
+pub struct AcceptRole<'info> {
+    /// The key proposed for the role
+    #[account(address = amm_config.pending_key @ GammaError::InvalidOwner)]
+    pub new_owner: Signer<'info>,
+
+    /// The amm config account with the pending role transfer
+    #[account(mut)]
+    pub amm_config: Account<'info, AmmConfig>,
+}
+
+pub fn accept_role(ctx: Context<AcceptRole>) -> Result<()> {
+    let amm_config = &mut ctx.accounts.amm_config;
+    let role =
+        ConfigRole::new(amm_config.pending_role).ok_or(GammaError::NoPendingRoleTransfer)?;
+    let old_key = amm_config.role_key(role);
+    let new_key = ctx.accounts.new_owner.key();
+
+    amm_config.set_role_key(role, new_key);
+    amm_config.pending_role = 0;
+    amm_config.pending_key = Pubkey::default();
+
+    emit!(RoleTransferEvent {
+        amm_config: amm_config.key(),
+        authority: new_key,
+        role: role as u8,
+        current_key: old_key,
+        pending_key: new_key,
+        action: 1,
+    });
+    emit!(ConfigUpdateEvent {
+        amm_config: amm_config.key(),
+        authority: new_key,
+        param: role.name(),
+        old_value: ParamValue::Pubkey(old_key),
+        new_value: ParamValue::Pubkey(new_key),
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelRoleTransfer<'info> {
+    /// A key holding the role admin capability
+    pub owner: Signer<'info>,
+
+    /// The amm config account with the pending role transfer
+    #[account(mut)]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    /// CHECK: roles account granting capabilities, read by `Roles::load`. The hard-coded admin
+    /// keys are only trusted until it is created
+    #[account(seeds = [ROLES_SEED.as_bytes()], bump)]
+    pub roles: UncheckedAccount<'info>,
+}
+
+pub fn cancel_role_transfer(ctx: Context<CancelRoleTransfer>) -> Result<()> {
+    require!(
+        Roles::is_granted(
+            Roles::load(&ctx.accounts.roles)?.as_ref(),
+            ctx.accounts.owner.key(),
+            Capability::RoleAdmin
+        ),
+        GammaError::InvalidOwner
+    );
+    let amm_config = &mut ctx.accounts.amm_config;
+    let role =
+        ConfigRole::new(amm_config.pending_role).ok_or(GammaError::NoPendingRoleTransfer)?;
+    let pending_key = amm_config.pending_key;
+
+    amm_config.pending_role = 0;
+    amm_config.pending_key = Pubkey::default();
+
+    emit!(RoleTransferEvent {
+        amm_config: amm_config.key(),
+        authority: ctx.accounts.owner.key(),
+        role: role as u8,
+        current_key: amm_config.role_key(role),
+        pending_key,
+        action: 2,
+    });
+    Ok(())
+}
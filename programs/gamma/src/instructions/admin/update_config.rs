@@ -3,116 +3,234 @@ use anchor_lang::prelude::*;
 use crate::{
     error::GammaError,
     fees::FEE_RATE_DENOMINATOR_VALUE,
-    states::{validate_config_rates, AmmConfig},
+    states::{
+        validate_config_rates, AmmConfig, ConfigRole, ConfigUpdate, ConfigUpdateEvent,
+        RoleTransferEvent, Roles, MAX_CREATOR_LP_LOCK_DURATION, MAX_TIMELOCK_DELAY, ROLES_SEED,
+    },
 };
 
 #[derive(Accounts)]
 pub struct UpdateAmmConfig<'info> {
-    /// The amm config owner or admin
-    #[account( address = crate::admin::id() @GammaError::InvalidOwner)]
+    /// The admin, or a key holding the capability the update requires
     pub owner: Signer<'info>,
 
     /// The amm config account to update
     #[account(mut)]
     pub amm_config: Account<'info, AmmConfig>,
+
+    /// CHECK: roles account granting capabilities, read by `Roles::load`. The hard-coded admin
+    /// keys are only trusted until it is created
+    #[account(seeds = [ROLES_SEED.as_bytes()], bump)]
+    pub roles: UncheckedAccount<'info>,
+}
+
+pub(crate) fn check_config_authority(
+    authority: Pubkey,
+    roles: Option<&Roles>,
+    update: &ConfigUpdate,
+) -> bool {
+    Roles::is_granted(roles, authority, update.required_capability())
 }
 
 pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, param: u16, value: u64) -> Result<()> {
-    let amm_config = &mut ctx.accounts.amm_config;
-    match param {
-        0 => update_trade_fee_rate(amm_config, value),
-        1 => update_protocol_fee_rate(amm_config, value),
-        2 => update_fund_fee_rate(amm_config, value),
-        3 => {
-            let new_protocol_owner = match ctx.remaining_accounts.iter().next() {
-                Some(account) => account.key(),
-                None => return err!(GammaError::InvalidInput),
-            };
-            set_new_protocol_owner(amm_config, new_protocol_owner)?;
+    let update = match param {
+        0 => ConfigUpdate::TradeFeeRate {
+            trade_fee_rate: value,
+        },
+        1 => ConfigUpdate::ProtocolFeeRate {
+            protocol_fee_rate: value,
+        },
+        2 => ConfigUpdate::FundFeeRate {
+            fund_fee_rate: value,
+        },
+        3 => ConfigUpdate::ProtocolOwner {
+            protocol_owner: remaining_account_key(ctx.remaining_accounts)?,
+        },
+        4 => ConfigUpdate::FundOwner {
+            fund_owner: remaining_account_key(ctx.remaining_accounts)?,
+        },
+        5 => ConfigUpdate::CreatePoolFee {
+            create_pool_fee: value,
+        },
+        6 => ConfigUpdate::DisableCreatePool {
+            disable_create_pool: value != 0,
+        },
+        7 => ConfigUpdate::MaxOpenTime {
+            max_open_time: value,
+        },
+        8 => ConfigUpdate::SecondaryAdmin {
+            secondary_admin: remaining_account_key(ctx.remaining_accounts)?,
+        },
+        _ => return err!(GammaError::InvalidInput),
+    };
+    apply_amm_config_update(ctx, update)
+}
+
+pub fn apply_amm_config_update(ctx: Context<UpdateAmmConfig>, update: ConfigUpdate) -> Result<()> {
+    require!(
+        check_config_authority(
+            ctx.accounts.owner.key(),
+            Roles::load(&ctx.accounts.roles)?.as_ref(),
+            &update
+        ),
+        GammaError::InvalidOwner
+    );
+    require!(
+        ctx.accounts.amm_config.timelock_delay == 0 || update.is_emergency(),
+        GammaError::TimelockRequired
+    );
+    execute_amm_config_update(
+        &mut ctx.accounts.amm_config,
+        ctx.accounts.owner.key(),
+        update,
+    )
+}
+
+/// Applies `update` without any timelock check, shared with queued updates
+pub(crate) fn execute_amm_config_update(
+    amm_config: &mut Account<AmmConfig>,
+    authority: Pubkey,
+    update: ConfigUpdate,
+) -> Result<()> {
+    let old_value = update.current_value(amm_config);
+    match update {
+        ConfigUpdate::TradeFeeRate { trade_fee_rate } => {
+            update_trade_fee_rate(amm_config, trade_fee_rate)?
         }
-        4 => {
-            let new_fund_owner = match ctx.remaining_accounts.iter().next() {
-                Some(account) => account.key(),
-                None => return err!(GammaError::InvalidInput),
-            };
-            set_new_fund_owner(amm_config, new_fund_owner)?;
+        ConfigUpdate::ProtocolFeeRate { protocol_fee_rate } => {
+            update_protocol_fee_rate(amm_config, protocol_fee_rate)?
         }
-        5 => amm_config.create_pool_fee = value,
-        6 => amm_config.disable_create_pool = if value == 0 { false } else { true },
-        7 => amm_config.max_open_time = value,
-        8 => {
-            let new_secondary_admin = match ctx.remaining_accounts.iter().next() {
-                Some(account) => account.key(),
-                None => return err!(GammaError::InvalidInput),
-            };
-            set_new_secondary_admin(amm_config, new_secondary_admin)?;
+        ConfigUpdate::FundFeeRate { fund_fee_rate } => {
+            update_fund_fee_rate(amm_config, fund_fee_rate)?
+        }
+        // Role keys only take effect once the new key accepts them
+        ConfigUpdate::ProtocolOwner { protocol_owner } => {
+            return propose_role_transfer(
+                amm_config,
+                authority,
+                ConfigRole::ProtocolOwner,
+                protocol_owner,
+            )
+        }
+        ConfigUpdate::FundOwner { fund_owner } => {
+            return propose_role_transfer(amm_config, authority, ConfigRole::FundOwner, fund_owner)
+        }
+        ConfigUpdate::CreatePoolFee { create_pool_fee } => {
+            amm_config.create_pool_fee = create_pool_fee
+        }
+        ConfigUpdate::DisableCreatePool {
+            disable_create_pool,
+        } => amm_config.disable_create_pool = disable_create_pool,
+        ConfigUpdate::MaxOpenTime { max_open_time } => amm_config.max_open_time = max_open_time,
+        ConfigUpdate::SecondaryAdmin { secondary_admin } => {
+            return propose_role_transfer(
+                amm_config,
+                authority,
+                ConfigRole::SecondaryAdmin,
+                secondary_admin,
+            )
+        }
+        ConfigUpdate::TimelockDelay { timelock_delay } => {
+            require_gte!(MAX_TIMELOCK_DELAY, timelock_delay, GammaError::InvalidInput);
+            amm_config.timelock_delay = timelock_delay
+        }
+        ConfigUpdate::CreatorLpLockDuration {
+            creator_lp_lock_duration,
+        } => {
+            require_gte!(
+                MAX_CREATOR_LP_LOCK_DURATION,
+                creator_lp_lock_duration,
+                GammaError::InvalidInput
+            );
+            amm_config.creator_lp_lock_duration = creator_lp_lock_duration
         }
-        _ => return err!(GammaError::InvalidInput),
     }
 
     validate_config_rates(amm_config)?;
 
-    Ok(())
-}
+    emit!(ConfigUpdateEvent {
+        amm_config: amm_config.key(),
+        authority,
+        param: update.name(),
+        old_value,
+        new_value: update.current_value(amm_config),
+    });
 
-fn update_trade_fee_rate(amm_config: &mut Account<AmmConfig>, trade_fee_rate: u64) {
-    assert!(trade_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
-    amm_config.trade_fee_rate = trade_fee_rate;
+    Ok(())
 }
 
-fn update_protocol_fee_rate(amm_config: &mut Account<AmmConfig>, protocol_fee_rate: u64) {
-    assert!(protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
-    assert!(protocol_fee_rate + amm_config.fund_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
-    amm_config.protocol_fee_rate = protocol_fee_rate;
+fn remaining_account_key(remaining_accounts: &[AccountInfo]) -> Result<Pubkey> {
+    match remaining_accounts.iter().next() {
+        Some(account) => Ok(account.key()),
+        None => err!(GammaError::InvalidInput),
+    }
 }
 
-fn update_fund_fee_rate(amm_config: &mut Account<AmmConfig>, fund_fee_rate: u64) {
-    assert!(fund_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
-    assert!(fund_fee_rate + amm_config.protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
-    amm_config.fund_fee_rate = fund_fee_rate;
+fn update_trade_fee_rate(amm_config: &mut Account<AmmConfig>, trade_fee_rate: u64) -> Result<()> {
+    require_gte!(
+        FEE_RATE_DENOMINATOR_VALUE,
+        trade_fee_rate,
+        GammaError::InvalidFee
+    );
+    amm_config.trade_fee_rate = trade_fee_rate;
+    Ok(())
 }
 
-fn set_new_protocol_owner(
+fn update_protocol_fee_rate(
     amm_config: &mut Account<AmmConfig>,
-    new_protocol_owner: Pubkey,
+    protocol_fee_rate: u64,
 ) -> Result<()> {
-    require_keys_neq!(amm_config.protocol_owner, new_protocol_owner);
-    require_keys_neq!(new_protocol_owner, Pubkey::default());
-    #[cfg(feature = "enable-log")]
-    msg!(
-        "amm_config, old_protocol_owner:{}, new_owner:{}",
-        amm_config.protocol_owner.to_string(),
-        new_protocol_owner.key().to_string()
+    require_gte!(
+        FEE_RATE_DENOMINATOR_VALUE,
+        protocol_fee_rate
+            .checked_add(amm_config.fund_fee_rate)
+            .ok_or(GammaError::MathOverflow)?,
+        GammaError::InvalidFee
     );
-    amm_config.protocol_owner = new_protocol_owner;
+    amm_config.protocol_fee_rate = protocol_fee_rate;
     Ok(())
 }
 
-fn set_new_secondary_admin(
-    amm_config: &mut Account<AmmConfig>,
-    new_secondary_admin: Pubkey,
-) -> Result<()> {
-    require_keys_neq!(amm_config.secondary_admin, new_secondary_admin);
-    require_keys_neq!(new_secondary_admin, Pubkey::default());
-    #[cfg(feature = "enable-log")]
-    msg!(
-        "amm_config, old_secondary_admin:{}, new_owner:{}",
-        amm_config.secondary_admin.to_string(),
-        new_secondary_admin.key().to_string()
+fn update_fund_fee_rate(amm_config: &mut Account<AmmConfig>, fund_fee_rate: u64) -> Result<()> {
+    require_gte!(
+        FEE_RATE_DENOMINATOR_VALUE,
+        fund_fee_rate
+            .checked_add(amm_config.protocol_fee_rate)
+            .ok_or(GammaError::MathOverflow)?,
+        GammaError::InvalidFee
     );
-    amm_config.secondary_admin = new_secondary_admin;
+    amm_config.fund_fee_rate = fund_fee_rate;
     Ok(())
 }
 
-fn set_new_fund_owner(amm_config: &mut Account<AmmConfig>, new_fund_owner: Pubkey) -> Result<()> {
-    require_keys_neq!(amm_config.fund_owner, new_fund_owner);
-    require_keys_neq!(new_fund_owner, Pubkey::default());
+fn propose_role_transfer(
+    amm_config: &mut Account<AmmConfig>,
+    authority: Pubkey,
+    role: ConfigRole,
+    pending_key: Pubkey,
+) -> Result<()> {
+    // A single transfer can be pending, it has to be accepted or cancelled before the next one
+    require_eq!(amm_config.pending_role, 0, GammaError::RoleTransferPending);
+    let current_key = amm_config.role_key(role);
+    require_keys_neq!(current_key, pending_key);
+    require_keys_neq!(pending_key, Pubkey::default());
     #[cfg(feature = "enable-log")]
     msg!(
-        "amm_config, old_fund_owner:{}, new_owner:{}",
-        amm_config.fund_owner.to_string(),
-        new_fund_owner.key().to_string()
+        "amm_config, role:{}, current_key:{}, pending_key:{}",
+        role.name(),
+        current_key.to_string(),
+        pending_key.to_string()
     );
-    amm_config.fund_owner = new_fund_owner;
+    amm_config.pending_role = role as u8;
+    amm_config.pending_key = pending_key;
+
+    emit!(RoleTransferEvent {
+        amm_config: amm_config.key(),
+        authority,
+        role: role as u8,
+        current_key,
+        pending_key,
+        action: 0,
+    });
     Ok(())
 }
@@ -1,15 +1,15 @@
 use crate::fees::MAX_SHARED_WITH_KAMINO_RATE;
-use crate::states::AmmConfig;
+use crate::states::{
+    AmmConfig, GuardianResumeEvent, OracleUpdateMode, PoolUpdate, PoolUpdateEvent, Roles,
+    MAX_GUARDIAN_COOLDOWN, ROLES_SEED,
+};
 use crate::{error::GammaError, fees::FEE_RATE_DENOMINATOR_VALUE, states::PoolState};
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::clock;
 
 #[derive(Accounts)]
-#[instruction(param: u32, value: u64)]
 pub struct UpdatePool<'info> {
-    #[account(
-        constraint = check_authority(authority.key(), &amm_config, param)
-    )]
+    /// The admin, or a key holding the capability the update requires
     pub authority: Signer<'info>,
 
     #[account(mut)]
@@ -19,69 +19,182 @@ pub struct UpdatePool<'info> {
         constraint = amm_config.key() == pool_state.load()?.amm_config
     )]
     pub amm_config: Account<'info, AmmConfig>,
-}
 
-fn check_authority(authority: Pubkey, amm_config: &AmmConfig, param: u32) -> bool {
-    let params_update_allowed_with_secondary_admin = [3, 4];
-    if params_update_allowed_with_secondary_admin.contains(&param) {
-        return authority == amm_config.secondary_admin || authority == crate::admin::id();
-    }
-
-    authority == crate::admin::id()
+    /// CHECK: roles account granting capabilities, read by `Roles::load`. The hard-coded admin
+    /// keys are only trusted until it is created
+    #[account(seeds = [ROLES_SEED.as_bytes()], bump)]
+    pub roles: UncheckedAccount<'info>,
 }
 
-pub fn update_pool(ctx: Context<UpdatePool>, param: u32, value: u64) -> Result<()> {
-    match param {
-        0 => update_pool_status(ctx, value as u8),
-        1 => update_max_trade_fee_rate(ctx, value),
-        2 => update_volatility_factor(ctx, value),
-        3 => update_max_shared_token0(ctx, value),
-        4 => update_max_shared_token1(ctx, value),
-        5 => update_open_time(ctx),
-        _ => Err(GammaError::InvalidInput.into()),
+pub(crate) fn check_authority(
+    authority: Pubkey,
+    amm_config: &AmmConfig,
+    roles: Option<&Roles>,
+    update: &PoolUpdate,
+) -> bool {
+    // Like the hard-coded admin, the secondary admin of the config is only trusted for the
+    // updates it was allowed to make until the roles account is created, from then on it needs
+    // the capability as any other key
+    let secondary_admin_allowed = matches!(
+        update,
+        PoolUpdate::MaxSharedToken0 { .. }
+            | PoolUpdate::MaxSharedToken1 { .. }
+            | PoolUpdate::ResumeGuardianHalt
+    );
+    if roles.is_none() && secondary_admin_allowed && authority == amm_config.secondary_admin {
+        return true;
     }
+    Roles::is_granted(roles, authority, update.required_capability())
 }
 
-fn update_open_time(ctx: Context<UpdatePool>) -> Result<()> {
-    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
-    let block_timestamp = clock::Clock::get()?.unix_timestamp as u64;
-    pool_state.open_time = block_timestamp;
-    Ok(())
-}
-
-fn update_max_trade_fee_rate(ctx: Context<UpdatePool>, max_trade_fee_rate: u64) -> Result<()> {
-    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
-    pool_state.max_trade_fee_rate = max_trade_fee_rate;
-    require_gt!(FEE_RATE_DENOMINATOR_VALUE, max_trade_fee_rate);
-    Ok(())
+pub fn update_pool(ctx: Context<UpdatePool>, param: u32, value: u64) -> Result<()> {
+    let update = match param {
+        0 => PoolUpdate::Status {
+            status: u8::try_from(value).or(err!(GammaError::InvalidInput))?,
+        },
+        1 => PoolUpdate::MaxTradeFeeRate {
+            max_trade_fee_rate: value,
+        },
+        2 => PoolUpdate::VolatilityFactor {
+            volatility_factor: value,
+        },
+        3 => PoolUpdate::MaxSharedToken0 {
+            max_shared_token0: value,
+        },
+        4 => PoolUpdate::MaxSharedToken1 {
+            max_shared_token1: value,
+        },
+        5 => PoolUpdate::OpenTimeNow,
+        6 => PoolUpdate::GuardianDeviationThreshold {
+            guardian_deviation_threshold: u32::try_from(value)
+                .or(err!(GammaError::InvalidInput))?,
+        },
+        7 => PoolUpdate::GuardianCooldown {
+            guardian_cooldown: u32::try_from(value).or(err!(GammaError::InvalidInput))?,
+        },
+        8 => PoolUpdate::ResumeGuardianHalt,
+        9 => PoolUpdate::OracleUpdateMode {
+            oracle_update_mode: u8::try_from(value).or(err!(GammaError::InvalidInput))?,
+        },
+        10 => PoolUpdate::Verified {
+            verified: value != 0,
+        },
+        _ => return err!(GammaError::InvalidInput),
+    };
+    apply_pool_update(ctx, update)
 }
 
-fn update_max_shared_token0(ctx: Context<UpdatePool>, max_shared_token0: u64) -> Result<()> {
+pub fn apply_pool_update(ctx: Context<UpdatePool>, update: PoolUpdate) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    require!(
+        check_authority(
+            authority,
+            &ctx.accounts.amm_config,
+            Roles::load(&ctx.accounts.roles)?.as_ref(),
+            &update
+        ),
+        GammaError::InvalidOwner
+    );
+    let pool_id = ctx.accounts.pool_state.key();
     let mut pool_state = ctx.accounts.pool_state.load_mut()?;
-    pool_state.max_shared_token0 = max_shared_token0;
-    require_gte!(MAX_SHARED_WITH_KAMINO_RATE, max_shared_token0);
-    require_gt!(FEE_RATE_DENOMINATOR_VALUE, max_shared_token0);
-    Ok(())
+    require!(
+        ctx.accounts.amm_config.timelock_delay == 0 || update.is_emergency(&pool_state),
+        GammaError::TimelockRequired
+    );
+    execute_pool_update(pool_id, &mut pool_state, authority, update)
 }
 
-fn update_max_shared_token1(ctx: Context<UpdatePool>, max_shared_token1: u64) -> Result<()> {
-    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
-    pool_state.max_shared_token1 = max_shared_token1;
-    require_gte!(MAX_SHARED_WITH_KAMINO_RATE, max_shared_token1);
-    require_gt!(FEE_RATE_DENOMINATOR_VALUE, max_shared_token1);
-    Ok(())
-}
+/// Applies `update` without authority or timelock checks, shared with queued updates
+pub(crate) fn execute_pool_update(
+    pool_id: Pubkey,
+    pool_state: &mut PoolState,
+    authority: Pubkey,
+    update: PoolUpdate,
+) -> Result<()> {
+    let old_value = update.current_value(pool_state);
+    match update {
+        PoolUpdate::Status { status } => {
+            pool_state.set_status(status);
+            pool_state.recent_epoch = Clock::get()?.epoch;
+        }
+        PoolUpdate::MaxTradeFeeRate { max_trade_fee_rate } => {
+            require_gt!(
+                FEE_RATE_DENOMINATOR_VALUE,
+                max_trade_fee_rate,
+                GammaError::InvalidFee
+            );
+            pool_state.max_trade_fee_rate = max_trade_fee_rate;
+        }
+        PoolUpdate::VolatilityFactor { volatility_factor } => {
+            require_gte!(
+                FEE_RATE_DENOMINATOR_VALUE,
+                volatility_factor,
+                GammaError::InvalidInput
+            );
+            pool_state.volatility_factor = volatility_factor;
+        }
+        PoolUpdate::MaxSharedToken0 { max_shared_token0 } => {
+            require_gte!(
+                MAX_SHARED_WITH_KAMINO_RATE,
+                max_shared_token0,
+                GammaError::InvalidInput
+            );
+            pool_state.max_shared_token0 = max_shared_token0;
+        }
+        PoolUpdate::MaxSharedToken1 { max_shared_token1 } => {
+            require_gte!(
+                MAX_SHARED_WITH_KAMINO_RATE,
+                max_shared_token1,
+                GammaError::InvalidInput
+            );
+            pool_state.max_shared_token1 = max_shared_token1;
+        }
+        PoolUpdate::OpenTimeNow => {
+            pool_state.open_time = clock::Clock::get()?.unix_timestamp as u64;
+        }
+        PoolUpdate::GuardianDeviationThreshold {
+            guardian_deviation_threshold,
+        } => {
+            require_gt!(
+                FEE_RATE_DENOMINATOR_VALUE,
+                guardian_deviation_threshold as u64,
+                GammaError::InvalidInput
+            );
+            pool_state.guardian_deviation_threshold = guardian_deviation_threshold;
+        }
+        PoolUpdate::GuardianCooldown { guardian_cooldown } => {
+            require_gte!(
+                MAX_GUARDIAN_COOLDOWN,
+                guardian_cooldown as u64,
+                GammaError::InvalidInput
+            );
+            pool_state.guardian_cooldown = guardian_cooldown;
+        }
+        PoolUpdate::ResumeGuardianHalt => {
+            pool_state.resume_guardian_halt();
+            emit!(GuardianResumeEvent {
+                pool_id,
+                resumed_by: authority,
+            });
+        }
+        PoolUpdate::OracleUpdateMode { oracle_update_mode } => {
+            // Validate the mode
+            OracleUpdateMode::new(oracle_update_mode)?;
+            pool_state.oracle_update_mode = oracle_update_mode;
+            // A price recorded under an earlier mode is stale, the next swap records it again
+            pool_state.oracle_token_0_price_x32 = 0;
+            pool_state.oracle_token_1_price_x32 = 0;
+        }
+        PoolUpdate::Verified { verified } => pool_state.verified = verified as u8,
+    }
 
-fn update_volatility_factor(ctx: Context<UpdatePool>, volatility_factor: u64) -> Result<()> {
-    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
-    pool_state.volatility_factor = volatility_factor;
-    Ok(())
-}
+    emit!(PoolUpdateEvent {
+        pool_id,
+        authority,
+        param: update.name(),
+        old_value,
+        new_value: update.current_value(pool_state),
+    });
 
-fn update_pool_status(ctx: Context<UpdatePool>, status: u8) -> Result<()> {
-    require_gte!(255, status);
-    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
-    pool_state.set_status(status);
-    pool_state.recent_epoch = Clock::get()?.epoch;
     Ok(())
 }
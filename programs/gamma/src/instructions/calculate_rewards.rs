@@ -1,6 +1,9 @@
 use crate::{
     error::GammaError,
-    states::{PoolState, RewardInfo, UserPoolLiquidity, UserRewardInfo, USER_POOL_LIQUIDITY_SEED},
+    states::{
+        Capability, PoolState, RewardInfo, Roles, UserPoolLiquidity, UserRewardInfo, ROLES_SEED,
+        USER_POOL_LIQUIDITY_SEED,
+    },
     USER_REWARD_INFO_SEED,
 };
 use anchor_lang::prelude::*;
@@ -52,31 +55,28 @@ pub struct CalculateRewards<'info> {
     )]
     pub user_pool_liquidity: Account<'info, UserPoolLiquidity>,
 
+    /// CHECK: roles account granting capabilities, read by `Roles::load`. The hard-coded admin
+    /// keys are only trusted until it is created
+    #[account(seeds = [ROLES_SEED.as_bytes()], bump)]
+    pub roles: UncheckedAccount<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
 pub fn calculate_rewards(ctx: Context<CalculateRewards>) -> Result<()> {
     #[cfg(not(feature = "test-sbf"))]
-    if ctx.accounts.signer.key() != crate::CALCULATE_REWARDS_ADMIN {
+    if !Roles::is_granted(
+        Roles::load(&ctx.accounts.roles)?.as_ref(),
+        ctx.accounts.signer.key(),
+        Capability::RewardsOperator,
+    ) {
         return err!(GammaError::InvalidOwner);
     }
 
     let pool_state = &mut ctx.accounts.pool_state.load()?;
-    let current_time = Clock::get()?.unix_timestamp as u64;
-    if ctx.accounts.user_reward_info.rewards_last_calculated_at >= current_time {
-        return Ok(());
-    }
-    // Start accrual of rewards from the time user first deposit.
-    // This prevents the user from creating a invest at the end of rewards and getting
-    // boosted rewards for the full period.
-    if ctx.accounts.user_reward_info.rewards_last_calculated_at == 0 {
-        ctx.accounts.user_reward_info.rewards_last_calculated_at =
-            ctx.accounts.user_pool_liquidity.first_investment_at;
-    }
-
     let user_reward_info = &mut ctx.accounts.user_reward_info;
-    user_reward_info.calculate_claimable_rewards(
-        ctx.accounts.user_pool_liquidity.lp_tokens_owned as u64,
+    user_reward_info.settle(
+        &ctx.accounts.user_pool_liquidity,
         pool_state.lp_supply as u64,
         &ctx.accounts.reward_info,
     )?;
@@ -0,0 +1,164 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+
+use crate::{
+    states::{
+        Partner, PartnerFeesClaimEvent, PoolPartner, PoolState, PARTNER_SEED, POOL_PARTNER_SEED,
+    },
+    utils::transfer_from_pool_vault_to_user,
+};
+
+#[derive(Accounts)]
+pub struct ClaimPartnerFees<'info> {
+    /// Anyone can claim, the fees only go to the payout wallet of the partner
+    pub payer: Signer<'info>,
+
+    /// CHECK: pool vault authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// Pool state stores the partner fees set aside
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        seeds = [
+            PARTNER_SEED.as_bytes(),
+            partner.id.to_le_bytes().as_ref(),
+        ],
+        bump = partner.bump,
+    )]
+    pub partner: Account<'info, Partner>,
+
+    #[account(
+        mut,
+        seeds = [
+            POOL_PARTNER_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            partner.id.to_le_bytes().as_ref(),
+        ],
+        bump = pool_partner.bump,
+    )]
+    pub pool_partner: Account<'info, PoolPartner>,
+
+    /// The address that holds pool tokens for token_0
+    #[account(
+        mut,
+        constraint = token_0_vault.key() == pool_state.load()?.token_0_vault
+    )]
+    pub token_0_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for token_1
+    #[account(
+        mut,
+        constraint = token_1_vault.key() == pool_state.load()?.token_1_vault
+    )]
+    pub token_1_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of token_0 vault
+    #[account(
+        address = token_0_vault.mint
+    )]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of token_1 vault
+    #[account(
+        address = token_1_vault.mint
+    )]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Token account of the payout wallet receiving token_0
+    #[account(
+        mut,
+        token::mint = vault_0_mint,
+        token::authority = partner.payout_wallet,
+    )]
+    pub payout_token_0_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token account of the payout wallet receiving token_1
+    #[account(
+        mut,
+        token::mint = vault_1_mint,
+        token::authority = partner.payout_wallet,
+    )]
+    pub payout_token_1_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The SPL program to perform token transfers
+    pub token_program: Program<'info, Token>,
+
+    /// The SPL program 2022 to perform token transfers
+    pub token_program_2022: Program<'info, Token2022>,
+}
+
+/// Pays the revenue share of the fees accrued by the partner since the last claim, at the rate of
+/// the partner at the previous claim. The rest of them is released to the protocol fees.
+pub fn claim_partner_fees<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, ClaimPartnerFees<'info>>,
+) -> Result<()> {
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_partner = &mut ctx.accounts.pool_partner;
+    let amount_0: u64;
+    let amount_1: u64;
+    let auth_bump: u8;
+    {
+        let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+        // The accrued fees leave the partner fees at the rate they accrued at, only the revenue
+        // share leaves the protocol fees
+        (amount_0, amount_1) = pool_partner.settle(&mut pool_state)?;
+        pool_partner.revenue_share_rate = ctx.accounts.partner.revenue_share_rate;
+        pool_state.release_partner_fees_rounding();
+
+        auth_bump = pool_state.auth_bump;
+        pool_state.recent_epoch = Clock::get()?.epoch;
+
+        emit!(PartnerFeesClaimEvent {
+            pool_id,
+            partner_id: pool_partner.partner_id,
+            payout_wallet: ctx.accounts.partner.payout_wallet,
+            amount_0,
+            amount_1,
+            remaining_partner_fees_token_0: pool_state.partner_fees_token_0,
+            remaining_partner_fees_token_1: pool_state.partner_fees_token_1,
+        });
+    }
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_0_vault.to_account_info(),
+        ctx.accounts.payout_token_0_account.to_account_info(),
+        ctx.accounts.vault_0_mint.to_account_info(),
+        if ctx.accounts.vault_0_mint.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        },
+        amount_0,
+        ctx.accounts.vault_0_mint.decimals,
+        ctx.remaining_accounts,
+        &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
+    )?;
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_1_vault.to_account_info(),
+        ctx.accounts.payout_token_1_account.to_account_info(),
+        ctx.accounts.vault_1_mint.to_account_info(),
+        if ctx.accounts.vault_1_mint.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        },
+        amount_1,
+        ctx.accounts.vault_1_mint.decimals,
+        ctx.remaining_accounts,
+        &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
+    )?;
+
+    Ok(())
+}
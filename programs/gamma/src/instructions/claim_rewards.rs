@@ -1,5 +1,5 @@
 use crate::{
-    states::{PoolState, RewardInfo, UserRewardInfo},
+    states::{check_position_signer, OperatorPermission, PoolState, RewardInfo, UserRewardInfo},
     utils::transfer_from_pool_vault_to_user,
     USER_REWARD_INFO_SEED,
 };
@@ -49,10 +49,11 @@ pub struct ClaimRewards<'info> {
     )]
     pub reward_vault: Box<InterfaceAccount<'info, TokenAccount>>,
 
+    /// Token account of the owner of the rewards
     #[account(
         mut,
         token::mint = reward_mint,
-        token::authority = user,
+        token::authority = user_reward_info.user,
     )]
     pub user_token_account: InterfaceAccount<'info, TokenAccount>,
 
@@ -62,7 +63,7 @@ pub struct ClaimRewards<'info> {
         seeds = [
             USER_REWARD_INFO_SEED.as_bytes(),
             reward_info.key().as_ref(),
-            user.key().as_ref(),
+            user_reward_info.user.as_ref(),
         ],
         bump,
     )]
@@ -82,7 +83,16 @@ pub struct ClaimRewards<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+pub fn claim_rewards<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, ClaimRewards<'info>>,
+) -> Result<()> {
+    check_position_signer(
+        ctx.accounts.pool_state.key(),
+        ctx.accounts.user_reward_info.user,
+        ctx.accounts.user.key(),
+        ctx.remaining_accounts,
+        OperatorPermission::ClaimRewards,
+    )?;
     let user_reward_info = &mut ctx.accounts.user_reward_info;
     let total_claimable_rewards = user_reward_info.get_total_claimable_rewards();
     if total_claimable_rewards == 0 {
@@ -103,6 +113,7 @@ pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
         },
         total_claimable_rewards,
         ctx.accounts.reward_mint.decimals,
+        ctx.remaining_accounts,
         &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
     )?;
 
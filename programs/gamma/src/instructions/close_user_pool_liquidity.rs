@@ -0,0 +1,163 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::GammaError,
+    states::{
+        close_position_operator, PoolState, RewardInfo, UserPoolLiquidity, UserRewardInfo,
+        POSITION_OPERATOR_SEED, USER_POOL_LIQUIDITY_SEED,
+    },
+    USER_REWARD_INFO_SEED,
+};
+
+#[derive(Accounts)]
+pub struct CloseUserPoolLiquidity<'info> {
+    /// Owner of the position, receives the rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: only the address is used, the pool may be closed already
+    pub pool_state: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump,
+        close = owner,
+    )]
+    pub user_pool_liquidity: Box<Account<'info, UserPoolLiquidity>>,
+
+    /// CHECK: PositionOperator of the position, closed with it when the owner approved one so it
+    /// does not carry over to a position opened again
+    #[account(
+        mut,
+        seeds = [
+            POSITION_OPERATOR_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub position_operator: UncheckedAccount<'info>,
+}
+
+/// Closes an empty position.
+/// Remaining accounts: a (RewardInfo, UserRewardInfo of the owner) pair for each of the
+/// `reward_info_count` rewards of the pool sorted by RewardInfo address. The owner must have
+/// claimed every reward, nothing is left to check once the pool is closed. A pool with an older
+/// layout has to be resized and its rewards counted with `migrate_reward_info_count` first.
+pub fn close_user_pool_liquidity<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, CloseUserPoolLiquidity<'info>>,
+) -> Result<()>
+where
+    'c: 'info,
+{
+    let user_pool_liquidity = &ctx.accounts.user_pool_liquidity;
+    require_eq!(
+        user_pool_liquidity.lp_tokens_locked,
+        0,
+        GammaError::LpLocked
+    );
+    require_eq!(
+        user_pool_liquidity.lp_tokens_owned,
+        0,
+        GammaError::PositionNotEmpty
+    );
+
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state_info = ctx.accounts.pool_state.to_account_info();
+    // close_pool leaves the pool empty, there are no rewards left to check then
+    if !pool_state_info.data_is_empty() {
+        require_keys_eq!(
+            *pool_state_info.owner,
+            crate::id(),
+            GammaError::InvalidInput
+        );
+        // A pool with an older layout has to go through resize_pool_state first
+        require_gte!(
+            pool_state_info.data_len(),
+            PoolState::LEN,
+            GammaError::InvalidInput
+        );
+        let pool_state = PoolState::try_deserialize(&mut &pool_state_info.try_borrow_data()?[..])?;
+        // The rewards of a pool created before they were counted are not all known
+        require_eq!(
+            pool_state.reward_info_count_migrated,
+            1,
+            GammaError::RewardInfoCountNotMigrated
+        );
+        let reward_accounts_len = usize::try_from(pool_state.reward_info_count)
+            .map_err(|_| GammaError::MathOverflow)?
+            .checked_mul(2)
+            .ok_or(GammaError::MathOverflow)?;
+        require_gte!(
+            ctx.remaining_accounts.len(),
+            reward_accounts_len,
+            GammaError::UnclaimedRewards
+        );
+        check_rewards_claimed(
+            pool_id,
+            user_pool_liquidity.user,
+            &ctx.remaining_accounts[..reward_accounts_len],
+        )?;
+    }
+
+    close_position_operator(
+        &ctx.accounts.position_operator.to_account_info(),
+        &ctx.accounts.owner.to_account_info(),
+    )?;
+
+    // The PoolPartner of the partner of the position holds none of its LP anymore, as
+    // lp_token_linked_with_partner only counts lp_tokens_owned. Closing the account is all that
+    // is left to detach it.
+    Ok(())
+}
+
+/// Every reward of the pool must have nothing left to claim for the owner, its UserRewardInfo
+/// is left uninitialized when no rewards were calculated for the position
+fn check_rewards_claimed<'info>(
+    pool_id: Pubkey,
+    owner: Pubkey,
+    reward_accounts: &'info [AccountInfo<'info>],
+) -> Result<()> {
+    let mut previous_reward_info = Pubkey::default();
+    for accounts in reward_accounts.chunks(2) {
+        let reward_info = Account::<RewardInfo>::try_from(&accounts[0])?;
+        require_keys_eq!(reward_info.pool, pool_id, GammaError::InvalidInput);
+        // Sorted so that each RewardInfo of the pool is counted once
+        require!(
+            reward_info.key() > previous_reward_info,
+            GammaError::InvalidInput
+        );
+        previous_reward_info = reward_info.key();
+
+        let user_reward_info_info = &accounts[1];
+        let (user_reward_info_key, __bump) = Pubkey::find_program_address(
+            &[
+                USER_REWARD_INFO_SEED.as_bytes(),
+                reward_info.key().as_ref(),
+                owner.as_ref(),
+            ],
+            &crate::id(),
+        );
+        require_keys_eq!(
+            user_reward_info_info.key(),
+            user_reward_info_key,
+            GammaError::InvalidInput
+        );
+        if *user_reward_info_info.owner != crate::id() {
+            continue;
+        }
+        let user_reward_info =
+            UserRewardInfo::try_deserialize(&mut &user_reward_info_info.try_borrow_data()?[..])?;
+        require_eq!(
+            user_reward_info.get_total_claimable_rewards(),
+            0,
+            GammaError::UnclaimedRewards
+        );
+    }
+    Ok(())
+}
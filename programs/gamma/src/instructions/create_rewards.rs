@@ -1,7 +1,7 @@
 use crate::{
     error::GammaError,
     states::{PoolState, RewardInfo},
-    utils::transfer_from_user_to_pool_vault,
+    utils::{thaw_vault_if_frozen, transfer_from_user_to_pool_vault},
     REWARD_VAULT_SEED,
 };
 use anchor_lang::prelude::*;
@@ -25,7 +25,7 @@ pub struct CreateRewards<'info> {
     )]
     pub authority: UncheckedAccount<'info>,
 
-    #[account()]
+    #[account(mut)]
     pub pool_state: AccountLoader<'info, PoolState>,
 
     #[account(
@@ -75,8 +75,8 @@ pub struct CreateRewards<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn create_rewards(
-    ctx: Context<CreateRewards>,
+pub fn create_rewards<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, CreateRewards<'info>>,
     start_time: u64,
     end_time: u64,
     reward_amount: u64,
@@ -90,6 +90,20 @@ pub fn create_rewards(
         return err!(GammaError::InvalidRewardTime);
     }
 
+    let token_program =
+        if ctx.accounts.reward_mint.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        };
+    let reward_provider = [ctx.accounts.reward_provider.to_account_info()];
+    thaw_vault_if_frozen(
+        &token_program,
+        &ctx.accounts.reward_vault.to_account_info(),
+        &ctx.accounts.reward_mint.to_account_info(),
+        &[&reward_provider[..], ctx.remaining_accounts].concat(),
+    )?;
+
     transfer_from_user_to_pool_vault(
         ctx.accounts.reward_provider.to_account_info(),
         ctx.accounts
@@ -97,13 +111,10 @@ pub fn create_rewards(
             .to_account_info(),
         ctx.accounts.reward_vault.to_account_info(),
         ctx.accounts.reward_mint.to_account_info(),
-        if ctx.accounts.reward_mint.to_account_info().owner == ctx.accounts.token_program.key {
-            ctx.accounts.token_program.to_account_info()
-        } else {
-            ctx.accounts.token_program_2022.to_account_info()
-        },
+        token_program,
         reward_amount,
         ctx.accounts.reward_mint.decimals,
+        ctx.remaining_accounts,
     )?;
     ctx.accounts.reward_vault.reload()?;
 
@@ -119,5 +130,11 @@ pub fn create_rewards(
 
     reward_info.pool = ctx.accounts.pool_state.key();
 
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    pool_state.reward_info_count = pool_state
+        .reward_info_count
+        .checked_add(1)
+        .ok_or(GammaError::MathOverflow)?;
+
     Ok(())
 }
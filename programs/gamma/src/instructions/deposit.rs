@@ -2,8 +2,8 @@ use crate::{
     curve::{CurveCalculator, RoundDirection},
     error::GammaError,
     states::{
-        LpChangeEvent, PartnerType, PoolState, PoolStatusBitIndex, UserPoolLiquidity,
-        USER_POOL_LIQUIDITY_SEED,
+        check_position_signer, update_pool_partner_lp, LpChangeEvent, OperatorPermission,
+        PoolState, PoolStatusBitIndex, UserPoolLiquidity, USER_POOL_LIQUIDITY_SEED,
     },
     utils::{get_transfer_inverse_fee, transfer_from_user_to_pool_vault},
 };
@@ -15,7 +15,7 @@ use anchor_spl::{
 
 #[derive(Accounts)]
 pub struct Deposit<'info> {
-    /// Owner of the liquidity provided
+    /// Owner of the liquidity provided, or an operator approved by the owner
     pub owner: Signer<'info>,
 
     /// CHECK: pool vault authority
@@ -36,7 +36,7 @@ pub struct Deposit<'info> {
         seeds = [
             USER_POOL_LIQUIDITY_SEED.as_bytes(),
             pool_state.key().as_ref(),
-            owner.key().as_ref(),
+            user_pool_liquidity.user.as_ref(),
         ],
         bump,
     )]
@@ -90,30 +90,44 @@ pub struct Deposit<'info> {
     pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
 }
 
-pub fn deposit(
-    ctx: Context<Deposit>,
+pub fn deposit<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, Deposit<'info>>,
     lp_token_amount: u64,
     maximum_token_0_amount: u64,
     maximum_token_1_amount: u64,
 ) -> Result<()> {
     deposit_to_gamma_pool(
         ctx.accounts,
+        ctx.remaining_accounts,
         lp_token_amount,
         maximum_token_0_amount,
         maximum_token_1_amount,
     )
 }
 
-pub fn deposit_to_gamma_pool(
-    accounts: &mut Deposit,
+/// `remaining_accounts` are passed to the token transfers for the token-2022 extensions,
+/// and hold the PositionOperator when an operator signs and the PoolPartner of the partner
+/// of the position once the pool has PoolPartner accounts
+pub fn deposit_to_gamma_pool<'info>(
+    accounts: &mut Deposit<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
     lp_token_amount: u64,
     maximum_token_0_amount: u64,
     maximum_token_1_amount: u64,
 ) -> Result<()> {
     require_gt!(lp_token_amount, 0);
     let pool_id = accounts.pool_state.key();
+    check_position_signer(
+        pool_id,
+        accounts.user_pool_liquidity.user,
+        accounts.owner.key(),
+        remaining_accounts,
+        OperatorPermission::Deposit,
+    )?;
     let pool_state = &mut accounts.pool_state.load_mut()?;
-    if !pool_state.get_status_by_bit(PoolStatusBitIndex::Deposit) {
+    if !pool_state.get_status_by_bit(PoolStatusBitIndex::Deposit)
+        || !pool_state.get_status_by_bit(PoolStatusBitIndex::ReduceOnly)
+    {
         return err!(GammaError::NotApproved);
     }
     let (total_token_0_amount, total_token_1_amount) = pool_state.vault_amount_without_fee()?;
@@ -196,6 +210,7 @@ pub fn deposit_to_gamma_pool(
         },
         transfer_token_0_amount,
         accounts.vault_0_mint.decimals,
+        remaining_accounts,
     )?;
 
     transfer_from_user_to_pool_vault(
@@ -210,6 +225,7 @@ pub fn deposit_to_gamma_pool(
         },
         transfer_token_1_amount,
         accounts.vault_1_mint.decimals,
+        remaining_accounts,
     )?;
 
     pool_state.token_0_vault_amount = pool_state
@@ -226,6 +242,7 @@ pub fn deposit_to_gamma_pool(
         .checked_add(lp_token_amount)
         .ok_or(GammaError::MathOverflow)?;
     let user_pool_liquidity = &mut accounts.user_pool_liquidity;
+    user_pool_liquidity.update_fees_earned(pool_state)?;
     user_pool_liquidity.token_0_deposited = user_pool_liquidity
         .token_0_deposited
         .checked_add(u128::from(token_0_amount))
@@ -240,18 +257,13 @@ pub fn deposit_to_gamma_pool(
         .ok_or(GammaError::MathOverflow)?;
     pool_state.recent_epoch = Clock::get()?.epoch;
 
-    if let Some(user_pool_liquidity_partner) = user_pool_liquidity.partner {
-        let mut pool_state_partners = pool_state.partners;
-        let partner: Option<&mut crate::states::PartnerInfo> = pool_state_partners
-            .iter_mut()
-            .find(|p| PartnerType::new(p.partner_id) == user_pool_liquidity_partner);
-        if let Some(partner) = partner {
-            partner.lp_token_linked_with_partner = partner
-                .lp_token_linked_with_partner
-                .checked_add(lp_token_amount)
-                .ok_or(GammaError::MathOverflow)?;
-        }
-        pool_state.partners = pool_state_partners;
-    }
+    update_pool_partner_lp(
+        pool_id,
+        pool_state,
+        user_pool_liquidity.partner,
+        remaining_accounts,
+        lp_token_amount,
+        true,
+    )?;
     Ok(())
 }
@@ -0,0 +1,104 @@
+use crate::error::GammaError;
+use crate::fees::{DynamicFee, VOLATILITY_WINDOW};
+use crate::states::{oracle, ObservationState, PoolState, Q32};
+use crate::utils::U256;
+use anchor_lang::prelude::*;
+
+// Shortest span of observations the TWAP pricing the LP may be computed over. In every
+// OracleUpdateMode the observations are at least OBSERVATION_UPDATE_DURATION_DEFAULT apart, so
+// the OBSERVATION_NUM observations of a pool trading every 15 seconds span 1485 seconds
+pub const LP_PRICE_MIN_TWAP_WINDOW: u64 = 1200; // 20 minutes
+
+#[derive(Accounts)]
+pub struct GetLpPrice<'info> {
+    /// The program account of the pool to price the LP of
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The program account for the most recent oracle observation
+    #[account(address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LpPrice {
+    /// Value of one LP unit in terms of token_0, Q32.32
+    pub token_0_per_lp_x32: u128,
+    /// Value of one LP unit in terms of token_1, Q32.32
+    pub token_1_per_lp_x32: u128,
+    /// The token_0 TWAP (token_1 per token_0) used for pricing, Q32.32
+    pub twap_token_0_price_x32: u128,
+}
+
+/// Prices the LP with the fair-reserves formula so that it cannot be moved by
+/// manipulating the spot reserves within a transaction:
+///   k = x * y, p = TWAP of token_0 in token_1
+///   fair_x = sqrt(k / p), fair_y = sqrt(k * p)
+///   LP value = 2 * fair_x (in token_0) = 2 * fair_y (in token_1), divided by lp_supply
+/// The reserves include the amounts deployed to Kamino. The observations of the TWAP have to
+/// span at least `LP_PRICE_MIN_TWAP_WINDOW`.
+pub fn get_lp_price(ctx: Context<GetLpPrice>) -> Result<LpPrice> {
+    let pool_state = ctx.accounts.pool_state.load()?;
+    let observation_state = ctx.accounts.observation_state.load()?;
+
+    let lp_supply = pool_state.lp_supply;
+    if lp_supply == 0 {
+        return err!(GammaError::EmptySupply);
+    }
+
+    let block_timestamp = oracle::block_timestamp()?;
+    // A TWAP over observations seconds apart is as easy to move as the spot price
+    if observation_state.observed_window(block_timestamp, VOLATILITY_WINDOW)
+        < LP_PRICE_MIN_TWAP_WINDOW
+    {
+        return err!(GammaError::InsufficientObservations);
+    }
+    let (_, _, twap_token_0_price_x32) =
+        DynamicFee::get_price_range(&observation_state, block_timestamp, VOLATILITY_WINDOW)?;
+    if twap_token_0_price_x32 == 0 {
+        return err!(GammaError::InsufficientObservations);
+    }
+
+    let (token_0_amount, token_1_amount) = pool_state.vault_amount_without_fee()?;
+    let constant_product = U256::from(token_0_amount)
+        .checked_mul(U256::from(token_1_amount))
+        .ok_or(GammaError::MathOverflow)?;
+    let q32 = U256::from(Q32);
+    let twap = U256::from(twap_token_0_price_x32);
+
+    // 2 * sqrt(k / p) * Q32 = 2 * sqrt(k * Q32^3 / p_x32)
+    let token_0_per_lp_x32 = constant_product
+        .checked_mul(q32 * q32 * q32)
+        .ok_or(GammaError::MathOverflow)?
+        .checked_div(twap)
+        .ok_or(GammaError::MathOverflow)?
+        .integer_sqrt()
+        .checked_mul(U256::from(2))
+        .ok_or(GammaError::MathOverflow)?
+        .checked_div(U256::from(lp_supply))
+        .ok_or(GammaError::MathOverflow)?;
+
+    // 2 * sqrt(k * p) * Q32 = 2 * sqrt(k * p_x32 * Q32)
+    let token_1_per_lp_x32 = constant_product
+        .checked_mul(twap)
+        .ok_or(GammaError::MathOverflow)?
+        .checked_mul(q32)
+        .ok_or(GammaError::MathOverflow)?
+        .integer_sqrt()
+        .checked_mul(U256::from(2))
+        .ok_or(GammaError::MathOverflow)?
+        .checked_div(U256::from(lp_supply))
+        .ok_or(GammaError::MathOverflow)?;
+
+    Ok(LpPrice {
+        token_0_per_lp_x32: to_u128(token_0_per_lp_x32)?,
+        token_1_per_lp_x32: to_u128(token_1_per_lp_x32)?,
+        twap_token_0_price_x32,
+    })
+}
+
+fn to_u128(value: U256) -> Result<u128> {
+    if value > U256::from(u128::MAX) {
+        return err!(GammaError::MathOverflow);
+    }
+    Ok(value.as_u128())
+}
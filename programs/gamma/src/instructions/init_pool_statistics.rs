@@ -0,0 +1,35 @@
+use crate::states::{PoolState, PoolStatistics, POOL_STATISTICS_SEED};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitPoolStatistics<'info> {
+    /// Pays for the statistics account, anyone can create it
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The program account of the pool to track
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The ring buffer of per-epoch volume and fee statistics of the pool
+    #[account(
+        init,
+        seeds = [
+            POOL_STATISTICS_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+        payer = payer,
+        space = PoolStatistics::LEN
+    )]
+    pub pool_statistics: AccountLoader<'info, PoolStatistics>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init_pool_statistics(ctx: Context<InitPoolStatistics>) -> Result<()> {
+    let mut pool_statistics = ctx.accounts.pool_statistics.load_init()?;
+    pool_statistics.pool_id = ctx.accounts.pool_state.key();
+    ctx.accounts.pool_state.load_mut()?.pool_statistics_enabled = 1;
+    Ok(())
+}
@@ -1,6 +1,8 @@
 use anchor_lang::prelude::*;
 
-use crate::states::{PartnerType, PoolState, UserPoolLiquidity, USER_POOL_LIQUIDITY_SEED};
+use crate::states::{
+    Partner, PoolState, UserPoolLiquidity, PARTNER_SEED, USER_POOL_LIQUIDITY_SEED,
+};
 
 #[derive(Accounts)]
 pub struct InitUserPoolLiquidity<'info> {
@@ -22,23 +24,28 @@ pub struct InitUserPoolLiquidity<'info> {
     )]
     pub user_pool_liquidity: Box<Account<'info, UserPoolLiquidity>>,
 
+    /// Partner of the registry the position is attributed to, if any
+    #[account(
+        seeds = [
+            PARTNER_SEED.as_bytes(),
+            partner.id.to_le_bytes().as_ref(),
+        ],
+        bump = partner.bump,
+    )]
+    pub partner: Option<Account<'info, Partner>>,
+
     /// To create a new program account
     pub system_program: Program<'info, System>,
 }
 
+/// `_partner` was the name of the partner before the registry, it is ignored
 pub fn init_user_pool_liquidity(
     ctx: Context<InitUserPoolLiquidity>,
-    partner: Option<String>,
+    _partner: Option<String>,
 ) -> Result<()> {
     let user_pool_liquidity = &mut ctx.accounts.user_pool_liquidity;
 
-    let partner = match partner {
-        Some(partner_value) => match partner_value.as_str() {
-            "AssetDash" => Some(PartnerType::AssetDash),
-            _ => None,
-        },
-        None => None,
-    };
+    let partner = ctx.accounts.partner.as_ref().map(|partner| partner.id);
     let current_time = Clock::get()?.unix_timestamp as u64;
 
     user_pool_liquidity.initialize(
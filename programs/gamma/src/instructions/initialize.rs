@@ -4,10 +4,13 @@ use crate::{
     curve::CurveCalculator,
     error::GammaError,
     states::{
-        AmmConfig, ObservationState, PoolState, UserPoolLiquidity, OBSERVATION_SEED, POOL_SEED,
-        POOL_VAULT_SEED, USER_POOL_LIQUIDITY_SEED,
+        check_pool_mints, AmmConfig, ObservationState, PoolState, UserPoolLiquidity,
+        OBSERVATION_SEED, POOL_SEED, POOL_VAULT_SEED, USER_POOL_LIQUIDITY_SEED,
+    },
+    utils::{
+        create_token_account, is_supported_mint, thaw_vault_if_frozen,
+        transfer_from_user_to_pool_vault, U128,
     },
-    utils::{create_token_account, is_supported_mint, transfer_from_user_to_pool_vault, U128},
     LOCK_LP_AMOUNT,
 };
 use anchor_lang::{
@@ -157,8 +160,8 @@ pub struct Initialize<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
-pub fn initialize(
-    ctx: Context<Initialize>,
+pub fn initialize<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, Initialize<'info>>,
     init_amount_0: u64,
     init_amount_1: u64,
     mut open_time: u64,
@@ -174,6 +177,18 @@ pub fn initialize(
     if ctx.accounts.amm_config.disable_create_pool {
         return err!(GammaError::NotApproved);
     }
+    // The registry accounts are passed as remaining accounts so existing clients keep working
+    // for configs without a mint registry
+    let verified = if ctx.accounts.amm_config.mint_registry_enabled {
+        check_pool_mints(
+            ctx.accounts.amm_config.key(),
+            ctx.remaining_accounts,
+            ctx.accounts.token_0_mint.key(),
+            ctx.accounts.token_1_mint.key(),
+        )?
+    } else {
+        false
+    };
     let block_timestamp = clock::Clock::get()?.unix_timestamp as u64;
     if open_time <= block_timestamp {
         open_time = block_timestamp + 1;
@@ -212,6 +227,23 @@ pub fn initialize(
         ][..]],
     )?;
 
+    // The freeze authority of a mint with a frozen default state signs as the creator
+    // or as one of the remaining accounts
+    let creator = [ctx.accounts.creator.to_account_info()];
+    let signers = [&creator[..], ctx.remaining_accounts].concat();
+    thaw_vault_if_frozen(
+        &ctx.accounts.token_0_program.to_account_info(),
+        &ctx.accounts.token_0_vault.to_account_info(),
+        &ctx.accounts.token_0_mint.to_account_info(),
+        &signers,
+    )?;
+    thaw_vault_if_frozen(
+        &ctx.accounts.token_1_program.to_account_info(),
+        &ctx.accounts.token_1_vault.to_account_info(),
+        &ctx.accounts.token_1_mint.to_account_info(),
+        &signers,
+    )?;
+
     let mut observation_state = ctx.accounts.observation_state.load_init()?;
     observation_state.pool_id = ctx.accounts.pool_state.key();
 
@@ -226,6 +258,7 @@ pub fn initialize(
         ctx.accounts.token_0_program.to_account_info(),
         init_amount_0,
         ctx.accounts.token_0_mint.decimals,
+        ctx.remaining_accounts,
     )?;
 
     transfer_from_user_to_pool_vault(
@@ -236,6 +269,7 @@ pub fn initialize(
         ctx.accounts.token_1_program.to_account_info(),
         init_amount_1,
         ctx.accounts.token_1_mint.decimals,
+        ctx.remaining_accounts,
     )?;
 
     let token_0_vault =
@@ -314,6 +348,7 @@ pub fn initialize(
         &ctx.accounts.token_1_mint,
         ctx.accounts.observation_state.key(),
     )?;
+    pool_state.verified = verified as u8;
 
     let user_pool_liquidity = &mut ctx.accounts.user_pool_liquidity;
     let current_time = Clock::get()?.unix_timestamp as u64;
@@ -329,5 +364,15 @@ pub fn initialize(
         .checked_sub(LOCK_LP_AMOUNT.into())
         .ok_or(GammaError::MathOverflow)?;
 
+    // The config can require the initial LP of the creator to stay in the pool for a while
+    let creator_lp_lock_duration = ctx.accounts.amm_config.creator_lp_lock_duration;
+    if creator_lp_lock_duration != 0 {
+        pool_state.creator_lp_locked = u64::try_from(user_pool_liquidity.lp_tokens_owned)
+            .or(err!(GammaError::MathOverflow))?;
+        pool_state.creator_lp_unlock_time = open_time
+            .checked_add(creator_lp_lock_duration)
+            .ok_or(GammaError::MathOverflow)?;
+    }
+
     Ok(())
 }
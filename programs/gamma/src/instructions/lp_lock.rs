@@ -0,0 +1,158 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::GammaError,
+    states::{
+        LpLock, LpLockEvent, PoolState, UserPoolLiquidity, LP_LOCK_SEED, USER_POOL_LIQUIDITY_SEED,
+    },
+};
+
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct LockLp<'info> {
+    /// Owner of the liquidity position, pays for the lock
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub user_pool_liquidity: Box<Account<'info, UserPoolLiquidity>>,
+
+    #[account(
+        init,
+        seeds = [
+            LP_LOCK_SEED.as_bytes(),
+            user_pool_liquidity.key().as_ref(),
+            id.to_le_bytes().as_ref(),
+        ],
+        bump,
+        payer = owner,
+        space = LpLock::LEN,
+    )]
+    pub lp_lock: Account<'info, LpLock>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn lock_lp(
+    ctx: Context<LockLp>,
+    id: u64,
+    lp_amount: u64,
+    cliff_time: u64,
+    end_time: u64,
+) -> Result<()> {
+    require_gt!(lp_amount, 0);
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    require!(
+        current_time <= cliff_time && cliff_time <= end_time && current_time < end_time,
+        GammaError::InvalidUnlockSchedule
+    );
+    let pool_state = ctx.accounts.pool_state.load()?;
+    let user_pool_liquidity = &mut ctx.accounts.user_pool_liquidity;
+    require_gte!(
+        user_pool_liquidity.unlocked_lp_tokens(&pool_state, current_time),
+        u128::from(lp_amount),
+        GammaError::LpLocked
+    );
+    user_pool_liquidity.lp_tokens_locked = user_pool_liquidity
+        .lp_tokens_locked
+        .checked_add(lp_amount)
+        .ok_or(GammaError::MathOverflow)?;
+
+    let lp_lock = &mut ctx.accounts.lp_lock;
+    lp_lock.bump = ctx.bumps.lp_lock;
+    lp_lock.id = id;
+    lp_lock.pool_state = ctx.accounts.pool_state.key();
+    lp_lock.owner = ctx.accounts.owner.key();
+    lp_lock.locked_amount = lp_amount;
+    lp_lock.released_amount = 0;
+    lp_lock.start_time = current_time;
+    lp_lock.cliff_time = cliff_time;
+    lp_lock.end_time = end_time;
+
+    emit!(LpLockEvent {
+        pool_id: lp_lock.pool_state,
+        lp_lock: lp_lock.key(),
+        owner: lp_lock.owner,
+        locked_amount: lp_amount,
+        released_amount: 0,
+        cliff_time,
+        end_time,
+        change_type: 0,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ReleaseLp<'info> {
+    /// Owner of the lock, receives its rent once fully released
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            lp_lock.pool_state.as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub user_pool_liquidity: Box<Account<'info, UserPoolLiquidity>>,
+
+    #[account(
+        mut,
+        seeds = [
+            LP_LOCK_SEED.as_bytes(),
+            user_pool_liquidity.key().as_ref(),
+            lp_lock.id.to_le_bytes().as_ref(),
+        ],
+        bump = lp_lock.bump,
+        has_one = owner,
+    )]
+    pub lp_lock: Account<'info, LpLock>,
+}
+
+/// Gives the LP unlocked by the schedule back to the position, closing the lock once
+/// everything is released
+pub fn release_lp(ctx: Context<ReleaseLp>) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    let lp_lock = &mut ctx.accounts.lp_lock;
+    let unlocked_amount = lp_lock.unlocked_amount(current_time)?;
+    let release_amount = unlocked_amount
+        .checked_sub(lp_lock.released_amount)
+        .ok_or(GammaError::MathOverflow)?;
+    require_gt!(release_amount, 0, GammaError::LpLocked);
+
+    let user_pool_liquidity = &mut ctx.accounts.user_pool_liquidity;
+    user_pool_liquidity.lp_tokens_locked = user_pool_liquidity
+        .lp_tokens_locked
+        .checked_sub(release_amount)
+        .ok_or(GammaError::MathOverflow)?;
+    lp_lock.released_amount = unlocked_amount;
+
+    emit!(LpLockEvent {
+        pool_id: lp_lock.pool_state,
+        lp_lock: lp_lock.key(),
+        owner: lp_lock.owner,
+        locked_amount: lp_lock.locked_amount,
+        released_amount: lp_lock.released_amount,
+        cliff_time: lp_lock.cliff_time,
+        end_time: lp_lock.end_time,
+        change_type: 1,
+    });
+
+    if lp_lock.released_amount == lp_lock.locked_amount {
+        lp_lock.close(ctx.accounts.owner.to_account_info())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,296 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount, TokenInterface};
+
+use crate::{
+    error::GammaError,
+    states::{
+        settle_user_rewards, update_pool_partner_lp, Capability, LpWrapEvent, PoolState, Roles,
+        UserPoolLiquidity, LP_MINT_DECIMALS, POOL_LP_MINT_SEED, ROLES_SEED,
+        USER_POOL_LIQUIDITY_SEED,
+    },
+    utils::{token_burn, token_mint_to},
+};
+
+#[derive(Accounts)]
+pub struct InitLpMint<'info> {
+    /// The pool creator or a key holding the config creator capability, pays for the mint
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// LP mint of the pool, a token-2022 mint so that `close_pool` can close it
+    #[account(
+        init,
+        seeds = [
+            POOL_LP_MINT_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+        mint::decimals = LP_MINT_DECIMALS,
+        mint::authority = authority,
+        mint::token_program = token_program,
+        extensions::close_authority::authority = authority,
+        payer = payer,
+    )]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Token-2022 program, owner of the LP mint
+    pub token_program: Program<'info, Token2022>,
+
+    /// CHECK: roles account granting capabilities, read by `Roles::load`. The hard-coded admin
+    /// keys are only trusted until it is created
+    #[account(seeds = [ROLES_SEED.as_bytes()], bump)]
+    pub roles: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init_lp_mint(ctx: Context<InitLpMint>) -> Result<()> {
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    let payer = ctx.accounts.payer.key();
+    require!(
+        payer == pool_state.pool_creator
+            || Roles::is_granted(
+                Roles::load(&ctx.accounts.roles)?.as_ref(),
+                payer,
+                Capability::ConfigCreator
+            ),
+        GammaError::InvalidOwner
+    );
+
+    pool_state.lp_mint = ctx.accounts.lp_mint.key();
+    pool_state.lp_mint_decimals = LP_MINT_DECIMALS;
+    pool_state.lp_mint_enabled = 1;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct WrapLp<'info> {
+    /// Owner of the liquidity position
+    pub owner: Signer<'info>,
+
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub user_pool_liquidity: Box<Account<'info, UserPoolLiquidity>>,
+
+    #[account(
+        mut,
+        address = pool_state.load()?.lp_mint,
+    )]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Receives the LP tokens, can belong to anyone
+    #[account(
+        mut,
+        token::mint = lp_mint,
+    )]
+    pub lp_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token program of the LP mint
+    pub token_program: Interface<'info, TokenInterface>,
+    // remaining accounts:
+    // - pool_partner, the PoolPartner of the partner of the position if it has one
+    // - for every active reward of the pool: reward_info, user_reward_info of owner
+}
+
+/// Moves `lp_amount` of the position into LP tokens. The rewards passed as remaining accounts
+/// are settled first, from then on the wrapped LP earns no rewards and does not count towards a
+/// partner until unwrapped
+pub fn wrap_lp<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, WrapLp<'info>>,
+    lp_amount: u64,
+) -> Result<()>
+where
+    'c: 'info,
+{
+    require_gt!(lp_amount, 0);
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    require!(
+        pool_state.lp_mint_enabled == 1,
+        GammaError::LpMintNotEnabled
+    );
+
+    let user_pool_liquidity = &mut ctx.accounts.user_pool_liquidity;
+    require_gte!(
+        user_pool_liquidity.lp_tokens_owned,
+        u128::from(lp_amount),
+        GammaError::InvalidLpTokenAmount
+    );
+    require_gte!(
+        user_pool_liquidity.unlocked_lp_tokens(pool_state, Clock::get()?.unix_timestamp as u64),
+        u128::from(lp_amount),
+        GammaError::LpLocked
+    );
+    settle_user_rewards(
+        pool_id,
+        user_pool_liquidity,
+        pool_state.lp_supply,
+        ctx.remaining_accounts,
+    )?;
+    user_pool_liquidity.update_fees_earned(pool_state)?;
+    user_pool_liquidity.lp_tokens_owned = user_pool_liquidity
+        .lp_tokens_owned
+        .checked_sub(u128::from(lp_amount))
+        .ok_or(GammaError::MathOverflow)?;
+    update_pool_partner_lp(
+        pool_id,
+        pool_state,
+        user_pool_liquidity.partner,
+        ctx.remaining_accounts,
+        lp_amount,
+        false,
+    )?;
+
+    token_mint_to(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.lp_mint.to_account_info(),
+        ctx.accounts.lp_token_account.to_account_info(),
+        lp_amount,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+    )?;
+
+    emit!(LpWrapEvent {
+        pool_id,
+        owner: ctx.accounts.owner.key(),
+        lp_mint: ctx.accounts.lp_mint.key(),
+        lp_amount,
+        lp_tokens_owned: user_pool_liquidity.lp_tokens_owned,
+        change_type: 0,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UnwrapLp<'info> {
+    /// Owner of the LP tokens and of the liquidity position credited
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// Created with `init_user_pool_liquidity` if the owner has no position yet
+    #[account(
+        mut,
+        seeds = [
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub user_pool_liquidity: Box<Account<'info, UserPoolLiquidity>>,
+
+    #[account(
+        mut,
+        address = pool_state.load()?.lp_mint,
+    )]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The LP tokens to burn
+    #[account(
+        mut,
+        token::mint = lp_mint,
+        token::authority = owner,
+    )]
+    pub lp_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token program of the LP mint
+    pub token_program: Interface<'info, TokenInterface>,
+    // remaining accounts:
+    // - pool_partner, the PoolPartner of the partner of the position if it has one
+    // - for every active reward of the pool: reward_info, user_reward_info of owner
+}
+
+/// Burns `lp_amount` LP tokens and credits them back to the position of the owner. The rewards
+/// passed as remaining accounts are settled first, an empty position accrues rewards from now on
+pub fn unwrap_lp<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, UnwrapLp<'info>>,
+    lp_amount: u64,
+) -> Result<()>
+where
+    'c: 'info,
+{
+    require_gt!(lp_amount, 0);
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    require!(
+        pool_state.lp_mint_enabled == 1,
+        GammaError::LpMintNotEnabled
+    );
+
+    token_burn(
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.lp_mint.to_account_info(),
+        ctx.accounts.lp_token_account.to_account_info(),
+        lp_amount,
+        &[],
+    )?;
+
+    let user_pool_liquidity = &mut ctx.accounts.user_pool_liquidity;
+    settle_user_rewards(
+        pool_id,
+        user_pool_liquidity,
+        pool_state.lp_supply,
+        ctx.remaining_accounts,
+    )?;
+    user_pool_liquidity.update_fees_earned(pool_state)?;
+    // The LP earned no rewards while wrapped and may have been wrapped by another position, it
+    // must not earn them for the time before it was credited here. The owner forfeits the rewards
+    // not settled here, as when receiving LP with `transfer_liquidity`
+    user_pool_liquidity.first_investment_at = Clock::get()?.unix_timestamp as u64;
+    user_pool_liquidity.lp_tokens_owned = user_pool_liquidity
+        .lp_tokens_owned
+        .checked_add(u128::from(lp_amount))
+        .ok_or(GammaError::MathOverflow)?;
+    update_pool_partner_lp(
+        pool_id,
+        pool_state,
+        user_pool_liquidity.partner,
+        ctx.remaining_accounts,
+        lp_amount,
+        true,
+    )?;
+
+    emit!(LpWrapEvent {
+        pool_id,
+        owner: ctx.accounts.owner.key(),
+        lp_mint: ctx.accounts.lp_mint.key(),
+        lp_amount,
+        lp_tokens_owned: user_pool_liquidity.lp_tokens_owned,
+        change_type: 1,
+    });
+    Ok(())
+}
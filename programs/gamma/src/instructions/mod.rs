@@ -1,29 +1,53 @@
 pub mod admin;
 pub mod calculate_rewards;
+pub mod claim_partner_fees;
 pub mod claim_rewards;
+pub mod close_user_pool_liquidity;
 pub mod create_rewards;
 pub mod deposit;
+pub mod get_lp_price;
+pub mod init_pool_statistics;
 pub mod init_user_pool_liquidity;
 pub mod initialize;
+pub mod lp_lock;
+pub mod lp_mint;
+pub mod pool_partner;
+pub mod position_operator;
+pub mod position_partner;
 // pub mod migrate_orca_to_gamma;
 // pub mod migrate_raydium_to_gamma;
 pub mod quote_swap;
 pub mod rebalance;
+pub mod resize;
+pub mod snapshot_lp_price;
 pub mod swap_base_input;
 pub mod swap_base_output;
+pub mod transfer_liquidity;
 pub mod withdraw;
 
 pub use admin::*;
 pub use deposit::*;
+pub use get_lp_price::*;
+pub use init_pool_statistics::*;
 pub use init_user_pool_liquidity::*;
 pub use initialize::*;
+pub use lp_lock::*;
+pub use lp_mint::*;
+pub use pool_partner::*;
+pub use position_operator::*;
+pub use position_partner::*;
 // pub use migrate_orca_to_gamma::*;
 // pub use migrate_raydium_to_gamma::*;
 pub use calculate_rewards::*;
+pub use claim_partner_fees::*;
 pub use claim_rewards::*;
+pub use close_user_pool_liquidity::*;
 pub use create_rewards::*;
 pub use quote_swap::*;
 pub use rebalance::*;
+pub use resize::*;
+pub use snapshot_lp_price::*;
 pub use swap_base_input::*;
 pub use swap_base_output::*;
+pub use transfer_liquidity::*;
 pub use withdraw::*;
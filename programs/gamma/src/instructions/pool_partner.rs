@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::GammaError,
+    states::{
+        Partner, PartnerInfo, PoolPartner, PoolState, LEGACY_PARTNER_ID, PARTNER_SEED,
+        POOL_PARTNER_SEED,
+    },
+};
+
+#[derive(Accounts)]
+pub struct InitPoolPartner<'info> {
+    /// Pays for the account, anyone can create the accounting of a registered partner
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        seeds = [
+            PARTNER_SEED.as_bytes(),
+            partner.id.to_le_bytes().as_ref(),
+        ],
+        bump = partner.bump,
+    )]
+    pub partner: Account<'info, Partner>,
+
+    #[account(
+        init,
+        seeds = [
+            POOL_PARTNER_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            partner.id.to_le_bytes().as_ref(),
+        ],
+        bump,
+        payer = payer,
+        space = PoolPartner::LEN,
+    )]
+    pub pool_partner: Account<'info, PoolPartner>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init_pool_partner(ctx: Context<InitPoolPartner>) -> Result<()> {
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    let pool_partner = &mut ctx.accounts.pool_partner;
+    pool_partner.bump = ctx.bumps.pool_partner;
+    pool_partner.pool_state = ctx.accounts.pool_state.key();
+    pool_partner.partner_id = ctx.accounts.partner.id;
+    pool_partner.revenue_share_rate = ctx.accounts.partner.revenue_share_rate;
+    pool_partner.protocol_fee_growth_checkpoint_token_0_x64 =
+        pool_state.protocol_fee_growth_per_lp_token_0_x64;
+    pool_partner.protocol_fee_growth_checkpoint_token_1_x64 =
+        pool_state.protocol_fee_growth_per_lp_token_1_x64;
+    pool_state.pool_partners_created = 1;
+
+    if pool_partner.partner_id == LEGACY_PARTNER_ID {
+        // Move the accounting kept in the pool before PoolPartner accounts. What it accrued was
+        // settled by hand, only the fees from now on can be claimed.
+        let mut partners = pool_state.partners;
+        let legacy = &mut partners[0];
+        pool_partner.lp_token_linked_with_partner = legacy.lp_token_linked_with_partner;
+        pool_partner.cumulative_fee_total_times_tvl_share_token_0 =
+            legacy.cumulative_fee_total_times_tvl_share_token_0;
+        pool_partner.cumulative_fee_total_times_tvl_share_token_1 =
+            legacy.cumulative_fee_total_times_tvl_share_token_1;
+        pool_partner.cumulative_fee_settled_token_0 =
+            legacy.cumulative_fee_total_times_tvl_share_token_0;
+        pool_partner.cumulative_fee_settled_token_1 =
+            legacy.cumulative_fee_total_times_tvl_share_token_1;
+        pool_state.lp_token_linked_with_partners = pool_state
+            .lp_token_linked_with_partners
+            .checked_add(legacy.lp_token_linked_with_partner)
+            .ok_or(GammaError::MathOverflow)?;
+        *legacy = PartnerInfo::default();
+        pool_state.partners = partners;
+    }
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::GammaError,
+    states::{
+        OperatorPermission, PoolState, PositionOperator, PositionOperatorEvent, UserPoolLiquidity,
+        POSITION_OPERATOR_SEED, USER_POOL_LIQUIDITY_SEED,
+    },
+};
+
+#[derive(Accounts)]
+pub struct SetPositionOperator<'info> {
+    /// Owner of the liquidity position
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        seeds = [
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub user_pool_liquidity: Box<Account<'info, UserPoolLiquidity>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [
+            POSITION_OPERATOR_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump,
+        payer = owner,
+        space = PositionOperator::LEN,
+    )]
+    pub position_operator: Account<'info, PositionOperator>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Approve `operator` on the position of the signer, replacing any previous operator
+pub fn set_position_operator(
+    ctx: Context<SetPositionOperator>,
+    operator: Pubkey,
+    permissions: u32,
+    expires_at: u64,
+) -> Result<()> {
+    let owner = ctx.accounts.owner.key();
+    require_keys_neq!(operator, owner, GammaError::InvalidInput);
+    require_keys_neq!(operator, Pubkey::default(), GammaError::InvalidInput);
+    require!(
+        permissions != 0 && (permissions & !OperatorPermission::ALL_MASK) == 0,
+        GammaError::InvalidInput
+    );
+    require_gt!(
+        expires_at,
+        Clock::get()?.unix_timestamp as u64,
+        GammaError::InvalidInput
+    );
+
+    let position_operator = &mut ctx.accounts.position_operator;
+    position_operator.bump = ctx.bumps.position_operator;
+    position_operator.pool_state = ctx.accounts.pool_state.key();
+    position_operator.owner = owner;
+    position_operator.operator = operator;
+    position_operator.permissions = permissions;
+    position_operator.expires_at = expires_at;
+
+    emit!(PositionOperatorEvent {
+        pool_id: position_operator.pool_state,
+        owner,
+        operator,
+        permissions,
+        expires_at,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RevokePositionOperator<'info> {
+    /// Owner of the liquidity position, receives the rent of the delegation
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            POSITION_OPERATOR_SEED.as_bytes(),
+            position_operator.pool_state.as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump = position_operator.bump,
+        has_one = owner,
+        close = owner,
+    )]
+    pub position_operator: Account<'info, PositionOperator>,
+}
+
+pub fn revoke_position_operator(ctx: Context<RevokePositionOperator>) -> Result<()> {
+    let position_operator = &ctx.accounts.position_operator;
+    emit!(PositionOperatorEvent {
+        pool_id: position_operator.pool_state,
+        owner: position_operator.owner,
+        operator: position_operator.operator,
+        permissions: 0,
+        expires_at: position_operator.expires_at,
+    });
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::GammaError,
+    states::{
+        update_pool_partner_lp, Partner, PoolState, PositionPartnerEvent, UserPoolLiquidity,
+        PARTNER_SEED, USER_POOL_LIQUIDITY_SEED,
+    },
+};
+
+#[derive(Accounts)]
+pub struct SetPositionPartner<'info> {
+    /// Owner of the liquidity position
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub user_pool_liquidity: Box<Account<'info, UserPoolLiquidity>>,
+
+    /// Partner to attribute the position to, none to remove the attribution
+    #[account(
+        seeds = [
+            PARTNER_SEED.as_bytes(),
+            partner.id.to_le_bytes().as_ref(),
+        ],
+        bump = partner.bump,
+    )]
+    pub partner: Option<Account<'info, Partner>>,
+
+    /// Payout wallet of the new partner, co-signs to confirm the attribution
+    pub partner_signer: Option<Signer<'info>>,
+
+    /// CHECK: PoolPartner of the current partner of the position, if it has one. Checked
+    /// against the partner in `update_pool_partner_lp`, the legacy partner may not have one yet
+    #[account(mut)]
+    pub pool_partner: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: PoolPartner of the new partner, if any. Checked against the partner in
+    /// `update_pool_partner_lp`
+    #[account(mut)]
+    pub new_pool_partner: Option<UncheckedAccount<'info>>,
+}
+
+/// Sets, changes or removes the partner of the position of the signer. Its LP moves between
+/// the PoolPartner accounts of the partners.
+pub fn set_position_partner(ctx: Context<SetPositionPartner>) -> Result<()> {
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    let user_pool_liquidity = &mut ctx.accounts.user_pool_liquidity;
+    let old_partner = user_pool_liquidity.partner;
+    let new_partner = ctx.accounts.partner.as_ref().map(|partner| partner.id);
+    require!(old_partner != new_partner, GammaError::InvalidInput);
+
+    let partner_signed = match &ctx.accounts.partner_signer {
+        Some(partner_signer) => {
+            let payout_wallet = ctx
+                .accounts
+                .partner
+                .as_ref()
+                .map(|partner| partner.payout_wallet)
+                .ok_or(GammaError::InvalidInput)?;
+            require_keys_eq!(
+                partner_signer.key(),
+                payout_wallet,
+                GammaError::InvalidOwner
+            );
+            true
+        }
+        None => false,
+    };
+
+    let lp_amount =
+        u64::try_from(user_pool_liquidity.lp_tokens_owned).or(err!(GammaError::MathOverflow))?;
+    let pool_partner: Vec<AccountInfo> = ctx
+        .accounts
+        .pool_partner
+        .iter()
+        .map(|pool_partner| pool_partner.to_account_info())
+        .collect();
+    update_pool_partner_lp(
+        pool_id,
+        pool_state,
+        old_partner,
+        &pool_partner,
+        lp_amount,
+        false,
+    )?;
+    let new_pool_partner: Vec<AccountInfo> = ctx
+        .accounts
+        .new_pool_partner
+        .iter()
+        .map(|pool_partner| pool_partner.to_account_info())
+        .collect();
+    update_pool_partner_lp(
+        pool_id,
+        pool_state,
+        new_partner,
+        &new_pool_partner,
+        lp_amount,
+        true,
+    )?;
+    user_pool_liquidity.partner = new_partner;
+
+    emit!(PositionPartnerEvent {
+        pool_id,
+        owner: ctx.accounts.owner.key(),
+        old_partner,
+        new_partner,
+        lp_amount,
+        partner_signed,
+    });
+    Ok(())
+}
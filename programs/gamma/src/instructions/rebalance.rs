@@ -3,6 +3,7 @@ use crate::{
     error::GammaError,
     fees::FEE_RATE_DENOMINATOR_VALUE,
     states::{PoolState, POOL_KAMINO_DEPOSITS_SEED},
+    utils::has_transfer_hook,
 };
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::sysvar::instructions::ID as INSTRUCTION_SYSVAR_ID;
@@ -104,6 +105,11 @@ pub struct Rebalance<'info> {
 pub fn rebalance_kamino<'c, 'info>(
     ctx: Context<'_, '_, 'c, 'info, Rebalance<'info>>,
 ) -> Result<()> {
+    // The Kamino deposit and redeem CPIs can't carry the extra accounts of a transfer hook,
+    // the vaults of hooked mints stay out of Kamino so withdrawals never redeem from it
+    if has_transfer_hook(&ctx.accounts.token_mint.to_account_info())? {
+        return err!(GammaError::NotSupportMint);
+    }
     let deposit_withdraw_amounts = get_deposit_withdraw_amounts(
         ctx.accounts.pool_state.clone(),
         ctx.accounts.token_vault.clone(),
@@ -0,0 +1,128 @@
+use anchor_lang::{prelude::*, Discriminator};
+
+use crate::{
+    error::GammaError,
+    states::{PoolState, UserPoolLiquidity, LEGACY_PARTNER_ID},
+    utils::resize_account,
+};
+
+#[derive(Accounts)]
+pub struct ResizeUserPoolLiquidity<'info> {
+    /// Pays for the extra rent, anyone can resize a position
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: created with an older layout, so it can't be deserialized as a UserPoolLiquidity
+    /// until resized. Owner and discriminator are checked in the handler.
+    #[account(mut, owner = crate::id())]
+    pub user_pool_liquidity: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorDeserialize)]
+enum LegacyPartnerType {
+    AssetDash,
+}
+
+/// Layout of UserPoolLiquidity before the partner registry, when partners were an enum.
+/// Accounts created before fee tracking only hold the fields up to `first_investment_at`,
+/// the rest reads as zero once resized.
+#[derive(AnchorDeserialize)]
+struct LegacyUserPoolLiquidity {
+    user: Pubkey,
+    pool_state: Pubkey,
+    token_0_deposited: u128,
+    token_1_deposited: u128,
+    token_0_withdrawn: u128,
+    token_1_withdrawn: u128,
+    lp_tokens_owned: u128,
+    partner: Option<LegacyPartnerType>,
+    first_investment_at: u64,
+    lp_tokens_locked: u64,
+    fee_growth_checkpoint_token_0_x64: u128,
+    fee_growth_checkpoint_token_1_x64: u128,
+    fees_earned_token_0: u128,
+    fees_earned_token_1: u128,
+}
+
+/// Grows a UserPoolLiquidity to the current `UserPoolLiquidity::LEN` and rewrites it in the
+/// current layout. The fee checkpoints of positions created before fee tracking start at zero,
+/// which is the fee growth of every pool when it was introduced.
+pub fn resize_user_pool_liquidity(ctx: Context<ResizeUserPoolLiquidity>) -> Result<()> {
+    let user_pool_liquidity_info = ctx.accounts.user_pool_liquidity.to_account_info();
+    require!(
+        user_pool_liquidity_info.try_borrow_data()?.get(..8)
+            == Some(&UserPoolLiquidity::DISCRIMINATOR[..]),
+        GammaError::InvalidInput
+    );
+    if user_pool_liquidity_info.data_len() >= UserPoolLiquidity::LEN {
+        return Ok(());
+    }
+
+    resize_account(
+        ctx.accounts.payer.to_account_info(),
+        user_pool_liquidity_info.clone(),
+        ctx.accounts.system_program.to_account_info(),
+        UserPoolLiquidity::LEN,
+    )?;
+
+    let legacy = LegacyUserPoolLiquidity::deserialize(
+        &mut &user_pool_liquidity_info.try_borrow_data()?[8..],
+    )?;
+    let user_pool_liquidity = UserPoolLiquidity {
+        user: legacy.user,
+        pool_state: legacy.pool_state,
+        token_0_deposited: legacy.token_0_deposited,
+        token_1_deposited: legacy.token_1_deposited,
+        token_0_withdrawn: legacy.token_0_withdrawn,
+        token_1_withdrawn: legacy.token_1_withdrawn,
+        lp_tokens_owned: legacy.lp_tokens_owned,
+        partner: legacy.partner.map(|partner| match partner {
+            LegacyPartnerType::AssetDash => LEGACY_PARTNER_ID,
+        }),
+        first_investment_at: legacy.first_investment_at,
+        lp_tokens_locked: legacy.lp_tokens_locked,
+        fee_growth_checkpoint_token_0_x64: legacy.fee_growth_checkpoint_token_0_x64,
+        fee_growth_checkpoint_token_1_x64: legacy.fee_growth_checkpoint_token_1_x64,
+        fees_earned_token_0: legacy.fees_earned_token_0,
+        fees_earned_token_1: legacy.fees_earned_token_1,
+        padding: [0u64; 4],
+    };
+    user_pool_liquidity
+        .try_serialize(&mut &mut user_pool_liquidity_info.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ResizePoolState<'info> {
+    /// Pays for the extra rent, anyone can resize a pool
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: created with an older layout, so it can't be loaded as a PoolState until resized.
+    /// Owner and discriminator are checked in the handler.
+    #[account(mut, owner = crate::id())]
+    pub pool_state: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Grows a PoolState to the current `PoolState::LEN`, the new fields start at zero
+pub fn resize_pool_state(ctx: Context<ResizePoolState>) -> Result<()> {
+    let pool_state_info = ctx.accounts.pool_state.to_account_info();
+    require!(
+        pool_state_info.try_borrow_data()?.get(..8) == Some(&PoolState::DISCRIMINATOR[..]),
+        GammaError::InvalidInput
+    );
+    if pool_state_info.data_len() >= PoolState::LEN {
+        return Ok(());
+    }
+
+    resize_account(
+        ctx.accounts.payer.to_account_info(),
+        pool_state_info,
+        ctx.accounts.system_program.to_account_info(),
+        PoolState::LEN,
+    )
+}
@@ -0,0 +1,52 @@
+use crate::states::{oracle, LpPriceHistory, PoolState, LP_PRICE_HISTORY_SEED};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct SnapshotLpPrice<'info> {
+    /// Pays for the history account on the first snapshot, anyone can crank
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The program account of the pool to snapshot
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The ring buffer of per-epoch LP virtual prices of the pool
+    #[account(
+        init_if_needed,
+        seeds = [
+            LP_PRICE_HISTORY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+        payer = payer,
+        space = LpPriceHistory::LEN
+    )]
+    pub lp_price_history: AccountLoader<'info, LpPriceHistory>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn snapshot_lp_price(ctx: Context<SnapshotLpPrice>) -> Result<()> {
+    let pool_state = ctx.accounts.pool_state.load()?;
+    let mut lp_price_history = match ctx.accounts.lp_price_history.load_mut() {
+        Ok(lp_price_history) => lp_price_history,
+        // Account was created by this instruction
+        Err(_) => {
+            let mut lp_price_history = ctx.accounts.lp_price_history.load_init()?;
+            lp_price_history.pool_id = ctx.accounts.pool_state.key();
+            lp_price_history
+        }
+    };
+
+    let (token_0_vault_amount, token_1_vault_amount) = pool_state.vault_amount_without_fee()?;
+    // Keyed on the epoch the pool was last updated in, so the snapshot matches the reserves it
+    // records. Later calls until the pool is updated in a new epoch are no-ops
+    lp_price_history.snapshot(
+        pool_state.recent_epoch,
+        oracle::block_timestamp()?,
+        token_0_vault_amount,
+        token_1_vault_amount,
+        pool_state.lp_supply,
+    )?;
+    Ok(())
+}
@@ -5,9 +5,11 @@ use crate::external::dflow_segmenter::is_invoked_by_segmenter;
 use crate::states::oracle;
 use crate::states::AmmConfig;
 use crate::states::ObservationState;
+use crate::states::OracleUpdateMode;
 use crate::states::PoolState;
+use crate::states::PoolStatistics;
 use crate::states::PoolStatusBitIndex;
-use crate::states::SwapEvent;
+use crate::states::{GuardianResumeEvent, SwapEvent};
 use crate::utils::{swap_referral::*, token::*};
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program;
@@ -84,6 +86,7 @@ pub struct SwapRemainingAccounts<'info> {
     pub registry: Option<AccountInfo<'info>>,
     pub referral_account: Option<AccountInfo<'info>>,
     pub referral_token_account: Option<AccountInfo<'info>>,
+    pub pool_statistics: Option<AccountInfo<'info>>,
 }
 
 pub fn decode_account_info<'info>(
@@ -103,6 +106,19 @@ pub fn decode_account_info<'info>(
     }
 }
 
+/// Remaining accounts of a swap with a fixed meaning, the token transfers get the ones after them
+pub const SWAP_REMAINING_ACCOUNTS_LEN: usize = 5;
+
+/// The accounts token-2022 extensions need during the transfers of a swap,
+/// see `transfer_from_user_to_pool_vault`
+pub fn swap_transfer_extra_accounts<'a, 'info>(
+    remaining_accounts: &'a [AccountInfo<'info>],
+) -> &'a [AccountInfo<'info>] {
+    remaining_accounts
+        .get(SWAP_REMAINING_ACCOUNTS_LEN..)
+        .unwrap_or(&[])
+}
+
 impl<'info> SwapRemainingAccounts<'info> {
     pub fn new(remaining_accounts: &[AccountInfo<'info>]) -> Self {
         Self {
@@ -110,6 +126,24 @@ impl<'info> SwapRemainingAccounts<'info> {
             registry: decode_account_info(remaining_accounts, 1),
             referral_account: decode_account_info(remaining_accounts, 2),
             referral_token_account: decode_account_info(remaining_accounts, 3),
+            pool_statistics: decode_account_info(remaining_accounts, 4),
+        }
+    }
+
+    /// The statistics account of the pool if the swap passes it. The program id stands in its
+    /// place when it is omitted, so the transfer accounts after it keep their position.
+    /// It can only be omitted until it is created, every swap is counted from then on.
+    pub fn pool_statistics_of(
+        &self,
+        pool_id: Pubkey,
+        pool_statistics_enabled: bool,
+    ) -> Result<Option<&AccountInfo<'info>>> {
+        match self.pool_statistics.as_ref() {
+            Some(account) if *account.key != PoolStatistics::find_address(pool_id) => {
+                err!(GammaError::InvalidPoolStatistics)
+            }
+            None if pool_statistics_enabled => err!(GammaError::InvalidPoolStatistics),
+            pool_statistics => Ok(pool_statistics),
         }
     }
 }
@@ -120,6 +154,7 @@ pub fn swap_base_input<'c, 'info>(
     minimum_amount_out: u64,
 ) -> Result<()> {
     let swap_remaining_accounts = SwapRemainingAccounts::new(&ctx.remaining_accounts);
+    let transfer_extra_accounts = swap_transfer_extra_accounts(ctx.remaining_accounts);
     let referral_info = extract_referral_info(
         ctx.accounts.input_token_mint.key(),
         ctx.accounts.amm_config.referral_project,
@@ -129,6 +164,14 @@ pub fn swap_base_input<'c, 'info>(
     let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
     let pool_id = ctx.accounts.pool_state.key();
     let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    let pool_statistics_info = swap_remaining_accounts
+        .pool_statistics_of(pool_id, pool_state.pool_statistics_enabled == 1)?;
+    if pool_state.resume_guardian_halt_if_expired(block_timestamp) {
+        emit!(GuardianResumeEvent {
+            pool_id,
+            resumed_by: Pubkey::default(),
+        });
+    }
     if !pool_state.get_status_by_bit(PoolStatusBitIndex::Swap)
         || block_timestamp < pool_state.open_time
     {
@@ -181,6 +224,9 @@ pub fn swap_base_input<'c, 'info>(
         } else {
             return err!(GammaError::InvalidVault);
         };
+    if !pool_state.get_status_by_bit(PoolStatusBitIndex::swap_direction(trade_direction)) {
+        return err!(GammaError::NotApproved);
+    }
     let constant_before = u128::from(total_input_token_amount)
         .checked_mul(u128::from(total_output_token_amount))
         .ok_or(GammaError::MathOverflow)?;
@@ -215,6 +261,25 @@ pub fn swap_base_input<'c, 'info>(
         Err(_) => return err!(GammaError::ZeroTradingTokens),
     };
 
+    pool_state.check_reduce_only(
+        &observation_state,
+        trade_direction,
+        &result,
+        block_timestamp,
+    )?;
+
+    // The swap that trips the guardian still goes through its slippage checks and executes, the
+    // halt applies to the swaps that follow in its direction
+    if let Some(guardian_halt_event) = pool_state.check_price_guardian(
+        pool_id,
+        &observation_state,
+        trade_direction,
+        &result,
+        block_timestamp,
+    )? {
+        emit!(guardian_halt_event);
+    }
+
     let constant_after = u128::from(
         result
             .new_swap_source_amount
@@ -263,6 +328,12 @@ pub fn swap_base_input<'c, 'info>(
     let mut protocol_fee = u64::try_from(result.protocol_fee).or(err!(GammaError::MathOverflow))?;
     let mut fund_fee = u64::try_from(result.fund_fee).or(err!(GammaError::MathOverflow))?;
     let dynamic_fee = u64::try_from(result.dynamic_fee).or(err!(GammaError::MathOverflow))?;
+    // The share of the trade fee that stays in the pool for the liquidity providers
+    let lp_fee = dynamic_fee
+        .checked_sub(protocol_fee)
+        .ok_or(GammaError::MathOverflow)?
+        .checked_sub(fund_fee)
+        .ok_or(GammaError::MathOverflow)?;
 
     let mut transfer_referral_amount = None;
     if let Some(ref info) = referral_info {
@@ -297,40 +368,8 @@ pub fn swap_base_input<'c, 'info>(
             transfer_referral_amount = Some(referral_amount)
         }
     }
-    // Save fees metric for the pool partners.
-    let mut partners = pool_state.partners;
-    for partner in partners.iter_mut() {
-        // we multiply by 100000 to keep decimals.
-        let decimal_number = 100000;
-        let tvl_share = partner
-            .lp_token_linked_with_partner
-            .checked_mul(decimal_number)
-            .ok_or(GammaError::MathOverflow)?
-            .checked_div(pool_state.lp_supply)
-            .ok_or(GammaError::MathOverflow)?;
-
-        let partner_fee = protocol_fee
-            .checked_mul(tvl_share)
-            .ok_or(GammaError::MathOverflow)?
-            .checked_div(decimal_number)
-            .ok_or(GammaError::MathOverflow)?;
-
-        match trade_direction {
-            TradeDirection::ZeroForOne => {
-                partner.cumulative_fee_total_times_tvl_share_token_0 = partner
-                    .cumulative_fee_total_times_tvl_share_token_0
-                    .checked_add(partner_fee)
-                    .ok_or(GammaError::MathOverflow)?;
-            }
-            TradeDirection::OneForZero => {
-                partner.cumulative_fee_total_times_tvl_share_token_1 = partner
-                    .cumulative_fee_total_times_tvl_share_token_1
-                    .checked_add(partner_fee)
-                    .ok_or(GammaError::MathOverflow)?;
-            }
-        }
-    }
-    pool_state.partners = partners;
+    // Save fees metric for the pool partners, each PoolPartner accrues its tvl share from it.
+    pool_state.accrue_protocol_fee(trade_direction, protocol_fee)?;
 
     match trade_direction {
         TradeDirection::ZeroForOne => {
@@ -405,6 +444,23 @@ pub fn swap_base_input<'c, 'info>(
         }
     };
     pool_state.latest_dynamic_fee_rate = result.dynamic_fee_rate;
+    pool_state.accrue_lp_fee(trade_direction, lp_fee)?;
+
+    // Recorded when the swap passes the statistics account of the pool and it is created
+    if let Some(pool_statistics_info) =
+        pool_statistics_info.filter(|account| *account.owner == crate::id())
+    {
+        let mut pool_statistics = PoolStatistics::load_mut(pool_statistics_info, pool_id)?;
+        let bucket = pool_statistics.current_bucket(Clock::get()?.epoch, block_timestamp);
+        bucket.add_volume(trade_direction, actual_amount_in, output_transfer_amount)?;
+        bucket.add_fees(
+            trade_direction,
+            protocol_fee,
+            fund_fee,
+            lp_fee,
+            result.dynamic_fee_rate,
+        )?;
+    }
 
     emit!(SwapEvent {
         pool_id,
@@ -434,6 +490,7 @@ pub fn swap_base_input<'c, 'info>(
         ctx.accounts.input_token_program.to_account_info(),
         input_transfer_amount,
         ctx.accounts.input_token_mint.decimals,
+        transfer_extra_accounts,
     )?;
     transfer_from_pool_vault_to_user(
         ctx.accounts.authority.to_account_info(),
@@ -443,6 +500,7 @@ pub fn swap_base_input<'c, 'info>(
         ctx.accounts.output_token_program.to_account_info(),
         output_transfer_amount,
         ctx.accounts.output_token_mint.decimals,
+        transfer_extra_accounts,
         &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
     )?;
 
@@ -455,26 +513,31 @@ pub fn swap_base_input<'c, 'info>(
     // (2) is(optionally) user->referrer token transfer
     if let Some(amount) = transfer_referral_amount {
         let info = referral_info.expect("referral_info to be non-null");
-        anchor_spl::token_2022::transfer_checked(
-            CpiContext::new(
-                ctx.accounts.input_token_program.to_account_info(),
-                anchor_spl::token_2022::TransferChecked {
-                    from: ctx.accounts.input_token_account.to_account_info(),
-                    to: info.referral_token_account.to_account_info(),
-                    authority: ctx.accounts.payer.to_account_info(),
-                    mint: ctx.accounts.input_token_mint.to_account_info(),
-                },
-            ),
+        transfer_from_user_to_referrer(
+            ctx.accounts.payer.to_account_info(),
+            ctx.accounts.input_token_account.to_account_info(),
+            info.referral_token_account.to_account_info(),
+            ctx.accounts.input_token_mint.to_account_info(),
+            ctx.accounts.input_token_program.to_account_info(),
             amount,
             ctx.accounts.input_token_mint.decimals,
+            transfer_extra_accounts,
         )?;
     }
 
-    observation_state.update(
-        oracle::block_timestamp()?,
-        token_0_price_x64_before_swap,
-        token_1_price_x64_before_swap,
-    )?;
+    match OracleUpdateMode::new(pool_state.oracle_update_mode)? {
+        OracleUpdateMode::PreSwap => observation_state.update(
+            oracle::block_timestamp()?,
+            token_0_price_x64_before_swap,
+            token_1_price_x64_before_swap,
+        )?,
+        OracleUpdateMode::PostSwapPerSlot => pool_state.update_oracle_post_swap(
+            &mut observation_state,
+            oracle::block_timestamp()?,
+            token_0_price_x64_before_swap,
+            token_1_price_x64_before_swap,
+        )?,
+    }
 
     pool_state.recent_epoch = Clock::get()?.epoch;
 
@@ -2,9 +2,12 @@ use super::swap_base_input::Swap;
 use crate::curve::{calculator::CurveCalculator, TradeDirection};
 use crate::error::GammaError;
 use crate::external::dflow_segmenter::is_invoked_by_segmenter;
-use crate::states::{oracle, PoolStatusBitIndex, SwapEvent};
+use crate::states::{
+    oracle, GuardianResumeEvent, OracleUpdateMode, PoolStatistics, PoolStatusBitIndex,
+    SwapEvent,
+};
 use crate::utils::{swap_referral::*, token::*};
-use crate::SwapRemainingAccounts;
+use crate::{swap_transfer_extra_accounts, SwapRemainingAccounts};
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program;
 
@@ -15,6 +18,7 @@ pub fn swap_base_output<'c, 'info>(
 ) -> Result<()> {
     require_gt!(amount_out_less_fee, 0);
     let swap_remaining_accounts = SwapRemainingAccounts::new(&ctx.remaining_accounts);
+    let transfer_extra_accounts = swap_transfer_extra_accounts(ctx.remaining_accounts);
     let referral_info = extract_referral_info(
         ctx.accounts.input_token_mint.key(),
         ctx.accounts.amm_config.referral_project,
@@ -24,7 +28,16 @@ pub fn swap_base_output<'c, 'info>(
     let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
     let pool_id = ctx.accounts.pool_state.key();
     let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    let pool_statistics_info = swap_remaining_accounts
+        .pool_statistics_of(pool_id, pool_state.pool_statistics_enabled == 1)?;
+    if pool_state.resume_guardian_halt_if_expired(block_timestamp) {
+        emit!(GuardianResumeEvent {
+            pool_id,
+            resumed_by: Pubkey::default(),
+        });
+    }
     if !pool_state.get_status_by_bit(PoolStatusBitIndex::Swap)
+        || !pool_state.get_status_by_bit(PoolStatusBitIndex::SwapBaseOutput)
         || block_timestamp < pool_state.open_time
     {
         return err!(GammaError::NotApproved);
@@ -78,6 +91,9 @@ pub fn swap_base_output<'c, 'info>(
         } else {
             return err!(GammaError::InvalidVault);
         };
+    if !pool_state.get_status_by_bit(PoolStatusBitIndex::swap_direction(trade_direction)) {
+        return err!(GammaError::NotApproved);
+    }
     let constant_before = u128::from(total_input_token_amount)
         .checked_mul(u128::from(total_output_token_amount))
         .ok_or(GammaError::MathOverflow)?;
@@ -111,6 +127,25 @@ pub fn swap_base_output<'c, 'info>(
         Err(_) => return err!(GammaError::ZeroTradingTokens),
     };
 
+    pool_state.check_reduce_only(
+        &observation_state,
+        trade_direction,
+        &result,
+        block_timestamp,
+    )?;
+
+    // The swap that trips the guardian still goes through its slippage checks and executes, the
+    // halt applies to the swaps that follow in its direction
+    if let Some(guardian_halt_event) = pool_state.check_price_guardian(
+        pool_id,
+        &observation_state,
+        trade_direction,
+        &result,
+        block_timestamp,
+    )? {
+        emit!(guardian_halt_event);
+    }
+
     let constant_after = u128::from(
         result
             .new_swap_source_amount
@@ -161,6 +196,12 @@ pub fn swap_base_output<'c, 'info>(
     let mut protocol_fee = u64::try_from(result.protocol_fee).or(err!(GammaError::MathOverflow))?;
     let mut fund_fee = u64::try_from(result.fund_fee).or(err!(GammaError::MathOverflow))?;
     let dynamic_fee = u64::try_from(result.dynamic_fee).or(err!(GammaError::MathOverflow))?;
+    // The share of the trade fee that stays in the pool for the liquidity providers
+    let lp_fee = dynamic_fee
+        .checked_sub(protocol_fee)
+        .ok_or(GammaError::MathOverflow)?
+        .checked_sub(fund_fee)
+        .ok_or(GammaError::MathOverflow)?;
 
     let mut source_amount_swapped =
         u64::try_from(result.source_amount_swapped).or(err!(GammaError::MathOverflow))?;
@@ -198,40 +239,8 @@ pub fn swap_base_output<'c, 'info>(
         }
     }
 
-    // Save fees metric for the pool partners.
-    let mut partners = pool_state.partners;
-    for partner in partners.iter_mut() {
-        // we multiply by 100000 to keep decimals.
-        let decimal_number = 100000;
-        let tvl_share = partner
-            .lp_token_linked_with_partner
-            .checked_mul(decimal_number)
-            .ok_or(GammaError::MathOverflow)?
-            .checked_div(pool_state.lp_supply)
-            .ok_or(GammaError::MathOverflow)?;
-
-        let partner_fee = protocol_fee
-            .checked_mul(tvl_share)
-            .ok_or(GammaError::MathOverflow)?
-            .checked_div(decimal_number)
-            .ok_or(GammaError::MathOverflow)?;
-
-        match trade_direction {
-            TradeDirection::ZeroForOne => {
-                partner.cumulative_fee_total_times_tvl_share_token_0 = partner
-                    .cumulative_fee_total_times_tvl_share_token_0
-                    .checked_add(partner_fee)
-                    .ok_or(GammaError::MathOverflow)?;
-            }
-            TradeDirection::OneForZero => {
-                partner.cumulative_fee_total_times_tvl_share_token_1 = partner
-                    .cumulative_fee_total_times_tvl_share_token_1
-                    .checked_add(partner_fee)
-                    .ok_or(GammaError::MathOverflow)?;
-            }
-        }
-    }
-    pool_state.partners = partners;
+    // Save fees metric for the pool partners, each PoolPartner accrues its tvl share from it.
+    pool_state.accrue_protocol_fee(trade_direction, protocol_fee)?;
 
     match trade_direction {
         TradeDirection::ZeroForOne => {
@@ -308,6 +317,23 @@ pub fn swap_base_output<'c, 'info>(
         }
     };
     pool_state.latest_dynamic_fee_rate = result.dynamic_fee_rate;
+    pool_state.accrue_lp_fee(trade_direction, lp_fee)?;
+
+    // Recorded when the swap passes the statistics account of the pool and it is created
+    if let Some(pool_statistics_info) =
+        pool_statistics_info.filter(|account| *account.owner == crate::id())
+    {
+        let mut pool_statistics = PoolStatistics::load_mut(pool_statistics_info, pool_id)?;
+        let bucket = pool_statistics.current_bucket(Clock::get()?.epoch, block_timestamp);
+        bucket.add_volume(trade_direction, input_transfer_amount, output_transfer_amount)?;
+        bucket.add_fees(
+            trade_direction,
+            protocol_fee,
+            fund_fee,
+            lp_fee,
+            result.dynamic_fee_rate,
+        )?;
+    }
 
     emit!(SwapEvent {
         pool_id,
@@ -338,6 +364,7 @@ pub fn swap_base_output<'c, 'info>(
         ctx.accounts.input_token_program.to_account_info(),
         input_transfer_amount,
         ctx.accounts.input_token_mint.decimals,
+        transfer_extra_accounts,
     )?;
 
     transfer_from_pool_vault_to_user(
@@ -348,6 +375,7 @@ pub fn swap_base_output<'c, 'info>(
         ctx.accounts.output_token_program.to_account_info(),
         output_transfer_amount,
         ctx.accounts.output_token_mint.decimals,
+        transfer_extra_accounts,
         &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
     )?;
 
@@ -360,26 +388,31 @@ pub fn swap_base_output<'c, 'info>(
     // (2) is(optionally) user->referrer token transfer
     if let Some(amount) = transfer_referral_amount {
         let info = referral_info.expect("referral_info to be non-null");
-        anchor_spl::token_2022::transfer_checked(
-            CpiContext::new(
-                ctx.accounts.input_token_program.to_account_info(),
-                anchor_spl::token_2022::TransferChecked {
-                    from: ctx.accounts.input_token_account.to_account_info(),
-                    to: info.referral_token_account.to_account_info(),
-                    authority: ctx.accounts.payer.to_account_info(),
-                    mint: ctx.accounts.input_token_mint.to_account_info(),
-                },
-            ),
+        transfer_from_user_to_referrer(
+            ctx.accounts.payer.to_account_info(),
+            ctx.accounts.input_token_account.to_account_info(),
+            info.referral_token_account.to_account_info(),
+            ctx.accounts.input_token_mint.to_account_info(),
+            ctx.accounts.input_token_program.to_account_info(),
             amount,
             ctx.accounts.input_token_mint.decimals,
+            transfer_extra_accounts,
         )?;
     }
 
-    observation_state.update(
-        oracle::block_timestamp()?,
-        token_0_price_x64_before_swap,
-        token_1_price_x64_before_swap,
-    )?;
+    match OracleUpdateMode::new(pool_state.oracle_update_mode)? {
+        OracleUpdateMode::PreSwap => observation_state.update(
+            oracle::block_timestamp()?,
+            token_0_price_x64_before_swap,
+            token_1_price_x64_before_swap,
+        )?,
+        OracleUpdateMode::PostSwapPerSlot => pool_state.update_oracle_post_swap(
+            &mut observation_state,
+            oracle::block_timestamp()?,
+            token_0_price_x64_before_swap,
+            token_1_price_x64_before_swap,
+        )?,
+    }
     pool_state.recent_epoch = Clock::get()?.epoch;
 
     Ok(())
@@ -0,0 +1,213 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::GammaError,
+    states::{
+        settle_user_reward_info, update_pool_partner_lp, LiquidityTransferEvent, PoolState,
+        RewardInfo, UserPoolLiquidity, USER_POOL_LIQUIDITY_SEED,
+    },
+};
+
+#[derive(Accounts)]
+pub struct TransferLiquidity<'info> {
+    /// Owner of the position the LP is taken from, pays for the new position if needed
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Any wallet can receive a position, it must sign when its position already holds LP
+    pub new_owner: UncheckedAccount<'info>,
+
+    /// Keeps the LP linked with partners in sync
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub user_pool_liquidity: Box<Account<'info, UserPoolLiquidity>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            new_owner.key().as_ref(),
+        ],
+        bump,
+        payer = owner,
+        space = UserPoolLiquidity::LEN,
+    )]
+    pub new_user_pool_liquidity: Box<Account<'info, UserPoolLiquidity>>,
+
+    /// CHECK: PoolPartner of the partner of the position the LP is taken from, needed when the
+    /// positions have different partners. Checked against the partner in
+    /// `update_pool_partner_lp`, the legacy partner may not have one yet
+    #[account(mut)]
+    pub pool_partner: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: PoolPartner of the partner of the new position, needed when the positions have
+    /// different partners. Checked against the partner in `update_pool_partner_lp`
+    #[account(mut)]
+    pub new_pool_partner: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Program<'info, System>,
+    // remaining accounts, for every active reward of the pool:
+    // - reward_info
+    // - user_reward_info of owner
+    // - user_reward_info of new_owner, left uninitialized if no rewards were calculated yet
+    // Rewards of the new position not settled here are forfeited, it accrues from now on
+}
+
+pub fn transfer_liquidity<'c, 'info>(
+    ctx: Context<'_, '_, 'c, 'info, TransferLiquidity<'info>>,
+    lp_amount: u64,
+) -> Result<()>
+where
+    'c: 'info,
+{
+    require_gt!(lp_amount, 0);
+    let owner = ctx.accounts.owner.key();
+    let new_owner = ctx.accounts.new_owner.key();
+    require_keys_neq!(owner, new_owner, GammaError::InvalidInput);
+    let pool_id = ctx.accounts.pool_state.key();
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+
+    let from = &mut ctx.accounts.user_pool_liquidity;
+    let to = &mut ctx.accounts.new_user_pool_liquidity;
+    require_gte!(
+        from.lp_tokens_owned,
+        u128::from(lp_amount),
+        GammaError::InvalidLpTokenAmount
+    );
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    require_gte!(
+        from.unlocked_lp_tokens(pool_state, current_time),
+        u128::from(lp_amount),
+        GammaError::LpLocked
+    );
+    if to.user == Pubkey::default() {
+        // A new position keeps the partner of the one it is split from
+        to.initialize(new_owner, pool_id, from.partner, current_time);
+    } else if to.lp_tokens_owned > 0 {
+        // The reward clock of the position is reset below, only its owner can agree to forfeit
+        // the rewards not settled here
+        require!(ctx.accounts.new_owner.is_signer, GammaError::InvalidOwner);
+    }
+
+    // Rewards and fees accrue on the balances held until now
+    let lp_supply = pool_state.lp_supply;
+    for accounts in ctx.remaining_accounts.chunks(3) {
+        let [reward_info, from_reward_info, to_reward_info] = accounts else {
+            return err!(GammaError::InvalidInput);
+        };
+        let reward_info = Account::<RewardInfo>::try_from(reward_info)?;
+        require_keys_eq!(reward_info.pool, pool_id, GammaError::InvalidInput);
+        settle_user_reward_info(&reward_info, from_reward_info, from, lp_supply)?;
+        settle_user_reward_info(&reward_info, to_reward_info, to, lp_supply)?;
+    }
+    from.update_fees_earned(pool_state)?;
+    to.update_fees_earned(pool_state)?;
+    // The received LP must not earn rewards for the time before it was received
+    to.first_investment_at = current_time;
+
+    // Cost basis moves pro-rata so deposited and withdrawn amounts per LP stay the same
+    let lp_owned = from.lp_tokens_owned;
+    let pro_rata = |amount: u128| {
+        amount
+            .checked_mul(u128::from(lp_amount))
+            .and_then(|amount| amount.checked_div(lp_owned))
+            .ok_or(GammaError::MathOverflow)
+    };
+    let token_0_deposited = pro_rata(from.token_0_deposited)?;
+    let token_1_deposited = pro_rata(from.token_1_deposited)?;
+    let token_0_withdrawn = pro_rata(from.token_0_withdrawn)?;
+    let token_1_withdrawn = pro_rata(from.token_1_withdrawn)?;
+
+    from.lp_tokens_owned = lp_owned
+        .checked_sub(u128::from(lp_amount))
+        .ok_or(GammaError::MathOverflow)?;
+    from.token_0_deposited = from
+        .token_0_deposited
+        .checked_sub(token_0_deposited)
+        .ok_or(GammaError::MathOverflow)?;
+    from.token_1_deposited = from
+        .token_1_deposited
+        .checked_sub(token_1_deposited)
+        .ok_or(GammaError::MathOverflow)?;
+    from.token_0_withdrawn = from
+        .token_0_withdrawn
+        .checked_sub(token_0_withdrawn)
+        .ok_or(GammaError::MathOverflow)?;
+    from.token_1_withdrawn = from
+        .token_1_withdrawn
+        .checked_sub(token_1_withdrawn)
+        .ok_or(GammaError::MathOverflow)?;
+
+    to.lp_tokens_owned = to
+        .lp_tokens_owned
+        .checked_add(u128::from(lp_amount))
+        .ok_or(GammaError::MathOverflow)?;
+    to.token_0_deposited = to
+        .token_0_deposited
+        .checked_add(token_0_deposited)
+        .ok_or(GammaError::MathOverflow)?;
+    to.token_1_deposited = to
+        .token_1_deposited
+        .checked_add(token_1_deposited)
+        .ok_or(GammaError::MathOverflow)?;
+    to.token_0_withdrawn = to
+        .token_0_withdrawn
+        .checked_add(token_0_withdrawn)
+        .ok_or(GammaError::MathOverflow)?;
+    to.token_1_withdrawn = to
+        .token_1_withdrawn
+        .checked_add(token_1_withdrawn)
+        .ok_or(GammaError::MathOverflow)?;
+
+    if from.partner != to.partner {
+        let pool_partner: Vec<AccountInfo> = ctx
+            .accounts
+            .pool_partner
+            .iter()
+            .map(|pool_partner| pool_partner.to_account_info())
+            .collect();
+        update_pool_partner_lp(
+            pool_id,
+            pool_state,
+            from.partner,
+            &pool_partner,
+            lp_amount,
+            false,
+        )?;
+        let new_pool_partner: Vec<AccountInfo> = ctx
+            .accounts
+            .new_pool_partner
+            .iter()
+            .map(|pool_partner| pool_partner.to_account_info())
+            .collect();
+        update_pool_partner_lp(
+            pool_id,
+            pool_state,
+            to.partner,
+            &new_pool_partner,
+            lp_amount,
+            true,
+        )?;
+    }
+
+    emit!(LiquidityTransferEvent {
+        pool_id,
+        from: owner,
+        to: new_owner,
+        lp_amount,
+        token_0_deposited,
+        token_1_deposited,
+    });
+    Ok(())
+}
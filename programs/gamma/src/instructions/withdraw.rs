@@ -5,8 +5,8 @@ use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
 use crate::curve::{CurveCalculator, RoundDirection};
 use crate::external::kamino::KaminoProgram;
 use crate::states::{
-    LpChangeEvent, PartnerType, PoolStatusBitIndex, UserPoolLiquidity, POOL_KAMINO_DEPOSITS_SEED,
-    USER_POOL_LIQUIDITY_SEED,
+    check_position_signer, update_pool_partner_lp, LpChangeEvent, OperatorPermission,
+    PoolStatusBitIndex, UserPoolLiquidity, POOL_KAMINO_DEPOSITS_SEED, USER_POOL_LIQUIDITY_SEED,
 };
 use crate::utils::{get_transfer_fee, transfer_from_pool_vault_to_user};
 use crate::{error::GammaError, states::PoolState};
@@ -16,7 +16,7 @@ use super::calculate_amount_to_be_withdrawn_from_kamino_in_withdraw_instruction_
 
 #[derive(Accounts)]
 pub struct Withdraw<'info> {
-    /// Owner of the liquidity provided
+    /// Owner of the liquidity provided, or an operator approved by the owner
     pub owner: Signer<'info>,
 
     /// CHECK: pool vault authority
@@ -38,7 +38,7 @@ pub struct Withdraw<'info> {
         seeds = [
             USER_POOL_LIQUIDITY_SEED.as_bytes(),
             pool_state.key().as_ref(),
-            owner.key().as_ref(),
+            user_pool_liquidity.user.as_ref(),
         ],
         bump,
     )]
@@ -48,7 +48,7 @@ pub struct Withdraw<'info> {
     #[account(
         mut,
         token::mint = token_0_vault.mint,
-        token::authority = owner
+        token::authority = user_pool_liquidity.user
     )]
     pub token_0_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
@@ -56,7 +56,7 @@ pub struct Withdraw<'info> {
     #[account(
         mut,
         token::mint = token_1_vault.mint,
-        token::authority = owner
+        token::authority = user_pool_liquidity.user
     )]
     pub token_1_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
@@ -118,8 +118,22 @@ where
     'c: 'info,
 {
     require_gt!(lp_token_amount, 0);
+    check_position_signer(
+        ctx.accounts.pool_state.key(),
+        ctx.accounts.user_pool_liquidity.user,
+        ctx.accounts.owner.key(),
+        ctx.remaining_accounts,
+        OperatorPermission::Withdraw,
+    )?;
     let pool_id = ctx.accounts.pool_state.key();
     let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    require_gte!(
+        ctx.accounts
+            .user_pool_liquidity
+            .unlocked_lp_tokens(pool_state, Clock::get()?.unix_timestamp as u64),
+        u128::from(lp_token_amount),
+        GammaError::LpLocked
+    );
     if !pool_state.get_status_by_bit(PoolStatusBitIndex::Withdraw) {
         return err!(GammaError::NotApproved);
     }
@@ -207,6 +221,7 @@ where
         .checked_sub(lp_token_amount)
         .ok_or(GammaError::MathOverflow)?;
     let user_pool_liquidity = &mut ctx.accounts.user_pool_liquidity;
+    user_pool_liquidity.update_fees_earned(pool_state)?;
     user_pool_liquidity.lp_tokens_owned = user_pool_liquidity
         .lp_tokens_owned
         .checked_sub(u128::from(lp_token_amount))
@@ -220,19 +235,14 @@ where
         .checked_add(u128::from(receive_token_1_amount))
         .ok_or(GammaError::MathOverflow)?;
 
-    if let Some(user_pool_liquidity_partner) = user_pool_liquidity.partner {
-        let mut pool_state_partners = pool_state.partners;
-        let partner: Option<&mut crate::states::PartnerInfo> = pool_state_partners
-            .iter_mut()
-            .find(|p| PartnerType::new(p.partner_id) == user_pool_liquidity_partner);
-        if let Some(partner) = partner {
-            partner.lp_token_linked_with_partner = partner
-                .lp_token_linked_with_partner
-                .checked_sub(lp_token_amount)
-                .ok_or(GammaError::MathOverflow)?;
-        }
-        pool_state.partners = pool_state_partners;
-    }
+    update_pool_partner_lp(
+        ctx.accounts.pool_state.key(),
+        pool_state,
+        user_pool_liquidity.partner,
+        ctx.remaining_accounts,
+        lp_token_amount,
+        false,
+    )?;
 
     transfer_from_pool_vault_to_user(
         ctx.accounts.authority.to_account_info(),
@@ -246,6 +256,7 @@ where
         },
         token_0_amount,
         ctx.accounts.vault_0_mint.decimals,
+        ctx.remaining_accounts,
         &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
     )?;
 
@@ -261,6 +272,7 @@ where
         },
         token_1_amount,
         ctx.accounts.vault_1_mint.decimals,
+        ctx.remaining_accounts,
         &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
     )?;
 
@@ -39,6 +39,7 @@ pub mod create_pool_fee_reveiver {
     declare_id!("8PhehuioLjhJ35A5eavazJSwoXcA4J7WwzgoWDBDFSuY");
 }
 
+/// Rewards crank key, holds the RewardsOperator capability until the roles account revokes it
 pub const CALCULATE_REWARDS_ADMIN: Pubkey = pubkey!("5CW8MEhPPxiRmwWgigwpCKCHaZDLX62BkrneijrxoKkR");
 
 pub const AUTH_SEED: &str = "vault_and_lp_mint_auth_seed";
@@ -67,6 +68,8 @@ pub mod gamma {
     /// * `trade_fee_rate` - Trade fee rate, can be changed.
     /// * `protocol_fee_rate` - The rate of protocol fee within tarde fee.
     /// * `fund_fee_rate` - The rate of fund fee within tarde fee.
+    /// * `protocol_owner` - Collects the protocol fees, a fee collector unless signed by a role admin.
+    /// * `fund_owner` - Collects the fund fees, a fee collector unless signed by a role admin.
     ///
     pub fn create_amm_config(
         ctx: Context<CreateAmmConfig>,
@@ -76,6 +79,8 @@ pub mod gamma {
         fund_fee_rate: u64,
         create_pool_fee: u64,
         max_open_time: u64,
+        protocol_owner: Pubkey,
+        fund_owner: Pubkey,
     ) -> Result<()> {
         assert!(trade_fee_rate < FEE_RATE_DENOMINATOR_VALUE);
         assert!(protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
@@ -89,6 +94,8 @@ pub mod gamma {
             fund_fee_rate,
             create_pool_fee,
             max_open_time,
+            protocol_owner,
+            fund_owner,
         )
     }
 
@@ -115,14 +122,28 @@ pub mod gamma {
     /// * `trade_fee_rate`- The new trade fee rate of amm config, be set when `param` is 0
     /// * `protocol_fee_rate`- The new protocol fee rate of amm config, be set when `param` is 1
     /// * `fund_fee_rate`- The new fund fee rate of amm config, be set when `param` is 2
-    /// * `new_owner`- The config's new owner, be set when `param` is 3
-    /// * `new_fund_owner`- The config's new fund owner, be set when `param` is 4
+    /// * `new_owner`- The config's proposed owner, be set when `param` is 3, takes effect on `accept_role`
+    /// * `new_fund_owner`- The config's proposed fund owner, be set when `param` is 4, takes effect on `accept_role`
     /// * `param`- The vaule can be 0 | 1 | 2 | 3 | 4, otherwise will report a error
     ///
     pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, param: u16, value: u64) -> Result<()> {
         instructions::update_amm_config(ctx, param, value)
     }
 
+    /// Apply a typed update to an amm config, replaces the numeric `param` dispatch of `update_amm_config`
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `update`- The field to update together with its new value
+    ///
+    pub fn apply_amm_config_update(
+        ctx: Context<UpdateAmmConfig>,
+        update: states::ConfigUpdate,
+    ) -> Result<()> {
+        instructions::apply_amm_config_update(ctx, update)
+    }
+
     /// Update pool status for given vaule
     ///
     /// # Arguments
@@ -135,6 +156,226 @@ pub mod gamma {
         instructions::update_pool(ctx, param, value)
     }
 
+    /// Accept a pending role (protocol owner, fund owner or secondary admin) of an amm config.
+    /// Must be signed by the proposed key.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn accept_role(ctx: Context<AcceptRole>) -> Result<()> {
+        instructions::accept_role(ctx)
+    }
+
+    /// Cancel the pending role transfer of an amm config, must be signed by a role admin.
+    /// A new role transfer can only be proposed once none is pending
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn cancel_role_transfer(ctx: Context<CancelRoleTransfer>) -> Result<()> {
+        instructions::cancel_role_transfer(ctx)
+    }
+
+    /// Queue a config or pool update behind the amm config timelock delay.
+    /// Required for non-emergency updates once `timelock_delay` is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `id`- Id of the queued update, part of its seeds
+    /// * `action`- The update to apply once the delay has passed
+    ///
+    pub fn queue_update(
+        ctx: Context<QueueUpdate>,
+        id: u64,
+        action: states::QueuedAction,
+    ) -> Result<()> {
+        instructions::queue_update(ctx, id, action)
+    }
+
+    /// Apply a queued update whose eta has passed, can be called by anyone
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn execute_queued_update(ctx: Context<ExecuteQueuedUpdate>) -> Result<()> {
+        instructions::execute_queued_update(ctx)
+    }
+
+    /// Cancel a queued update, must be signed by the proposer or a key holding the capability
+    /// the update requires
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn cancel_queued_update(ctx: Context<CancelQueuedUpdate>) -> Result<()> {
+        instructions::cancel_queued_update(ctx)
+    }
+
+    /// Create the roles account that grants admin capabilities to keys, must be signed by the admin.
+    /// The admin starts with every capability and the rewards crank key with RewardsOperator,
+    /// from then on the hard-coded keys only hold what the roles account grants them
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn init_roles(ctx: Context<InitRoles>) -> Result<()> {
+        instructions::init_roles(ctx)
+    }
+
+    /// Close a pool holding only the liquidity locked at initialization. The vault dust goes to the
+    /// protocol owner and the rent of the pool, observation, vault and LP mint accounts to the pool
+    /// creator. Every reward of the pool must have ended, what is left in its vault and the rent
+    /// of the vault go back to the reward creator. The pool address stays taken by an empty
+    /// account, so positions and other accounts of the pool can't attach to a new one.
+    /// Must be signed by the pool creator or a config creator.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn close_pool<'c, 'info>(ctx: Context<'_, '_, 'c, 'info, ClosePool<'info>>) -> Result<()>
+    where
+        'c: 'info,
+    {
+        instructions::close_pool(ctx)
+    }
+
+    /// Move a pool to another amm config, must be signed by a fee manager. The pool address keeps
+    /// the seeds of the old config.
+    /// Protocol and fund fees accrued under the old config are paid to its owners first.
+    /// When the old config has a timelock, the new one must not raise fees nor shorten the delay.
+    /// When the new config has a mint registry, the registry and the entries of token_0 and token_1
+    /// are the first remaining accounts, and the registry sets the verified flag of the pool.
+    /// The extra accounts of token-2022 transfers of the fees follow them.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn rebind_pool_config<'c, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, RebindPoolConfig<'info>>,
+    ) -> Result<()> {
+        instructions::rebind_pool_config(ctx)
+    }
+
+    /// Create the mint registry of an amm config, `initialize` enforces it from then on
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `mode`- 0: disabled, 1: allowlist, 2: denylist
+    ///
+    pub fn init_mint_registry(ctx: Context<InitMintRegistry>, mode: u8) -> Result<()> {
+        instructions::init_mint_registry(ctx, mode)
+    }
+
+    /// Change the mode of a mint registry
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `mode`- 0: disabled, 1: allowlist, 2: denylist
+    ///
+    pub fn set_mint_registry_mode(ctx: Context<SetMintRegistryMode>, mode: u8) -> Result<()> {
+        instructions::set_mint_registry_mode(ctx, mode)
+    }
+
+    /// Allow or deny a mint in the registry of an amm config
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `mint`- The mint of the entry
+    /// * `status`- 1: allowed, 2: denied
+    ///
+    pub fn set_mint_entry(ctx: Context<SetMintEntry>, mint: Pubkey, status: u8) -> Result<()> {
+        instructions::set_mint_entry(ctx, mint, status)
+    }
+
+    /// Remove a mint from the registry of an amm config
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn remove_mint_entry(ctx: Context<RemoveMintEntry>) -> Result<()> {
+        instructions::remove_mint_entry(ctx)
+    }
+
+    /// Register a partner, positions attributed to it count towards its PoolPartner accounts
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `id` - Id of the partner, 0 is reserved for the partner migrated from the pools
+    /// * `name` - Name of the partner, up to 32 bytes
+    /// * `payout_wallet` - Wallet receiving the revenue share of the partner
+    /// * `revenue_share_rate` - Share of the protocol fees paid to the partner, denominated in hundredths of a bip (10^-6)
+    ///
+    pub fn create_partner(
+        ctx: Context<CreatePartner>,
+        id: u64,
+        name: String,
+        payout_wallet: Pubkey,
+        revenue_share_rate: u64,
+    ) -> Result<()> {
+        instructions::create_partner(ctx, id, name, payout_wallet, revenue_share_rate)
+    }
+
+    /// Update the name, payout wallet and revenue share of a partner. In every pool, the new
+    /// revenue share applies to the fees accrued after the next claim_partner_fees, which settles
+    /// the pending ones at the previous rate
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `name` - Name of the partner, up to 32 bytes
+    /// * `payout_wallet` - Wallet receiving the revenue share of the partner
+    /// * `revenue_share_rate` - Share of the protocol fees paid to the partner, denominated in hundredths of a bip (10^-6)
+    ///
+    pub fn update_partner(
+        ctx: Context<UpdatePartner>,
+        name: String,
+        payout_wallet: Pubkey,
+        revenue_share_rate: u64,
+    ) -> Result<()> {
+        instructions::update_partner(ctx, name, payout_wallet, revenue_share_rate)
+    }
+
+    /// Replace the capabilities of a key, 0 removes the key from the roles account.
+    /// Must be signed by a role admin, the last role admin can not be removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `member`- The key to grant the capabilities to
+    /// * `capabilities`- Bitmask of `states::Capability`
+    ///
+    pub fn set_capabilities(
+        ctx: Context<SetCapabilities>,
+        member: Pubkey,
+        capabilities: u32,
+    ) -> Result<()> {
+        instructions::set_capabilities(ctx, member, capabilities)
+    }
+
+    /// Apply a typed update to a pool, replaces the numeric `param` dispatch of `update_pool`
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `update`- The field to update together with its new value
+    ///
+    pub fn apply_pool_update(ctx: Context<UpdatePool>, update: states::PoolUpdate) -> Result<()> {
+        instructions::apply_pool_update(ctx, update)
+    }
+
     /// Collect the protocol fee accrued to the pool
     ///
     /// # Arguments
@@ -143,8 +384,8 @@ pub mod gamma {
     /// * `amount_0_requested` - The maximum amount of token_0 to send, can be 0 to collect fees in only token_1
     /// * `amount_1_requested` - The maximum amount of token_1 to send, can be 0 to collect fees in only token_0
     ///
-    pub fn collect_protocol_fee(
-        ctx: Context<CollectProtocolFee>,
+    pub fn collect_protocol_fee<'c, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, CollectProtocolFee<'info>>,
         amount_0_requested: u64,
         amount_1_requested: u64,
     ) -> Result<()> {
@@ -159,15 +400,17 @@ pub mod gamma {
     /// * `amount_0_requested` - The maximum amount of token_0 to send, can be 0 to collect fees in only token_1
     /// * `amount_1_requested` - The maximum amount of token_1 to send, can be 0 to collect fees in only token_0
     ///
-    pub fn collect_fund_fee(
-        ctx: Context<CollectFundFee>,
+    pub fn collect_fund_fee<'c, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, CollectFundFee<'info>>,
         amount_0_requested: u64,
         amount_1_requested: u64,
     ) -> Result<()> {
         instructions::collect_fund_fee(ctx, amount_0_requested, amount_1_requested)
     }
 
-    /// Creates a pool for the given token pair and the initial price
+    /// Creates a pool for the given token pair and the initial price.
+    /// For mints whose accounts start frozen, the freeze authority signs as the creator
+    /// or as a remaining account so the vaults can be thawed
     ///
     /// # Arguments
     ///
@@ -178,8 +421,8 @@ pub mod gamma {
     /// * `max_trade_fee_rate` - The maximum trade fee that can be charged on swaps
     /// * `volatility_factor` - The volatility factor of the pool to determine the trade fee
     ///
-    pub fn initialize(
-        ctx: Context<Initialize>,
+    pub fn initialize<'c, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, Initialize<'info>>,
         init_amount_0: u64,
         init_amount_1: u64,
         open_time: u64,
@@ -196,6 +439,14 @@ pub mod gamma {
         )
     }
 
+    /// Create the position of the signer in a pool, attributed to the partner passed if any
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `partner` - Ignored, kept for existing clients. The partner is now the optional
+    ///   Partner account of the registry
+    ///
     pub fn init_user_pool_liquidity(
         ctx: Context<InitUserPoolLiquidity>,
         partner: Option<String>,
@@ -203,6 +454,88 @@ pub mod gamma {
         instructions::init_user_pool_liquidity(ctx, partner)
     }
 
+    /// Create the accounting of a partner in a pool, needed before positions of the partner
+    /// can deposit. For the partner 0 it takes over the legacy accounting of the pool. From then on
+    /// the positions of every partner pass the PoolPartner of their partner, created or not.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn init_pool_partner(ctx: Context<InitPoolPartner>) -> Result<()> {
+        instructions::init_pool_partner(ctx)
+    }
+
+    /// Pay the revenue share of the protocol fees accrued by a partner in a pool to its payout
+    /// wallet, can be called by anyone
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn claim_partner_fees<'c, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, ClaimPartnerFees<'info>>,
+    ) -> Result<()> {
+        instructions::claim_partner_fees(ctx)
+    }
+
+    /// Grow a pool created before partner fee tracking to the current size, it has to be
+    /// called once before the pool can be used again
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn resize_pool_state(ctx: Context<ResizePoolState>) -> Result<()> {
+        instructions::resize_pool_state(ctx)
+    }
+
+    /// Count the rewards of a pool created before they were counted, once, so that closing the
+    /// pool and its positions checks all of them. Must be signed by a rewards operator, every
+    /// RewardInfo of the pool is passed sorted by address as remaining accounts
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn migrate_reward_info_count<'c, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, MigrateRewardInfoCount<'info>>,
+    ) -> Result<()>
+    where
+        'c: 'info,
+    {
+        instructions::migrate_reward_info_count(ctx)
+    }
+
+    /// Grow a UserPoolLiquidity created with an older layout to the current size and convert
+    /// it, it has to be called once before the position can be used again
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn resize_user_pool_liquidity(ctx: Context<ResizeUserPoolLiquidity>) -> Result<()> {
+        instructions::resize_user_pool_liquidity(ctx)
+    }
+
+    /// Close the empty UserPoolLiquidity of the signer and its PositionOperator, refunding their
+    /// rent. Every RewardInfo of the pool is passed with the UserRewardInfo of the signer as
+    /// remaining accounts, and the signer must have no unclaimed rewards. The rewards of a pool
+    /// created before they were counted have to be migrated first.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn close_user_pool_liquidity<'c, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, CloseUserPoolLiquidity<'info>>,
+    ) -> Result<()>
+    where
+        'c: 'info,
+    {
+        instructions::close_user_pool_liquidity(ctx)
+    }
+
     /// Creates a pool for the given token pair and the initial price
     ///
     /// # Arguments
@@ -212,8 +545,8 @@ pub mod gamma {
     /// * `maximum_token_0_amount` -  Maximum token 0 amount to deposit, prevents excessive slippage
     /// * `maximum_token_1_amount` - Maximum token 1 amount to deposit, prevents excessive slippage
     ///
-    pub fn deposit(
-        ctx: Context<Deposit>,
+    pub fn deposit<'c, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, Deposit<'info>>,
         lp_token_amount: u64,
         maximum_token_0_amount: u64,
         maximum_token_1_amount: u64,
@@ -252,7 +585,151 @@ pub mod gamma {
         )
     }
 
-    /// Swap the tokens in the pool base input amount
+    /// Move LP shares of the signer to the position of another wallet, creating it if needed.
+    /// The cost basis moves pro-rata and the rewards passed as remaining accounts are settled first,
+    /// the receiving position then only accrues rewards from now on. The receiving wallet must sign
+    /// too when its position already holds LP, as its unsettled rewards are forfeited.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `lp_amount` - Amount of lp_tokens_owned to transfer
+    ///
+    pub fn transfer_liquidity<'c, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, TransferLiquidity<'info>>,
+        lp_amount: u64,
+    ) -> Result<()>
+    where
+        'c: 'info,
+    {
+        instructions::transfer_liquidity(ctx, lp_amount)
+    }
+
+    /// Lock LP of the signer's position, it keeps earning fees and rewards but can't be
+    /// withdrawn or moved until released
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `id` - Picked by the owner to derive the lock address
+    /// * `lp_amount` - Amount of lp_tokens_owned to lock
+    /// * `cliff_time` - Nothing unlocks before this timestamp
+    /// * `end_time` - Timestamp at which everything is unlocked, LP vests linearly until then
+    ///
+    pub fn lock_lp(
+        ctx: Context<LockLp>,
+        id: u64,
+        lp_amount: u64,
+        cliff_time: u64,
+        end_time: u64,
+    ) -> Result<()> {
+        instructions::lock_lp(ctx, id, lp_amount, cliff_time, end_time)
+    }
+
+    /// Release the LP unlocked so far by a lock
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn release_lp(ctx: Context<ReleaseLp>) -> Result<()> {
+        instructions::release_lp(ctx)
+    }
+
+    /// Approve an operator to deposit, withdraw or claim rewards on behalf of the signer's
+    /// position. Tokens still only move to and from the owner's token accounts.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `operator` - The wallet allowed to act on the position
+    /// * `permissions` - Bitmask of OperatorPermission
+    /// * `expires_at` - Timestamp after which the approval is no longer valid
+    ///
+    pub fn set_position_operator(
+        ctx: Context<SetPositionOperator>,
+        operator: Pubkey,
+        permissions: u32,
+        expires_at: u64,
+    ) -> Result<()> {
+        instructions::set_position_operator(ctx, operator, permissions, expires_at)
+    }
+
+    /// Revoke the operator of the signer's position
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn revoke_position_operator(ctx: Context<RevokePositionOperator>) -> Result<()> {
+        instructions::revoke_position_operator(ctx)
+    }
+
+    /// Set, change or remove the partner the signer's position is attributed to. The payout
+    /// wallet of the new partner can co-sign to confirm the attribution.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn set_position_partner(ctx: Context<SetPositionPartner>) -> Result<()> {
+        instructions::set_position_partner(ctx)
+    }
+
+    /// Create the token-2022 LP mint of a pool, letting positions be wrapped into LP tokens.
+    /// The pool authority can close it together with the pool.
+    /// Must be signed by the pool creator or a config creator.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn init_lp_mint(ctx: Context<InitLpMint>) -> Result<()> {
+        instructions::init_lp_mint(ctx)
+    }
+
+    /// Mint LP tokens backed 1:1 by the lp_tokens_owned of the signer, settling the rewards
+    /// passed as remaining accounts first
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `lp_amount` - Amount of lp_tokens_owned to wrap
+    ///
+    pub fn wrap_lp<'c, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, WrapLp<'info>>,
+        lp_amount: u64,
+    ) -> Result<()>
+    where
+        'c: 'info,
+    {
+        instructions::wrap_lp(ctx, lp_amount)
+    }
+
+    /// Burn LP tokens and credit them back to the lp_tokens_owned of the signer. The position
+    /// accrues rewards from now on, the rewards of the LP it already holds have to be settled by
+    /// passing them as remaining accounts or they are forfeited
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `lp_amount` - Amount of LP tokens to unwrap
+    ///
+    pub fn unwrap_lp<'c, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, UnwrapLp<'info>>,
+        lp_amount: u64,
+    ) -> Result<()>
+    where
+        'c: 'info,
+    {
+        instructions::unwrap_lp(ctx, lp_amount)
+    }
+
+    /// Swap the tokens in the pool base input amount. Remaining accounts: registered segmenter,
+    /// registry, referral account, referral token account, the program id in place of the ones
+    /// not used, then the PoolStatistics address of the pool, the program id in its place only
+    /// until it is created. The accounts after them are passed to the token transfers (transfer hooks,
+    /// memo program)
     ///
     /// # Arguments
     ///
@@ -269,7 +746,11 @@ pub mod gamma {
         instructions::swap_base_input(ctx, amount_in, minimum_amount_out)
     }
 
-    /// Swap the tokens in the pool base output amount
+    /// Swap the tokens in the pool base output amount. Remaining accounts: registered segmenter,
+    /// registry, referral account, referral token account, the program id in place of the ones
+    /// not used, then the PoolStatistics address of the pool, the program id in its place only
+    /// until it is created. The accounts after them are passed to the token transfers (transfer hooks,
+    /// memo program)
     ///
     /// # Arguments
     ///
@@ -310,6 +791,41 @@ pub mod gamma {
         unimplemented!("This only provides an interface for clients and CPI")
     }
 
+    /// Get the manipulation-resistant value of one LP unit, returned as return data.
+    /// Uses the observation TWAP and the pool invariant (fair reserves), including the Kamino-deployed amounts.
+    /// Fails unless the observations of the TWAP span at least 30 minutes.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    ///
+    pub fn get_lp_price(ctx: Context<GetLpPrice>) -> Result<LpPrice> {
+        instructions::get_lp_price(ctx)
+    }
+
+    /// Snapshot the LP virtual price (reserves per lp_supply) of the pool for the epoch it was last
+    /// updated in (`recent_epoch`).
+    /// Permissionless, the history account is created on the first call.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    ///
+    pub fn snapshot_lp_price(ctx: Context<SnapshotLpPrice>) -> Result<()> {
+        instructions::snapshot_lp_price(ctx)
+    }
+
+    /// Create the per-epoch volume and fee statistics account of the pool.
+    /// Once it is created every swap has to pass its address as remaining account 4 and updates it.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    ///
+    pub fn init_pool_statistics(ctx: Context<InitPoolStatistics>) -> Result<()> {
+        instructions::init_pool_statistics(ctx)
+    }
+
     /// Create rewards for the pool
     /// Initializes a new reward info account and a reward vault account
     /// Transfers the rewards to the reward vault
@@ -321,8 +837,8 @@ pub mod gamma {
     /// * `end_time` - The end time of the reward
     /// * `reward_amount` - The amount of the reward
     ///
-    pub fn create_rewards(
-        ctx: Context<CreateRewards>,
+    pub fn create_rewards<'c, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, CreateRewards<'info>>,
         start_time: u64,
         end_time: u64,
         reward_amount: u64,
@@ -337,7 +853,9 @@ pub mod gamma {
     ///
     /// * `ctx` - The context of accounts
     ///
-    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+    pub fn claim_rewards<'c, 'info>(
+        ctx: Context<'_, '_, 'c, 'info, ClaimRewards<'info>>,
+    ) -> Result<()> {
         instructions::claim_rewards(ctx)
     }
 
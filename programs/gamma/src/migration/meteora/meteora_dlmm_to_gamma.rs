@@ -138,8 +138,8 @@ pub struct MeteoraDlmmToGamma<'info> {
     pub gamma_vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
 }
 
-pub fn meteora_dlmm_to_gamma(
-    ctx: Context<MeteoraDlmmToGamma>,
+pub fn meteora_dlmm_to_gamma<'info>(
+    ctx: Context<'_, '_, '_, 'info, MeteoraDlmmToGamma<'info>>,
     bin_liquidity_reduction: Vec<BinLiquidityReduction>,
     maximum_token_0_amount: u64,
     maximum_token_1_amount: u64,
@@ -210,6 +210,7 @@ pub fn meteora_dlmm_to_gamma(
 
     deposit_to_gamma_pool(
         &mut deposit_accounts,
+        ctx.remaining_accounts,
         gamma_lp_tokens as u64,
         maximum_token_0_amount,
         maximum_token_1_amount,
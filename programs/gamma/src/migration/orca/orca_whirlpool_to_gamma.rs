@@ -214,6 +214,7 @@ pub fn orca_whirlpool_to_gamma<'info>(
 
     deposit_to_gamma_pool(
         &mut deposit_accounts,
+        ctx.remaining_accounts,
         gamma_lp_tokens as u64,
         maximum_token_0_amount,
         maximum_token_1_amount,
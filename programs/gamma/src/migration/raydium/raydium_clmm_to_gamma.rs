@@ -218,6 +218,7 @@ pub fn raydium_clmm_to_gamma<'a, 'b, 'c, 'info>(
 
     deposit_to_gamma_pool(
         &mut deposit_accounts,
+        ctx.remaining_accounts,
         gamma_lp_tokens as u64,
         maximum_token_0_amount,
         maximum_token_1_amount,
@@ -217,6 +217,7 @@ pub fn raydium_cp_swap_to_gamma<'a, 'b, 'c, 'info>(
     // Deposit into Gamma pool
     deposit_to_gamma_pool(
         &mut deposit_accounts,
+        ctx.remaining_accounts,
         gamma_lp_tokens as u64,
         maximum_token_0_amount,
         maximum_token_1_amount,
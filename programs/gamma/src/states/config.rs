@@ -1,8 +1,13 @@
 use anchor_lang::prelude::*;
 
 use crate::fees::FEE_RATE_DENOMINATOR_VALUE;
+use crate::states::{Capability, ParamValue};
 
 pub const AMM_CONFIG_SEED: &str = "amm_config";
+/// Upper bound for `AmmConfig::timelock_delay`, 30 days
+pub const MAX_TIMELOCK_DELAY: u64 = 30 * 24 * 60 * 60;
+/// Upper bound for `AmmConfig::creator_lp_lock_duration`, 4 years
+pub const MAX_CREATOR_LP_LOCK_DURATION: u64 = 4 * 365 * 24 * 60 * 60;
 
 #[account]
 #[derive(Default, Debug)]
@@ -32,12 +37,153 @@ pub struct AmmConfig {
     pub max_open_time: u64,
     // This account is not a multisig and is allowed to update certain config values on pools
     pub secondary_admin: Pubkey,
+    /// Role of the pending two-step transfer, see `ConfigRole`, 0 when there is none
+    pub pending_role: u8,
+    /// Key that has to accept the pending role transfer
+    pub pending_key: Pubkey,
+    /// Seconds a queued update has to wait before it can be executed, 0 disables the timelock
+    pub timelock_delay: u64,
+    /// Set once the mint registry of the config is created, `initialize` then enforces it
+    pub mint_registry_enabled: bool,
+    /// Seconds the LP minted to the creator in `initialize` stays locked after the pool opens,
+    /// 0 disables the lock
+    pub creator_lp_lock_duration: u64,
     /// padding
-    pub padding: [u64; 7],
+    pub padding: [u8; 6],
+}
+
+/// Keys of an AmmConfig that are transferred with propose/accept
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigRole {
+    ProtocolOwner = 1,
+    FundOwner = 2,
+    SecondaryAdmin = 3,
+}
+
+impl ConfigRole {
+    pub fn new(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(ConfigRole::ProtocolOwner),
+            2 => Some(ConfigRole::FundOwner),
+            3 => Some(ConfigRole::SecondaryAdmin),
+            _ => None,
+        }
+    }
+
+    /// Name of the AmmConfig field holding the role
+    pub fn name(&self) -> String {
+        match self {
+            ConfigRole::ProtocolOwner => "protocol_owner",
+            ConfigRole::FundOwner => "fund_owner",
+            ConfigRole::SecondaryAdmin => "secondary_admin",
+        }
+        .to_string()
+    }
+}
+
+/// A single typed change to an AmmConfig
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum ConfigUpdate {
+    TradeFeeRate { trade_fee_rate: u64 },
+    ProtocolFeeRate { protocol_fee_rate: u64 },
+    FundFeeRate { fund_fee_rate: u64 },
+    ProtocolOwner { protocol_owner: Pubkey },
+    FundOwner { fund_owner: Pubkey },
+    CreatePoolFee { create_pool_fee: u64 },
+    DisableCreatePool { disable_create_pool: bool },
+    MaxOpenTime { max_open_time: u64 },
+    SecondaryAdmin { secondary_admin: Pubkey },
+    TimelockDelay { timelock_delay: u64 },
+    CreatorLpLockDuration { creator_lp_lock_duration: u64 },
+}
+
+impl ConfigUpdate {
+    /// Name of the AmmConfig field the update changes
+    pub fn name(&self) -> String {
+        match self {
+            ConfigUpdate::TradeFeeRate { .. } => "trade_fee_rate",
+            ConfigUpdate::ProtocolFeeRate { .. } => "protocol_fee_rate",
+            ConfigUpdate::FundFeeRate { .. } => "fund_fee_rate",
+            ConfigUpdate::ProtocolOwner { .. } => "protocol_owner",
+            ConfigUpdate::FundOwner { .. } => "fund_owner",
+            ConfigUpdate::CreatePoolFee { .. } => "create_pool_fee",
+            ConfigUpdate::DisableCreatePool { .. } => "disable_create_pool",
+            ConfigUpdate::MaxOpenTime { .. } => "max_open_time",
+            ConfigUpdate::SecondaryAdmin { .. } => "secondary_admin",
+            ConfigUpdate::TimelockDelay { .. } => "timelock_delay",
+            ConfigUpdate::CreatorLpLockDuration { .. } => "creator_lp_lock_duration",
+        }
+        .to_string()
+    }
+
+    /// Current value of the AmmConfig field the update changes
+    pub fn current_value(&self, amm_config: &AmmConfig) -> ParamValue {
+        match self {
+            ConfigUpdate::TradeFeeRate { .. } => ParamValue::U64(amm_config.trade_fee_rate),
+            ConfigUpdate::ProtocolFeeRate { .. } => ParamValue::U64(amm_config.protocol_fee_rate),
+            ConfigUpdate::FundFeeRate { .. } => ParamValue::U64(amm_config.fund_fee_rate),
+            ConfigUpdate::ProtocolOwner { .. } => ParamValue::Pubkey(amm_config.protocol_owner),
+            ConfigUpdate::FundOwner { .. } => ParamValue::Pubkey(amm_config.fund_owner),
+            ConfigUpdate::CreatePoolFee { .. } => ParamValue::U64(amm_config.create_pool_fee),
+            ConfigUpdate::DisableCreatePool { .. } => {
+                ParamValue::Bool(amm_config.disable_create_pool)
+            }
+            ConfigUpdate::MaxOpenTime { .. } => ParamValue::U64(amm_config.max_open_time),
+            ConfigUpdate::SecondaryAdmin { .. } => ParamValue::Pubkey(amm_config.secondary_admin),
+            ConfigUpdate::TimelockDelay { .. } => ParamValue::U64(amm_config.timelock_delay),
+            ConfigUpdate::CreatorLpLockDuration { .. } => {
+                ParamValue::U64(amm_config.creator_lp_lock_duration)
+            }
+        }
+    }
+
+    /// Capability allowed to make the update
+    pub fn required_capability(&self) -> Capability {
+        match self {
+            ConfigUpdate::TradeFeeRate { .. }
+            | ConfigUpdate::ProtocolFeeRate { .. }
+            | ConfigUpdate::FundFeeRate { .. }
+            | ConfigUpdate::CreatePoolFee { .. } => Capability::FeeManager,
+            ConfigUpdate::DisableCreatePool { .. } => Capability::Pauser,
+            ConfigUpdate::MaxOpenTime { .. } | ConfigUpdate::CreatorLpLockDuration { .. } => {
+                Capability::ConfigCreator
+            }
+            ConfigUpdate::ProtocolOwner { .. }
+            | ConfigUpdate::FundOwner { .. }
+            | ConfigUpdate::SecondaryAdmin { .. }
+            | ConfigUpdate::TimelockDelay { .. } => Capability::RoleAdmin,
+        }
+    }
+
+    /// Emergency updates skip the timelock, only disabling pool creation qualifies
+    pub fn is_emergency(&self) -> bool {
+        matches!(
+            self,
+            ConfigUpdate::DisableCreatePool {
+                disable_create_pool: true
+            }
+        )
+    }
 }
 
 impl AmmConfig {
     pub const LEN: usize = 8 + 1 + 1 + 2 + 4 * 8 + 2 * 32 + 8 * 16;
+
+    pub fn role_key(&self, role: ConfigRole) -> Pubkey {
+        match role {
+            ConfigRole::ProtocolOwner => self.protocol_owner,
+            ConfigRole::FundOwner => self.fund_owner,
+            ConfigRole::SecondaryAdmin => self.secondary_admin,
+        }
+    }
+
+    pub fn set_role_key(&mut self, role: ConfigRole, key: Pubkey) {
+        match role {
+            ConfigRole::ProtocolOwner => self.protocol_owner = key,
+            ConfigRole::FundOwner => self.fund_owner = key,
+            ConfigRole::SecondaryAdmin => self.secondary_admin = key,
+        }
+    }
 }
 
 // require all rates to be less than 1 (100%)
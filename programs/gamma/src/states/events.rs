@@ -1,3 +1,4 @@
+use crate::states::QueuedAction;
 use anchor_lang::prelude::*;
 
 /// Emitted when deposit or withdraw
@@ -63,3 +64,323 @@ pub struct MigrationEvent {
     pub token_1_amount_withdrawn: u64,
     pub lp_tokens_migrated: u128,
 }
+
+/// Emitted when the price guardian halts swaps in one direction, the swap that tripped it is not
+/// executed
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+#[derive(Clone, Debug)]
+pub struct GuardianHaltEvent {
+    #[index]
+    pub pool_id: Pubkey,
+    /// true if token_0 -> token_1 swaps were halted, false for token_1 -> token_0
+    pub zero_for_one: bool,
+    /// token_0 price the swap would have left
+    pub spot_price_x32: u128,
+    /// token_0 TWAP the spot price was compared against
+    pub twap_price_x32: u128,
+    /// deviation of the spot price from the TWAP, after dividing by 1_000_000
+    pub deviation_rate: u64,
+    /// timestamp at which swaps resume automatically
+    pub halted_until: u64,
+}
+
+/// Emitted when a guardian halt is lifted
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+#[derive(Clone, Debug)]
+pub struct GuardianResumeEvent {
+    #[index]
+    pub pool_id: Pubkey,
+    /// Pubkey::default() when resumed automatically after the cooldown
+    pub resumed_by: Pubkey,
+}
+
+/// Value of a config or pool parameter before or after an admin change
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum ParamValue {
+    U64(u64),
+    Bool(bool),
+    Pubkey(Pubkey),
+}
+
+/// Emitted when an AmmConfig is created
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+#[derive(Clone, Debug)]
+pub struct CreateAmmConfigEvent {
+    #[index]
+    pub amm_config: Pubkey,
+    pub authority: Pubkey,
+    pub index: u16,
+    pub trade_fee_rate: u64,
+    pub protocol_fee_rate: u64,
+    pub fund_fee_rate: u64,
+    pub create_pool_fee: u64,
+    pub max_open_time: u64,
+    pub protocol_owner: Pubkey,
+    pub fund_owner: Pubkey,
+}
+
+/// Emitted when a parameter of an AmmConfig is changed
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+#[derive(Clone, Debug)]
+pub struct ConfigUpdateEvent {
+    #[index]
+    pub amm_config: Pubkey,
+    /// The signer of the change
+    pub authority: Pubkey,
+    /// Name of the AmmConfig field, e.g. "trade_fee_rate"
+    pub param: String,
+    pub old_value: ParamValue,
+    pub new_value: ParamValue,
+}
+
+/// Emitted when a parameter of a pool is changed
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+#[derive(Clone, Debug)]
+pub struct PoolUpdateEvent {
+    #[index]
+    pub pool_id: Pubkey,
+    /// The signer of the change
+    pub authority: Pubkey,
+    /// Name of the PoolState field, e.g. "status"
+    pub param: String,
+    pub old_value: ParamValue,
+    pub new_value: ParamValue,
+}
+
+/// Emitted when protocol or fund fees are collected
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+#[derive(Clone, Debug)]
+pub struct CollectFeeEvent {
+    #[index]
+    pub pool_id: Pubkey,
+    /// The signer of the collection
+    pub authority: Pubkey,
+    pub recipient_token_0_account: Pubkey,
+    pub recipient_token_1_account: Pubkey,
+    /// collected amounts without transfer fees
+    pub amount_0: u64,
+    pub amount_1: u64,
+    /// fees left in the pool after the collection
+    pub remaining_fees_token_0: u64,
+    pub remaining_fees_token_1: u64,
+    // 0: protocol fee, 1: fund fee
+    pub fee_type: u8,
+}
+
+/// Emitted on each step of a two-step role transfer of an AmmConfig
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+#[derive(Clone, Debug)]
+pub struct RoleTransferEvent {
+    #[index]
+    pub amm_config: Pubkey,
+    /// The signer of the step
+    pub authority: Pubkey,
+    /// 1: protocol_owner, 2: fund_owner, 3: secondary_admin
+    pub role: u8,
+    /// The key holding the role when the step was taken
+    pub current_key: Pubkey,
+    /// The proposed key
+    pub pending_key: Pubkey,
+    // 0: proposed, 1: accepted, 2: cancelled
+    pub action: u8,
+}
+
+/// Emitted when a timelocked update is queued, executed or cancelled
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+#[derive(Clone, Debug)]
+pub struct QueuedUpdateEvent {
+    #[index]
+    pub queued_update: Pubkey,
+    pub amm_config: Pubkey,
+    /// Default for config updates
+    pub pool_state: Pubkey,
+    /// The signer of the step
+    pub authority: Pubkey,
+    pub action: QueuedAction,
+    pub eta: u64,
+    // 0: queued, 1: executed, 2: cancelled
+    pub status: u8,
+}
+
+/// Emitted when the capabilities of a key change in the roles account
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+#[derive(Clone, Debug)]
+pub struct CapabilitiesUpdateEvent {
+    #[index]
+    pub member: Pubkey,
+    /// The signer of the change
+    pub authority: Pubkey,
+    pub old_capabilities: u32,
+    pub new_capabilities: u32,
+}
+
+/// Emitted when an empty pool is closed
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+#[derive(Clone, Debug)]
+pub struct ClosePoolEvent {
+    #[index]
+    pub pool_id: Pubkey,
+    /// The signer closing the pool
+    pub authority: Pubkey,
+    /// Receives the rent of the closed accounts
+    pub rent_receiver: Pubkey,
+    /// Vault balances swept to the protocol owner
+    pub dust_token_0: u64,
+    pub dust_token_1: u64,
+}
+
+/// Emitted when a pool is moved to another amm config
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+#[derive(Clone, Debug)]
+pub struct RebindPoolConfigEvent {
+    #[index]
+    pub pool_id: Pubkey,
+    /// The signer of the rebind
+    pub authority: Pubkey,
+    pub old_amm_config: Pubkey,
+    pub new_amm_config: Pubkey,
+}
+
+/// Emitted when the mode of a mint registry changes, old_mode is 0 when the registry is created
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+#[derive(Clone, Debug)]
+pub struct MintRegistryModeEvent {
+    #[index]
+    pub amm_config: Pubkey,
+    /// The signer of the change
+    pub authority: Pubkey,
+    pub old_mode: u8,
+    pub new_mode: u8,
+}
+
+/// Emitted when a mint entry of a registry changes, status 0 means no entry
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+#[derive(Clone, Debug)]
+pub struct MintEntryEvent {
+    #[index]
+    pub amm_config: Pubkey,
+    pub mint: Pubkey,
+    /// The signer of the change
+    pub authority: Pubkey,
+    pub old_status: u8,
+    pub new_status: u8,
+}
+
+/// Emitted when LP shares of a user are wrapped into or unwrapped from the LP mint
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+#[derive(Clone, Debug)]
+pub struct LpWrapEvent {
+    #[index]
+    pub pool_id: Pubkey,
+    pub owner: Pubkey,
+    pub lp_mint: Pubkey,
+    pub lp_amount: u64,
+    // lp_tokens_owned of the UserPoolLiquidity after the change
+    pub lp_tokens_owned: u128,
+    // 0: wrap, 1: unwrap
+    pub change_type: u8,
+}
+
+/// Emitted when LP shares move from one liquidity position to another
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+#[derive(Clone, Debug)]
+pub struct LiquidityTransferEvent {
+    #[index]
+    pub pool_id: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub lp_amount: u64,
+    // Share of token_0_deposited and token_1_deposited moved with the LP
+    pub token_0_deposited: u128,
+    pub token_1_deposited: u128,
+}
+
+/// Emitted when LP is locked, and when locked LP is released by its unlock schedule
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+#[derive(Clone, Debug)]
+pub struct LpLockEvent {
+    #[index]
+    pub pool_id: Pubkey,
+    pub lp_lock: Pubkey,
+    pub owner: Pubkey,
+    pub locked_amount: u64,
+    // Total LP released by the lock so far
+    pub released_amount: u64,
+    pub cliff_time: u64,
+    pub end_time: u64,
+    // 0: lock, 1: release
+    pub change_type: u8,
+}
+
+/// Emitted when the owner of a position approves or revokes an operator, permissions is 0 on revoke
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+#[derive(Clone, Debug)]
+pub struct PositionOperatorEvent {
+    #[index]
+    pub pool_id: Pubkey,
+    pub owner: Pubkey,
+    pub operator: Pubkey,
+    pub permissions: u32,
+    pub expires_at: u64,
+}
+
+/// Emitted when a partner is registered or updated
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+#[derive(Clone, Debug)]
+pub struct PartnerEvent {
+    #[index]
+    pub partner_id: u64,
+    pub name: String,
+    pub payout_wallet: Pubkey,
+    pub revenue_share_rate: u64,
+}
+
+/// Emitted when the revenue share of a partner in a pool is paid to its payout wallet
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+#[derive(Clone, Debug)]
+pub struct PartnerFeesClaimEvent {
+    #[index]
+    pub pool_id: Pubkey,
+    pub partner_id: u64,
+    pub payout_wallet: Pubkey,
+    pub amount_0: u64,
+    pub amount_1: u64,
+    // Partner fees of the pool left after the claim
+    pub remaining_partner_fees_token_0: u64,
+    pub remaining_partner_fees_token_1: u64,
+}
+
+/// Emitted when the partner a position is attributed to is set, changed or removed
+#[event]
+#[cfg_attr(feature = "client", derive(Debug))]
+#[derive(Clone, Debug)]
+pub struct PositionPartnerEvent {
+    #[index]
+    pub pool_id: Pubkey,
+    pub owner: Pubkey,
+    pub old_partner: Option<u64>,
+    pub new_partner: Option<u64>,
+    // LP of the position moved between the PoolPartner accounts
+    pub lp_amount: u64,
+    // true if the payout wallet of the new partner co-signed
+    pub partner_signed: bool,
+}
@@ -0,0 +1,98 @@
+use crate::error::GammaError;
+use anchor_lang::prelude::*;
+
+/// Seed to derive account address and signature
+pub const LP_LOCK_SEED: &str = "lp_lock";
+
+/// LP of a UserPoolLiquidity that can't be withdrawn or moved before it unlocks.
+/// The locked LP stays in lp_tokens_owned, so it keeps earning swap fees and rewards.
+/// Nothing unlocks before `cliff_time`, then LP vests linearly from `start_time`
+/// until `end_time`. A cliff only lock has `cliff_time == end_time`.
+#[account]
+#[derive(Default, Debug)]
+pub struct LpLock {
+    /// Bump to identify PDA
+    pub bump: u8,
+    /// Picked by the owner to hold several locks on the same position
+    pub id: u64,
+    pub pool_state: Pubkey,
+    pub owner: Pubkey,
+    pub locked_amount: u64,
+    /// LP already removed from lp_tokens_locked of the position
+    pub released_amount: u64,
+    pub start_time: u64,
+    pub cliff_time: u64,
+    pub end_time: u64,
+    /// padding
+    pub padding: [u64; 4],
+}
+
+impl LpLock {
+    pub const LEN: usize = 8 + 1 + 8 + 32 * 2 + 8 * 5 + 8 * 4;
+
+    /// LP unlocked by the schedule at `current_time`, released or not
+    pub fn unlocked_amount(&self, current_time: u64) -> Result<u64> {
+        if current_time < self.cliff_time {
+            return Ok(0);
+        }
+        if current_time >= self.end_time {
+            return Ok(self.locked_amount);
+        }
+        let unlocked = u128::from(self.locked_amount)
+            .checked_mul(u128::from(current_time - self.start_time))
+            .ok_or(GammaError::MathOverflow)?
+            .checked_div(u128::from(self.end_time - self.start_time))
+            .ok_or(GammaError::MathOverflow)?;
+        u64::try_from(unlocked).map_err(|_| error!(GammaError::MathOverflow))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn lp_lock(start_time: u64, cliff_time: u64, end_time: u64) -> LpLock {
+        LpLock {
+            locked_amount: 1_000,
+            start_time,
+            cliff_time,
+            end_time,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn nothing_unlocks_before_the_cliff() {
+        let lock = lp_lock(100, 150, 200);
+        assert_eq!(lock.unlocked_amount(0).unwrap(), 0);
+        assert_eq!(lock.unlocked_amount(149).unwrap(), 0);
+        // The LP vested since start_time unlocks at once at the cliff
+        assert_eq!(lock.unlocked_amount(150).unwrap(), 500);
+    }
+
+    #[test]
+    fn unlocks_linearly_until_the_end() {
+        let lock = lp_lock(100, 100, 200);
+        assert_eq!(lock.unlocked_amount(100).unwrap(), 0);
+        assert_eq!(lock.unlocked_amount(125).unwrap(), 250);
+        assert_eq!(lock.unlocked_amount(199).unwrap(), 990);
+        assert_eq!(lock.unlocked_amount(200).unwrap(), 1_000);
+        assert_eq!(lock.unlocked_amount(u64::MAX).unwrap(), 1_000);
+    }
+
+    #[test]
+    fn cliff_only_lock_unlocks_everything_at_the_end() {
+        let lock = lp_lock(100, 200, 200);
+        assert_eq!(lock.unlocked_amount(199).unwrap(), 0);
+        assert_eq!(lock.unlocked_amount(200).unwrap(), 1_000);
+    }
+
+    #[test]
+    fn large_amounts_do_not_overflow() {
+        let lock = LpLock {
+            locked_amount: u64::MAX,
+            ..lp_lock(0, 0, u64::MAX)
+        };
+        assert_eq!(lock.unlocked_amount(u64::MAX / 2).unwrap(), u64::MAX / 2);
+    }
+}
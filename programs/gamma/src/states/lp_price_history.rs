@@ -0,0 +1,190 @@
+use crate::error::GammaError;
+use crate::states::Q32;
+use anchor_lang::prelude::*;
+
+/// Seed to derive account address and signature
+pub const LP_PRICE_HISTORY_SEED: &str = "lp_price_history";
+// Number of LpPriceSnapshot elements, one per epoch
+pub const LP_PRICE_HISTORY_NUM: usize = 64;
+
+/// The element of snapshots in LpPriceHistory
+#[zero_copy(unsafe)]
+#[repr(packed)]
+#[derive(Default, Debug)]
+pub struct LpPriceSnapshot {
+    /// The epoch of the snapshot
+    pub epoch: u64,
+    /// The block timestamp of the snapshot
+    pub block_timestamp: u64,
+    /// token_0_vault_amount (including kamino deployed amount) per lp_supply, Q32.32
+    pub token_0_per_lp_x32: u128,
+    /// token_1_vault_amount (including kamino deployed amount) per lp_supply, Q32.32
+    pub token_1_per_lp_x32: u128,
+}
+
+impl LpPriceSnapshot {
+    pub const LEN: usize = 8 + 8 + 16 + 16;
+}
+
+/// Ring buffer of the LP virtual price per epoch, used to compute APYs from account state alone
+#[account(zero_copy(unsafe))]
+#[repr(packed)]
+#[cfg_attr(any(feature = "client", feature = "test-sbf"), derive(Debug))]
+pub struct LpPriceHistory {
+    /// Whether the first snapshot is written
+    pub initialized: bool,
+    /// The most recently written index of the snapshots array
+    pub snapshot_index: u16,
+    pub pool_id: Pubkey,
+    /// snapshot array
+    pub snapshots: [LpPriceSnapshot; LP_PRICE_HISTORY_NUM],
+    /// padding
+    pub padding: [u64; 4],
+}
+
+impl Default for LpPriceHistory {
+    #[inline]
+    fn default() -> LpPriceHistory {
+        LpPriceHistory {
+            initialized: false,
+            snapshot_index: 0,
+            pool_id: Pubkey::default(),
+            snapshots: [LpPriceSnapshot::default(); LP_PRICE_HISTORY_NUM],
+            padding: [0u64; 4],
+        }
+    }
+}
+
+impl LpPriceHistory {
+    pub const LEN: usize =
+        8 + 1 + 2 + 32 + (LP_PRICE_HISTORY_NUM * LpPriceSnapshot::LEN) + 4 * 8;
+
+    /// Writes the virtual price of the pool for the given epoch.
+    /// Writable at most once per epoch, returns false if the epoch is already snapshotted.
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The LpPriceHistory account to write in
+    /// * `epoch` - The epoch the pool was last updated in
+    /// * `block_timestamp` - The current timestamp
+    /// * `token_0_vault_amount` - The pool token_0 amount, including kamino deployed amount
+    /// * `token_1_vault_amount` - The pool token_1 amount, including kamino deployed amount
+    /// * `lp_supply` - The lp supply of the pool
+    ///
+    pub fn snapshot(
+        &mut self,
+        epoch: u64,
+        block_timestamp: u64,
+        token_0_vault_amount: u64,
+        token_1_vault_amount: u64,
+        lp_supply: u64,
+    ) -> Result<bool> {
+        if lp_supply == 0 {
+            return err!(GammaError::EmptySupply);
+        }
+
+        let snapshot_index = if !self.initialized {
+            self.initialized = true;
+            self.snapshot_index
+        } else {
+            if self.snapshots[self.snapshot_index as usize].epoch >= epoch {
+                return Ok(false);
+            }
+            if self.snapshot_index as usize == LP_PRICE_HISTORY_NUM - 1 {
+                0
+            } else {
+                self.snapshot_index + 1
+            }
+        };
+
+        let snapshot = &mut self.snapshots[snapshot_index as usize];
+        snapshot.epoch = epoch;
+        snapshot.block_timestamp = block_timestamp;
+        snapshot.token_0_per_lp_x32 = u128::from(token_0_vault_amount)
+            .checked_mul(Q32)
+            .ok_or(GammaError::MathOverflow)?
+            .checked_div(u128::from(lp_supply))
+            .ok_or(GammaError::MathOverflow)?;
+        snapshot.token_1_per_lp_x32 = u128::from(token_1_vault_amount)
+            .checked_mul(Q32)
+            .ok_or(GammaError::MathOverflow)?
+            .checked_div(u128::from(lp_supply))
+            .ok_or(GammaError::MathOverflow)?;
+        self.snapshot_index = snapshot_index;
+        Ok(true)
+    }
+
+    /// Returns the written snapshots ordered from oldest to newest
+    pub fn ordered_snapshots(&self) -> Vec<LpPriceSnapshot> {
+        if !self.initialized {
+            return Vec::new();
+        }
+        let newest_index = self.snapshot_index as usize;
+        (1..=LP_PRICE_HISTORY_NUM)
+            .map(|offset| self.snapshots[(newest_index + offset) % LP_PRICE_HISTORY_NUM])
+            .filter(|snapshot| snapshot.block_timestamp != 0)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn snapshot_epoch(lp_price_history: &mut LpPriceHistory, epoch: u64) -> bool {
+        lp_price_history
+            .snapshot(epoch, epoch * 100, 1_000 + epoch, 2_000, 1_000)
+            .unwrap()
+    }
+
+    #[test]
+    fn snapshots_once_per_epoch() {
+        let mut lp_price_history = LpPriceHistory::default();
+        assert!(lp_price_history.ordered_snapshots().is_empty());
+
+        assert!(snapshot_epoch(&mut lp_price_history, 10));
+        // The same or an older epoch leaves the snapshot of the epoch as written
+        assert!(!lp_price_history
+            .snapshot(10, 2_000, 5_000, 5_000, 1_000)
+            .unwrap());
+        assert!(!snapshot_epoch(&mut lp_price_history, 9));
+        assert!(snapshot_epoch(&mut lp_price_history, 12));
+
+        assert_eq!({ lp_price_history.snapshot_index }, 1);
+        let snapshots = lp_price_history.ordered_snapshots();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!({ snapshots[0].epoch }, 10);
+        assert_eq!({ snapshots[0].block_timestamp }, 1_000);
+        assert_eq!({ snapshots[0].token_0_per_lp_x32 }, 1_010 * Q32 / 1_000);
+        assert_eq!({ snapshots[0].token_1_per_lp_x32 }, 2 * Q32);
+        assert_eq!({ snapshots[1].epoch }, 12);
+    }
+
+    #[test]
+    fn wraps_around_keeping_the_latest_epochs_in_order() {
+        let mut lp_price_history = LpPriceHistory::default();
+        let last_epoch = LP_PRICE_HISTORY_NUM as u64 + 3;
+        for epoch in 1..=last_epoch {
+            assert!(snapshot_epoch(&mut lp_price_history, epoch));
+        }
+
+        // The three oldest snapshots are overwritten from the start of the array
+        assert_eq!({ lp_price_history.snapshot_index }, 2);
+        assert_eq!({ lp_price_history.snapshots[0].epoch }, last_epoch - 2);
+        let snapshots = lp_price_history.ordered_snapshots();
+        assert_eq!(snapshots.len(), LP_PRICE_HISTORY_NUM);
+        for (snapshot, epoch) in snapshots.iter().zip(4..=last_epoch) {
+            assert_eq!({ snapshot.epoch }, epoch);
+            assert_eq!({ snapshot.block_timestamp }, epoch * 100);
+        }
+        assert!(!snapshot_epoch(&mut lp_price_history, last_epoch));
+    }
+
+    #[test]
+    fn empty_supply_is_not_snapshotted() {
+        let mut lp_price_history = LpPriceHistory::default();
+        assert!(lp_price_history.snapshot(1, 100, 1_000, 1_000, 0).is_err());
+        assert!(!{ lp_price_history.initialized });
+        assert!(lp_price_history.ordered_snapshots().is_empty());
+    }
+}
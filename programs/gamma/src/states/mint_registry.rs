@@ -0,0 +1,139 @@
+use crate::error::GammaError;
+use anchor_lang::prelude::*;
+
+/// Seed to derive account address and signature
+pub const MINT_REGISTRY_SEED: &str = "mint_registry";
+/// Seed to derive account address and signature
+pub const MINT_ENTRY_SEED: &str = "mint_entry";
+/// Remaining accounts read by `check_pool_mints`: the registry and the entries of both mints
+pub const MINT_REGISTRY_ACCOUNTS_LEN: usize = 3;
+
+/// How the registry of an AmmConfig restricts the mints of new pools
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MintRegistryMode {
+    /// Any supported mint can be used
+    Disabled,
+    /// Both mints need an `Allowed` entry
+    Allowlist,
+    /// Mints with a `Denied` entry are rejected
+    Denylist,
+}
+
+impl MintRegistryMode {
+    pub fn new(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(MintRegistryMode::Disabled),
+            1 => Ok(MintRegistryMode::Allowlist),
+            2 => Ok(MintRegistryMode::Denylist),
+            _ => err!(GammaError::InvalidInput),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MintEntryStatus {
+    Allowed = 1,
+    Denied = 2,
+}
+
+impl MintEntryStatus {
+    pub fn new(value: u8) -> Result<Self> {
+        match value {
+            1 => Ok(MintEntryStatus::Allowed),
+            2 => Ok(MintEntryStatus::Denied),
+            _ => err!(GammaError::InvalidInput),
+        }
+    }
+}
+
+/// Mint registry of an AmmConfig, enforced by `initialize` once created
+#[account]
+#[derive(Default, Debug)]
+pub struct MintRegistry {
+    /// Bump to identify PDA
+    pub bump: u8,
+    pub amm_config: Pubkey,
+    /// See `MintRegistryMode`
+    pub mode: u8,
+    /// padding
+    pub padding: [u64; 4],
+}
+
+impl MintRegistry {
+    pub const LEN: usize = 8 + 1 + 32 + 1 + 8 * 4;
+}
+
+/// Allowed or denied mint in the registry of an AmmConfig, closed to remove it
+#[account]
+#[derive(Default, Debug)]
+pub struct MintEntry {
+    /// Bump to identify PDA
+    pub bump: u8,
+    pub amm_config: Pubkey,
+    pub mint: Pubkey,
+    /// See `MintEntryStatus`
+    pub status: u8,
+    /// padding
+    pub padding: [u64; 2],
+}
+
+impl MintEntry {
+    pub const LEN: usize = 8 + 1 + 32 * 2 + 1 + 8 * 2;
+}
+
+/// Status of the registry entry of `mint`, `entry_info` must be the entry PDA even when
+/// no entry exists so the check cannot be skipped
+fn mint_entry_status(amm_config: Pubkey, mint: Pubkey, entry_info: &AccountInfo) -> Result<u8> {
+    let (entry_key, _) = Pubkey::find_program_address(
+        &[
+            MINT_ENTRY_SEED.as_bytes(),
+            amm_config.as_ref(),
+            mint.as_ref(),
+        ],
+        &crate::id(),
+    );
+    require_keys_eq!(entry_info.key(), entry_key, GammaError::InvalidInput);
+    if *entry_info.owner != crate::id() || entry_info.data_is_empty() {
+        return Ok(0);
+    }
+    let entry = MintEntry::try_deserialize(&mut &entry_info.try_borrow_data()?[..])?;
+    Ok(entry.status)
+}
+
+/// Enforces the mint registry of `amm_config` on the mints of a new pool.
+/// `remaining_accounts` holds the registry followed by the entries of token_0 and token_1.
+/// Returns true if both mints are allowed, which marks the pool as verified.
+pub fn check_pool_mints(
+    amm_config: Pubkey,
+    remaining_accounts: &[AccountInfo],
+    token_0_mint: Pubkey,
+    token_1_mint: Pubkey,
+) -> Result<bool> {
+    let (registry_info, entry_0_info, entry_1_info) = match remaining_accounts {
+        [registry_info, entry_0_info, entry_1_info, ..] => {
+            (registry_info, entry_0_info, entry_1_info)
+        }
+        _ => return err!(GammaError::InvalidInput),
+    };
+    let (registry_key, _) = Pubkey::find_program_address(
+        &[MINT_REGISTRY_SEED.as_bytes(), amm_config.as_ref()],
+        &crate::id(),
+    );
+    require_keys_eq!(registry_info.key(), registry_key, GammaError::InvalidInput);
+    require_keys_eq!(*registry_info.owner, crate::id(), GammaError::InvalidInput);
+    let registry = MintRegistry::try_deserialize(&mut &registry_info.try_borrow_data()?[..])?;
+
+    let status_0 = mint_entry_status(amm_config, token_0_mint, entry_0_info)?;
+    let status_1 = mint_entry_status(amm_config, token_1_mint, entry_1_info)?;
+    let allowed =
+        status_0 == MintEntryStatus::Allowed as u8 && status_1 == MintEntryStatus::Allowed as u8;
+    let denied =
+        status_0 == MintEntryStatus::Denied as u8 || status_1 == MintEntryStatus::Denied as u8;
+
+    match MintRegistryMode::new(registry.mode)? {
+        MintRegistryMode::Disabled => {}
+        MintRegistryMode::Allowlist => require!(allowed, GammaError::MintNotAllowed),
+        MintRegistryMode::Denylist => require!(!denied, GammaError::MintNotAllowed),
+    }
+    Ok(allowed)
+}
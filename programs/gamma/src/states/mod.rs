@@ -1,15 +1,31 @@
 pub mod config;
 pub mod events;
+pub mod lp_lock;
+pub mod lp_price_history;
+pub mod mint_registry;
 pub mod oracle;
+pub mod partner;
 pub mod pool;
+pub mod pool_statistics;
+pub mod position_operator;
+pub mod queued_update;
 pub mod reward_info;
+pub mod roles;
 pub mod user_pool_liquidity;
 pub mod user_reward_info;
 
 pub use config::*;
 pub use events::*;
+pub use lp_lock::*;
+pub use lp_price_history::*;
+pub use mint_registry::*;
 pub use oracle::*;
+pub use partner::*;
 pub use pool::*;
+pub use pool_statistics::*;
+pub use position_operator::*;
+pub use queued_update::*;
 pub use reward_info::*;
+pub use roles::*;
 pub use user_pool_liquidity::*;
 pub use user_reward_info::*;
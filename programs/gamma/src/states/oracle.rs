@@ -10,6 +10,32 @@ pub const OBSERVATION_NUM: usize = 100;
 /// The duration of observation update in seconds
 pub const OBSERVATION_UPDATE_DURATION_DEFAULT: u64 = 15;
 
+/// How the pool writes to its ObservationState after a swap.
+/// In both modes consecutive observations are at least `OBSERVATION_UPDATE_DURATION_DEFAULT`
+/// seconds apart, so the `OBSERVATION_NUM` observations of a pool trading at least every 15
+/// seconds cover about 1485 seconds. Pools trading less often cover a longer window.
+#[derive(PartialEq, Eq)]
+pub enum OracleUpdateMode {
+    /// Record the pre-swap price, at most once per `OBSERVATION_UPDATE_DURATION_DEFAULT` seconds.
+    /// The prices of the swaps in between are not recorded
+    PreSwap,
+    /// Record the post-swap price of every swap, it is accumulated over the time until the next one.
+    /// Swaps of a same slot share its block timestamp and are merged into one observation, swaps
+    /// less than `OBSERVATION_UPDATE_DURATION_DEFAULT` seconds after the previous observation
+    /// extend the latest one instead of writing a new one
+    PostSwapPerSlot,
+}
+
+impl OracleUpdateMode {
+    pub fn new(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(OracleUpdateMode::PreSwap),
+            1 => Ok(OracleUpdateMode::PostSwapPerSlot),
+            _ => err!(GammaError::InvalidInput),
+        }
+    }
+}
+
 /// The element of observations in ObservationState
 #[zero_copy(unsafe)]
 #[repr(packed)]
@@ -113,6 +139,97 @@ impl ObservationState {
             Ok(())
         }
     }
+
+    /// Writes an observation for `OracleUpdateMode::PostSwapPerSlot`. The price recorded after the
+    /// previous swap held since the latest observation, so it is the one accumulated over the
+    /// elapsed time. The caller then records the post-swap price for the next interval, a price
+    /// only ever weighs the time it actually held. Nothing is written when no time elapsed, which
+    /// merges the swaps of a slot since they all share its block timestamp.
+    /// While the latest observation is less than `OBSERVATION_UPDATE_DURATION_DEFAULT` seconds
+    /// after the previous one it is moved forward instead of writing a new one, so the
+    /// observations span as long a window as in `OracleUpdateMode::PreSwap`.
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The ObservationState account to write in
+    /// * `block_timestamp` - The current timestamp of to update
+    /// * `recorded_token_0_price_x32` - The token_0_price_x32 recorded after the previous swap
+    /// * `recorded_token_1_price_x32` - The token_1_price_x32 recorded after the previous swap
+    ///
+    pub fn update_post_swap(
+        &mut self,
+        block_timestamp: u64,
+        recorded_token_0_price_x32: u128,
+        recorded_token_1_price_x32: u128,
+    ) -> Result<()> {
+        if !self.initialized {
+            return self.update(
+                block_timestamp,
+                recorded_token_0_price_x32,
+                recorded_token_1_price_x32,
+            );
+        }
+
+        let observation_index = self.observation_index;
+        let last_observation = self.observations[observation_index as usize];
+        let delta_time = block_timestamp.saturating_sub(last_observation.block_timestamp);
+        if delta_time == 0 {
+            return Ok(());
+        }
+        let delta_token_0_price_x32 = recorded_token_0_price_x32
+            .checked_mul(delta_time.into())
+            .ok_or(GammaError::MathOverflow)?;
+        let delta_token_1_price_x32 = recorded_token_1_price_x32
+            .checked_mul(delta_time.into())
+            .ok_or(GammaError::MathOverflow)?;
+        let previous_observation_index = if observation_index == 0 {
+            OBSERVATION_NUM - 1
+        } else {
+            observation_index as usize - 1
+        };
+        let previous_observation = self.observations[previous_observation_index];
+        let next_observation_index = if last_observation
+            .block_timestamp
+            .saturating_sub(previous_observation.block_timestamp)
+            < OBSERVATION_UPDATE_DURATION_DEFAULT
+        {
+            observation_index
+        } else if observation_index as usize == OBSERVATION_NUM - 1 {
+            0
+        } else {
+            observation_index + 1
+        };
+        self.observations[next_observation_index as usize].block_timestamp = block_timestamp;
+        self.observations[next_observation_index as usize].cumulative_token_0_price_x32 =
+            last_observation
+                .cumulative_token_0_price_x32
+                .wrapping_add(delta_token_0_price_x32);
+        self.observations[next_observation_index as usize].cumulative_token_1_price_x32 =
+            last_observation
+                .cumulative_token_1_price_x32
+                .wrapping_add(delta_token_1_price_x32);
+        self.observation_index = next_observation_index;
+        Ok(())
+    }
+
+    /// Seconds between the oldest and the newest observation within `window` of `current_time`,
+    /// the span the TWAP of `DynamicFee::get_price_range` is computed over
+    pub fn observed_window(&self, current_time: u64, window: u64) -> u64 {
+        let timestamps = self
+            .observations
+            .iter()
+            .filter(|observation| {
+                observation.block_timestamp != 0
+                    && observation.cumulative_token_0_price_x32 != 0
+                    && observation.cumulative_token_1_price_x32 != 0
+                    && current_time.saturating_sub(observation.block_timestamp) <= window
+            })
+            .map(|observation| observation.block_timestamp);
+        match (timestamps.clone().min(), timestamps.max()) {
+            (Some(oldest), Some(newest)) => newest - oldest,
+            _ => 0,
+        }
+    }
 }
 
 /// Returns the block timestamp truncated to 32 bits, i.e. mod 2**32
@@ -124,3 +241,117 @@ pub fn block_timestamp() -> Result<u64> {
     };
     Ok(clock.unix_timestamp as u64)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fees::DynamicFee;
+
+    const PRICE_X32: u128 = 100 << 32;
+
+    #[test]
+    fn one_slot_spike_does_not_dominate_the_twap() {
+        let mut observation_state = ObservationState::default();
+        let mut timestamp = 1_000;
+        observation_state
+            .update_post_swap(timestamp, PRICE_X32, PRICE_X32)
+            .unwrap();
+
+        // A swap every 10 seconds at a steady price
+        for _ in 0..10 {
+            timestamp += 10;
+            observation_state
+                .update_post_swap(timestamp, PRICE_X32, PRICE_X32)
+                .unwrap();
+        }
+
+        // After a long quiet gap a single swap moves the price 100x, then it is swapped back one
+        // second later. The gap is accumulated at the price that held, the spike weighs 1 second.
+        timestamp += 60;
+        observation_state
+            .update_post_swap(timestamp, PRICE_X32, PRICE_X32)
+            .unwrap();
+        let spike_price_x32 = PRICE_X32 * 100;
+        timestamp += 1;
+        observation_state
+            .update_post_swap(timestamp, spike_price_x32, spike_price_x32)
+            .unwrap();
+
+        // The first observation has no accumulated price yet and the swaps 10 seconds apart are
+        // merged in pairs, the TWAP starts 20 seconds after the first one
+        let (min_price_x32, max_price_x32, twap_x32) =
+            DynamicFee::get_price_range(&observation_state, timestamp, 161).unwrap();
+        // 140 seconds at the price and 1 second at the spike
+        assert_eq!(twap_x32, (PRICE_X32 * 140 + spike_price_x32) / 141);
+        assert!(twap_x32 < PRICE_X32 * 2);
+        assert_eq!(min_price_x32, PRICE_X32);
+        assert_eq!(max_price_x32, spike_price_x32);
+    }
+
+    #[test]
+    fn same_slot_updates_are_merged() {
+        let mut observation_state = ObservationState::default();
+        for timestamp in [1_000, 1_015, 1_030] {
+            observation_state
+                .update_post_swap(timestamp, PRICE_X32, PRICE_X32)
+                .unwrap();
+        }
+        let observation_index = observation_state.observation_index;
+        // Swaps landing in the slot of the latest observation share its timestamp, they only
+        // move the recorded price kept by the pool, whatever price they pass
+        observation_state
+            .update_post_swap(1_030, PRICE_X32 * 1_000, PRICE_X32 * 1_000)
+            .unwrap();
+        let latest_observation_index = observation_state.observation_index;
+        assert_eq!(latest_observation_index, observation_index);
+        assert_eq!(
+            DynamicFee::get_price_range(&observation_state, 1_030, 60).unwrap(),
+            (PRICE_X32, PRICE_X32, PRICE_X32)
+        );
+    }
+
+    #[test]
+    fn swaps_every_second_keep_the_update_duration_between_observations() {
+        let mut observation_state = ObservationState::default();
+        let mut timestamp = 1_000;
+        observation_state
+            .update_post_swap(timestamp, PRICE_X32, PRICE_X32)
+            .unwrap();
+        // A swap every second for an hour, at a price moving every second
+        for second in 0..3_600u128 {
+            timestamp += 1;
+            observation_state
+                .update_post_swap(timestamp, PRICE_X32 + second, PRICE_X32 + second)
+                .unwrap();
+        }
+
+        // The observations are still 15 seconds apart
+        let latest_index = observation_state.observation_index as usize;
+        for offset in 0..OBSERVATION_NUM - 1 {
+            let index = (latest_index + OBSERVATION_NUM - offset) % OBSERVATION_NUM;
+            let previous_index = (index + OBSERVATION_NUM - 1) % OBSERVATION_NUM;
+            let observation = observation_state.observations[index];
+            let previous_observation = observation_state.observations[previous_index];
+            assert_eq!(
+                observation.block_timestamp - previous_observation.block_timestamp,
+                OBSERVATION_UPDATE_DURATION_DEFAULT
+            );
+        }
+        assert_eq!(
+            observation_state.observed_window(timestamp, 3_600),
+            (OBSERVATION_NUM as u64 - 1) * OBSERVATION_UPDATE_DURATION_DEFAULT
+        );
+
+        // Extending the latest observation keeps the cumulative price exact, the price of the
+        // second after `1_000 + second` is `PRICE_X32 + second`
+        let oldest = observation_state.observations[(latest_index + 1) % OBSERVATION_NUM];
+        let latest = observation_state.observations[latest_index];
+        let expected_sum: u128 = (oldest.block_timestamp - 1_000..latest.block_timestamp - 1_000)
+            .map(|second| PRICE_X32 + u128::from(second))
+            .sum();
+        assert_eq!(
+            { latest.cumulative_token_0_price_x32 } - { oldest.cumulative_token_0_price_x32 },
+            expected_sum
+        );
+    }
+}
@@ -0,0 +1,287 @@
+use crate::error::GammaError;
+use crate::fees::FEE_RATE_DENOMINATOR_VALUE;
+use crate::utils::U256;
+use anchor_lang::prelude::*;
+
+use super::{PoolState, Q64};
+
+/// Seed to derive account address and signature
+pub const PARTNER_SEED: &str = "partner";
+/// Seed to derive account address and signature
+pub const POOL_PARTNER_SEED: &str = "pool_partner";
+
+pub const PARTNER_NAME_MAX_LEN: usize = 32;
+
+/// Id of AssetDash, the only partner before the registry, as stored in `PoolState::partners`
+pub const LEGACY_PARTNER_ID: u64 = 0;
+
+/// Partner of the registry, positions attributed to it count towards its PoolPartner accounts
+#[account]
+#[derive(Default, Debug)]
+pub struct Partner {
+    /// Bump to identify PDA
+    pub bump: u8,
+    pub id: u64,
+    pub name: String,
+    /// Wallet receiving the revenue share of the partner
+    pub payout_wallet: Pubkey,
+    /// Share of the protocol fees earned on the LP of the partner that is paid out to it,
+    /// after dividing by 1_000_000
+    pub revenue_share_rate: u64,
+    /// padding
+    pub padding: [u64; 4],
+}
+
+impl Partner {
+    pub const LEN: usize = 8 + 1 + 8 + 4 + PARTNER_NAME_MAX_LEN + 32 + 8 + 8 * 4;
+}
+
+/// Accounting of a partner in a pool, replaces `PoolState::partners`
+#[account]
+#[derive(Default, Debug)]
+pub struct PoolPartner {
+    /// Bump to identify PDA
+    pub bump: u8,
+    pub pool_state: Pubkey,
+    pub partner_id: u64,
+    /// LP tokens owned by positions attributed to the partner
+    pub lp_token_linked_with_partner: u64,
+    /// protocol_fee_growth_per_lp_token_0_x64 of the pool when the cumulative fees were last updated
+    pub protocol_fee_growth_checkpoint_token_0_x64: u128,
+    /// protocol_fee_growth_per_lp_token_1_x64 of the pool when the cumulative fees were last updated
+    pub protocol_fee_growth_checkpoint_token_1_x64: u128,
+    /// tvl_share * protocol fees of token_0, summed over the swaps
+    pub cumulative_fee_total_times_tvl_share_token_0: u64,
+    /// tvl_share * protocol fees of token_1, summed over the swaps
+    pub cumulative_fee_total_times_tvl_share_token_1: u64,
+    /// Part of cumulative_fee_total_times_tvl_share_token_0 already settled by `claim_partner_fees`
+    pub cumulative_fee_settled_token_0: u64,
+    /// Part of cumulative_fee_total_times_tvl_share_token_1 already settled by `claim_partner_fees`
+    pub cumulative_fee_settled_token_1: u64,
+    /// Revenue share of token_0 paid to the partner so far
+    pub fees_paid_token_0: u64,
+    /// Revenue share of token_1 paid to the partner so far
+    pub fees_paid_token_1: u64,
+    /// Revenue share rate the fees accrued since the last claim are paid at. Takes the one of the
+    /// partner on every claim, so a rate change applies to the fees accrued after the next claim
+    pub revenue_share_rate: u64,
+    /// padding
+    pub padding: [u64; 3],
+}
+
+impl PoolPartner {
+    pub const LEN: usize = 8 + 1 + 32 + 8 * 2 + 16 * 2 + 8 * 7 + 8 * 3;
+
+    pub fn find_address(pool_id: Pubkey, partner_id: u64) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                POOL_PARTNER_SEED.as_bytes(),
+                pool_id.as_ref(),
+                partner_id.to_le_bytes().as_ref(),
+            ],
+            &crate::id(),
+        )
+        .0
+    }
+
+    /// Cumulative fees of the partner, including the ones since the last checkpoint
+    pub fn cumulative_fees(&self, pool_state: &PoolState) -> Result<(u64, u64)> {
+        let cumulative_fee_token_0 = self
+            .cumulative_fee_total_times_tvl_share_token_0
+            .checked_add(self.fees_since_checkpoint(
+                pool_state.protocol_fee_growth_per_lp_token_0_x64,
+                self.protocol_fee_growth_checkpoint_token_0_x64,
+            )?)
+            .ok_or(GammaError::MathOverflow)?;
+        let cumulative_fee_token_1 = self
+            .cumulative_fee_total_times_tvl_share_token_1
+            .checked_add(self.fees_since_checkpoint(
+                pool_state.protocol_fee_growth_per_lp_token_1_x64,
+                self.protocol_fee_growth_checkpoint_token_1_x64,
+            )?)
+            .ok_or(GammaError::MathOverflow)?;
+        Ok((cumulative_fee_token_0, cumulative_fee_token_1))
+    }
+
+    /// Moves the checkpoints to the current protocol fee growth of the pool, counting the fees
+    /// accrued since then as owed to the partners.
+    /// Must be called before lp_token_linked_with_partner changes.
+    pub fn update_cumulative_fees(&mut self, pool_state: &mut PoolState) -> Result<()> {
+        let (cumulative_fee_token_0, cumulative_fee_token_1) = self.cumulative_fees(pool_state)?;
+        pool_state.partner_fees_owed_token_0 = pool_state
+            .partner_fees_owed_token_0
+            .checked_add(cumulative_fee_token_0 - self.cumulative_fee_total_times_tvl_share_token_0)
+            .ok_or(GammaError::MathOverflow)?;
+        pool_state.partner_fees_owed_token_1 = pool_state
+            .partner_fees_owed_token_1
+            .checked_add(cumulative_fee_token_1 - self.cumulative_fee_total_times_tvl_share_token_1)
+            .ok_or(GammaError::MathOverflow)?;
+        self.cumulative_fee_total_times_tvl_share_token_0 = cumulative_fee_token_0;
+        self.cumulative_fee_total_times_tvl_share_token_1 = cumulative_fee_token_1;
+        self.protocol_fee_growth_checkpoint_token_0_x64 =
+            pool_state.protocol_fee_growth_per_lp_token_0_x64;
+        self.protocol_fee_growth_checkpoint_token_1_x64 =
+            pool_state.protocol_fee_growth_per_lp_token_1_x64;
+        Ok(())
+    }
+
+    /// Settles the fees accrued since the last settlement at `revenue_share_rate`. They leave
+    /// the partner fees of the pool and the returned revenue share leaves the protocol fees,
+    /// it has to be paid out to the partner.
+    pub fn settle(&mut self, pool_state: &mut PoolState) -> Result<(u64, u64)> {
+        self.update_cumulative_fees(pool_state)?;
+        let accrued_0 = self
+            .cumulative_fee_total_times_tvl_share_token_0
+            .checked_sub(self.cumulative_fee_settled_token_0)
+            .ok_or(GammaError::MathOverflow)?;
+        let accrued_1 = self
+            .cumulative_fee_total_times_tvl_share_token_1
+            .checked_sub(self.cumulative_fee_settled_token_1)
+            .ok_or(GammaError::MathOverflow)?;
+        let amount_0 = revenue_share_of(accrued_0, self.revenue_share_rate)?;
+        let amount_1 = revenue_share_of(accrued_1, self.revenue_share_rate)?;
+
+        pool_state.partner_fees_token_0 = pool_state
+            .partner_fees_token_0
+            .checked_sub(accrued_0)
+            .ok_or(GammaError::MathOverflow)?;
+        pool_state.partner_fees_token_1 = pool_state
+            .partner_fees_token_1
+            .checked_sub(accrued_1)
+            .ok_or(GammaError::MathOverflow)?;
+        pool_state.partner_fees_owed_token_0 = pool_state
+            .partner_fees_owed_token_0
+            .checked_sub(accrued_0)
+            .ok_or(GammaError::MathOverflow)?;
+        pool_state.partner_fees_owed_token_1 = pool_state
+            .partner_fees_owed_token_1
+            .checked_sub(accrued_1)
+            .ok_or(GammaError::MathOverflow)?;
+        pool_state.protocol_fees_token_0 = pool_state
+            .protocol_fees_token_0
+            .checked_sub(amount_0)
+            .ok_or(GammaError::MathOverflow)?;
+        pool_state.protocol_fees_token_1 = pool_state
+            .protocol_fees_token_1
+            .checked_sub(amount_1)
+            .ok_or(GammaError::MathOverflow)?;
+
+        self.cumulative_fee_settled_token_0 = self.cumulative_fee_total_times_tvl_share_token_0;
+        self.cumulative_fee_settled_token_1 = self.cumulative_fee_total_times_tvl_share_token_1;
+        self.fees_paid_token_0 = self
+            .fees_paid_token_0
+            .checked_add(amount_0)
+            .ok_or(GammaError::MathOverflow)?;
+        self.fees_paid_token_1 = self
+            .fees_paid_token_1
+            .checked_add(amount_1)
+            .ok_or(GammaError::MathOverflow)?;
+        Ok((amount_0, amount_1))
+    }
+
+    /// Adds or removes `lp_amount` from the LP linked with the partner, and from the total of
+    /// the pool
+    pub fn update_lp(
+        &mut self,
+        pool_state: &mut PoolState,
+        lp_amount: u64,
+        add: bool,
+    ) -> Result<()> {
+        self.update_cumulative_fees(pool_state)?;
+        if add {
+            self.lp_token_linked_with_partner = self
+                .lp_token_linked_with_partner
+                .checked_add(lp_amount)
+                .ok_or(GammaError::MathOverflow)?;
+            pool_state.lp_token_linked_with_partners = pool_state
+                .lp_token_linked_with_partners
+                .checked_add(lp_amount)
+                .ok_or(GammaError::MathOverflow)?;
+        } else {
+            self.lp_token_linked_with_partner = self
+                .lp_token_linked_with_partner
+                .checked_sub(lp_amount)
+                .ok_or(GammaError::MathOverflow)?;
+            pool_state.lp_token_linked_with_partners = pool_state
+                .lp_token_linked_with_partners
+                .checked_sub(lp_amount)
+                .ok_or(GammaError::MathOverflow)?;
+            pool_state.release_partner_fees_rounding();
+        }
+        Ok(())
+    }
+
+    fn fees_since_checkpoint(
+        &self,
+        fee_growth_x64: u128,
+        fee_growth_checkpoint_x64: u128,
+    ) -> Result<u64> {
+        let fees = U256::from(self.lp_token_linked_with_partner)
+            .checked_mul(U256::from(
+                fee_growth_x64.wrapping_sub(fee_growth_checkpoint_x64),
+            ))
+            .ok_or(GammaError::MathOverflow)?
+            / U256::from(Q64);
+        u64::try_from(fees).or(err!(GammaError::MathOverflow))
+    }
+}
+
+fn revenue_share_of(accrued: u64, revenue_share_rate: u64) -> Result<u64> {
+    let amount = u128::from(accrued)
+        .checked_mul(u128::from(revenue_share_rate))
+        .ok_or(GammaError::MathOverflow)?
+        / u128::from(FEE_RATE_DENOMINATOR_VALUE);
+    u64::try_from(amount).or(err!(GammaError::MathOverflow))
+}
+
+/// Adds or removes `lp_amount` from the PoolPartner of `partner_id`, whose address has to be
+/// passed in `remaining_accounts` once a PoolPartner exists for the pool. Nothing to do for
+/// positions without a partner.
+/// Until the PoolPartner is created the LP of the legacy partner is kept in `PoolState::partners`
+/// for `init_pool_partner` to move, and the LP leaving other partners is not tracked, so that
+/// withdrawing never waits on the partner accounting.
+pub fn update_pool_partner_lp(
+    pool_id: Pubkey,
+    pool_state: &mut PoolState,
+    partner_id: Option<u64>,
+    remaining_accounts: &[AccountInfo],
+    lp_amount: u64,
+    add: bool,
+) -> Result<()> {
+    let Some(partner_id) = partner_id else {
+        return Ok(());
+    };
+    let pool_partner_key = PoolPartner::find_address(pool_id, partner_id);
+    let pool_partner_info = remaining_accounts
+        .iter()
+        .find(|account| *account.key == pool_partner_key);
+    // Clients written before PoolPartner accounts don't pass it, none can exist for the pool yet
+    require!(
+        pool_partner_info.is_some() || pool_state.pool_partners_created == 0,
+        GammaError::PoolPartnerMissing
+    );
+    let Some(pool_partner_info) =
+        pool_partner_info.filter(|pool_partner_info| *pool_partner_info.owner == crate::id())
+    else {
+        if partner_id == LEGACY_PARTNER_ID {
+            let mut partners = pool_state.partners;
+            let legacy = &mut partners[0];
+            legacy.lp_token_linked_with_partner = if add {
+                legacy.lp_token_linked_with_partner.checked_add(lp_amount)
+            } else {
+                legacy.lp_token_linked_with_partner.checked_sub(lp_amount)
+            }
+            .ok_or(GammaError::MathOverflow)?;
+            pool_state.partners = partners;
+            return Ok(());
+        }
+        // LP can only be added to a partner once its PoolPartner exists
+        require!(!add, GammaError::PoolPartnerMissing);
+        return Ok(());
+    };
+    let mut pool_partner =
+        PoolPartner::try_deserialize(&mut &pool_partner_info.try_borrow_data()?[..])?;
+    pool_partner.update_lp(pool_state, lp_amount, add)?;
+    pool_partner.try_serialize(&mut &mut pool_partner_info.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
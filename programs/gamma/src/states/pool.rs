@@ -1,4 +1,9 @@
+use crate::curve::{SwapResult, TradeDirection};
 use crate::error::GammaError;
+use crate::fees::{ceil_div, DynamicFee, FEE_RATE_DENOMINATOR_VALUE};
+use crate::states::{
+    Capability, GuardianHaltEvent, ObservationState, OracleUpdateMode, ParamValue,
+};
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::Mint;
 use std::ops::{BitAnd, BitOr, BitXor};
@@ -10,36 +15,141 @@ pub const POOL_VAULT_SEED: &str = "pool_vault";
 // This is for deriving the token account where kamino collateral is deposited
 pub const POOL_KAMINO_DEPOSITS_SEED: &str = "pool_kamino_deposits";
 
+// Decimals of the LP mint, LP amounts are the geometric mean of both token amounts
+pub const LP_MINT_DECIMALS: u8 = 9;
+
 pub const Q32: u128 = (u32::MAX as u128) + 1; // 2^32
+pub const Q64: u128 = (u64::MAX as u128) + 1; // 2^64
+
+// Cooldown used by the price guardian when none is configured on the pool
+pub const GUARDIAN_DEFAULT_COOLDOWN: u64 = 300; // 5 minutes
+
+// Longest cooldown that can be configured on a pool
+pub const MAX_GUARDIAN_COOLDOWN: u64 = 86400; // 1 day
+
+// Window of observations used to compute the TWAP the guardian compares against
+pub const GUARDIAN_TWAP_WINDOW: u64 = 3600; // 1 hour
 
 pub enum PoolStatusBitIndex {
     Deposit,
     Withdraw,
     Swap,
+    /// Swaps selling token_0 for token_1, also set in `guardian_status` by the price guardian
+    SwapZeroForOne,
+    /// Swaps selling token_1 for token_0, also set in `guardian_status` by the price guardian
+    SwapOneForZero,
+    /// Exact output swaps, leaving only base input swaps
+    SwapBaseOutput,
+    /// Wind down the pool: deposits are rejected, withdrawals stay allowed and swaps are only
+    /// allowed when they move the price towards the TWAP
+    ReduceOnly,
 }
 
-#[derive(PartialEq, Eq)]
-pub enum PoolStatusBitFlag {
-    Enable,
-    Disable,
+impl PoolStatusBitIndex {
+    pub fn swap_direction(trade_direction: TradeDirection) -> Self {
+        match trade_direction {
+            TradeDirection::ZeroForOne => PoolStatusBitIndex::SwapZeroForOne,
+            TradeDirection::OneForZero => PoolStatusBitIndex::SwapOneForZero,
+        }
+    }
 }
 
-#[derive(Default, Debug, PartialEq, Eq, Clone, Copy, AnchorDeserialize, AnchorSerialize)]
-#[repr(u64)]
-pub enum PartnerType {
-    #[default]
-    AssetDash = 0,
+/// A single typed change to a PoolState
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum PoolUpdate {
+    Status { status: u8 },
+    MaxTradeFeeRate { max_trade_fee_rate: u64 },
+    VolatilityFactor { volatility_factor: u64 },
+    MaxSharedToken0 { max_shared_token0: u64 },
+    MaxSharedToken1 { max_shared_token1: u64 },
+    /// Opens the pool for swaps now
+    OpenTimeNow,
+    GuardianDeviationThreshold { guardian_deviation_threshold: u32 },
+    GuardianCooldown { guardian_cooldown: u32 },
+    ResumeGuardianHalt,
+    OracleUpdateMode { oracle_update_mode: u8 },
+    Verified { verified: bool },
 }
 
-impl PartnerType {
-    pub fn new(value: u64) -> Self {
-        match value {
-            0 => PartnerType::AssetDash,
-            _ => PartnerType::AssetDash,
+impl PoolUpdate {
+    /// Name of the PoolState field the update changes
+    pub fn name(&self) -> String {
+        match self {
+            PoolUpdate::Status { .. } => "status",
+            PoolUpdate::MaxTradeFeeRate { .. } => "max_trade_fee_rate",
+            PoolUpdate::VolatilityFactor { .. } => "volatility_factor",
+            PoolUpdate::MaxSharedToken0 { .. } => "max_shared_token0",
+            PoolUpdate::MaxSharedToken1 { .. } => "max_shared_token1",
+            PoolUpdate::OpenTimeNow => "open_time",
+            PoolUpdate::GuardianDeviationThreshold { .. } => "guardian_deviation_threshold",
+            PoolUpdate::GuardianCooldown { .. } => "guardian_cooldown",
+            PoolUpdate::ResumeGuardianHalt => "guardian_status",
+            PoolUpdate::OracleUpdateMode { .. } => "oracle_update_mode",
+            PoolUpdate::Verified { .. } => "verified",
         }
+        .to_string()
     }
+
+    /// Current value of the PoolState field the update changes
+    pub fn current_value(&self, pool_state: &PoolState) -> ParamValue {
+        ParamValue::U64(match self {
+            PoolUpdate::Status { .. } => pool_state.status as u64,
+            PoolUpdate::ResumeGuardianHalt => pool_state.guardian_status as u64,
+            PoolUpdate::MaxTradeFeeRate { .. } => pool_state.max_trade_fee_rate,
+            PoolUpdate::VolatilityFactor { .. } => pool_state.volatility_factor,
+            PoolUpdate::MaxSharedToken0 { .. } => pool_state.max_shared_token0,
+            PoolUpdate::MaxSharedToken1 { .. } => pool_state.max_shared_token1,
+            PoolUpdate::OpenTimeNow => pool_state.open_time,
+            PoolUpdate::GuardianDeviationThreshold { .. } => {
+                pool_state.guardian_deviation_threshold as u64
+            }
+            PoolUpdate::GuardianCooldown { .. } => pool_state.guardian_cooldown as u64,
+            PoolUpdate::OracleUpdateMode { .. } => pool_state.oracle_update_mode as u64,
+            PoolUpdate::Verified { .. } => pool_state.verified as u64,
+        })
+    }
+
+    /// Capability allowed to make the update
+    pub fn required_capability(&self) -> Capability {
+        match self {
+            PoolUpdate::Status { .. }
+            | PoolUpdate::OpenTimeNow
+            | PoolUpdate::GuardianDeviationThreshold { .. }
+            | PoolUpdate::GuardianCooldown { .. }
+            | PoolUpdate::ResumeGuardianHalt => Capability::Pauser,
+            PoolUpdate::MaxTradeFeeRate { .. } | PoolUpdate::VolatilityFactor { .. } => {
+                Capability::FeeManager
+            }
+            PoolUpdate::MaxSharedToken0 { .. } | PoolUpdate::MaxSharedToken1 { .. } => {
+                Capability::KaminoShareManager
+            }
+            PoolUpdate::Verified { .. } | PoolUpdate::OracleUpdateMode { .. } => {
+                Capability::ConfigCreator
+            }
+        }
+    }
+
+    /// Emergency updates skip the timelock: status changes that only set disable bits,
+    /// resuming a guardian halt and removing the verified flag
+    pub fn is_emergency(&self, pool_state: &PoolState) -> bool {
+        match self {
+            PoolUpdate::Status { status } => {
+                let current_status = pool_state.status;
+                (status & current_status) == current_status
+            }
+            PoolUpdate::ResumeGuardianHalt | PoolUpdate::Verified { verified: false } => true,
+            _ => false,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq)]
+pub enum PoolStatusBitFlag {
+    Enable,
+    Disable,
 }
 
+/// Accounting of a partner before PoolPartner accounts, kept for the migration of existing pools
 #[zero_copy(unsafe)]
 #[repr(packed)]
 #[derive(Default, Debug)]
@@ -68,10 +178,9 @@ pub struct PoolState {
     /// Vault to store Token B of the pool
     pub token_1_vault: Pubkey,
 
-    /// Pool tokens are issued when Token A or Token B are deposited
-    /// Pool tokens can be withdrawn back to the original Token A or Token B
-    // pub lp_mint: Pubkey,
-    pub _padding1: [u8; 32],
+    /// Mint of the tokenized LP shares, wrapping `lp_tokens_owned` of a UserPoolLiquidity.
+    /// Only meaningful once `lp_mint_enabled` is set, older pools may hold a stale key here
+    pub lp_mint: Pubkey,
     /// Mint info of Token A
     pub token_0_mint: Pubkey,
     /// Mint info of Token B
@@ -91,11 +200,14 @@ pub struct PoolState {
     /// Bit0: 1 - Disable Deposit(value will be 1), 0 - Deposit can be done(normal)
     /// Bit1: 1 - Disable Withdraw(value will be 2), 0 - Withdraw can be done(normal)
     /// Bit2: 1 - Disable Swap(value will be 4), 0 - Swap can be done(normal)
+    /// Bit3: 1 - Disable token_0 -> token_1 swaps(value will be 8), 0 - normal
+    /// Bit4: 1 - Disable token_1 -> token_0 swaps(value will be 16), 0 - normal
+    /// Bit5: 1 - Disable exact output swaps(value will be 32), 0 - normal
+    /// Bit6: 1 - Reduce only, no deposits and swaps only towards the TWAP(value will be 64)
     pub status: u8,
 
     /// lp_mint decimals
-    // pub lp_mint_decimals: u8,
-    pub _padding2: u8,
+    pub lp_mint_decimals: u8,
     /// mint0 and mint1 decimals
     pub mint_0_decimals: u8,
     pub mint_1_decimals: u8,
@@ -140,7 +252,8 @@ pub struct PoolState {
     pub max_shared_token0: u64,
     pub max_shared_token1: u64,
 
-    // This will store the partner information, like how much token0 and token1 they was invested from their platforms.
+    // Legacy accounting of AssetDash, moved to its PoolPartner account by `init_pool_partner`.
+    // No longer updated by swaps.
     pub partners: [PartnerInfo; 1],
 
     // Keeps track of the absolute amount we put in kamino, in terms of the token0 or token1.
@@ -150,12 +263,75 @@ pub struct PoolState {
     // To keep track of the profit we made from kamino, in terms of the token0 or token1.
     pub withdrawn_kamino_profit_token_0: u64,
     pub withdrawn_kamino_profit_token_1: u64,
+
+    // Max deviation of the spot price from the TWAP after a swap, after dividing by 1_000_000.
+    // If zero the price guardian is disabled.
+    pub guardian_deviation_threshold: u32,
+    // Seconds a swap direction stays halted once the guardian trips, if zero then default of 300 is used
+    pub guardian_cooldown: u32,
+    // Timestamp at which the guardian halt is lifted, zero when no halt is active
+    pub guardian_halted_until: u64,
+    // How swaps write to the observation state, see `OracleUpdateMode`
+    pub oracle_update_mode: u8,
+    // Swap directions halted by the price guardian, same bits as `status`.
+    // Kept apart from `status` so lifting a halt never re-enables a direction the admin
+    // disabled, which means `status` alone doesn't show a halt. Read the pool through
+    // `get_status_by_bit`, it counts these bits as disabled until a swap after
+    // `guardian_halted_until` or `ResumeGuardianHalt` clears them
+    pub guardian_status: u8,
+    // 1 if both mints are allowed by the mint registry of the config, or set by the admin
+    pub verified: u8,
+    // 1 once the LP mint is created, LP shares can then be wrapped into lp_mint tokens
+    pub lp_mint_enabled: u8,
+    // 1 once the PoolStatistics account of the pool is created, swaps then have to pass it
+    pub pool_statistics_enabled: u8,
+    // 1 once reward_info_count counts every RewardInfo of the pool. Pools created before the
+    // rewards were counted start at 0 until `migrate_reward_info_count`
+    pub reward_info_count_migrated: u8,
+    // 1 once a PoolPartner account is created for the pool, the positions of a partner then have
+    // to pass the PoolPartner of their partner
+    pub pool_partners_created: u8,
+    pub _padding3: [u8; 1],
+    // Trade fees of token_0 left in the pool for the liquidity providers, per LP token as a Q64.64.
+    // Only ever increases, wrapping around on overflow
+    pub fee_growth_per_lp_token_0_x64: u128,
+    // Trade fees of token_1 left in the pool for the liquidity providers, per LP token as a Q64.64
+    pub fee_growth_per_lp_token_1_x64: u128,
+    pub _padding4: [u64; 1],
+    // Protocol fees of token_0 per LP token as a Q64.64, after referrals. PoolPartner accounts
+    // accrue their tvl share of the protocol fees from it. Wraps around on overflow
+    pub protocol_fee_growth_per_lp_token_0_x64: u128,
+    // Protocol fees of token_1 per LP token as a Q64.64, after referrals
+    pub protocol_fee_growth_per_lp_token_1_x64: u128,
+    // Sum of the lp_token_linked_with_partner of the PoolPartner accounts of the pool
+    pub lp_token_linked_with_partners: u64,
+    // Part of protocol_fees_token_0 set aside for the partners until claimed, not collectable
+    pub partner_fees_token_0: u64,
+    // Part of protocol_fees_token_1 set aside for the partners until claimed, not collectable
+    pub partner_fees_token_1: u64,
+    // LP minted to the pool creator in `initialize` that is locked until creator_lp_unlock_time,
+    // when the amm config requires it
+    pub creator_lp_locked: u64,
+    // Timestamp from which the creator can withdraw or move creator_lp_locked
+    pub creator_lp_unlock_time: u64,
+    // token_0_price_x32 after the latest swap, accumulated in the observations over the time until
+    // the next one when the oracle is in OracleUpdateMode::PostSwapPerSlot. Zero until recorded
+    pub oracle_token_0_price_x32: u128,
+    // token_1_price_x32 after the latest swap
+    pub oracle_token_1_price_x32: u128,
+    // Part of partner_fees_token_0 accrued by the PoolPartner accounts and not claimed yet, the
+    // rest of it is rounding
+    pub partner_fees_owed_token_0: u64,
+    // Part of partner_fees_token_1 accrued by the PoolPartner accounts and not claimed yet
+    pub partner_fees_owed_token_1: u64,
+    // Number of RewardInfo accounts created for the pool, all of them are checked when closing it
+    pub reward_info_count: u64,
     /// padding
-    pub padding: [u64; 8],
+    pub padding: [u64; 4],
 }
 
 impl PoolState {
-    pub const LEN: usize = 8 + 10 * 32 + 5 * 1 + 7 * 8 + 16 * 4 + 23 * 8;
+    pub const LEN: usize = 8 + 10 * 32 + 5 * 1 + 7 * 8 + 16 * 8 + 35 * 8;
 
     pub fn initialize(
         &mut self,
@@ -213,7 +389,34 @@ impl PoolState {
 
         self.partners = [PartnerInfo::default(); 1];
 
-        self.padding = [0u64; 8];
+        self.guardian_deviation_threshold = 0;
+        self.guardian_cooldown = 0;
+        self.guardian_halted_until = 0;
+        self.oracle_update_mode = OracleUpdateMode::PreSwap as u8;
+        self.guardian_status = 0;
+        self.verified = 0;
+        self.lp_mint_enabled = 0;
+        self.pool_statistics_enabled = 0;
+        self.reward_info_count_migrated = 1;
+        self.pool_partners_created = 0;
+        self._padding3 = [0u8; 1];
+        self.fee_growth_per_lp_token_0_x64 = 0;
+        self.fee_growth_per_lp_token_1_x64 = 0;
+        self._padding4 = [0u64; 1];
+        self.protocol_fee_growth_per_lp_token_0_x64 = 0;
+        self.protocol_fee_growth_per_lp_token_1_x64 = 0;
+        self.lp_token_linked_with_partners = 0;
+        self.partner_fees_token_0 = 0;
+        self.partner_fees_token_1 = 0;
+        self.partner_fees_owed_token_0 = 0;
+        self.partner_fees_owed_token_1 = 0;
+        self.reward_info_count = 0;
+        self.creator_lp_locked = 0;
+        self.creator_lp_unlock_time = 0;
+        self.oracle_token_0_price_x32 = 0;
+        self.oracle_token_1_price_x32 = 0;
+
+        self.padding = [0u64; 4];
         Ok(())
     }
 
@@ -232,15 +435,117 @@ impl PoolState {
     }
 
     // Get status by bit, if it is 'normal'/enabled return true
+    // Bits halted by the price guardian count as disabled
     pub fn get_status_by_bit(&self, bit: PoolStatusBitIndex) -> bool {
         let status = u8::from(1) << (bit as u8);
-        self.status.bitand(status) == 0
+        self.status.bitor(self.guardian_status).bitand(status) == 0
     }
 
     pub fn vault_amount_without_fee(&self) -> Result<(u64, u64)> {
         Ok((self.token_0_vault_amount, self.token_1_vault_amount))
     }
 
+    /// Adds the share of a swap fee that stays in the pool to the fee growth of the input token
+    pub fn accrue_lp_fee(&mut self, trade_direction: TradeDirection, lp_fee: u64) -> Result<()> {
+        let fee_growth = self.fee_growth_per_lp_token(lp_fee)?;
+        match trade_direction {
+            TradeDirection::ZeroForOne => {
+                self.fee_growth_per_lp_token_0_x64 =
+                    self.fee_growth_per_lp_token_0_x64.wrapping_add(fee_growth);
+            }
+            TradeDirection::OneForZero => {
+                self.fee_growth_per_lp_token_1_x64 =
+                    self.fee_growth_per_lp_token_1_x64.wrapping_add(fee_growth);
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the protocol fee of a swap to the protocol fee growth of the input token, and sets
+    /// aside the tvl share of the partners. It is rounded up so it always covers what the
+    /// PoolPartner accounts accrue.
+    pub fn accrue_protocol_fee(
+        &mut self,
+        trade_direction: TradeDirection,
+        protocol_fee: u64,
+    ) -> Result<()> {
+        let fee_growth = self.fee_growth_per_lp_token(protocol_fee)?;
+        let partner_fee = self.partner_share_of(protocol_fee)?;
+        match trade_direction {
+            TradeDirection::ZeroForOne => {
+                self.protocol_fee_growth_per_lp_token_0_x64 = self
+                    .protocol_fee_growth_per_lp_token_0_x64
+                    .wrapping_add(fee_growth);
+                self.partner_fees_token_0 = self
+                    .partner_fees_token_0
+                    .checked_add(partner_fee)
+                    .ok_or(GammaError::MathOverflow)?;
+            }
+            TradeDirection::OneForZero => {
+                self.protocol_fee_growth_per_lp_token_1_x64 = self
+                    .protocol_fee_growth_per_lp_token_1_x64
+                    .wrapping_add(fee_growth);
+                self.partner_fees_token_1 = self
+                    .partner_fees_token_1
+                    .checked_add(partner_fee)
+                    .ok_or(GammaError::MathOverflow)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Protocol fees that can be collected, the ones set aside for the partners excluded
+    pub fn collectable_protocol_fees(&self) -> Result<(u64, u64)> {
+        Ok((
+            self.protocol_fees_token_0
+                .checked_sub(self.partner_fees_token_0)
+                .ok_or(GammaError::MathOverflow)?,
+            self.protocol_fees_token_1
+                .checked_sub(self.partner_fees_token_1)
+                .ok_or(GammaError::MathOverflow)?,
+        ))
+    }
+
+    /// Releases to the protocol fees what rounding left in the partner fees, once no LP is
+    /// linked with a partner and the PoolPartner accounts have nothing more to accrue
+    pub fn release_partner_fees_rounding(&mut self) {
+        if self.lp_token_linked_with_partners == 0 {
+            self.partner_fees_token_0 = self
+                .partner_fees_token_0
+                .min(self.partner_fees_owed_token_0);
+            self.partner_fees_token_1 = self
+                .partner_fees_token_1
+                .min(self.partner_fees_owed_token_1);
+        }
+    }
+
+    fn partner_share_of(&self, fee: u64) -> Result<u64> {
+        if self.lp_supply == 0 {
+            return Ok(0);
+        }
+        let share = ceil_div(
+            u128::from(fee),
+            u128::from(self.lp_token_linked_with_partners),
+            u128::from(self.lp_supply),
+        )
+        .ok_or(GammaError::MathOverflow)?;
+        Ok(u64::try_from(share)
+            .or(err!(GammaError::MathOverflow))?
+            .min(fee))
+    }
+
+    fn fee_growth_per_lp_token(&self, fee: u64) -> Result<u128> {
+        if self.lp_supply == 0 {
+            return Ok(0);
+        }
+        let fee_growth = u128::from(fee)
+            .checked_mul(Q64)
+            .ok_or(GammaError::MathOverflow)?
+            .checked_div(u128::from(self.lp_supply))
+            .ok_or(GammaError::MathOverflow)?;
+        Ok(fee_growth)
+    }
+
     pub fn token_price_x32(&self) -> Result<(u128, u128)> {
         let (token_0_amount, token_1_amount) = self.vault_amount_without_fee()?;
         Ok((
@@ -248,4 +553,165 @@ impl PoolState {
             token_0_amount as u128 * Q32 as u128 / token_1_amount as u128,
         ))
     }
+
+    /// Writes the observation of a swap in `OracleUpdateMode::PostSwapPerSlot`: the price recorded
+    /// after the previous swap is accumulated over the time it held, then the post-swap price is
+    /// recorded for the next interval. The pre-swap price stands in until a price was recorded.
+    pub fn update_oracle_post_swap(
+        &mut self,
+        observation_state: &mut ObservationState,
+        block_timestamp: u64,
+        token_0_price_x32_before_swap: u128,
+        token_1_price_x32_before_swap: u128,
+    ) -> Result<()> {
+        let (recorded_token_0_price_x32, recorded_token_1_price_x32) =
+            if self.oracle_token_0_price_x32 == 0 || self.oracle_token_1_price_x32 == 0 {
+                (token_0_price_x32_before_swap, token_1_price_x32_before_swap)
+            } else {
+                (self.oracle_token_0_price_x32, self.oracle_token_1_price_x32)
+            };
+        observation_state.update_post_swap(
+            block_timestamp,
+            recorded_token_0_price_x32,
+            recorded_token_1_price_x32,
+        )?;
+        let (token_0_price_x32, token_1_price_x32) = self.token_price_x32()?;
+        self.oracle_token_0_price_x32 = token_0_price_x32;
+        self.oracle_token_1_price_x32 = token_1_price_x32;
+        Ok(())
+    }
+
+    /// Lifts the guardian halt once its cooldown has elapsed.
+    /// Returns true if the pool was resumed.
+    pub fn resume_guardian_halt_if_expired(&mut self, block_timestamp: u64) -> bool {
+        if self.guardian_halted_until == 0 || block_timestamp < self.guardian_halted_until {
+            return false;
+        }
+        self.resume_guardian_halt();
+        true
+    }
+
+    /// Clears the guardian halt, directions disabled in `status` by the admin stay disabled
+    pub fn resume_guardian_halt(&mut self) {
+        self.guardian_status = 0;
+        self.guardian_halted_until = 0;
+    }
+
+    /// Compares the spot price a swap would leave with the TWAP of the observations and halts
+    /// swaps in the traded direction if the deviation exceeds `guardian_deviation_threshold`.
+    /// Must be called before the swap changes the pool, the swap itself is still executed.
+    /// Returns the event to emit if the guardian tripped.
+    pub fn check_price_guardian(
+        &mut self,
+        pool_id: Pubkey,
+        observation_state: &ObservationState,
+        trade_direction: TradeDirection,
+        swap_result: &SwapResult,
+        block_timestamp: u64,
+    ) -> Result<Option<GuardianHaltEvent>> {
+        if self.guardian_deviation_threshold == 0 {
+            return Ok(None);
+        }
+
+        let (_, _, twap_price_x32) =
+            DynamicFee::get_price_range(observation_state, block_timestamp, GUARDIAN_TWAP_WINDOW)?;
+        if twap_price_x32 == 0 {
+            // Not enough observations to compare against
+            return Ok(None);
+        }
+
+        let spot_price_x32 = Self::price_after_swap_x32(trade_direction, swap_result)?;
+        let deviation_rate = spot_price_x32
+            .abs_diff(twap_price_x32)
+            .checked_mul(FEE_RATE_DENOMINATOR_VALUE as u128)
+            .ok_or(GammaError::MathOverflow)?
+            .checked_div(twap_price_x32)
+            .ok_or(GammaError::MathOverflow)?;
+        if deviation_rate <= self.guardian_deviation_threshold as u128 {
+            return Ok(None);
+        }
+
+        let cooldown = if self.guardian_cooldown == 0 {
+            GUARDIAN_DEFAULT_COOLDOWN
+        } else {
+            self.guardian_cooldown as u64
+        };
+        let halted_until = block_timestamp
+            .checked_add(cooldown)
+            .ok_or(GammaError::MathOverflow)?;
+        self.guardian_status = self
+            .guardian_status
+            .bitor(u8::from(1) << (PoolStatusBitIndex::swap_direction(trade_direction) as u8));
+        self.guardian_halted_until = std::cmp::max(self.guardian_halted_until, halted_until);
+
+        Ok(Some(GuardianHaltEvent {
+            pool_id,
+            zero_for_one: trade_direction == TradeDirection::ZeroForOne,
+            spot_price_x32,
+            twap_price_x32,
+            deviation_rate: u64::try_from(deviation_rate).unwrap_or(u64::MAX),
+            halted_until: self.guardian_halted_until,
+        }))
+    }
+
+    /// In reduce-only mode a swap must not move the price away from the TWAP, swaps are rejected
+    /// while there are not enough observations to compare against
+    pub fn check_reduce_only(
+        &self,
+        observation_state: &ObservationState,
+        trade_direction: TradeDirection,
+        swap_result: &SwapResult,
+        block_timestamp: u64,
+    ) -> Result<()> {
+        if self.get_status_by_bit(PoolStatusBitIndex::ReduceOnly) {
+            return Ok(());
+        }
+
+        let (_, _, twap_price_x32) =
+            DynamicFee::get_price_range(observation_state, block_timestamp, GUARDIAN_TWAP_WINDOW)?;
+        require_neq!(twap_price_x32, 0, GammaError::ReduceOnlySwap);
+
+        let (token_0_amount, token_1_amount) = self.vault_amount_without_fee()?;
+        let price_before_swap_x32 = u128::from(token_1_amount)
+            .checked_mul(Q32)
+            .ok_or(GammaError::MathOverflow)?
+            .checked_div(u128::from(token_0_amount))
+            .ok_or(GammaError::MathOverflow)?;
+        let price_after_swap_x32 = Self::price_after_swap_x32(trade_direction, swap_result)?;
+        require_gte!(
+            price_before_swap_x32.abs_diff(twap_price_x32),
+            price_after_swap_x32.abs_diff(twap_price_x32),
+            GammaError::ReduceOnlySwap
+        );
+        Ok(())
+    }
+
+    /// Price of token_0 in token_1 once the swap is executed, as Q32
+    fn price_after_swap_x32(
+        trade_direction: TradeDirection,
+        swap_result: &SwapResult,
+    ) -> Result<u128> {
+        // The protocol and fund fees leave the vault amounts, the lp fee stays in the pool
+        let source_amount_after_swap = swap_result
+            .new_swap_source_amount
+            .checked_sub(swap_result.protocol_fee)
+            .ok_or(GammaError::MathOverflow)?
+            .checked_sub(swap_result.fund_fee)
+            .ok_or(GammaError::MathOverflow)?;
+        let (token_0_amount_after_swap, token_1_amount_after_swap) = match trade_direction {
+            TradeDirection::ZeroForOne => (
+                source_amount_after_swap,
+                swap_result.new_swap_destination_amount,
+            ),
+            TradeDirection::OneForZero => (
+                swap_result.new_swap_destination_amount,
+                source_amount_after_swap,
+            ),
+        };
+        Ok(token_1_amount_after_swap
+            .checked_mul(Q32)
+            .ok_or(GammaError::MathOverflow)?
+            .checked_div(token_0_amount_after_swap)
+            .ok_or(GammaError::MathOverflow)?)
+    }
 }
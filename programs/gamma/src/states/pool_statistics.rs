@@ -0,0 +1,219 @@
+use crate::curve::TradeDirection;
+use crate::error::GammaError;
+use anchor_lang::prelude::*;
+use std::cell::RefMut;
+use std::ops::DerefMut;
+
+/// Seed to derive account address and signature
+pub const POOL_STATISTICS_SEED: &str = "pool_statistics";
+// Number of PoolStatisticsBucket elements, one per epoch
+pub const POOL_STATISTICS_NUM: usize = 64;
+
+/// Volume and fees of the pool during one epoch
+#[zero_copy(unsafe)]
+#[repr(packed)]
+#[derive(Default, Debug)]
+pub struct PoolStatisticsBucket {
+    /// The epoch of the bucket
+    pub epoch: u64,
+    /// The block timestamp of the first swap in the bucket
+    pub start_timestamp: u64,
+    /// Volume of token_0 swapped in or out
+    pub volume_token_0: u128,
+    /// Volume of token_1 swapped in or out
+    pub volume_token_1: u128,
+    /// Fees taken by the protocol, after referral
+    pub protocol_fees_token_0: u64,
+    pub protocol_fees_token_1: u64,
+    /// Fees taken by the fund, after referral
+    pub fund_fees_token_0: u64,
+    pub fund_fees_token_1: u64,
+    /// Fees left in the pool for the liquidity providers
+    pub lp_fees_token_0: u64,
+    pub lp_fees_token_1: u64,
+    /// Number of swaps
+    pub swap_count: u64,
+    /// Highest dynamic fee rate charged
+    pub max_dynamic_fee_rate: u64,
+}
+
+impl PoolStatisticsBucket {
+    pub const LEN: usize = 8 + 8 + 16 * 2 + 8 * 6 + 8 + 8;
+
+    pub fn add_volume(
+        &mut self,
+        trade_direction: TradeDirection,
+        amount_in: u64,
+        amount_out: u64,
+    ) -> Result<()> {
+        let (volume_in, volume_out) = match trade_direction {
+            TradeDirection::ZeroForOne => (self.volume_token_0, self.volume_token_1),
+            TradeDirection::OneForZero => (self.volume_token_1, self.volume_token_0),
+        };
+        let volume_in = volume_in
+            .checked_add(amount_in as u128)
+            .ok_or(GammaError::MathOverflow)?;
+        let volume_out = volume_out
+            .checked_add(amount_out as u128)
+            .ok_or(GammaError::MathOverflow)?;
+        match trade_direction {
+            TradeDirection::ZeroForOne => {
+                self.volume_token_0 = volume_in;
+                self.volume_token_1 = volume_out;
+            }
+            TradeDirection::OneForZero => {
+                self.volume_token_1 = volume_in;
+                self.volume_token_0 = volume_out;
+            }
+        }
+        self.swap_count = self.swap_count.saturating_add(1);
+        Ok(())
+    }
+
+    /// Fees are always charged on the input token
+    pub fn add_fees(
+        &mut self,
+        trade_direction: TradeDirection,
+        protocol_fee: u64,
+        fund_fee: u64,
+        lp_fee: u64,
+        dynamic_fee_rate: u64,
+    ) -> Result<()> {
+        match trade_direction {
+            TradeDirection::ZeroForOne => {
+                self.protocol_fees_token_0 = self
+                    .protocol_fees_token_0
+                    .checked_add(protocol_fee)
+                    .ok_or(GammaError::MathOverflow)?;
+                self.fund_fees_token_0 = self
+                    .fund_fees_token_0
+                    .checked_add(fund_fee)
+                    .ok_or(GammaError::MathOverflow)?;
+                self.lp_fees_token_0 = self
+                    .lp_fees_token_0
+                    .checked_add(lp_fee)
+                    .ok_or(GammaError::MathOverflow)?;
+            }
+            TradeDirection::OneForZero => {
+                self.protocol_fees_token_1 = self
+                    .protocol_fees_token_1
+                    .checked_add(protocol_fee)
+                    .ok_or(GammaError::MathOverflow)?;
+                self.fund_fees_token_1 = self
+                    .fund_fees_token_1
+                    .checked_add(fund_fee)
+                    .ok_or(GammaError::MathOverflow)?;
+                self.lp_fees_token_1 = self
+                    .lp_fees_token_1
+                    .checked_add(lp_fee)
+                    .ok_or(GammaError::MathOverflow)?;
+            }
+        }
+        self.max_dynamic_fee_rate = std::cmp::max(self.max_dynamic_fee_rate, dynamic_fee_rate);
+        Ok(())
+    }
+}
+
+/// Ring buffer of per-epoch volume and fee statistics of a pool, updated on swap
+#[account(zero_copy(unsafe))]
+#[repr(packed)]
+#[cfg_attr(any(feature = "client", feature = "test-sbf"), derive(Debug))]
+pub struct PoolStatistics {
+    /// Whether the first bucket is written
+    pub initialized: bool,
+    /// The most recently written index of the buckets array
+    pub bucket_index: u16,
+    pub pool_id: Pubkey,
+    /// bucket array
+    pub buckets: [PoolStatisticsBucket; POOL_STATISTICS_NUM],
+    /// padding
+    pub padding: [u64; 4],
+}
+
+impl Default for PoolStatistics {
+    #[inline]
+    fn default() -> PoolStatistics {
+        PoolStatistics {
+            initialized: false,
+            bucket_index: 0,
+            pool_id: Pubkey::default(),
+            buckets: [PoolStatisticsBucket::default(); POOL_STATISTICS_NUM],
+            padding: [0u64; 4],
+        }
+    }
+}
+
+impl PoolStatistics {
+    pub const LEN: usize =
+        8 + 1 + 2 + 32 + (POOL_STATISTICS_NUM * PoolStatisticsBucket::LEN) + 4 * 8;
+
+    pub fn find_address(pool_id: Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[POOL_STATISTICS_SEED.as_bytes(), pool_id.as_ref()],
+            &crate::id(),
+        )
+        .0
+    }
+
+    /// Returns the bucket of the given epoch, rolling over to a new bucket when the epoch changed
+    pub fn current_bucket(
+        &mut self,
+        epoch: u64,
+        block_timestamp: u64,
+    ) -> &mut PoolStatisticsBucket {
+        let bucket_index = if !self.initialized {
+            self.initialized = true;
+            self.bucket_index
+        } else if self.buckets[self.bucket_index as usize].epoch >= epoch {
+            return &mut self.buckets[self.bucket_index as usize];
+        } else if self.bucket_index as usize == POOL_STATISTICS_NUM - 1 {
+            0
+        } else {
+            self.bucket_index + 1
+        };
+
+        self.bucket_index = bucket_index;
+        let bucket = &mut self.buckets[bucket_index as usize];
+        *bucket = PoolStatisticsBucket::default();
+        bucket.epoch = epoch;
+        bucket.start_timestamp = block_timestamp;
+        bucket
+    }
+
+    /// Returns the written buckets ordered from oldest to newest
+    pub fn ordered_buckets(&self) -> Vec<PoolStatisticsBucket> {
+        if !self.initialized {
+            return Vec::new();
+        }
+        let newest_index = self.bucket_index as usize;
+        (1..=POOL_STATISTICS_NUM)
+            .map(|offset| self.buckets[(newest_index + offset) % POOL_STATISTICS_NUM])
+            .filter(|bucket| bucket.start_timestamp != 0)
+            .collect()
+    }
+
+    /// Loads the statistics account passed in the swap remaining accounts
+    pub fn load_mut<'a, 'info>(
+        account_info: &'a AccountInfo<'info>,
+        pool_id: Pubkey,
+    ) -> Result<RefMut<'a, PoolStatistics>> {
+        require_keys_eq!(
+            *account_info.owner,
+            crate::id(),
+            ErrorCode::AccountOwnedByWrongProgram
+        );
+        let data = account_info.try_borrow_mut_data()?;
+        if data.len() < PoolStatistics::LEN
+            || data[..8] != <PoolStatistics as anchor_lang::Discriminator>::DISCRIMINATOR
+        {
+            return err!(ErrorCode::AccountDiscriminatorMismatch);
+        }
+        let pool_statistics: RefMut<'a, PoolStatistics> = RefMut::map(data, |data| {
+            bytemuck::from_bytes_mut(
+                &mut data.deref_mut()[8..std::mem::size_of::<PoolStatistics>() + 8],
+            )
+        });
+        require_keys_eq!(pool_statistics.pool_id, pool_id, GammaError::InvalidInput);
+        Ok(pool_statistics)
+    }
+}
@@ -0,0 +1,118 @@
+use crate::error::GammaError;
+use crate::states::PositionOperatorEvent;
+use anchor_lang::prelude::*;
+
+/// Seed to derive account address and signature
+pub const POSITION_OPERATOR_SEED: &str = "position_operator";
+
+/// Action an operator may take on a position, stored as bit `1 << permission`
+/// of `PositionOperator::permissions`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OperatorPermission {
+    /// Deposit from the operator's token accounts into the position
+    Deposit = 0,
+    /// Withdraw from the position, only to token accounts of the owner
+    Withdraw = 1,
+    /// Claim rewards of the position, only to token accounts of the owner
+    ClaimRewards = 2,
+}
+
+impl OperatorPermission {
+    pub const ALL_MASK: u32 = (1 << 3) - 1;
+
+    pub fn mask(self) -> u32 {
+        1 << (self as u32)
+    }
+}
+
+/// Operator approved by the owner of a UserPoolLiquidity to act on the position
+#[account]
+#[derive(Default, Debug)]
+pub struct PositionOperator {
+    /// Bump to identify PDA
+    pub bump: u8,
+    pub pool_state: Pubkey,
+    pub owner: Pubkey,
+    pub operator: Pubkey,
+    /// Bitmask of `OperatorPermission`
+    pub permissions: u32,
+    /// Timestamp from which the operator can no longer act
+    pub expires_at: u64,
+    /// padding
+    pub padding: [u64; 4],
+}
+
+impl PositionOperator {
+    pub const LEN: usize = 8 + 1 + 32 * 3 + 4 + 8 + 8 * 4;
+}
+
+/// Checks that `signer` may take `permission` on the position of `owner` in `pool_id`.
+/// The owner always can, an operator has to pass its PositionOperator in `remaining_accounts`.
+pub fn check_position_signer(
+    pool_id: Pubkey,
+    owner: Pubkey,
+    signer: Pubkey,
+    remaining_accounts: &[AccountInfo],
+    permission: OperatorPermission,
+) -> Result<()> {
+    if signer == owner {
+        return Ok(());
+    }
+    let (position_operator_key, _) = Pubkey::find_program_address(
+        &[
+            POSITION_OPERATOR_SEED.as_bytes(),
+            pool_id.as_ref(),
+            owner.as_ref(),
+        ],
+        &crate::id(),
+    );
+    let position_operator_info = remaining_accounts
+        .iter()
+        .find(|account| *account.key == position_operator_key)
+        .ok_or(GammaError::OperatorNotApproved)?;
+    require_keys_eq!(
+        *position_operator_info.owner,
+        crate::id(),
+        GammaError::OperatorNotApproved
+    );
+    let position_operator =
+        PositionOperator::try_deserialize(&mut &position_operator_info.try_borrow_data()?[..])?;
+
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    require!(
+        position_operator.operator == signer
+            && (position_operator.permissions & permission.mask()) != 0
+            && current_time < position_operator.expires_at,
+        GammaError::OperatorNotApproved
+    );
+    Ok(())
+}
+
+/// Closes the PositionOperator of a position if the owner approved one, refunding its rent
+pub fn close_position_operator<'info>(
+    position_operator_info: &AccountInfo<'info>,
+    owner: &AccountInfo<'info>,
+) -> Result<()> {
+    if *position_operator_info.owner != crate::id() {
+        return Ok(());
+    }
+    let position_operator =
+        PositionOperator::try_deserialize(&mut &position_operator_info.try_borrow_data()?[..])?;
+    emit!(PositionOperatorEvent {
+        pool_id: position_operator.pool_state,
+        owner: position_operator.owner,
+        operator: position_operator.operator,
+        permissions: 0,
+        expires_at: position_operator.expires_at,
+    });
+
+    let lamports = position_operator_info.lamports();
+    **owner.try_borrow_mut_lamports()? = owner
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(GammaError::MathOverflow)?;
+    **position_operator_info.try_borrow_mut_lamports()? = 0;
+    position_operator_info.assign(&System::id());
+    position_operator_info.realloc(0, false)?;
+    Ok(())
+}
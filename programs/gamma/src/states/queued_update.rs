@@ -0,0 +1,42 @@
+use crate::states::{ConfigUpdate, PoolUpdate};
+use anchor_lang::prelude::*;
+
+/// Seed to derive account address and signature
+pub const QUEUED_UPDATE_SEED: &str = "queued_update";
+
+/// The change held by a QueuedUpdate
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum QueuedAction {
+    Config(ConfigUpdate),
+    Pool(PoolUpdate),
+}
+
+impl QueuedAction {
+    /// Largest serialized size of any action: enum tag plus the largest ConfigUpdate
+    pub const MAX_LEN: usize = 1 + 1 + 32;
+}
+
+/// An admin change waiting for `AmmConfig::timelock_delay` before anyone can execute it
+#[account]
+#[derive(Debug)]
+pub struct QueuedUpdate {
+    /// Bump to identify PDA
+    pub bump: u8,
+    /// Id chosen by the proposer, part of the seeds
+    pub id: u64,
+    /// The amm config the update belongs to
+    pub amm_config: Pubkey,
+    /// The pool a `QueuedAction::Pool` applies to, default for config updates
+    pub pool_state: Pubkey,
+    /// Signer that queued the update, receives the rent back
+    pub proposer: Pubkey,
+    /// Unix timestamp from which the update can be executed
+    pub eta: u64,
+    pub action: QueuedAction,
+    /// padding
+    pub padding: [u64; 4],
+}
+
+impl QueuedUpdate {
+    pub const LEN: usize = 8 + 1 + 8 + 32 * 3 + 8 + QueuedAction::MAX_LEN + 8 * 4;
+}
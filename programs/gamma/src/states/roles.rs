@@ -0,0 +1,126 @@
+use crate::error::GammaError;
+use anchor_lang::prelude::*;
+
+/// Seed to derive account address and signature
+pub const ROLES_SEED: &str = "roles";
+/// Number of keys the roles account can hold
+pub const ROLES_MAX_MEMBERS: usize = 16;
+
+/// Capability granted to a key, stored as bit `1 << capability` of `RoleMember::capabilities`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Capability {
+    /// Pause and resume pools and pool creation
+    Pauser = 0,
+    /// Change the fee parameters of configs and pools, and move pools to another config
+    FeeManager = 1,
+    /// Change the share of the pool reserves deployed to kamino
+    KaminoShareManager = 2,
+    /// Calculate user rewards and count the rewards of pools created before they were counted
+    RewardsOperator = 3,
+    /// Create amm configs and referral projects, and manage the mints and pools they list
+    ConfigCreator = 4,
+    /// Grant and revoke capabilities, propose role transfers and change the timelock delay
+    RoleAdmin = 5,
+    /// Register partners and set their payout wallet and revenue share
+    PartnerManager = 6,
+    /// Collect the protocol and fund fees of any pool
+    FeeCollector = 7,
+}
+
+impl Capability {
+    pub const ALL_MASK: u32 = (1 << 8) - 1;
+
+    pub fn mask(self) -> u32 {
+        1 << (self as u32)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct RoleMember {
+    pub key: Pubkey,
+    /// Bitmask of `Capability`
+    pub capabilities: u32,
+}
+
+/// Keys holding admin capabilities, replaces hard-coded admin keys so they can be rotated
+#[account]
+#[derive(Default, Debug)]
+pub struct Roles {
+    /// Bump to identify PDA
+    pub bump: u8,
+    pub members: [RoleMember; ROLES_MAX_MEMBERS],
+    /// padding
+    pub padding: [u64; 8],
+}
+
+impl Roles {
+    pub const LEN: usize = 8 + 1 + (32 + 4) * ROLES_MAX_MEMBERS + 8 * 8;
+
+    /// Reads the roles account passed at its address, `None` while it is not created
+    pub fn load(roles_info: &AccountInfo) -> Result<Option<Self>> {
+        if *roles_info.owner != crate::id() {
+            return Ok(None);
+        }
+        Ok(Some(Roles::try_deserialize(
+            &mut &roles_info.try_borrow_data()?[..],
+        )?))
+    }
+
+    /// The hard-coded admin and rewards crank keys are only trusted until the roles account is
+    /// created, from then on only its grants count so every key can be revoked
+    pub fn is_granted(roles: Option<&Roles>, key: Pubkey, capability: Capability) -> bool {
+        match roles {
+            Some(roles) => (roles.capabilities_of(key) & capability.mask()) != 0,
+            None => {
+                key == crate::admin::id()
+                    || (capability == Capability::RewardsOperator
+                        && key == crate::CALCULATE_REWARDS_ADMIN)
+            }
+        }
+    }
+
+    /// Whether a key can still grant and revoke capabilities
+    pub fn has_role_admin(&self) -> bool {
+        self.members
+            .iter()
+            .any(|member| (member.capabilities & Capability::RoleAdmin.mask()) != 0)
+    }
+
+    pub fn capabilities_of(&self, key: Pubkey) -> u32 {
+        self.members
+            .iter()
+            .find(|member| member.key == key)
+            .map_or(0, |member| member.capabilities)
+    }
+
+    /// Replace the capabilities of `key`, removes the member when `capabilities` is 0.
+    /// Returns the previous capabilities.
+    pub fn set_capabilities(&mut self, key: Pubkey, capabilities: u32) -> Result<u32> {
+        require!(
+            (capabilities & !Capability::ALL_MASK) == 0,
+            GammaError::InvalidInput
+        );
+        require_keys_neq!(key, Pubkey::default(), GammaError::InvalidInput);
+
+        if let Some(member) = self.members.iter_mut().find(|member| member.key == key) {
+            let old_capabilities = member.capabilities;
+            if capabilities == 0 {
+                *member = RoleMember::default();
+            } else {
+                member.capabilities = capabilities;
+            }
+            return Ok(old_capabilities);
+        }
+
+        if capabilities != 0 {
+            let member = self
+                .members
+                .iter_mut()
+                .find(|member| member.key == Pubkey::default())
+                .ok_or(GammaError::RolesFull)?;
+            member.key = key;
+            member.capabilities = capabilities;
+        }
+        Ok(0)
+    }
+}
@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 
-use super::PartnerType;
+use super::{PoolState, Q64};
+use crate::{error::GammaError, utils::U256};
 
 pub const USER_POOL_LIQUIDITY_SEED: &str = "user-pool-liquidity";
 
@@ -14,19 +15,31 @@ pub struct UserPoolLiquidity {
     pub token_0_withdrawn: u128,
     pub token_1_withdrawn: u128,
     pub lp_tokens_owned: u128,
-    pub partner: Option<PartnerType>,
+    /// Id of the Partner the position is attributed to
+    pub partner: Option<u64>,
+    /// Rewards accrue from this time, moved to the time LP is received from another position
     pub first_investment_at: u64,
-    pub padding: [u8; 15],
+    /// Part of lp_tokens_owned held by LpLock accounts, it can't be withdrawn or moved
+    pub lp_tokens_locked: u64,
+    /// fee_growth_per_lp_token_0_x64 of the pool when fees_earned_token_0 was last updated
+    pub fee_growth_checkpoint_token_0_x64: u128,
+    /// fee_growth_per_lp_token_1_x64 of the pool when fees_earned_token_1 was last updated
+    pub fee_growth_checkpoint_token_1_x64: u128,
+    /// Trade fees of token_0 earned by the position up to the checkpoint, they stay in the pool
+    pub fees_earned_token_0: u128,
+    /// Trade fees of token_1 earned by the position up to the checkpoint, they stay in the pool
+    pub fees_earned_token_1: u128,
+    pub padding: [u64; 4],
 }
 
 impl UserPoolLiquidity {
-    pub const LEN: usize = 8 + 32 * 2 + 16 * 5 + 32;
+    pub const LEN: usize = 8 + 32 * 2 + 16 * 9 + 9 + 8 * 2 + 8 * 4;
 
     pub fn initialize(
         &mut self,
         user: Pubkey,
         pool_state: Pubkey,
-        partner: Option<PartnerType>,
+        partner: Option<u64>,
         current_time: u64,
     ) {
         self.user = user;
@@ -38,6 +51,66 @@ impl UserPoolLiquidity {
         self.lp_tokens_owned = 0;
         self.partner = partner;
         self.first_investment_at = current_time;
-        self.padding = [0u8; 15];
+        self.lp_tokens_locked = 0;
+        self.fee_growth_checkpoint_token_0_x64 = 0;
+        self.fee_growth_checkpoint_token_1_x64 = 0;
+        self.fees_earned_token_0 = 0;
+        self.fees_earned_token_1 = 0;
+        self.padding = [0u64; 4];
+    }
+
+    /// Trade fees earned by the position so far, including the ones since the last checkpoint
+    pub fn fees_earned(&self, pool_state: &PoolState) -> Result<(u128, u128)> {
+        let fees_earned_token_0 = self
+            .fees_earned_token_0
+            .checked_add(Self::fees_since_checkpoint(
+                self.lp_tokens_owned,
+                pool_state.fee_growth_per_lp_token_0_x64,
+                self.fee_growth_checkpoint_token_0_x64,
+            )?)
+            .ok_or(GammaError::MathOverflow)?;
+        let fees_earned_token_1 = self
+            .fees_earned_token_1
+            .checked_add(Self::fees_since_checkpoint(
+                self.lp_tokens_owned,
+                pool_state.fee_growth_per_lp_token_1_x64,
+                self.fee_growth_checkpoint_token_1_x64,
+            )?)
+            .ok_or(GammaError::MathOverflow)?;
+        Ok((fees_earned_token_0, fees_earned_token_1))
+    }
+
+    /// Moves the checkpoints to the current fee growth of the pool, crediting the fees earned
+    /// since the last ones. Must be called before lp_tokens_owned changes.
+    pub fn update_fees_earned(&mut self, pool_state: &PoolState) -> Result<()> {
+        (self.fees_earned_token_0, self.fees_earned_token_1) = self.fees_earned(pool_state)?;
+        self.fee_growth_checkpoint_token_0_x64 = pool_state.fee_growth_per_lp_token_0_x64;
+        self.fee_growth_checkpoint_token_1_x64 = pool_state.fee_growth_per_lp_token_1_x64;
+        Ok(())
+    }
+
+    fn fees_since_checkpoint(
+        lp_tokens_owned: u128,
+        fee_growth_x64: u128,
+        fee_growth_checkpoint_x64: u128,
+    ) -> Result<u128> {
+        let fees = U256::from(lp_tokens_owned)
+            .checked_mul(U256::from(
+                fee_growth_x64.wrapping_sub(fee_growth_checkpoint_x64),
+            ))
+            .ok_or(GammaError::MathOverflow)?
+            / U256::from(Q64);
+        u128::try_from(fees).or(err!(GammaError::MathOverflow))
+    }
+
+    /// LP tokens that are not held by a lock, nor by the lock of the initial LP of the pool
+    /// creator
+    pub fn unlocked_lp_tokens(&self, pool_state: &PoolState, current_time: u64) -> u128 {
+        let mut locked = u128::from(self.lp_tokens_locked);
+        if self.user == pool_state.pool_creator && current_time < pool_state.creator_lp_unlock_time
+        {
+            locked += u128::from(pool_state.creator_lp_locked);
+        }
+        self.lp_tokens_owned.saturating_sub(locked)
     }
 }
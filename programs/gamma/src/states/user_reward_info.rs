@@ -1,10 +1,10 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, Discriminator};
 use rust_decimal::Decimal;
 
-use crate::{error::GammaError, LOCK_LP_AMOUNT};
+use crate::{error::GammaError, LOCK_LP_AMOUNT, USER_REWARD_INFO_SEED};
 use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
 
-use super::RewardInfo;
+use super::{RewardInfo, UserPoolLiquidity};
 
 #[account]
 pub struct UserRewardInfo {
@@ -21,6 +21,30 @@ impl UserRewardInfo {
         self.total_rewards.saturating_sub(self.total_claimed)
     }
 
+    /// Accrues the rewards of a position up to now, the way the rewards crank does
+    pub fn settle<'info>(
+        &mut self,
+        user_pool_liquidity: &UserPoolLiquidity,
+        current_lp_supply: u64,
+        reward_info: &Account<'info, RewardInfo>,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        if self.rewards_last_calculated_at >= current_time {
+            return Ok(());
+        }
+        // Start accrual of rewards from the time user first deposit, or last received LP from
+        // another position. This prevents the user from creating a invest at the end of rewards
+        // and getting boosted rewards for the full period.
+        self.rewards_last_calculated_at = self
+            .rewards_last_calculated_at
+            .max(user_pool_liquidity.first_investment_at);
+        self.calculate_claimable_rewards(
+            user_pool_liquidity.lp_tokens_owned as u64,
+            current_lp_supply,
+            reward_info,
+        )
+    }
+
     pub fn calculate_claimable_rewards<'info>(
         &mut self,
         lp_owned_by_user: u64,
@@ -77,3 +101,62 @@ impl UserRewardInfo {
         Ok(())
     }
 }
+
+/// Settles the rewards of a position, skipped when the crank has not created the
+/// user_reward_info yet since accrual then starts from first_investment_at
+pub fn settle_user_reward_info<'info>(
+    reward_info: &Account<'info, RewardInfo>,
+    user_reward_info: &'info AccountInfo<'info>,
+    user_pool_liquidity: &UserPoolLiquidity,
+    lp_supply: u64,
+) -> Result<()> {
+    let (user_reward_info_key, _) = Pubkey::find_program_address(
+        &[
+            USER_REWARD_INFO_SEED.as_bytes(),
+            reward_info.key().as_ref(),
+            user_pool_liquidity.user.as_ref(),
+        ],
+        &crate::id(),
+    );
+    require_keys_eq!(
+        user_reward_info.key(),
+        user_reward_info_key,
+        GammaError::InvalidInput
+    );
+    if *user_reward_info.owner != crate::id() {
+        return Ok(());
+    }
+    let mut user_reward_info = Account::<UserRewardInfo>::try_from(user_reward_info)?;
+    user_reward_info.settle(user_pool_liquidity, lp_supply, reward_info)?;
+    user_reward_info.exit(&crate::id())
+}
+
+/// Settles the rewards of a position for every reward_info in the remaining accounts, each
+/// followed by the user_reward_info of the position. Other remaining accounts are skipped
+pub fn settle_user_rewards<'info>(
+    pool_id: Pubkey,
+    user_pool_liquidity: &UserPoolLiquidity,
+    lp_supply: u64,
+    remaining_accounts: &'info [AccountInfo<'info>],
+) -> Result<()> {
+    let mut accounts = remaining_accounts.iter();
+    while let Some(account) = accounts.next() {
+        if *account.owner != crate::id()
+            || !account
+                .try_borrow_data()?
+                .starts_with(&RewardInfo::DISCRIMINATOR)
+        {
+            continue;
+        }
+        let reward_info = Account::<RewardInfo>::try_from(account)?;
+        require_keys_eq!(reward_info.pool, pool_id, GammaError::InvalidInput);
+        let user_reward_info = accounts.next().ok_or(GammaError::InvalidInput)?;
+        settle_user_reward_info(
+            &reward_info,
+            user_reward_info,
+            user_pool_liquidity,
+            lp_supply,
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+
+/// Grows `account` to `len` bytes, topping up its rent from `payer`. The new bytes are zeroed.
+pub fn resize_account<'info>(
+    payer: AccountInfo<'info>,
+    account: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    len: usize,
+) -> Result<()> {
+    let rent_exempt_lamports = Rent::get()?.minimum_balance(len);
+    let lamports_needed = rent_exempt_lamports.saturating_sub(account.lamports());
+    if lamports_needed > 0 {
+        transfer(
+            CpiContext::new(
+                system_program,
+                Transfer {
+                    from: payer,
+                    to: account.clone(),
+                },
+            ),
+            lamports_needed,
+        )?;
+    }
+    account.realloc(len, true)?;
+    Ok(())
+}
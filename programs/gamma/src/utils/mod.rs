@@ -1,7 +1,9 @@
+pub mod account;
 pub mod math;
 pub mod swap_referral;
 pub mod token;
 
+pub use account::*;
 pub use math::*;
 pub use swap_referral::*;
 pub use token::*;
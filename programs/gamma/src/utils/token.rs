@@ -7,9 +7,11 @@ use anchor_spl::{
         spl_token_2022::{
             self,
             extension::{
+                memo_transfer::MemoTransfer,
                 transfer_fee::{TransferFeeConfig, MAX_FEE_BASIS_POINTS},
-                ExtensionType, StateWithExtensions,
+                transfer_hook, ExtensionType, StateWithExtensions,
             },
+            state::AccountState,
         },
     },
     token_interface::{
@@ -26,6 +28,9 @@ const MINT_WHITELIST: [&'static str; 4] = [
     "2b1kV6DkPAnxd5ixfnxCpjxmKwqjjaYmCZfHsFu24GXo",
 ];
 
+/// `extra_accounts` are searched by key for the accounts token-2022 extensions need:
+/// the transfer-hook program, its validation account and extra metas, and the memo program
+/// when the destination requires incoming transfer memos.
 pub fn transfer_from_user_to_pool_vault<'a>(
     authority: AccountInfo<'a>,
     from: AccountInfo<'a>,
@@ -34,25 +39,22 @@ pub fn transfer_from_user_to_pool_vault<'a>(
     token_program: AccountInfo<'a>,
     amount: u64,
     mint_decimals: u8,
+    extra_accounts: &[AccountInfo<'a>],
 ) -> Result<()> {
-    if amount == 0 {
-        return Ok(());
-    }
-    token_2022::transfer_checked(
-        CpiContext::new(
-            token_program.to_account_info(),
-            token_2022::TransferChecked {
-                from,
-                to: to_vault,
-                authority,
-                mint,
-            },
-        ),
+    transfer_checked(
+        authority,
+        from,
+        to_vault,
+        mint,
+        token_program,
         amount,
         mint_decimals,
+        extra_accounts,
+        &[],
     )
 }
 
+/// See `transfer_from_user_to_pool_vault` for `extra_accounts`
 pub fn transfer_from_pool_vault_to_user<'a>(
     authority: AccountInfo<'a>,
     from_vault: AccountInfo<'a>,
@@ -61,25 +63,141 @@ pub fn transfer_from_pool_vault_to_user<'a>(
     token_program: AccountInfo<'a>,
     amount: u64,
     mint_decimals: u8,
+    extra_accounts: &[AccountInfo<'a>],
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    transfer_checked(
+        authority,
+        from_vault,
+        to,
+        mint,
+        token_program,
+        amount,
+        mint_decimals,
+        extra_accounts,
+        signer_seeds,
+    )
+}
+
+/// Pays the referrer from the token account of the user.
+/// See `transfer_from_user_to_pool_vault` for `extra_accounts`
+pub fn transfer_from_user_to_referrer<'a>(
+    authority: AccountInfo<'a>,
+    from: AccountInfo<'a>,
+    to_referrer: AccountInfo<'a>,
+    mint: AccountInfo<'a>,
+    token_program: AccountInfo<'a>,
+    amount: u64,
+    mint_decimals: u8,
+    extra_accounts: &[AccountInfo<'a>],
+) -> Result<()> {
+    transfer_checked(
+        authority,
+        from,
+        to_referrer,
+        mint,
+        token_program,
+        amount,
+        mint_decimals,
+        extra_accounts,
+        &[],
+    )
+}
+
+fn transfer_checked<'a>(
+    authority: AccountInfo<'a>,
+    from: AccountInfo<'a>,
+    to: AccountInfo<'a>,
+    mint: AccountInfo<'a>,
+    token_program: AccountInfo<'a>,
+    amount: u64,
+    mint_decimals: u8,
+    extra_accounts: &[AccountInfo<'a>],
     signer_seeds: &[&[&[u8]]],
 ) -> Result<()> {
     if amount == 0 {
         return Ok(());
     }
-    token_2022::transfer_checked(
-        CpiContext::new_with_signer(
-            token_program.to_account_info(),
-            token_2022::TransferChecked {
-                from: from_vault,
-                to,
-                authority,
-                mint,
-            },
-            signer_seeds,
-        ),
+    if *token_program.key == Token::id() {
+        return token_2022::transfer_checked(
+            CpiContext::new_with_signer(
+                token_program,
+                token_2022::TransferChecked {
+                    from,
+                    to,
+                    authority,
+                    mint,
+                },
+                signer_seeds,
+            ),
+            amount,
+            mint_decimals,
+        );
+    }
+
+    // token-2022 checks the memo is the instruction invoked right before the transfer
+    if memo_required(&to)? {
+        let memo_program = extra_accounts
+            .iter()
+            .find(|account| *account.key == spl_memo::id())
+            .ok_or(GammaError::InvalidInput)?;
+        let ix = spl_memo::build_memo(b"gamma", &[]);
+        anchor_lang::solana_program::program::invoke(&ix, &[memo_program.clone()])?;
+    }
+
+    if !has_transfer_hook(&mint)? {
+        return token_2022::transfer_checked(
+            CpiContext::new_with_signer(
+                token_program,
+                token_2022::TransferChecked {
+                    from,
+                    to,
+                    authority,
+                    mint,
+                },
+                signer_seeds,
+            ),
+            amount,
+            mint_decimals,
+        );
+    }
+    // Resolves the extra account metas of the hook from `extra_accounts`
+    spl_token_2022::onchain::invoke_transfer_checked(
+        token_program.key,
+        from,
+        mint,
+        to,
+        authority,
+        extra_accounts,
         amount,
         mint_decimals,
-    )
+        signer_seeds,
+    )?;
+    Ok(())
+}
+
+/// Whether transfers of the mint invoke a transfer-hook program
+pub fn has_transfer_hook(mint: &AccountInfo) -> Result<bool> {
+    if *mint.owner != token_2022::Token2022::id() {
+        return Ok(false);
+    }
+    let mint_data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    Ok(transfer_hook::get_program_id(&mint_state).is_some())
+}
+
+/// Whether a token-2022 account only accepts transfers preceded by a memo
+fn memo_required(account: &AccountInfo) -> Result<bool> {
+    if *account.owner != token_2022::Token2022::id() {
+        return Ok(false);
+    }
+    let account_data = account.try_borrow_data()?;
+    let account_state =
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data)?;
+    Ok(account_state
+        .get_extension::<MemoTransfer>()
+        .map(|memo_transfer| bool::from(memo_transfer.require_incoming_transfer_memos))
+        .unwrap_or(false))
 }
 
 /// Issue a spl_token `MintTo` instruction.
@@ -127,6 +245,51 @@ pub fn token_burn<'a>(
     )
 }
 
+/// Close a token account owned by the pool vault authority, sending its rent to `destination`
+pub fn close_token_account<'a>(
+    authority: AccountInfo<'a>,
+    token_program: AccountInfo<'a>,
+    account: AccountInfo<'a>,
+    destination: AccountInfo<'a>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    token_2022::close_account(CpiContext::new_with_signer(
+        token_program,
+        token_2022::CloseAccount {
+            account,
+            destination,
+            authority,
+        },
+        signer_seeds,
+    ))
+}
+
+/// Move the transfer fees withheld in a token-2022 account to its mint,
+/// an account still holding withheld fees cannot be closed
+pub fn harvest_withheld_fees<'a>(
+    token_program: AccountInfo<'a>,
+    mint: AccountInfo<'a>,
+    account: AccountInfo<'a>,
+) -> Result<()> {
+    if *mint.owner == Token::id() {
+        return Ok(());
+    }
+    {
+        let mint_data = mint.try_borrow_data()?;
+        let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+        if mint_state.get_extension::<TransferFeeConfig>().is_err() {
+            return Ok(());
+        }
+    }
+    let ix = spl_token_2022::extension::transfer_fee::instruction::harvest_withheld_tokens_to_mint(
+        token_program.key,
+        mint.key,
+        &[account.key],
+    )?;
+    anchor_lang::solana_program::program::invoke(&ix, &[mint, account, token_program])?;
+    Ok(())
+}
+
 /// Calculate the fee for output amount
 pub fn get_transfer_inverse_fee(mint_info: &AccountInfo, post_fee_amount: u64) -> Result<u64> {
     if *mint_info.owner == Token::id() {
@@ -186,16 +349,63 @@ pub fn is_supported_mint(mint_account: &InterfaceAccount<Mint>) -> Result<bool>
     let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
     let extensions = mint.get_extension_types()?;
     for e in extensions {
-        if e != ExtensionType::TransferFeeConfig
-            && e != ExtensionType::MetadataPointer
-            && e != ExtensionType::TokenMetadata
-        {
-            return Ok(false);
+        match e {
+            ExtensionType::TransferFeeConfig
+            | ExtensionType::MetadataPointer
+            | ExtensionType::TokenMetadata
+            | ExtensionType::TransferHook
+            | ExtensionType::InterestBearingConfig => {}
+            // Vaults created frozen are thawed by `thaw_vault_if_frozen`
+            ExtensionType::DefaultAccountState => {}
+            ExtensionType::PermanentDelegate => {
+                return err!(GammaError::PermanentDelegateNotSupported)
+            }
+            ExtensionType::NonTransferable => return err!(GammaError::NonTransferableNotSupported),
+            _ => return Ok(false),
         }
     }
     Ok(true)
 }
 
+/// Vaults of mints whose default account state is frozen are created frozen.
+/// The freeze authority of the mint has to sign, as one of `signers`, for them to be thawed.
+pub fn thaw_vault_if_frozen<'a>(
+    token_program: &AccountInfo<'a>,
+    vault: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    signers: &[AccountInfo<'a>],
+) -> Result<()> {
+    if *token_program.key != token_2022::Token2022::id() {
+        return Ok(());
+    }
+    let frozen = {
+        let vault_data = vault.try_borrow_data()?;
+        let vault_state =
+            StateWithExtensions::<spl_token_2022::state::Account>::unpack(&vault_data)?;
+        vault_state.base.state == AccountState::Frozen
+    };
+    if !frozen {
+        return Ok(());
+    }
+    let freeze_authority = {
+        let mint_data = mint.try_borrow_data()?;
+        let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+        Option::<Pubkey>::from(mint_state.base.freeze_authority)
+    };
+    let authority = signers
+        .iter()
+        .find(|account| account.is_signer && Some(*account.key) == freeze_authority)
+        .ok_or(GammaError::FrozenVault)?;
+    token_2022::thaw_account(CpiContext::new(
+        token_program.clone(),
+        token_2022::ThawAccount {
+            account: vault.clone(),
+            mint: mint.clone(),
+            authority: authority.clone(),
+        },
+    ))
+}
+
 pub fn create_token_account<'a>(
     authority: &AccountInfo<'a>,
     payer: &AccountInfo<'a>,
@@ -0,0 +1,296 @@
+use anchor_spl::token::TokenAccount;
+use gamma::error::GammaError;
+use gamma::states::{PoolState, UserPoolLiquidity, USER_POOL_LIQUIDITY_SEED};
+use gamma::{REWARD_INFO_SEED, REWARD_VAULT_SEED};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+async fn setup_empty_pool(test_env: &mut TestEnv, user: &Keypair, admin: &Keypair) -> Pubkey {
+    let amm_index = 0;
+    test_env
+        .create_config(admin, amm_index, 3000, 2000, 50, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 100000000000000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 100000000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
+            user,
+            amm_index,
+            200000000,
+            100000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    pool_id
+}
+
+async fn withdraw_creator_liquidity(test_env: &mut TestEnv, user: &Keypair, pool_id: Pubkey) {
+    let user_pool_liquidity = Pubkey::find_program_address(
+        &[
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_id.to_bytes().as_ref(),
+            user.pubkey().to_bytes().as_ref(),
+        ],
+        &gamma::id(),
+    )
+    .0;
+    let position: UserPoolLiquidity = test_env.fetch_account(user_pool_liquidity).await;
+    test_env
+        .withdraw(user, pool_id, 0, position.lp_tokens_owned as u64, 0, 0)
+        .await;
+}
+
+#[tokio::test]
+async fn should_close_pool_and_keep_its_address_taken() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+    let pool_id = setup_empty_pool(&mut test_env, &user, &admin).await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+
+    // The pool still holds the liquidity of its creator
+    assert!(test_env
+        .try_close_pool(&user, pool_id, vec![])
+        .await
+        .is_err());
+
+    test_env.jump_seconds(100).await;
+    withdraw_creator_liquidity(&mut test_env, &user, pool_id).await;
+    test_env
+        .try_close_pool(&user, pool_id, vec![])
+        .await
+        .unwrap();
+
+    // An empty account owned by the program stays at the pool address
+    let pool_account = test_env.get_account_info(pool_id).await.unwrap().unwrap();
+    assert_eq!(pool_account.owner, gamma::id());
+    assert!(pool_account.data.is_empty());
+    assert!(test_env
+        .get_account_info(pool_state.observation_key)
+        .await
+        .unwrap()
+        .is_none());
+
+    // The pool can't be created again, the positions of the old one can still be closed
+    assert!(test_env
+        .try_initialize_pool(
+            &user,
+            0,
+            200000000,
+            100000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await
+        .is_err());
+    test_env
+        .try_close_user_pool_liquidity(&user, pool_id, vec![])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn should_sweep_ended_reward_vaults_to_the_reward_creator() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+    let pool_id = setup_empty_pool(&mut test_env, &user, &admin).await;
+
+    let reward_mint = test_env.token_1_mint;
+    let start_time = test_env.timestamp_now().await as u64 + 10;
+    let end_time = start_time + 100;
+    test_env
+        .create_rewards(&user, pool_id, start_time, end_time, reward_mint, 1000000)
+        .await;
+    let (reward_info, __bump) = Pubkey::find_program_address(
+        &[
+            REWARD_INFO_SEED.as_bytes(),
+            pool_id.to_bytes().as_ref(),
+            &start_time.to_le_bytes(),
+            reward_mint.to_bytes().as_ref(),
+        ],
+        &gamma::id(),
+    );
+    let (reward_vault, __bump) = Pubkey::find_program_address(
+        &[
+            REWARD_VAULT_SEED.as_bytes(),
+            reward_info.to_bytes().as_ref(),
+        ],
+        &gamma::id(),
+    );
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.reward_info_count, 1);
+
+    test_env.jump_seconds(50).await;
+    withdraw_creator_liquidity(&mut test_env, &user, pool_id).await;
+
+    // Every RewardInfo of the pool must be passed
+    assert!(test_env
+        .try_close_pool(&user, pool_id, vec![])
+        .await
+        .is_err());
+    // The reward has not ended yet
+    assert!(test_env
+        .try_close_pool(&user, pool_id, vec![reward_info])
+        .await
+        .is_err());
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.lp_supply, gamma::LOCK_LP_AMOUNT);
+
+    test_env.jump_seconds(100).await;
+    let user_reward_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), reward_mint, &user)
+        .await;
+    let vault_before: TokenAccount = test_env.fetch_account(reward_vault).await;
+    let user_reward_before: TokenAccount = test_env.fetch_account(user_reward_account).await;
+    // Nobody claimed the rewards
+    assert!(vault_before.amount > 0);
+
+    test_env
+        .try_close_pool(&user, pool_id, vec![reward_info])
+        .await
+        .unwrap();
+
+    // What was left goes back to the reward creator and the vault is closed
+    let user_reward_after: TokenAccount = test_env.fetch_account(user_reward_account).await;
+    assert_eq!(
+        user_reward_after.amount - user_reward_before.amount,
+        vault_before.amount
+    );
+    assert!(test_env
+        .get_account_info(reward_vault)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(test_env
+        .get_account_info(pool_id)
+        .await
+        .unwrap()
+        .unwrap()
+        .data
+        .is_empty());
+}
+
+#[tokio::test]
+async fn should_refuse_to_close_a_legacy_pool_until_its_rewards_are_counted() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+    let pool_id = setup_empty_pool(&mut test_env, &user, &admin).await;
+
+    let start_time = test_env.timestamp_now().await as u64 + 10;
+    let end_time = start_time + 100;
+    let legacy_reward_mint = test_env.token_1_mint;
+    test_env
+        .create_rewards(
+            &user,
+            pool_id,
+            start_time,
+            end_time,
+            legacy_reward_mint,
+            1000000,
+        )
+        .await;
+    let legacy_reward_info = reward_info_address(pool_id, start_time, legacy_reward_mint);
+
+    // Older versions of the program did not count the rewards
+    let mut pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    pool_state.reward_info_count = 0;
+    pool_state.reward_info_count_migrated = 0;
+    test_env.set_pool_state(pool_id, &pool_state).await;
+
+    let reward_mint = test_env.token_0_mint;
+    test_env
+        .create_rewards(&user, pool_id, start_time, end_time, reward_mint, 1000000)
+        .await;
+    let reward_info = reward_info_address(pool_id, start_time, reward_mint);
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.reward_info_count, 1);
+
+    test_env.jump_seconds(200).await;
+    withdraw_creator_liquidity(&mut test_env, &user, pool_id).await;
+
+    // The count can't be trusted to cover every reward vault
+    assert!(test_env
+        .try_close_pool(&user, pool_id, vec![reward_info])
+        .await
+        .is_err());
+    assert!(test_env
+        .try_close_pool(&user, pool_id, vec![legacy_reward_info, reward_info])
+        .await
+        .is_err());
+
+    let result = test_env
+        .try_migrate_reward_info_count(&user, pool_id, vec![legacy_reward_info, reward_info])
+        .await;
+    assert_error!(result, GammaError::InvalidOwner);
+    // The rewards created after the resize are already counted
+    let result = test_env
+        .try_migrate_reward_info_count(&admin, pool_id, vec![legacy_reward_info])
+        .await;
+    assert_error!(result, GammaError::InvalidInput);
+
+    test_env
+        .try_migrate_reward_info_count(&admin, pool_id, vec![legacy_reward_info, reward_info])
+        .await
+        .unwrap_transaction();
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.reward_info_count, 2);
+    assert_eq_with_copy!(pool_state.reward_info_count_migrated, 1);
+
+    // The migration runs once
+    let result = test_env
+        .try_migrate_reward_info_count(&admin, pool_id, vec![legacy_reward_info, reward_info])
+        .await;
+    assert_error!(result, GammaError::InvalidInput);
+
+    assert!(test_env
+        .try_close_pool(&user, pool_id, vec![reward_info])
+        .await
+        .is_err());
+    test_env
+        .try_close_pool(&user, pool_id, vec![legacy_reward_info, reward_info])
+        .await
+        .unwrap();
+    assert!(test_env
+        .get_account_info(pool_id)
+        .await
+        .unwrap()
+        .unwrap()
+        .data
+        .is_empty());
+}
+
+#[tokio::test]
+async fn should_close_the_lp_mint_with_the_pool() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+    let pool_id = setup_empty_pool(&mut test_env, &user, &admin).await;
+    let lp_mint = test_env.init_lp_mint(&user, pool_id).await;
+
+    test_env.jump_seconds(100).await;
+    withdraw_creator_liquidity(&mut test_env, &user, pool_id).await;
+    test_env
+        .try_close_pool(&user, pool_id, vec![])
+        .await
+        .unwrap();
+
+    assert!(test_env.get_account_info(lp_mint).await.unwrap().is_none());
+}
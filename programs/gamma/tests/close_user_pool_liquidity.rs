@@ -0,0 +1,296 @@
+use gamma::{
+    states::{OperatorPermission, PoolState, PositionOperator, UserPoolLiquidity},
+    REWARD_INFO_SEED,
+};
+use solana_program_test::tokio;
+use solana_sdk::{account::AccountSharedData, pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+#[tokio::test]
+async fn should_close_empty_position_with_its_operator() {
+    let user = Keypair::new();
+    let lp_depositor = Keypair::new();
+    let operator = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![
+        user.pubkey(),
+        lp_depositor.pubkey(),
+        operator.pubkey(),
+        admin.pubkey(),
+    ])
+    .await;
+
+    test_env
+        .create_config(&admin, amm_index, 3000, 2000, 50, 0)
+        .await;
+
+    for owner in [&user, &lp_depositor] {
+        let token_0_account = test_env
+            .get_or_create_associated_token_account(owner.pubkey(), test_env.token_0_mint, owner)
+            .await;
+        test_env
+            .mint_base_tokens(token_0_account, 100000000000000, test_env.token_0_mint)
+            .await;
+        let token_1_account = test_env
+            .get_or_create_associated_token_account(owner.pubkey(), test_env.token_1_mint, owner)
+            .await;
+        test_env
+            .mint_base_tokens(token_1_account, 100000000000000, test_env.token_1_mint)
+            .await;
+    }
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            200000000,
+            100000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    test_env.jump_seconds(100).await;
+
+    test_env
+        .init_user_pool_liquidity(&lp_depositor, pool_id)
+        .await;
+    let lp_amount = 100000000;
+    test_env
+        .deposit(
+            &lp_depositor,
+            pool_id,
+            amm_index,
+            lp_amount,
+            u64::MAX,
+            u64::MAX,
+        )
+        .await;
+    let expires_at = test_env.timestamp_now().await as u64 + 3600;
+    let position_operator_id = test_env
+        .set_position_operator(
+            &lp_depositor,
+            pool_id,
+            operator.pubkey(),
+            OperatorPermission::Withdraw.mask(),
+            expires_at,
+        )
+        .await;
+
+    // A position holding LP can not be closed
+    assert!(test_env
+        .try_close_user_pool_liquidity(&lp_depositor, pool_id, vec![])
+        .await
+        .is_err());
+
+    test_env
+        .withdraw(&lp_depositor, pool_id, amm_index, lp_amount, 0, 0)
+        .await;
+    let lamports_before = test_env
+        .get_account_info(lp_depositor.pubkey())
+        .await
+        .unwrap()
+        .unwrap()
+        .lamports;
+    test_env
+        .try_close_user_pool_liquidity(&lp_depositor, pool_id, vec![])
+        .await
+        .unwrap();
+
+    let user_pool_liquidity_id = Pubkey::find_program_address(
+        &[
+            gamma::states::USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_id.to_bytes().as_ref(),
+            lp_depositor.pubkey().to_bytes().as_ref(),
+        ],
+        &gamma::id(),
+    )
+    .0;
+    assert!(test_env
+        .try_fetch_account::<UserPoolLiquidity>(user_pool_liquidity_id)
+        .await
+        .is_err());
+    // The operator approval does not carry over to a position opened again
+    assert!(test_env
+        .try_fetch_account::<PositionOperator>(position_operator_id)
+        .await
+        .is_err());
+    let lamports_after = test_env
+        .get_account_info(lp_depositor.pubkey())
+        .await
+        .unwrap()
+        .unwrap()
+        .lamports;
+    assert!(lamports_after > lamports_before);
+
+    // A position can be opened again and closed without an operator
+    test_env
+        .init_user_pool_liquidity(&lp_depositor, pool_id)
+        .await;
+    test_env
+        .try_close_user_pool_liquidity(&lp_depositor, pool_id, vec![])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn should_not_close_position_with_unclaimed_rewards() {
+    let user = Keypair::new();
+    let lp_depositor = Keypair::new();
+    let reward_provider = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![
+        user.pubkey(),
+        lp_depositor.pubkey(),
+        reward_provider.pubkey(),
+        admin.pubkey(),
+    ])
+    .await;
+
+    test_env
+        .create_config(&admin, amm_index, 3000, 2000, 50, 0)
+        .await;
+    for owner in [&user, &lp_depositor] {
+        for mint in [test_env.token_0_mint, test_env.token_1_mint] {
+            let token_account = test_env
+                .get_or_create_associated_token_account(owner.pubkey(), mint, owner)
+                .await;
+            test_env
+                .mint_base_tokens(token_account, 100000000000000, mint)
+                .await;
+        }
+    }
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            200000000,
+            100000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    test_env.jump_seconds(100).await;
+
+    test_env
+        .init_user_pool_liquidity(&lp_depositor, pool_id)
+        .await;
+    let lp_amount = 100000000;
+    test_env
+        .deposit(
+            &lp_depositor,
+            pool_id,
+            amm_index,
+            lp_amount,
+            u64::MAX,
+            u64::MAX,
+        )
+        .await;
+
+    let reward_mint = Keypair::new();
+    test_env
+        .create_token_mint(&reward_mint, &test_env.mint_authority.pubkey(), 9)
+        .await;
+    let reward_provider_token_account = test_env
+        .get_or_create_associated_token_account(
+            reward_provider.pubkey(),
+            reward_mint.pubkey(),
+            &reward_provider,
+        )
+        .await;
+    let reward_amount = 1000000000;
+    test_env
+        .mint_base_tokens(
+            reward_provider_token_account,
+            reward_amount,
+            reward_mint.pubkey(),
+        )
+        .await;
+    let start_time = test_env.timestamp_now().await as u64 + 10;
+    let end_time = start_time + 1000;
+    test_env
+        .create_rewards(
+            &reward_provider,
+            pool_id,
+            start_time,
+            end_time,
+            reward_mint.pubkey(),
+            reward_amount,
+        )
+        .await;
+    let (reward_info, _) = Pubkey::find_program_address(
+        &[
+            REWARD_INFO_SEED.as_bytes(),
+            pool_id.to_bytes().as_ref(),
+            &start_time.to_le_bytes(),
+            reward_mint.pubkey().to_bytes().as_ref(),
+        ],
+        &gamma::id(),
+    );
+
+    test_env.jump_seconds(500).await;
+    test_env
+        .calculate_rewards(&lp_depositor, pool_id, reward_info)
+        .await;
+    test_env
+        .withdraw(&lp_depositor, pool_id, amm_index, lp_amount, 0, 0)
+        .await;
+
+    // Every RewardInfo of the pool must be passed
+    assert!(test_env
+        .try_close_user_pool_liquidity(&lp_depositor, pool_id, vec![])
+        .await
+        .is_err());
+    // The rewards calculated for the position are not claimed yet
+    assert!(test_env
+        .try_close_user_pool_liquidity(&lp_depositor, pool_id, vec![reward_info])
+        .await
+        .is_err());
+
+    // A pool with an older layout is not skipped, it has to be resized first
+    let pool_account = test_env.get_account_info(pool_id).await.unwrap().unwrap();
+    let mut legacy_pool_account = pool_account.clone();
+    legacy_pool_account
+        .data
+        .truncate(8 + 10 * 32 + 5 + 7 * 8 + 16 * 4 + 23 * 8);
+    test_env
+        .program_test_context
+        .set_account(&pool_id, &AccountSharedData::from(legacy_pool_account));
+    assert!(test_env
+        .try_close_user_pool_liquidity(&lp_depositor, pool_id, vec![])
+        .await
+        .is_err());
+    test_env
+        .program_test_context
+        .set_account(&pool_id, &AccountSharedData::from(pool_account));
+
+    // Older versions of the program did not count the rewards, the unclaimed one would be
+    // skipped until the pool is migrated
+    let mut pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    pool_state.reward_info_count = 0;
+    pool_state.reward_info_count_migrated = 0;
+    test_env.set_pool_state(pool_id, &pool_state).await;
+    assert!(test_env
+        .try_close_user_pool_liquidity(&lp_depositor, pool_id, vec![])
+        .await
+        .is_err());
+    test_env
+        .try_migrate_reward_info_count(&admin, pool_id, vec![reward_info])
+        .await
+        .unwrap_transaction();
+    assert!(test_env
+        .try_close_user_pool_liquidity(&lp_depositor, pool_id, vec![])
+        .await
+        .is_err());
+
+    test_env
+        .claim_rewards(&lp_depositor, pool_id, reward_info, reward_mint.pubkey())
+        .await;
+    test_env
+        .try_close_user_pool_liquidity(&lp_depositor, pool_id, vec![reward_info])
+        .await
+        .unwrap();
+}
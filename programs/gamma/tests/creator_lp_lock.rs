@@ -0,0 +1,107 @@
+use gamma::states::{ConfigUpdate, PoolState, UserPoolLiquidity, USER_POOL_LIQUIDITY_SEED};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+#[tokio::test]
+async fn should_lock_the_initial_lp_of_the_creator_until_the_unlock_time() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+
+    test_env
+        .create_config(&admin, amm_index, 3000, 2000, 50, 0)
+        .await;
+    let creator_lp_lock_duration = 3600;
+    test_env
+        .apply_amm_config_update(
+            &admin,
+            amm_index,
+            ConfigUpdate::CreatorLpLockDuration {
+                creator_lp_lock_duration,
+            },
+        )
+        .await;
+
+    for mint in [test_env.token_0_mint, test_env.token_1_mint] {
+        let token_account = test_env
+            .get_or_create_associated_token_account(user.pubkey(), mint, &user)
+            .await;
+        test_env
+            .mint_base_tokens(token_account, 100000000000000, mint)
+            .await;
+    }
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            200000000,
+            100000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    test_env.jump_seconds(100).await;
+
+    let user_pool_liquidity_id = Pubkey::find_program_address(
+        &[
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_id.to_bytes().as_ref(),
+            user.pubkey().to_bytes().as_ref(),
+        ],
+        &gamma::id(),
+    )
+    .0;
+    let user_pool_liquidity: UserPoolLiquidity =
+        test_env.fetch_account(user_pool_liquidity_id).await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    let creator_lp_locked = { pool_state.creator_lp_locked };
+    assert_eq!(
+        u128::from(creator_lp_locked),
+        user_pool_liquidity.lp_tokens_owned
+    );
+    assert_eq_with_copy!(
+        pool_state.creator_lp_unlock_time,
+        pool_state.open_time + creator_lp_lock_duration
+    );
+
+    // None of the initial LP can be withdrawn before the unlock time
+    assert!(test_env
+        .try_withdraw_for(&user, user.pubkey(), pool_id, amm_index, 1, 0, 0)
+        .await
+        .is_err());
+
+    // LP deposited after initialize is not part of the lock
+    let lp_amount = 100000000;
+    test_env
+        .deposit(&user, pool_id, amm_index, lp_amount, u64::MAX, u64::MAX)
+        .await;
+    assert!(test_env
+        .try_withdraw_for(
+            &user,
+            user.pubkey(),
+            pool_id,
+            amm_index,
+            lp_amount + 1,
+            0,
+            0
+        )
+        .await
+        .is_err());
+    test_env
+        .withdraw(&user, pool_id, amm_index, lp_amount, 0, 0)
+        .await;
+
+    // Once unlocked the creator can withdraw the initial LP
+    test_env.jump_seconds(creator_lp_lock_duration as i64).await;
+    test_env
+        .withdraw(&user, pool_id, amm_index, creator_lp_locked, 0, 0)
+        .await;
+    let user_pool_liquidity: UserPoolLiquidity =
+        test_env.fetch_account(user_pool_liquidity_id).await;
+    assert_eq!(user_pool_liquidity.lp_tokens_owned, 0);
+}
@@ -0,0 +1,170 @@
+use gamma::curve::TradeDirection;
+use gamma::states::{
+    CollectFeeEvent, ConfigUpdate, ConfigUpdateEvent, ParamValue, PoolState, PoolUpdate,
+    PoolUpdateEvent, AMM_CONFIG_SEED,
+};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+async fn setup_pool(test_env: &mut TestEnv, user: &Keypair, admin: &Keypair) -> Pubkey {
+    test_env.create_config(admin, 0, 3000, 2000, 50, 0).await;
+
+    let token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, user)
+        .await;
+    test_env
+        .mint_base_tokens(token_0_account, 100000000000000, test_env.token_0_mint)
+        .await;
+    let token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, user)
+        .await;
+    test_env
+        .mint_base_tokens(token_1_account, 100000000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
+            user,
+            0,
+            200000000,
+            100000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    test_env.jump_seconds(100).await;
+    pool_id
+}
+
+#[tokio::test]
+async fn should_emit_old_and_new_values_of_config_updates() {
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![admin.pubkey()]).await;
+    test_env
+        .create_config(&admin, amm_index, 3000, 2000, 50, 0)
+        .await;
+    let amm_config_id = Pubkey::find_program_address(
+        &[AMM_CONFIG_SEED.as_bytes(), &amm_index.to_be_bytes()],
+        &gamma::id(),
+    )
+    .0;
+
+    let result = test_env
+        .try_apply_amm_config_update(
+            &admin,
+            amm_index,
+            ConfigUpdate::TradeFeeRate {
+                trade_fee_rate: 4000,
+            },
+        )
+        .await;
+    result.unwrap_transaction();
+    let events: Vec<ConfigUpdateEvent> = decode_events(&result);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].amm_config, amm_config_id);
+    assert_eq!(events[0].authority, admin.pubkey());
+    assert_eq!(events[0].param, "trade_fee_rate");
+    assert_eq!(events[0].old_value, ParamValue::U64(3000));
+    assert_eq!(events[0].new_value, ParamValue::U64(4000));
+
+    // The legacy params emit the same event
+    let result = test_env
+        .try_update_amm_config(&admin, amm_index, 6, 1, None)
+        .await;
+    result.unwrap_transaction();
+    let events: Vec<ConfigUpdateEvent> = decode_events(&result);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].param, "disable_create_pool");
+    assert_eq!(events[0].old_value, ParamValue::Bool(false));
+    assert_eq!(events[0].new_value, ParamValue::Bool(true));
+}
+
+#[tokio::test]
+async fn should_emit_old_and_new_values_of_pool_updates() {
+    let admin = get_admin();
+    let user = Keypair::new();
+    let mut test_env = TestEnv::new(vec![admin.pubkey(), user.pubkey()]).await;
+    let pool_id = setup_pool(&mut test_env, &user, &admin).await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    let volatility_factor = pool_state.volatility_factor;
+
+    let result = test_env
+        .try_apply_pool_update(
+            &admin,
+            pool_id,
+            0,
+            PoolUpdate::VolatilityFactor {
+                volatility_factor: volatility_factor + 1,
+            },
+        )
+        .await;
+    result.unwrap_transaction();
+    let events: Vec<PoolUpdateEvent> = decode_events(&result);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].pool_id, pool_id);
+    assert_eq!(events[0].authority, admin.pubkey());
+    assert_eq!(events[0].param, "volatility_factor");
+    assert_eq!(events[0].old_value, ParamValue::U64(volatility_factor));
+    assert_eq!(events[0].new_value, ParamValue::U64(volatility_factor + 1));
+
+    // The legacy params emit the same event
+    let result = test_env.try_update_pool(&admin, pool_id, 0, 0, 1).await;
+    result.unwrap_transaction();
+    let events: Vec<PoolUpdateEvent> = decode_events(&result);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].param, "status");
+    assert_eq!(events[0].old_value, ParamValue::U64(0));
+    assert_eq!(events[0].new_value, ParamValue::U64(1));
+}
+
+#[tokio::test]
+async fn should_emit_collected_and_remaining_fees() {
+    let admin = get_admin();
+    let user = Keypair::new();
+    let mut test_env = TestEnv::new(vec![admin.pubkey(), user.pubkey()]).await;
+    let pool_id = setup_pool(&mut test_env, &user, &admin).await;
+
+    test_env
+        .swap_base_input(&user, pool_id, 0, 10000000, 0, TradeDirection::ZeroForOne)
+        .await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    let protocol_fees_token_0 = pool_state.protocol_fees_token_0;
+    assert!(protocol_fees_token_0 > 0);
+
+    let result = test_env
+        .try_collect_protocol_fee(&admin, pool_id, admin.pubkey())
+        .await;
+    result.unwrap_transaction();
+    let recipient_token_0_account = test_env
+        .get_or_create_associated_token_account(admin.pubkey(), pool_state.token_0_mint, &admin)
+        .await;
+    let recipient_token_1_account = test_env
+        .get_or_create_associated_token_account(admin.pubkey(), pool_state.token_1_mint, &admin)
+        .await;
+
+    let events: Vec<CollectFeeEvent> = decode_events(&result);
+    assert_eq!(events.len(), 1);
+    let event = &events[0];
+    assert_eq!(event.pool_id, pool_id);
+    assert_eq!(event.authority, admin.pubkey());
+    assert_eq!(event.recipient_token_0_account, recipient_token_0_account);
+    assert_eq!(event.recipient_token_1_account, recipient_token_1_account);
+    assert_eq!(event.fee_type, 0);
+    assert_eq!(
+        event.amount_0 + event.remaining_fees_token_0,
+        protocol_fees_token_0
+    );
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(
+        pool_state.protocol_fees_token_0,
+        event.remaining_fees_token_0
+    );
+    assert_eq_with_copy!(
+        pool_state.protocol_fees_token_1,
+        event.remaining_fees_token_1
+    );
+}
@@ -0,0 +1,117 @@
+use gamma::curve::TradeDirection;
+use gamma::states::{OracleUpdateMode, PoolState, Q32};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+async fn setup_pool(test_env: &mut TestEnv, user: &Keypair, admin: &Keypair) -> Pubkey {
+    let amm_index = 0;
+    test_env
+        .create_config(admin, amm_index, 3000, 2000, 50, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 100000000000000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 100000000000000, test_env.token_1_mint)
+        .await;
+
+    test_env
+        .initialize_pool(
+            user,
+            amm_index,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await
+}
+
+/// One LP unit is worth about 2 / lp_supply of the geometric mean of the reserves in either
+/// token when the pool holds about as much of both tokens
+async fn assert_lp_price_of_balanced_pool(test_env: &mut TestEnv, user: &Keypair, pool_id: Pubkey) {
+    let lp_price = test_env.try_get_lp_price(user, pool_id).await.unwrap();
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    let expected_per_lp_x32 = 2 * 1000000000 * Q32 / u128::from({ pool_state.lp_supply });
+    assert!(lp_price.twap_token_0_price_x32.abs_diff(Q32) < Q32 / 100);
+    assert!(lp_price.token_0_per_lp_x32.abs_diff(expected_per_lp_x32) < expected_per_lp_x32 / 100);
+    assert!(lp_price.token_1_per_lp_x32.abs_diff(expected_per_lp_x32) < expected_per_lp_x32 / 100);
+}
+
+#[tokio::test]
+async fn should_price_lp_only_over_a_long_enough_twap() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+    let pool_id = setup_pool(&mut test_env, &user, &admin).await;
+
+    // Observations seconds apart are not enough to price the LP
+    for _ in 0..3 {
+        test_env.jump_seconds(20).await;
+        test_env
+            .swap_base_input(
+                &user,
+                pool_id,
+                amm_index,
+                1000,
+                0,
+                TradeDirection::ZeroForOne,
+            )
+            .await;
+    }
+    assert!(test_env.try_get_lp_price(&user, pool_id).await.is_err());
+
+    for _ in 0..4 {
+        test_env.jump_seconds(600).await;
+        test_env
+            .swap_base_input(
+                &user,
+                pool_id,
+                amm_index,
+                1000,
+                0,
+                TradeDirection::ZeroForOne,
+            )
+            .await;
+    }
+    assert_lp_price_of_balanced_pool(&mut test_env, &user, pool_id).await;
+}
+
+#[tokio::test]
+async fn should_price_lp_of_a_pool_trading_every_15_seconds() {
+    for oracle_update_mode in [OracleUpdateMode::PreSwap, OracleUpdateMode::PostSwapPerSlot] {
+        let user = Keypair::new();
+        let admin = get_admin();
+        let amm_index = 0;
+        let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+        let pool_id = setup_pool(&mut test_env, &user, &admin).await;
+        test_env
+            .update_pool(&admin, pool_id, 0, 9, oracle_update_mode as u64)
+            .await;
+
+        // An hour of swaps 15 seconds apart fills the observations more than once, they still
+        // span long enough to price the LP
+        for trade_direction in [TradeDirection::ZeroForOne, TradeDirection::OneForZero]
+            .into_iter()
+            .cycle()
+            .take(240)
+        {
+            test_env.jump_seconds(14).await;
+            test_env
+                .swap_base_input(&user, pool_id, amm_index, 1000, 0, trade_direction)
+                .await;
+        }
+        assert_lp_price_of_balanced_pool(&mut test_env, &user, pool_id).await;
+    }
+}
@@ -0,0 +1,525 @@
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use gamma::error::GammaError;
+use gamma::states::{
+    LpWrapEvent, PoolPartner, PoolState, UserPoolLiquidity, UserRewardInfo,
+    USER_POOL_LIQUIDITY_SEED,
+};
+use gamma::{LOCK_LP_AMOUNT, REWARD_INFO_SEED, USER_REWARD_INFO_SEED};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+fn user_pool_liquidity_key(pool_id: Pubkey, user: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_id.to_bytes().as_ref(),
+            user.to_bytes().as_ref(),
+        ],
+        &gamma::id(),
+    )
+    .0
+}
+
+fn user_reward_info_key(reward_info: Pubkey, user: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            USER_REWARD_INFO_SEED.as_bytes(),
+            reward_info.to_bytes().as_ref(),
+            user.to_bytes().as_ref(),
+        ],
+        &gamma::id(),
+    )
+    .0
+}
+
+async fn fund_user(test_env: &mut TestEnv, user: &Keypair, amount: u64) {
+    let token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, user)
+        .await;
+    test_env
+        .mint_base_tokens(token_0_account, amount, test_env.token_0_mint)
+        .await;
+    let token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, user)
+        .await;
+    test_env
+        .mint_base_tokens(token_1_account, amount, test_env.token_1_mint)
+        .await;
+}
+
+async fn lp_tokens_owned(test_env: &mut TestEnv, pool_id: Pubkey, user: Pubkey) -> u128 {
+    test_env
+        .fetch_account::<UserPoolLiquidity>(user_pool_liquidity_key(pool_id, user))
+        .await
+        .lp_tokens_owned
+}
+
+/// The LP supply of the pool is the LP of the positions, the wrapped LP and the locked LP
+async fn assert_lp_backing(test_env: &mut TestEnv, pool_id: Pubkey, users: &[Pubkey]) {
+    let mut lp_tokens = u128::from(LOCK_LP_AMOUNT);
+    for user in users {
+        lp_tokens += lp_tokens_owned(test_env, pool_id, *user).await;
+    }
+    let lp_mint: Mint = test_env.fetch_account(lp_mint_address(pool_id)).await;
+    lp_tokens += u128::from(lp_mint.supply);
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq!(lp_tokens, u128::from(pool_state.lp_supply));
+}
+
+#[tokio::test]
+async fn should_back_lp_tokens_one_to_one_with_positions() {
+    let admin = get_admin();
+    let user = Keypair::new();
+    let user2 = Keypair::new();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![admin.pubkey(), user.pubkey(), user2.pubkey()]).await;
+    test_env
+        .create_config(&admin, amm_index, 3000, 2000, 50, 0)
+        .await;
+    fund_user(&mut test_env, &user, 100000000000000).await;
+    fund_user(&mut test_env, &user2, 100000000000000).await;
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            200000000,
+            100000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    test_env.jump_seconds(100).await;
+    test_env.init_user_pool_liquidity(&user2, pool_id).await;
+    test_env
+        .deposit(&user2, pool_id, amm_index, 100000000, u64::MAX, u64::MAX)
+        .await;
+
+    // Only the pool creator or a config creator creates the LP mint
+    let result = test_env.try_init_lp_mint(&user2, pool_id).await;
+    assert_error!(result, GammaError::InvalidOwner);
+    test_env
+        .try_init_lp_mint(&user, pool_id)
+        .await
+        .unwrap_transaction();
+    let lp_mint = lp_mint_address(pool_id);
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.lp_mint, lp_mint);
+    assert_eq_with_copy!(pool_state.lp_mint_enabled, 1);
+    let lp_supply = pool_state.lp_supply;
+    assert_lp_backing(&mut test_env, pool_id, &[user.pubkey(), user2.pubkey()]).await;
+
+    // The LP can be wrapped into a token account of anyone
+    let user_lp_tokens = lp_tokens_owned(&mut test_env, pool_id, user.pubkey()).await;
+    let lp_amount = (user_lp_tokens / 2) as u64;
+    let user2_lp_token_account = test_env
+        .get_or_create_associated_token_account(user2.pubkey(), lp_mint, &user2)
+        .await;
+    let result = test_env
+        .try_wrap_lp(&user, pool_id, lp_amount, user2_lp_token_account, None, &[])
+        .await;
+    result.unwrap_transaction();
+    let events: Vec<LpWrapEvent> = decode_events(&result);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].owner, user.pubkey());
+    assert_eq!(events[0].lp_mint, lp_mint);
+    assert_eq!(events[0].lp_amount, lp_amount);
+    assert_eq!(
+        events[0].lp_tokens_owned,
+        user_lp_tokens - u128::from(lp_amount)
+    );
+    assert_eq!(events[0].change_type, 0);
+
+    let lp_token_account: TokenAccount = test_env.fetch_account(user2_lp_token_account).await;
+    assert_eq!(lp_token_account.amount, lp_amount);
+    assert_eq!(
+        lp_tokens_owned(&mut test_env, pool_id, user.pubkey()).await,
+        user_lp_tokens - u128::from(lp_amount)
+    );
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.lp_supply, lp_supply);
+    assert_lp_backing(&mut test_env, pool_id, &[user.pubkey(), user2.pubkey()]).await;
+
+    // The wrapped LP has left the position, it can't be wrapped or withdrawn twice
+    let remaining_lp_tokens = (user_lp_tokens - u128::from(lp_amount)) as u64;
+    let result = test_env
+        .try_wrap_lp(
+            &user,
+            pool_id,
+            remaining_lp_tokens + 1,
+            user2_lp_token_account,
+            None,
+            &[],
+        )
+        .await;
+    assert_error!(result, GammaError::InvalidLpTokenAmount);
+    assert!(test_env
+        .try_withdraw_for(
+            &user,
+            user.pubkey(),
+            pool_id,
+            amm_index,
+            remaining_lp_tokens + 1,
+            0,
+            0
+        )
+        .await
+        .is_err());
+
+    // The holder of the LP tokens burns them into its own position
+    let user2_lp_tokens = lp_tokens_owned(&mut test_env, pool_id, user2.pubkey()).await;
+    assert!(test_env
+        .try_unwrap_lp(&user2, pool_id, lp_amount + 1, None, &[])
+        .await
+        .unwrap()
+        .result
+        .is_err());
+    let result = test_env
+        .try_unwrap_lp(&user2, pool_id, lp_amount, None, &[])
+        .await;
+    result.unwrap_transaction();
+    let events: Vec<LpWrapEvent> = decode_events(&result);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].owner, user2.pubkey());
+    assert_eq!(events[0].change_type, 1);
+    assert_eq!(
+        lp_tokens_owned(&mut test_env, pool_id, user2.pubkey()).await,
+        user2_lp_tokens + u128::from(lp_amount)
+    );
+    let lp_token_account: TokenAccount = test_env.fetch_account(user2_lp_token_account).await;
+    assert_eq!(lp_token_account.amount, 0);
+    assert_lp_backing(&mut test_env, pool_id, &[user.pubkey(), user2.pubkey()]).await;
+
+    // Deposits and withdrawals keep the LP of the positions and the pool in line
+    test_env
+        .deposit(&user, pool_id, amm_index, 100000000, u64::MAX, u64::MAX)
+        .await;
+    assert_lp_backing(&mut test_env, pool_id, &[user.pubkey(), user2.pubkey()]).await;
+    let user2_lp_tokens = lp_tokens_owned(&mut test_env, pool_id, user2.pubkey()).await;
+    test_env
+        .withdraw(&user2, pool_id, amm_index, user2_lp_tokens as u64, 0, 0)
+        .await;
+    assert_eq!(
+        lp_tokens_owned(&mut test_env, pool_id, user2.pubkey()).await,
+        0
+    );
+    assert_lp_backing(&mut test_env, pool_id, &[user.pubkey(), user2.pubkey()]).await;
+}
+
+/// A pool with an LP mint and a reward running from 10 seconds from now, funded by
+/// `reward_provider`. Returns the pool, the LP token account of `user` and the RewardInfo
+async fn setup_pool_with_reward(
+    test_env: &mut TestEnv,
+    user: &Keypair,
+    reward_provider: &Keypair,
+) -> (Pubkey, Pubkey, Pubkey) {
+    let admin = get_admin();
+    let amm_index = 0;
+    test_env
+        .create_config(&admin, amm_index, 100, 20, 5, 0)
+        .await;
+    fund_user(test_env, user, 100000).await;
+    let pool_id = test_env
+        .initialize_pool(
+            user,
+            amm_index,
+            1000,
+            2000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    test_env
+        .try_init_lp_mint(user, pool_id)
+        .await
+        .unwrap_transaction();
+    let lp_token_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), lp_mint_address(pool_id), user)
+        .await;
+
+    let reward_mint = Keypair::new();
+    test_env
+        .create_token_mint(&reward_mint, &test_env.mint_authority.pubkey(), 9)
+        .await;
+    let reward_provider_token_account = test_env
+        .get_or_create_associated_token_account(
+            reward_provider.pubkey(),
+            reward_mint.pubkey(),
+            reward_provider,
+        )
+        .await;
+    let reward_amount = 1000000000;
+    test_env
+        .mint_base_tokens(
+            reward_provider_token_account,
+            reward_amount,
+            reward_mint.pubkey(),
+        )
+        .await;
+    let timestamp_now = test_env.timestamp_now().await as u64;
+    let start_time = timestamp_now + 10;
+    let end_time = timestamp_now + 3000;
+    test_env
+        .create_rewards(
+            reward_provider,
+            pool_id,
+            start_time,
+            end_time,
+            reward_mint.pubkey(),
+            reward_amount,
+        )
+        .await;
+    let (reward_info_key, _) = Pubkey::find_program_address(
+        &[
+            REWARD_INFO_SEED.as_bytes(),
+            pool_id.to_bytes().as_ref(),
+            &start_time.to_le_bytes(),
+            reward_mint.pubkey().to_bytes().as_ref(),
+        ],
+        &gamma::id(),
+    );
+    (pool_id, lp_token_account, reward_info_key)
+}
+
+#[tokio::test]
+async fn should_settle_rewards_when_wrapping_and_unwrapping() {
+    let user = Keypair::new();
+    let reward_provider = Keypair::new();
+    let admin = get_admin();
+    let mut test_env = TestEnv::new(vec![
+        user.pubkey(),
+        admin.pubkey(),
+        reward_provider.pubkey(),
+    ])
+    .await;
+    let (pool_id, lp_token_account, reward_info_key) =
+        setup_pool_with_reward(&mut test_env, &user, &reward_provider).await;
+    let user_reward_info_id = user_reward_info_key(reward_info_key, user.pubkey());
+
+    test_env.jump_seconds(20).await;
+    test_env
+        .calculate_rewards(&user, pool_id, reward_info_key)
+        .await;
+    let user_reward_info: UserRewardInfo = test_env.fetch_account(user_reward_info_id).await;
+    let rewards_before_wrap = user_reward_info.total_rewards;
+
+    let user_pool_liquidity: UserPoolLiquidity = test_env
+        .fetch_account(user_pool_liquidity_key(pool_id, user.pubkey()))
+        .await;
+    let first_investment_at = user_pool_liquidity.first_investment_at;
+
+    // The position is settled at its balance before the LP is wrapped
+    test_env.jump_seconds(500).await;
+    let lp_amount = lp_tokens_owned(&mut test_env, pool_id, user.pubkey()).await as u64;
+    test_env
+        .try_wrap_lp(
+            &user,
+            pool_id,
+            lp_amount,
+            lp_token_account,
+            None,
+            &[reward_info_key],
+        )
+        .await
+        .unwrap_transaction();
+    let user_reward_info: UserRewardInfo = test_env.fetch_account(user_reward_info_id).await;
+    let rewards_at_wrap = user_reward_info.total_rewards;
+    assert!(rewards_at_wrap > rewards_before_wrap);
+
+    // The wrapped LP earns no rewards
+    test_env.jump_seconds(500).await;
+    test_env
+        .calculate_rewards(&user, pool_id, reward_info_key)
+        .await;
+    let user_reward_info: UserRewardInfo = test_env.fetch_account(user_reward_info_id).await;
+    assert_eq!(user_reward_info.total_rewards, rewards_at_wrap);
+
+    // Unwrapping into the empty position settles it and restarts its accrual
+    test_env.jump_seconds(500).await;
+    let half = lp_amount / 2;
+    test_env
+        .try_unwrap_lp(&user, pool_id, half, None, &[reward_info_key])
+        .await
+        .unwrap_transaction();
+    let user_reward_info: UserRewardInfo = test_env.fetch_account(user_reward_info_id).await;
+    assert_eq!(user_reward_info.total_rewards, rewards_at_wrap);
+    let user_pool_liquidity: UserPoolLiquidity = test_env
+        .fetch_account(user_pool_liquidity_key(pool_id, user.pubkey()))
+        .await;
+    let unwrapped_at = user_pool_liquidity.first_investment_at;
+    assert!(unwrapped_at > first_investment_at);
+
+    test_env.jump_seconds(500).await;
+    test_env
+        .calculate_rewards(&user, pool_id, reward_info_key)
+        .await;
+    let user_reward_info: UserRewardInfo = test_env.fetch_account(user_reward_info_id).await;
+    assert!(user_reward_info.total_rewards > rewards_at_wrap);
+
+    // A position already holding LP restarts its accrual too
+    test_env
+        .try_unwrap_lp(&user, pool_id, lp_amount - half, None, &[reward_info_key])
+        .await
+        .unwrap_transaction();
+    let user_pool_liquidity: UserPoolLiquidity = test_env
+        .fetch_account(user_pool_liquidity_key(pool_id, user.pubkey()))
+        .await;
+    assert!(user_pool_liquidity.first_investment_at > unwrapped_at);
+    assert_eq!(user_pool_liquidity.lp_tokens_owned, u128::from(lp_amount));
+}
+
+#[tokio::test]
+async fn should_not_reward_the_wrapped_period_when_unwrapping_into_a_position_holding_lp() {
+    let user = Keypair::new();
+    let reward_provider = Keypair::new();
+    let admin = get_admin();
+    let mut test_env = TestEnv::new(vec![
+        user.pubkey(),
+        admin.pubkey(),
+        reward_provider.pubkey(),
+    ])
+    .await;
+    let (pool_id, lp_token_account, reward_info_key) =
+        setup_pool_with_reward(&mut test_env, &user, &reward_provider).await;
+    let user_reward_info_id = user_reward_info_key(reward_info_key, user.pubkey());
+
+    test_env.jump_seconds(20).await;
+    test_env
+        .calculate_rewards(&user, pool_id, reward_info_key)
+        .await;
+
+    // Half of the LP is wrapped, the position keeps the other half
+    let lp_amount = lp_tokens_owned(&mut test_env, pool_id, user.pubkey()).await as u64;
+    let half = lp_amount / 2;
+    test_env
+        .try_wrap_lp(
+            &user,
+            pool_id,
+            half,
+            lp_token_account,
+            None,
+            &[reward_info_key],
+        )
+        .await
+        .unwrap_transaction();
+    let user_reward_info: UserRewardInfo = test_env.fetch_account(user_reward_info_id).await;
+    let rewards_at_wrap = user_reward_info.total_rewards;
+
+    // Unwrapped without settling the reward, it is forfeited for the wrapped period instead of
+    // paying the full balance for it
+    test_env.jump_seconds(1000).await;
+    test_env
+        .try_unwrap_lp(&user, pool_id, half, None, &[])
+        .await
+        .unwrap_transaction();
+    test_env
+        .calculate_rewards(&user, pool_id, reward_info_key)
+        .await;
+    let user_reward_info: UserRewardInfo = test_env.fetch_account(user_reward_info_id).await;
+    assert_eq!(user_reward_info.total_rewards, rewards_at_wrap);
+
+    // The unwrapped LP earns from now on
+    test_env.jump_seconds(500).await;
+    test_env
+        .calculate_rewards(&user, pool_id, reward_info_key)
+        .await;
+    let user_reward_info: UserRewardInfo = test_env.fetch_account(user_reward_info_id).await;
+    assert!(user_reward_info.total_rewards > rewards_at_wrap);
+}
+
+#[tokio::test]
+async fn should_move_partner_lp_when_wrapping_and_unwrapping() {
+    let admin = get_admin();
+    let user = Keypair::new();
+    let depositor = Keypair::new();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![admin.pubkey(), user.pubkey(), depositor.pubkey()]).await;
+    test_env
+        .create_config(&admin, amm_index, 3000, 2000, 50, 0)
+        .await;
+    fund_user(&mut test_env, &user, 100000000000000).await;
+    fund_user(&mut test_env, &depositor, 100000000000000).await;
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            200000000,
+            100000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    test_env.jump_seconds(100).await;
+
+    let partner_id = 0;
+    test_env
+        .create_partner(&admin, partner_id, "AssetDash", depositor.pubkey(), 500_000)
+        .await;
+    let pool_partner_id = test_env.init_pool_partner(&user, pool_id, partner_id).await;
+    test_env
+        .init_user_pool_liquidity_with_partner(&depositor, pool_id, Some(partner_id))
+        .await;
+    let lp_deposit_amount = 200000000;
+    test_env
+        .deposit(
+            &depositor,
+            pool_id,
+            amm_index,
+            lp_deposit_amount,
+            u64::MAX,
+            u64::MAX,
+        )
+        .await;
+    test_env
+        .try_init_lp_mint(&user, pool_id)
+        .await
+        .unwrap_transaction();
+    let lp_token_account = test_env
+        .get_or_create_associated_token_account(
+            depositor.pubkey(),
+            lp_mint_address(pool_id),
+            &depositor,
+        )
+        .await;
+
+    // The PoolPartner of the position has to be passed
+    let lp_amount = 50000000;
+    let result = test_env
+        .try_wrap_lp(&depositor, pool_id, lp_amount, lp_token_account, None, &[])
+        .await;
+    assert_error!(result, GammaError::PoolPartnerMissing);
+
+    // The wrapped LP leaves the partner until it is unwrapped
+    test_env
+        .try_wrap_lp(
+            &depositor,
+            pool_id,
+            lp_amount,
+            lp_token_account,
+            Some(partner_id),
+            &[],
+        )
+        .await
+        .unwrap_transaction();
+    let pool_partner: PoolPartner = test_env.fetch_account(pool_partner_id).await;
+    assert_eq!(
+        pool_partner.lp_token_linked_with_partner,
+        lp_deposit_amount - lp_amount
+    );
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(
+        pool_state.lp_token_linked_with_partners,
+        lp_deposit_amount - lp_amount
+    );
+
+    test_env
+        .try_unwrap_lp(&depositor, pool_id, lp_amount, Some(partner_id), &[])
+        .await
+        .unwrap_transaction();
+    let pool_partner: PoolPartner = test_env.fetch_account(pool_partner_id).await;
+    assert_eq!(pool_partner.lp_token_linked_with_partner, lp_deposit_amount);
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.lp_token_linked_with_partners, lp_deposit_amount);
+}
@@ -0,0 +1,191 @@
+use gamma::error::GammaError;
+use gamma::states::{MintEntryStatus, MintRegistryMode, PoolState, AMM_CONFIG_SEED};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+fn amm_config_address(amm_index: u16) -> Pubkey {
+    Pubkey::find_program_address(
+        &[AMM_CONFIG_SEED.as_bytes(), &amm_index.to_be_bytes()],
+        &gamma::id(),
+    )
+    .0
+}
+
+async fn fund_user(test_env: &mut TestEnv, user: &Keypair) {
+    let token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, user)
+        .await;
+    test_env
+        .mint_base_tokens(token_0_account, 100000000000000, test_env.token_0_mint)
+        .await;
+    let token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, user)
+        .await;
+    test_env
+        .mint_base_tokens(token_1_account, 100000000000000, test_env.token_1_mint)
+        .await;
+}
+
+/// Initializes the pool of the config at `amm_index`, the registry accounts of that config are
+/// passed as remaining accounts when `with_registry_accounts` is set
+async fn try_initialize_pool_with_registry(
+    test_env: &mut TestEnv,
+    user: &Keypair,
+    amm_index: u16,
+    with_registry_accounts: bool,
+) -> (Pubkey, ProcessTransactionResult) {
+    let (pool_id, mut instruction) = test_env
+        .initialize_pool_instruction(
+            user,
+            amm_index,
+            200000000,
+            100000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    if with_registry_accounts {
+        instruction.accounts.extend(mint_registry_accounts(
+            amm_config_address(amm_index),
+            test_env.token_0_mint,
+            test_env.token_1_mint,
+        ));
+    }
+    let transaction =
+        get_signed_transaction(&mut test_env.program_test_context, &[instruction], user).await;
+    let result = test_env
+        .program_test_context
+        .banks_client
+        .process_transaction_with_metadata(transaction)
+        .await;
+    (pool_id, result)
+}
+
+#[tokio::test]
+async fn should_only_create_pools_of_allowed_mints_in_allowlist_mode() {
+    let admin = get_admin();
+    let user = Keypair::new();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![admin.pubkey(), user.pubkey()]).await;
+    test_env
+        .create_config(&admin, amm_index, 3000, 2000, 50, 0)
+        .await;
+    fund_user(&mut test_env, &user).await;
+    let token_0_mint = test_env.token_0_mint;
+    let token_1_mint = test_env.token_1_mint;
+
+    // Only the admin or a config creator manages the registry
+    let result = test_env
+        .try_init_mint_registry(&user, amm_index, MintRegistryMode::Allowlist as u8)
+        .await;
+    assert_error!(result, GammaError::InvalidOwner);
+    test_env
+        .try_init_mint_registry(&admin, amm_index, MintRegistryMode::Allowlist as u8)
+        .await
+        .unwrap_transaction();
+    let result = test_env
+        .try_set_mint_entry(
+            &user,
+            amm_index,
+            token_0_mint,
+            MintEntryStatus::Allowed as u8,
+        )
+        .await;
+    assert_error!(result, GammaError::InvalidOwner);
+
+    // The registry accounts can't be left out once the config has a registry
+    let (_, result) =
+        try_initialize_pool_with_registry(&mut test_env, &user, amm_index, false).await;
+    assert_error!(result, GammaError::InvalidInput);
+
+    // A mint without an entry is not allowed
+    test_env
+        .try_set_mint_entry(
+            &admin,
+            amm_index,
+            token_0_mint,
+            MintEntryStatus::Allowed as u8,
+        )
+        .await
+        .unwrap_transaction();
+    let (_, result) =
+        try_initialize_pool_with_registry(&mut test_env, &user, amm_index, true).await;
+    assert_error!(result, GammaError::MintNotAllowed);
+
+    // Nor is a denied one
+    test_env
+        .try_set_mint_entry(
+            &admin,
+            amm_index,
+            token_1_mint,
+            MintEntryStatus::Denied as u8,
+        )
+        .await
+        .unwrap_transaction();
+    let (_, result) =
+        try_initialize_pool_with_registry(&mut test_env, &user, amm_index, true).await;
+    assert_error!(result, GammaError::MintNotAllowed);
+
+    test_env
+        .try_set_mint_entry(
+            &admin,
+            amm_index,
+            token_1_mint,
+            MintEntryStatus::Allowed as u8,
+        )
+        .await
+        .unwrap_transaction();
+    let (pool_id, result) =
+        try_initialize_pool_with_registry(&mut test_env, &user, amm_index, true).await;
+    result.unwrap_transaction();
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.verified, 1);
+}
+
+#[tokio::test]
+async fn should_reject_denied_mints_in_denylist_mode() {
+    let admin = get_admin();
+    let user = Keypair::new();
+    let mut test_env = TestEnv::new(vec![admin.pubkey(), user.pubkey()]).await;
+    fund_user(&mut test_env, &user).await;
+    let token_0_mint = test_env.token_0_mint;
+    let token_1_mint = test_env.token_1_mint;
+    for amm_index in [0, 1, 2] {
+        test_env
+            .create_config(&admin, amm_index, 3000, 2000, 50, 0)
+            .await;
+        test_env
+            .try_init_mint_registry(&admin, amm_index, MintRegistryMode::Denylist as u8)
+            .await
+            .unwrap_transaction();
+    }
+
+    // A denied mint is rejected
+    test_env
+        .try_set_mint_entry(&admin, 0, token_1_mint, MintEntryStatus::Denied as u8)
+        .await
+        .unwrap_transaction();
+    let (_, result) = try_initialize_pool_with_registry(&mut test_env, &user, 0, true).await;
+    assert_error!(result, GammaError::MintNotAllowed);
+
+    // Mints without an entry are accepted, the pool is not verified
+    let (pool_id, result) = try_initialize_pool_with_registry(&mut test_env, &user, 1, true).await;
+    result.unwrap_transaction();
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.verified, 0);
+
+    // A pool of two allowed mints is verified
+    for mint in [token_0_mint, token_1_mint] {
+        test_env
+            .try_set_mint_entry(&admin, 2, mint, MintEntryStatus::Allowed as u8)
+            .await
+            .unwrap_transaction();
+    }
+    let (pool_id, result) = try_initialize_pool_with_registry(&mut test_env, &user, 2, true).await;
+    result.unwrap_transaction();
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.verified, 1);
+}
@@ -0,0 +1,140 @@
+use gamma::{
+    curve::TradeDirection,
+    states::{ObservationState, OracleUpdateMode, PoolState},
+};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+async fn setup_pool(test_env: &mut TestEnv, user: &Keypair, admin: &Keypair) -> Pubkey {
+    test_env.create_config(admin, 0, 3000, 2000, 50, 0).await;
+    for mint in [test_env.token_0_mint, test_env.token_1_mint] {
+        let token_account = test_env
+            .get_or_create_associated_token_account(user.pubkey(), mint, user)
+            .await;
+        test_env
+            .mint_base_tokens(token_account, 100000000000000, mint)
+            .await;
+    }
+    let pool_id = test_env
+        .initialize_pool(
+            user,
+            0,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    test_env.jump_seconds(100).await;
+    pool_id
+}
+
+async fn fetch_observation(test_env: &mut TestEnv, pool_id: Pubkey) -> ObservationState {
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    test_env.fetch_account(pool_state.observation_key).await
+}
+
+#[tokio::test]
+async fn should_skip_observations_within_the_update_duration_in_pre_swap_mode() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+    let pool_id = setup_pool(&mut test_env, &user, &admin).await;
+
+    test_env
+        .swap_base_input(&user, pool_id, 0, 1000000, 0, TradeDirection::ZeroForOne)
+        .await;
+    let observation = fetch_observation(&mut test_env, pool_id).await;
+    let observation_index = observation.observation_index;
+
+    // The default mode keeps ignoring updates less than 15 seconds apart
+    test_env.jump_seconds(5).await;
+    test_env
+        .swap_base_input(&user, pool_id, 0, 1000000, 0, TradeDirection::OneForZero)
+        .await;
+    let observation = fetch_observation(&mut test_env, pool_id).await;
+    assert_eq_with_copy!(observation.observation_index, observation_index);
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.oracle_token_0_price_x32, 0);
+}
+
+#[tokio::test]
+async fn should_accumulate_the_post_swap_price_per_slot() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+    let pool_id = setup_pool(&mut test_env, &user, &admin).await;
+
+    test_env
+        .update_pool(
+            &admin,
+            pool_id,
+            0,
+            9,
+            OracleUpdateMode::PostSwapPerSlot as u64,
+        )
+        .await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(
+        pool_state.oracle_update_mode,
+        OracleUpdateMode::PostSwapPerSlot as u8
+    );
+
+    // The price after the swap is recorded for the next observation
+    test_env
+        .swap_base_input(&user, pool_id, 0, 100000000, 0, TradeDirection::ZeroForOne)
+        .await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    let (token_0_price_x32, token_1_price_x32) = pool_state.token_price_x32().unwrap();
+    assert_eq_with_copy!(pool_state.oracle_token_0_price_x32, token_0_price_x32);
+    assert_eq_with_copy!(pool_state.oracle_token_1_price_x32, token_1_price_x32);
+    let observation = fetch_observation(&mut test_env, pool_id).await;
+    let observation_index = observation.observation_index;
+
+    // A swap a few seconds later writes an observation accumulating the recorded price
+    test_env.jump_seconds(5).await;
+    test_env
+        .swap_base_input(&user, pool_id, 0, 100000000, 0, TradeDirection::OneForZero)
+        .await;
+    let observation = fetch_observation(&mut test_env, pool_id).await;
+    assert_ne!({ observation.observation_index }, observation_index);
+    let observation_index = observation.observation_index;
+    assert_eq!(
+        get_current_price_token_0_price(observation),
+        token_0_price_x32
+    );
+
+    // Swaps of the same slot are merged into that observation
+    test_env
+        .swap_base_input(&user, pool_id, 0, 1000000, 0, TradeDirection::ZeroForOne)
+        .await;
+    let observation = fetch_observation(&mut test_env, pool_id).await;
+    assert_eq_with_copy!(observation.observation_index, observation_index);
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    let (token_0_price_x32, _) = pool_state.token_price_x32().unwrap();
+    assert_eq_with_copy!(pool_state.oracle_token_0_price_x32, token_0_price_x32);
+
+    // Swaps less than 15 seconds after the previous observation move the latest one forward
+    let block_timestamp = observation.observations[observation_index as usize].block_timestamp;
+    test_env.jump_seconds(10).await;
+    test_env
+        .swap_base_input(&user, pool_id, 0, 1000000, 0, TradeDirection::OneForZero)
+        .await;
+    let observation = fetch_observation(&mut test_env, pool_id).await;
+    assert_eq_with_copy!(observation.observation_index, observation_index);
+    assert!(
+        { observation.observations[observation_index as usize].block_timestamp } > block_timestamp
+    );
+
+    // Once the latest observation is 15 seconds after the previous one the next swap writes a
+    // new observation
+    test_env.jump_seconds(5).await;
+    test_env
+        .swap_base_input(&user, pool_id, 0, 1000000, 0, TradeDirection::ZeroForOne)
+        .await;
+    let observation = fetch_observation(&mut test_env, pool_id).await;
+    assert_ne!({ observation.observation_index }, observation_index);
+}
@@ -1,8 +1,8 @@
 use std::u64;
 
+use anchor_spl::token::TokenAccount;
 use gamma::curve::TradeDirection;
-use gamma::states::PartnerType;
-use gamma::states::PoolState;
+use gamma::states::{PoolPartner, PoolState};
 use solana_program_test::tokio;
 use solana_sdk::{signature::Keypair, signer::Signer};
 mod utils;
@@ -97,28 +97,25 @@ async fn should_track_cumulative_rates_correctly() {
     assert_eq_with_copy!(pool_state.cumulative_trade_fees_token_0, 0);
     assert_eq_with_copy!(pool_state.cumulative_trade_fees_token_1, 0);
 
-    assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_0,
-        0
-    );
-    assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_1,
-        0
-    );
+    let partner_id = 0;
+    test_env
+        .create_partner(
+            &admin,
+            partner_id,
+            "AssetDash",
+            lp_depositor_asset_dash.pubkey(),
+            500_000,
+        )
+        .await;
+    let pool_partner_id = test_env.init_pool_partner(&user, pool_id, partner_id).await;
 
-    assert_eq_with_copy!(pool_state.partners[0].lp_token_linked_with_partner, 0);
-    assert_eq_with_copy!(
-        PartnerType::new(pool_state.partners[0].partner_id),
-        PartnerType::AssetDash
-    );
-    assert_eq_with_copy!(pool_state.partners[0].partner_id, 0);
+    let pool_partner: PoolPartner = test_env.fetch_account(pool_partner_id).await;
+    assert_eq!(pool_partner.cumulative_fees(&pool_state).unwrap(), (0, 0));
+    assert_eq!(pool_partner.lp_token_linked_with_partner, 0);
+    assert_eq!(pool_partner.partner_id, partner_id);
 
     test_env
-        .init_user_pool_liquidity_with_partner(
-            &lp_depositor_asset_dash,
-            pool_id,
-            Some("AssetDash".to_string()),
-        )
+        .init_user_pool_liquidity_with_partner(&lp_depositor_asset_dash, pool_id, Some(partner_id))
         .await;
 
     let lp_deposit_amount = 200000000;
@@ -136,19 +133,9 @@ async fn should_track_cumulative_rates_correctly() {
     let pool_state: PoolState = test_env.fetch_account(pool_id).await;
     dbg!(2, pool_state.token_0_vault_amount, pool_state.token_1_vault_amount);
 
-
-    assert_eq_with_copy!(
-        pool_state.partners[0].lp_token_linked_with_partner,
-        lp_deposit_amount
-    );
-    assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_0,
-        0
-    );
-    assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_1,
-        0
-    );
+    let pool_partner: PoolPartner = test_env.fetch_account(pool_partner_id).await;
+    assert_eq!(pool_partner.lp_token_linked_with_partner, lp_deposit_amount);
+    assert_eq!(pool_partner.cumulative_fees(&pool_state).unwrap(), (0, 0));
 
     let withdraw_amount = 100000000;
     test_env
@@ -165,18 +152,12 @@ async fn should_track_cumulative_rates_correctly() {
     let pool_state: PoolState = test_env.fetch_account(pool_id).await;
     dbg!(3, pool_state.token_0_vault_amount, pool_state.token_1_vault_amount);
 
-    assert_eq_with_copy!(
-        pool_state.partners[0].lp_token_linked_with_partner,
+    let pool_partner: PoolPartner = test_env.fetch_account(pool_partner_id).await;
+    assert_eq!(
+        pool_partner.lp_token_linked_with_partner,
         lp_deposit_amount - withdraw_amount
     );
-    assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_0,
-        0
-    );
-    assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_1,
-        0
-    );
+    assert_eq!(pool_partner.cumulative_fees(&pool_state).unwrap(), (0, 0));
 
     test_env
         .swap_base_input(
@@ -192,19 +173,12 @@ async fn should_track_cumulative_rates_correctly() {
     let pool_state: PoolState = test_env.fetch_account(pool_id).await;
     dbg!(4, pool_state.token_0_vault_amount, pool_state.token_1_vault_amount);
 
-    assert_eq_with_copy!(
-        pool_state.partners[0].lp_token_linked_with_partner,
+    let pool_partner: PoolPartner = test_env.fetch_account(pool_partner_id).await;
+    assert_eq!(
+        pool_partner.lp_token_linked_with_partner,
         lp_deposit_amount - withdraw_amount
     );
-    assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_0,
-        0
-    );
-    assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_1,
-        (pool_state.partners[0].lp_token_linked_with_partner * pool_state.protocol_fees_token_1)
-            / pool_state.lp_supply
-    );
+    assert_cumulative_fees(&pool_partner, &pool_state);
 
     test_env
         .swap_base_input(
@@ -220,20 +194,12 @@ async fn should_track_cumulative_rates_correctly() {
     let pool_state: PoolState = test_env.fetch_account(pool_id).await;
     dbg!(5, pool_state.token_0_vault_amount, pool_state.token_1_vault_amount);
 
-    assert_eq_with_copy!(
-        pool_state.partners[0].lp_token_linked_with_partner,
+    let pool_partner: PoolPartner = test_env.fetch_account(pool_partner_id).await;
+    assert_eq!(
+        pool_partner.lp_token_linked_with_partner,
         lp_deposit_amount - withdraw_amount
     );
-    assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_0,
-        (pool_state.partners[0].lp_token_linked_with_partner * pool_state.protocol_fees_token_0)
-            / pool_state.lp_supply
-    );
-    assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_1,
-        (pool_state.partners[0].lp_token_linked_with_partner * pool_state.protocol_fees_token_1)
-            / pool_state.lp_supply
-    );
+    assert_cumulative_fees(&pool_partner, &pool_state);
 
     // swap base output
 
@@ -251,47 +217,544 @@ async fn should_track_cumulative_rates_correctly() {
     let pool_state: PoolState = test_env.fetch_account(pool_id).await;
     dbg!(6, pool_state.token_0_vault_amount, pool_state.token_1_vault_amount);
 
-    assert_eq_with_copy!(
-        pool_state.partners[0].lp_token_linked_with_partner,
+    let pool_partner: PoolPartner = test_env.fetch_account(pool_partner_id).await;
+    assert_eq!(
+        pool_partner.lp_token_linked_with_partner,
+        lp_deposit_amount - withdraw_amount
+    );
+    assert_cumulative_fees(&pool_partner, &pool_state);
+
+    test_env
+        .swap_base_output(
+            &user,
+            pool_id,
+            amm_index,
+            1000000000,
+            u64::MAX,
+            TradeDirection::ZeroForOne,
+        )
+        .await;
+
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    dbg!(7, pool_state.token_0_vault_amount, pool_state.token_1_vault_amount);
+
+    let pool_partner: PoolPartner = test_env.fetch_account(pool_partner_id).await;
+    assert_eq!(
+        pool_partner.lp_token_linked_with_partner,
         lp_deposit_amount - withdraw_amount
     );
+    assert_cumulative_fees(&pool_partner, &pool_state);
+
+    // claim the revenue share of the partner
+    let payout_token_0_before: TokenAccount =
+        test_env.fetch_account(lp_depositor_asset_dash_token0).await;
+    let payout_token_1_before: TokenAccount =
+        test_env.fetch_account(lp_depositor_asset_dash_token1).await;
+    let pool_partner: PoolPartner = test_env.fetch_account(pool_partner_id).await;
+    let (cumulative_fee_token_0, cumulative_fee_token_1) =
+        pool_partner.cumulative_fees(&pool_state).unwrap();
+
+    test_env
+        .claim_partner_fees(&user, pool_id, partner_id, lp_depositor_asset_dash.pubkey())
+        .await;
+
+    let payout_token_0_after: TokenAccount =
+        test_env.fetch_account(lp_depositor_asset_dash_token0).await;
+    let payout_token_1_after: TokenAccount =
+        test_env.fetch_account(lp_depositor_asset_dash_token1).await;
+    let claimed_token_0 = payout_token_0_after.amount - payout_token_0_before.amount;
+    let claimed_token_1 = payout_token_1_after.amount - payout_token_1_before.amount;
+    assert_eq!(claimed_token_0, cumulative_fee_token_0 / 2);
+    assert_eq!(claimed_token_1, cumulative_fee_token_1 / 2);
+
+    let pool_partner: PoolPartner = test_env.fetch_account(pool_partner_id).await;
+    assert_eq!(pool_partner.fees_paid_token_0, claimed_token_0);
+    assert_eq!(pool_partner.fees_paid_token_1, claimed_token_1);
+    assert_eq!(
+        pool_partner.cumulative_fee_settled_token_0,
+        cumulative_fee_token_0
+    );
+    assert_eq!(
+        pool_partner.cumulative_fee_settled_token_1,
+        cumulative_fee_token_1
+    );
+
+    let claimed_pool_state: PoolState = test_env.fetch_account(pool_id).await;
     assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_0,
-        (pool_state.partners[0].lp_token_linked_with_partner * pool_state.protocol_fees_token_0)
-            / pool_state.lp_supply
+        claimed_pool_state.protocol_fees_token_0,
+        pool_state.protocol_fees_token_0 - claimed_token_0
     );
     assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_1,
-        (pool_state.partners[0].lp_token_linked_with_partner * pool_state.protocol_fees_token_1)
-            / pool_state.lp_supply
+        claimed_pool_state.protocol_fees_token_1,
+        pool_state.protocol_fees_token_1 - claimed_token_1
     );
+    // Only the rounding of the tvl share set aside is left for the partners
+    assert_eq_with_copy!(claimed_pool_state.partner_fees_owed_token_0, 0);
+    assert_eq_with_copy!(claimed_pool_state.partner_fees_owed_token_1, 0);
+    assert!(claimed_pool_state.partner_fees_token_0 <= 4);
+    assert!(claimed_pool_state.partner_fees_token_1 <= 4);
 
+    // nothing more to pay until the next swaps
     test_env
-        .swap_base_output(
+        .claim_partner_fees(&user, pool_id, partner_id, lp_depositor_asset_dash.pubkey())
+        .await;
+    let pool_partner: PoolPartner = test_env.fetch_account(pool_partner_id).await;
+    assert_eq!(pool_partner.fees_paid_token_0, claimed_token_0);
+    assert_eq!(pool_partner.fees_paid_token_1, claimed_token_1);
+
+    // the rounding is released to the protocol once no LP is linked with a partner
+    test_env
+        .withdraw(
+            &lp_depositor_asset_dash,
+            pool_id,
+            amm_index,
+            lp_deposit_amount - withdraw_amount,
+            0,
+            0,
+        )
+        .await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.lp_token_linked_with_partners, 0);
+    assert_eq_with_copy!(pool_state.partner_fees_token_0, 0);
+    assert_eq_with_copy!(pool_state.partner_fees_token_1, 0);
+    assert_eq!(
+        pool_state.collectable_protocol_fees().unwrap(),
+        (
+            pool_state.protocol_fees_token_0,
+            pool_state.protocol_fees_token_1
+        )
+    );
+}
+
+#[tokio::test]
+async fn should_pay_accrued_fees_at_the_rate_they_accrued_at() {
+    let user = Keypair::new();
+    let lp_depositor = Keypair::new();
+    let payout_wallet = Keypair::new();
+
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![
+        user.pubkey(),
+        lp_depositor.pubkey(),
+        payout_wallet.pubkey(),
+        admin.pubkey(),
+    ])
+    .await;
+
+    test_env
+        .create_config(&admin, amm_index, 3000, 2000, 50, 0)
+        .await;
+
+    for owner in [&user, &lp_depositor] {
+        let token_0_account = test_env
+            .get_or_create_associated_token_account(owner.pubkey(), test_env.token_0_mint, owner)
+            .await;
+        test_env
+            .mint_base_tokens(token_0_account, 100000000000000, test_env.token_0_mint)
+            .await;
+        let token_1_account = test_env
+            .get_or_create_associated_token_account(owner.pubkey(), test_env.token_1_mint, owner)
+            .await;
+        test_env
+            .mint_base_tokens(token_1_account, 100000000000000, test_env.token_1_mint)
+            .await;
+    }
+    let payout_token_0 = test_env
+        .get_or_create_associated_token_account(
+            payout_wallet.pubkey(),
+            test_env.token_0_mint,
+            &payout_wallet,
+        )
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
             &user,
+            amm_index,
+            200000000,
+            100000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    test_env.jump_seconds(100).await;
+
+    let partner_id = 1;
+    test_env
+        .create_partner(
+            &admin,
+            partner_id,
+            "Partner",
+            payout_wallet.pubkey(),
+            500_000,
+        )
+        .await;
+    let pool_partner_id = test_env.init_pool_partner(&user, pool_id, partner_id).await;
+    test_env
+        .init_user_pool_liquidity_with_partner(&lp_depositor, pool_id, Some(partner_id))
+        .await;
+    test_env
+        .deposit(
+            &lp_depositor,
             pool_id,
             amm_index,
-            1000000000,
+            200000000,
+            u64::MAX,
             u64::MAX,
+        )
+        .await;
+
+    test_env
+        .swap_base_input(
+            &user,
+            pool_id,
+            amm_index,
+            1000000000,
+            0,
             TradeDirection::ZeroForOne,
         )
         .await;
 
+    // the fees accrued before the rate change are paid at the previous rate
+    test_env
+        .update_partner(
+            &admin,
+            partner_id,
+            "Partner",
+            payout_wallet.pubkey(),
+            100_000,
+        )
+        .await;
     let pool_state: PoolState = test_env.fetch_account(pool_id).await;
-    dbg!(7, pool_state.token_0_vault_amount, pool_state.token_1_vault_amount);
+    let pool_partner: PoolPartner = test_env.fetch_account(pool_partner_id).await;
+    let (accrued_token_0, _) = pool_partner.cumulative_fees(&pool_state).unwrap();
+    assert!(accrued_token_0 > 0);
+    test_env
+        .claim_partner_fees(&user, pool_id, partner_id, payout_wallet.pubkey())
+        .await;
+    let payout: TokenAccount = test_env.fetch_account(payout_token_0).await;
+    assert_eq!(payout.amount, accrued_token_0 / 2);
+    let pool_partner: PoolPartner = test_env.fetch_account(pool_partner_id).await;
+    assert_eq!(pool_partner.revenue_share_rate, 100_000);
+
+    // the fees accrued after the claim are paid at the new rate
+    test_env
+        .swap_base_input(
+            &user,
+            pool_id,
+            amm_index,
+            1000000000,
+            0,
+            TradeDirection::ZeroForOne,
+        )
+        .await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    let (cumulative_fee_token_0, _) = pool_partner.cumulative_fees(&pool_state).unwrap();
+    let accrued_token_0 = cumulative_fee_token_0 - pool_partner.cumulative_fee_settled_token_0;
+    test_env
+        .claim_partner_fees(&user, pool_id, partner_id, payout_wallet.pubkey())
+        .await;
+    let payout_after: TokenAccount = test_env.fetch_account(payout_token_0).await;
+    assert_eq!(payout_after.amount - payout.amount, accrued_token_0 / 10);
+}
+
+#[tokio::test]
+async fn should_move_lp_between_partners() {
+    let user = Keypair::new();
+    let lp_depositor = Keypair::new();
+
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env =
+        TestEnv::new(vec![user.pubkey(), lp_depositor.pubkey(), admin.pubkey()]).await;
+
+    test_env
+        .create_config(&admin, amm_index, 3000, 2000, 50, 0)
+        .await;
+
+    for owner in [&user, &lp_depositor] {
+        let token_0_account = test_env
+            .get_or_create_associated_token_account(owner.pubkey(), test_env.token_0_mint, owner)
+            .await;
+        test_env
+            .mint_base_tokens(token_0_account, 100000000000000, test_env.token_0_mint)
+            .await;
+        let token_1_account = test_env
+            .get_or_create_associated_token_account(owner.pubkey(), test_env.token_1_mint, owner)
+            .await;
+        test_env
+            .mint_base_tokens(token_1_account, 1000000000000000, test_env.token_1_mint)
+            .await;
+    }
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            200000000,
+            100000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    test_env.jump_seconds(100).await;
+
+    let (first_partner_id, second_partner_id) = (1, 2);
+    for partner_id in [first_partner_id, second_partner_id] {
+        test_env
+            .create_partner(&admin, partner_id, "Partner", admin.pubkey(), 500_000)
+            .await;
+        test_env.init_pool_partner(&user, pool_id, partner_id).await;
+    }
+
+    test_env
+        .init_user_pool_liquidity_with_partner(&lp_depositor, pool_id, Some(first_partner_id))
+        .await;
+    let lp_deposit_amount = 200000000;
+    test_env
+        .deposit(
+            &lp_depositor,
+            pool_id,
+            amm_index,
+            lp_deposit_amount,
+            u64::MAX,
+            u64::MAX,
+        )
+        .await;
+
+    test_env
+        .set_position_partner(
+            &lp_depositor,
+            pool_id,
+            Some(first_partner_id),
+            Some(second_partner_id),
+        )
+        .await;
+
+    let first_pool_partner: PoolPartner = test_env
+        .fetch_account(PoolPartner::find_address(pool_id, first_partner_id))
+        .await;
+    let second_pool_partner: PoolPartner = test_env
+        .fetch_account(PoolPartner::find_address(pool_id, second_partner_id))
+        .await;
+    assert_eq!(first_pool_partner.lp_token_linked_with_partner, 0);
+    assert_eq!(
+        second_pool_partner.lp_token_linked_with_partner,
+        lp_deposit_amount
+    );
+
+    test_env
+        .set_position_partner(&lp_depositor, pool_id, Some(second_partner_id), None)
+        .await;
+
+    let second_pool_partner: PoolPartner = test_env
+        .fetch_account(PoolPartner::find_address(pool_id, second_partner_id))
+        .await;
+    assert_eq!(second_pool_partner.lp_token_linked_with_partner, 0);
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.lp_token_linked_with_partners, 0);
+}
+
+#[tokio::test]
+async fn should_withdraw_legacy_partner_lp_before_pool_partner_exists() {
+    let user = Keypair::new();
+    let lp_depositor = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env =
+        TestEnv::new(vec![user.pubkey(), lp_depositor.pubkey(), admin.pubkey()]).await;
+    test_env
+        .create_config(&admin, amm_index, 3000, 2000, 50, 0)
+        .await;
+
+    for owner in [&user, &lp_depositor] {
+        let token_0_account = test_env
+            .get_or_create_associated_token_account(owner.pubkey(), test_env.token_0_mint, owner)
+            .await;
+        test_env
+            .mint_base_tokens(token_0_account, 100000000000000, test_env.token_0_mint)
+            .await;
+        let token_1_account = test_env
+            .get_or_create_associated_token_account(owner.pubkey(), test_env.token_1_mint, owner)
+            .await;
+        test_env
+            .mint_base_tokens(token_1_account, 1000000000000000, test_env.token_1_mint)
+            .await;
+    }
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            200000000,
+            100000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    test_env.jump_seconds(100).await;
+
+    let partner_id = 0;
+    test_env
+        .create_partner(&admin, partner_id, "AssetDash", admin.pubkey(), 500_000)
+        .await;
+    test_env
+        .init_user_pool_liquidity_with_partner(&lp_depositor, pool_id, Some(partner_id))
+        .await;
 
+    // Without a PoolPartner the LP of the legacy partner is kept in the pool, the PoolPartner
+    // address does not have to be passed then
+    let lp_deposit_amount = 200000000;
+    test_env
+        .deposit(
+            &lp_depositor,
+            pool_id,
+            amm_index,
+            lp_deposit_amount,
+            u64::MAX,
+            u64::MAX,
+        )
+        .await;
+    let withdraw_amount = 50000000;
+    test_env
+        .withdraw(&lp_depositor, pool_id, amm_index, withdraw_amount, 0, 0)
+        .await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
     assert_eq_with_copy!(
         pool_state.partners[0].lp_token_linked_with_partner,
         lp_deposit_amount - withdraw_amount
     );
-    assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_0,
-        (pool_state.partners[0].lp_token_linked_with_partner * pool_state.protocol_fees_token_0)
-            / pool_state.lp_supply
+
+    // And moved to the PoolPartner once it is created
+    let pool_partner_id = test_env.init_pool_partner(&user, pool_id, partner_id).await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.pool_partners_created, 1);
+    let pool_partner: PoolPartner = test_env.fetch_account(pool_partner_id).await;
+    assert_eq!(
+        pool_partner.lp_token_linked_with_partner,
+        lp_deposit_amount - withdraw_amount
+    );
+    test_env
+        .withdraw(&lp_depositor, pool_id, amm_index, withdraw_amount, 0, 0)
+        .await;
+    let pool_partner: PoolPartner = test_env.fetch_account(pool_partner_id).await;
+    assert_eq!(
+        pool_partner.lp_token_linked_with_partner,
+        lp_deposit_amount - 2 * withdraw_amount
     );
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.partners[0].lp_token_linked_with_partner, 0);
+}
+
+#[tokio::test]
+async fn should_move_legacy_partner_lp_before_pool_partner_exists() {
+    let user = Keypair::new();
+    let lp_depositor = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env =
+        TestEnv::new(vec![user.pubkey(), lp_depositor.pubkey(), admin.pubkey()]).await;
+    test_env
+        .create_config(&admin, amm_index, 3000, 2000, 50, 0)
+        .await;
+
+    for owner in [&user, &lp_depositor] {
+        let token_0_account = test_env
+            .get_or_create_associated_token_account(owner.pubkey(), test_env.token_0_mint, owner)
+            .await;
+        test_env
+            .mint_base_tokens(token_0_account, 100000000000000, test_env.token_0_mint)
+            .await;
+        let token_1_account = test_env
+            .get_or_create_associated_token_account(owner.pubkey(), test_env.token_1_mint, owner)
+            .await;
+        test_env
+            .mint_base_tokens(token_1_account, 1000000000000000, test_env.token_1_mint)
+            .await;
+    }
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            200000000,
+            100000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    test_env.jump_seconds(100).await;
+
+    let (legacy_partner_id, partner_id) = (0, 1);
+    test_env
+        .create_partner(
+            &admin,
+            legacy_partner_id,
+            "AssetDash",
+            admin.pubkey(),
+            500_000,
+        )
+        .await;
+    test_env
+        .create_partner(&admin, partner_id, "Partner", admin.pubkey(), 500_000)
+        .await;
+    let pool_partner_id = test_env.init_pool_partner(&user, pool_id, partner_id).await;
+    test_env
+        .init_user_pool_liquidity_with_partner(&lp_depositor, pool_id, Some(legacy_partner_id))
+        .await;
+    let lp_deposit_amount = 200000000;
+    test_env
+        .deposit(
+            &lp_depositor,
+            pool_id,
+            amm_index,
+            lp_deposit_amount,
+            u64::MAX,
+            u64::MAX,
+        )
+        .await;
+
+    // The LP of the legacy partner is taken from the pool while it has no PoolPartner
+    test_env
+        .set_position_partner(
+            &lp_depositor,
+            pool_id,
+            Some(legacy_partner_id),
+            Some(partner_id),
+        )
+        .await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.partners[0].lp_token_linked_with_partner, 0);
+    let pool_partner: PoolPartner = test_env.fetch_account(pool_partner_id).await;
+    assert_eq!(pool_partner.lp_token_linked_with_partner, lp_deposit_amount);
+
+    // And given back to the pool
+    test_env
+        .set_position_partner(
+            &lp_depositor,
+            pool_id,
+            Some(partner_id),
+            Some(legacy_partner_id),
+        )
+        .await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
     assert_eq_with_copy!(
-        pool_state.partners[0].cumulative_fee_total_times_tvl_share_token_1,
-        (pool_state.partners[0].lp_token_linked_with_partner * pool_state.protocol_fees_token_1)
-            / pool_state.lp_supply
+        pool_state.partners[0].lp_token_linked_with_partner,
+        lp_deposit_amount
     );
+    let pool_partner: PoolPartner = test_env.fetch_account(pool_partner_id).await;
+    assert_eq!(pool_partner.lp_token_linked_with_partner, 0);
+}
+
+/// The cumulative fees of the partner are its tvl share of the protocol fees, less the rounding
+/// of the fee growth
+fn assert_cumulative_fees(pool_partner: &PoolPartner, pool_state: &PoolState) {
+    let (cumulative_fee_token_0, cumulative_fee_token_1) =
+        pool_partner.cumulative_fees(pool_state).unwrap();
+    let lp_linked = pool_partner.lp_token_linked_with_partner;
+    let expected_token_0 = lp_linked * pool_state.protocol_fees_token_0 / pool_state.lp_supply;
+    let expected_token_1 = lp_linked * pool_state.protocol_fees_token_1 / pool_state.lp_supply;
+    assert!(expected_token_0 - cumulative_fee_token_0 <= 1);
+    assert!(expected_token_1 - cumulative_fee_token_1 <= 1);
 }
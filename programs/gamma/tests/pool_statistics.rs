@@ -0,0 +1,139 @@
+use gamma::curve::TradeDirection;
+use gamma::states::{PoolState, PoolStatistics};
+use solana_program_test::tokio;
+use solana_sdk::{instruction::AccountMeta, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+#[tokio::test]
+async fn should_record_swaps_when_the_statistics_account_is_passed() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+
+    test_env
+        .create_config(&admin, amm_index, 3000, 2000, 50, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 100000000000000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 100000000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    test_env.jump_seconds(100).await;
+
+    // The statistics account is optional, with or without its address before it is created
+    test_env
+        .swap_base_input(
+            &user,
+            pool_id,
+            amm_index,
+            1000000,
+            0,
+            TradeDirection::ZeroForOne,
+        )
+        .await;
+    let remaining_accounts_without_statistics =
+        vec![AccountMeta::new_readonly(gamma::id(), false); 5];
+    test_env
+        .try_swap_base_input_with_remaining_accounts(
+            &user,
+            pool_id,
+            amm_index,
+            1000000,
+            0,
+            TradeDirection::ZeroForOne,
+            remaining_accounts_without_statistics.clone(),
+        )
+        .await
+        .unwrap();
+
+    // Another account in its place is rejected
+    let mut remaining_accounts_with_other_account =
+        vec![AccountMeta::new_readonly(gamma::id(), false); 4];
+    remaining_accounts_with_other_account.push(AccountMeta::new(user.pubkey(), false));
+    assert!(test_env
+        .try_swap_base_input_with_remaining_accounts(
+            &user,
+            pool_id,
+            amm_index,
+            1000000,
+            0,
+            TradeDirection::ZeroForOne,
+            remaining_accounts_with_other_account,
+        )
+        .await
+        .is_err());
+
+    let pool_statistics_id = test_env.init_pool_statistics(&user, pool_id).await;
+    test_env
+        .swap_base_input(
+            &user,
+            pool_id,
+            amm_index,
+            1000000,
+            0,
+            TradeDirection::ZeroForOne,
+        )
+        .await;
+    test_env
+        .swap_base_input(
+            &user,
+            pool_id,
+            amm_index,
+            2000000,
+            0,
+            TradeDirection::OneForZero,
+        )
+        .await;
+
+    let pool_statistics: PoolStatistics = test_env.fetch_account(pool_statistics_id).await;
+    let buckets = pool_statistics.ordered_buckets();
+    assert_eq!(buckets.len(), 1);
+    let bucket = buckets[0];
+    assert_eq_with_copy!(bucket.swap_count, 2);
+    assert!({ bucket.volume_token_0 } > 1000000);
+    assert!({ bucket.volume_token_1 } > 2000000);
+    assert!({ bucket.protocol_fees_token_0 } > 0);
+    assert!({ bucket.protocol_fees_token_1 } > 0);
+
+    // Once created the statistics account can't be omitted, no swap goes uncounted
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.pool_statistics_enabled, 1);
+    for remaining_accounts in [remaining_accounts_without_statistics, vec![]] {
+        assert!(test_env
+            .try_swap_base_input_with_remaining_accounts(
+                &user,
+                pool_id,
+                amm_index,
+                1000000,
+                0,
+                TradeDirection::OneForZero,
+                remaining_accounts,
+            )
+            .await
+            .is_err());
+    }
+    let pool_statistics: PoolStatistics = test_env.fetch_account(pool_statistics_id).await;
+    assert_eq_with_copy!(pool_statistics.ordered_buckets()[0].swap_count, 2);
+}
@@ -0,0 +1,287 @@
+use anchor_spl::token_interface::TokenAccount;
+use gamma::{
+    states::{OperatorPermission, UserPoolLiquidity, UserRewardInfo},
+    REWARD_INFO_SEED, USER_REWARD_INFO_SEED,
+};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+#[tokio::test]
+async fn should_deposit_withdraw_and_claim_for_the_owner_with_an_operator() {
+    let user = Keypair::new();
+    let lp_depositor = Keypair::new();
+    let operator = Keypair::new();
+    let reward_provider = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![
+        user.pubkey(),
+        lp_depositor.pubkey(),
+        operator.pubkey(),
+        reward_provider.pubkey(),
+        admin.pubkey(),
+    ])
+    .await;
+
+    test_env
+        .create_config(&admin, amm_index, 3000, 2000, 50, 0)
+        .await;
+
+    for signer in [&user, &lp_depositor, &operator] {
+        for mint in [test_env.token_0_mint, test_env.token_1_mint] {
+            let token_account = test_env
+                .get_or_create_associated_token_account(signer.pubkey(), mint, signer)
+                .await;
+            test_env
+                .mint_base_tokens(token_account, 100000000000000, mint)
+                .await;
+        }
+    }
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            200000000,
+            100000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    test_env.jump_seconds(100).await;
+
+    test_env
+        .init_user_pool_liquidity(&lp_depositor, pool_id)
+        .await;
+    let lp_amount = 100000000;
+    test_env
+        .deposit(
+            &lp_depositor,
+            pool_id,
+            amm_index,
+            lp_amount,
+            u64::MAX,
+            u64::MAX,
+        )
+        .await;
+    let user_pool_liquidity_id = Pubkey::find_program_address(
+        &[
+            gamma::states::USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_id.to_bytes().as_ref(),
+            lp_depositor.pubkey().to_bytes().as_ref(),
+        ],
+        &gamma::id(),
+    )
+    .0;
+
+    let reward_mint = Keypair::new();
+    test_env
+        .create_token_mint(&reward_mint, &test_env.mint_authority.pubkey(), 9)
+        .await;
+    let reward_provider_token_account = test_env
+        .get_or_create_associated_token_account(
+            reward_provider.pubkey(),
+            reward_mint.pubkey(),
+            &reward_provider,
+        )
+        .await;
+    let reward_amount = 1000000000;
+    test_env
+        .mint_base_tokens(
+            reward_provider_token_account,
+            reward_amount,
+            reward_mint.pubkey(),
+        )
+        .await;
+    let timestamp_now = test_env.timestamp_now().await as u64;
+    let start_time = timestamp_now + 10;
+    let end_time = timestamp_now + 3000;
+    test_env
+        .create_rewards(
+            &reward_provider,
+            pool_id,
+            start_time,
+            end_time,
+            reward_mint.pubkey(),
+            reward_amount,
+        )
+        .await;
+    let (reward_info_key, _) = Pubkey::find_program_address(
+        &[
+            REWARD_INFO_SEED.as_bytes(),
+            pool_id.to_bytes().as_ref(),
+            &start_time.to_le_bytes(),
+            reward_mint.pubkey().to_bytes().as_ref(),
+        ],
+        &gamma::id(),
+    );
+    test_env
+        .calculate_rewards(&lp_depositor, pool_id, reward_info_key)
+        .await;
+
+    // Without an approval the operator can not act on the position
+    assert!(test_env
+        .try_deposit_for(
+            &operator,
+            lp_depositor.pubkey(),
+            pool_id,
+            amm_index,
+            lp_amount,
+            u64::MAX,
+            u64::MAX,
+        )
+        .await
+        .is_err());
+
+    let expires_at = timestamp_now + 100000;
+    test_env
+        .set_position_operator(
+            &lp_depositor,
+            pool_id,
+            operator.pubkey(),
+            OperatorPermission::Deposit.mask() | OperatorPermission::ClaimRewards.mask(),
+            expires_at,
+        )
+        .await;
+
+    // The operator deposits its own tokens into the position of the owner
+    let operator_token_0_account = test_env
+        .get_or_create_associated_token_account(operator.pubkey(), test_env.token_0_mint, &operator)
+        .await;
+    let operator_token_0_before: TokenAccount =
+        test_env.fetch_account(operator_token_0_account).await;
+    test_env
+        .try_deposit_for(
+            &operator,
+            lp_depositor.pubkey(),
+            pool_id,
+            amm_index,
+            lp_amount,
+            u64::MAX,
+            u64::MAX,
+        )
+        .await
+        .unwrap();
+    let operator_token_0_after: TokenAccount =
+        test_env.fetch_account(operator_token_0_account).await;
+    assert!(operator_token_0_after.amount < operator_token_0_before.amount);
+    let user_pool_liquidity: UserPoolLiquidity =
+        test_env.fetch_account(user_pool_liquidity_id).await;
+    assert_eq!(user_pool_liquidity.lp_tokens_owned, 2 * lp_amount as u128);
+
+    // Withdrawing needs its own permission
+    assert!(test_env
+        .try_withdraw_for(
+            &operator,
+            lp_depositor.pubkey(),
+            pool_id,
+            amm_index,
+            lp_amount,
+            0,
+            0,
+        )
+        .await
+        .is_err());
+    test_env
+        .set_position_operator(
+            &lp_depositor,
+            pool_id,
+            operator.pubkey(),
+            OperatorPermission::ALL_MASK,
+            expires_at,
+        )
+        .await;
+
+    // The withdrawn tokens go to the owner, not to the operator
+    let owner_token_0_account = test_env
+        .get_or_create_associated_token_account(
+            lp_depositor.pubkey(),
+            test_env.token_0_mint,
+            &lp_depositor,
+        )
+        .await;
+    let owner_token_0_before: TokenAccount = test_env.fetch_account(owner_token_0_account).await;
+    test_env
+        .try_withdraw_for(
+            &operator,
+            lp_depositor.pubkey(),
+            pool_id,
+            amm_index,
+            lp_amount,
+            0,
+            0,
+        )
+        .await
+        .unwrap();
+    let owner_token_0_after: TokenAccount = test_env.fetch_account(owner_token_0_account).await;
+    assert!(owner_token_0_after.amount > owner_token_0_before.amount);
+    let operator_token_0_after_withdraw: TokenAccount =
+        test_env.fetch_account(operator_token_0_account).await;
+    assert_eq!(
+        operator_token_0_after_withdraw.amount,
+        operator_token_0_after.amount
+    );
+    let user_pool_liquidity: UserPoolLiquidity =
+        test_env.fetch_account(user_pool_liquidity_id).await;
+    assert_eq!(user_pool_liquidity.lp_tokens_owned, lp_amount as u128);
+
+    // The claimed rewards go to the owner as well
+    test_env.jump_seconds(5000).await;
+    test_env
+        .calculate_rewards(&lp_depositor, pool_id, reward_info_key)
+        .await;
+    test_env
+        .try_claim_rewards_for(
+            &operator,
+            lp_depositor.pubkey(),
+            pool_id,
+            reward_info_key,
+            reward_mint.pubkey(),
+        )
+        .await
+        .unwrap();
+    let (user_reward_info_key, _) = Pubkey::find_program_address(
+        &[
+            USER_REWARD_INFO_SEED.as_bytes(),
+            reward_info_key.to_bytes().as_ref(),
+            lp_depositor.pubkey().to_bytes().as_ref(),
+        ],
+        &gamma::id(),
+    );
+    let user_reward_info: UserRewardInfo = test_env.fetch_account(user_reward_info_key).await;
+    assert!(user_reward_info.total_claimed > 0);
+    assert_eq!(
+        user_reward_info.total_claimed,
+        user_reward_info.total_rewards
+    );
+    let owner_reward_account = test_env
+        .get_or_create_associated_token_account(
+            lp_depositor.pubkey(),
+            reward_mint.pubkey(),
+            &lp_depositor,
+        )
+        .await;
+    let owner_reward_account: TokenAccount = test_env.fetch_account(owner_reward_account).await;
+    assert_eq!(owner_reward_account.amount, user_reward_info.total_claimed);
+
+    // The approval stops working once it expires
+    test_env.jump_seconds(100000).await;
+    assert!(test_env
+        .try_withdraw_for(
+            &operator,
+            lp_depositor.pubkey(),
+            pool_id,
+            amm_index,
+            lp_amount,
+            0,
+            0,
+        )
+        .await
+        .is_err());
+    test_env
+        .withdraw(&lp_depositor, pool_id, amm_index, lp_amount, 0, 0)
+        .await;
+}
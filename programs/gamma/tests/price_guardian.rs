@@ -0,0 +1,165 @@
+use anchor_spl::token::TokenAccount;
+use gamma::curve::TradeDirection;
+use gamma::states::{PoolState, PoolStatusBitIndex};
+use solana_program_test::tokio;
+use solana_sdk::{signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+#[tokio::test]
+async fn should_halt_swap_direction_after_executing_the_deviating_swap() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+
+    test_env
+        .create_config(&admin, amm_index, 3000, 2000, 50, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 100000000000000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 100000000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+
+    // Small swaps build observations around the initial price
+    for _ in 0..3 {
+        test_env.jump_seconds(20).await;
+        test_env
+            .swap_base_input(
+                &user,
+                pool_id,
+                amm_index,
+                1000,
+                0,
+                TradeDirection::ZeroForOne,
+            )
+            .await;
+    }
+
+    // 5% deviation from the TWAP trips the guardian
+    test_env
+        .update_pool(&admin, pool_id, amm_index, 6, 50_000)
+        .await;
+    // Deposits disabled by the admin
+    test_env
+        .update_pool(
+            &admin,
+            pool_id,
+            amm_index,
+            0,
+            1 << PoolStatusBitIndex::Deposit as u8,
+        )
+        .await;
+
+    let pool_state_before: PoolState = test_env.fetch_account(pool_id).await;
+    let user_token_0_before: TokenAccount = test_env.fetch_account(user_token_0_account).await;
+    let user_token_1_before: TokenAccount = test_env.fetch_account(user_token_1_account).await;
+
+    test_env.jump_seconds(20).await;
+    // The slippage checks still apply to the swap that trips the guardian, a failed swap halts
+    // nothing
+    assert!(test_env
+        .try_swap_base_input(
+            &user,
+            pool_id,
+            amm_index,
+            200000000,
+            u64::MAX,
+            TradeDirection::ZeroForOne,
+        )
+        .await
+        .is_err());
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.guardian_status, 0);
+
+    test_env
+        .swap_base_input(
+            &user,
+            pool_id,
+            amm_index,
+            200000000,
+            0,
+            TradeDirection::ZeroForOne,
+        )
+        .await;
+
+    // The swap that tripped the guardian was executed
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    let user_token_0_after: TokenAccount = test_env.fetch_account(user_token_0_account).await;
+    let user_token_1_after: TokenAccount = test_env.fetch_account(user_token_1_account).await;
+    assert_eq!(
+        user_token_0_after.amount,
+        user_token_0_before.amount - 200000000
+    );
+    assert!(user_token_1_after.amount > user_token_1_before.amount);
+    assert!({ pool_state.token_0_vault_amount } > { pool_state_before.token_0_vault_amount });
+    assert!({ pool_state.token_1_vault_amount } < { pool_state_before.token_1_vault_amount });
+    assert_eq_with_copy!(
+        pool_state.guardian_status,
+        1 << PoolStatusBitIndex::SwapZeroForOne as u8
+    );
+    assert_eq_with_copy!(pool_state.status, pool_state_before.status);
+    assert!(!pool_state.get_status_by_bit(PoolStatusBitIndex::SwapZeroForOne));
+
+    // The halted direction is rejected, the other one still trades
+    assert!(test_env
+        .try_swap_base_input(
+            &user,
+            pool_id,
+            amm_index,
+            1000,
+            0,
+            TradeDirection::ZeroForOne,
+        )
+        .await
+        .is_err());
+    test_env
+        .swap_base_input(
+            &user,
+            pool_id,
+            amm_index,
+            1000,
+            0,
+            TradeDirection::OneForZero,
+        )
+        .await;
+
+    // The halt is lifted after the cooldown, the admin status is left as it was
+    test_env.jump_seconds(301).await;
+    test_env
+        .swap_base_input(
+            &user,
+            pool_id,
+            amm_index,
+            1000,
+            0,
+            TradeDirection::ZeroForOne,
+        )
+        .await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.guardian_status, 0);
+    assert_eq_with_copy!(pool_state.guardian_halted_until, 0);
+    assert_eq_with_copy!(pool_state.status, pool_state_before.status);
+    assert!(!pool_state.get_status_by_bit(PoolStatusBitIndex::Deposit));
+}
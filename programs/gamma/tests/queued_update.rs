@@ -0,0 +1,285 @@
+use gamma::error::GammaError;
+use gamma::states::{
+    AmmConfig, Capability, ConfigUpdate, PoolState, PoolUpdate, QueuedAction, QueuedUpdate,
+    QueuedUpdateEvent, AMM_CONFIG_SEED,
+};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+const TIMELOCK_DELAY: u64 = 3600;
+
+fn amm_config_address(amm_index: u16) -> Pubkey {
+    Pubkey::find_program_address(
+        &[AMM_CONFIG_SEED.as_bytes(), &amm_index.to_be_bytes()],
+        &gamma::id(),
+    )
+    .0
+}
+
+async fn setup_timelocked_config(test_env: &mut TestEnv, admin: &Keypair, amm_index: u16) {
+    test_env
+        .create_config(admin, amm_index, 3000, 2000, 50, 0)
+        .await;
+    test_env
+        .apply_amm_config_update(
+            admin,
+            amm_index,
+            ConfigUpdate::TimelockDelay {
+                timelock_delay: TIMELOCK_DELAY,
+            },
+        )
+        .await;
+}
+
+#[tokio::test]
+async fn should_execute_queued_config_update_only_after_eta() {
+    let admin = get_admin();
+    let executor = Keypair::new();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![admin.pubkey(), executor.pubkey()]).await;
+    setup_timelocked_config(&mut test_env, &admin, amm_index).await;
+    let amm_config_id = amm_config_address(amm_index);
+
+    // With a timelock the update has to be queued
+    let update = ConfigUpdate::TradeFeeRate {
+        trade_fee_rate: 4000,
+    };
+    let result = test_env
+        .try_apply_amm_config_update(&admin, amm_index, update.clone())
+        .await;
+    assert_error!(result, GammaError::TimelockRequired);
+
+    let id = 0;
+    let queued_at = test_env.timestamp_now().await as u64;
+    let result = test_env
+        .try_queue_update(
+            &admin,
+            amm_index,
+            None,
+            id,
+            QueuedAction::Config(update.clone()),
+        )
+        .await;
+    result.unwrap_transaction();
+    let events: Vec<QueuedUpdateEvent> = decode_events(&result);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].status, 0);
+    let queued_update_id = queued_update_address(amm_config_id, id);
+    let queued_update: QueuedUpdate = test_env.fetch_account(queued_update_id).await;
+    assert_eq!(queued_update.proposer, admin.pubkey());
+    assert_eq!(queued_update.action, QueuedAction::Config(update));
+    assert!(queued_update.eta >= queued_at + TIMELOCK_DELAY);
+
+    let result = test_env
+        .try_execute_queued_update(&executor, amm_index, None, id, admin.pubkey())
+        .await;
+    assert_error!(result, GammaError::TimelockNotElapsed);
+
+    // Anyone can execute it once the eta has passed
+    test_env.jump_seconds(TIMELOCK_DELAY as i64).await;
+    let result = test_env
+        .try_execute_queued_update(&executor, amm_index, None, id, admin.pubkey())
+        .await;
+    result.unwrap_transaction();
+    let events: Vec<QueuedUpdateEvent> = decode_events(&result);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].status, 1);
+    let amm_config: AmmConfig = test_env.fetch_account(amm_config_id).await;
+    assert_eq!(amm_config.trade_fee_rate, 4000);
+    assert!(test_env
+        .try_fetch_account::<QueuedUpdate>(queued_update_id)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn should_execute_queued_pool_update_only_after_eta() {
+    let admin = get_admin();
+    let user = Keypair::new();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![admin.pubkey(), user.pubkey()]).await;
+    setup_timelocked_config(&mut test_env, &admin, amm_index).await;
+
+    let token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(token_0_account, 100000000000000, test_env.token_0_mint)
+        .await;
+    let token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(token_1_account, 100000000000000, test_env.token_1_mint)
+        .await;
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            200000000,
+            100000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+
+    let update = PoolUpdate::VolatilityFactor {
+        volatility_factor: 200000,
+    };
+    let result = test_env
+        .try_apply_pool_update(&admin, pool_id, amm_index, update.clone())
+        .await;
+    assert_error!(result, GammaError::TimelockRequired);
+
+    // A pool update needs the pool it applies to
+    let id = 0;
+    let result = test_env
+        .try_queue_update(
+            &admin,
+            amm_index,
+            None,
+            id,
+            QueuedAction::Pool(update.clone()),
+        )
+        .await;
+    assert_error!(result, GammaError::InvalidInput);
+    test_env
+        .try_queue_update(
+            &admin,
+            amm_index,
+            Some(pool_id),
+            id,
+            QueuedAction::Pool(update),
+        )
+        .await
+        .unwrap_transaction();
+
+    let result = test_env
+        .try_execute_queued_update(&user, amm_index, Some(pool_id), id, admin.pubkey())
+        .await;
+    assert_error!(result, GammaError::TimelockNotElapsed);
+
+    test_env.jump_seconds(TIMELOCK_DELAY as i64).await;
+    test_env
+        .try_execute_queued_update(&user, amm_index, Some(pool_id), id, admin.pubkey())
+        .await
+        .unwrap_transaction();
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.volatility_factor, 200000);
+
+    // Emergency updates bypass the timelock, lifting them does not
+    test_env
+        .try_apply_pool_update(
+            &admin,
+            pool_id,
+            amm_index,
+            PoolUpdate::Status { status: 1 << 2 },
+        )
+        .await
+        .unwrap_transaction();
+    let result = test_env
+        .try_apply_pool_update(&admin, pool_id, amm_index, PoolUpdate::Status { status: 0 })
+        .await;
+    assert_error!(result, GammaError::TimelockRequired);
+}
+
+#[tokio::test]
+async fn should_bypass_the_timelock_for_emergency_config_updates() {
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![admin.pubkey()]).await;
+    setup_timelocked_config(&mut test_env, &admin, amm_index).await;
+
+    test_env
+        .apply_amm_config_update(
+            &admin,
+            amm_index,
+            ConfigUpdate::DisableCreatePool {
+                disable_create_pool: true,
+            },
+        )
+        .await;
+    let amm_config: AmmConfig = test_env.fetch_account(amm_config_address(amm_index)).await;
+    assert!(amm_config.disable_create_pool);
+
+    let result = test_env
+        .try_apply_amm_config_update(
+            &admin,
+            amm_index,
+            ConfigUpdate::DisableCreatePool {
+                disable_create_pool: false,
+            },
+        )
+        .await;
+    assert_error!(result, GammaError::TimelockRequired);
+}
+
+#[tokio::test]
+async fn should_cancel_queued_update_by_proposer_or_capability_holder() {
+    let admin = get_admin();
+    let fee_manager = Keypair::new();
+    let pauser = Keypair::new();
+    let amm_index = 0;
+    let mut test_env =
+        TestEnv::new(vec![admin.pubkey(), fee_manager.pubkey(), pauser.pubkey()]).await;
+    setup_timelocked_config(&mut test_env, &admin, amm_index).await;
+    test_env.init_roles(&admin).await;
+    test_env
+        .try_set_capabilities(&admin, fee_manager.pubkey(), Capability::FeeManager.mask())
+        .await
+        .unwrap();
+    test_env
+        .try_set_capabilities(&admin, pauser.pubkey(), Capability::Pauser.mask())
+        .await
+        .unwrap();
+    let amm_config_id = amm_config_address(amm_index);
+
+    let update = QueuedAction::Config(ConfigUpdate::TradeFeeRate {
+        trade_fee_rate: 4000,
+    });
+    for id in [0, 1] {
+        test_env
+            .try_queue_update(&admin, amm_index, None, id, update.clone())
+            .await
+            .unwrap_transaction();
+    }
+
+    // A key without the capability of the update can not cancel it
+    let result = test_env
+        .try_cancel_queued_update(&pauser, amm_index, 0, admin.pubkey())
+        .await;
+    assert_error!(result, GammaError::InvalidOwner);
+
+    // Any key holding the capability can, not only the proposer
+    let result = test_env
+        .try_cancel_queued_update(&fee_manager, amm_index, 0, admin.pubkey())
+        .await;
+    result.unwrap_transaction();
+    let events: Vec<QueuedUpdateEvent> = decode_events(&result);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].status, 2);
+    assert_eq!(events[0].authority, fee_manager.pubkey());
+    test_env
+        .try_cancel_queued_update(&admin, amm_index, 1, admin.pubkey())
+        .await
+        .unwrap_transaction();
+
+    for id in [0, 1] {
+        assert!(test_env
+            .try_fetch_account::<QueuedUpdate>(queued_update_address(amm_config_id, id))
+            .await
+            .is_err());
+    }
+    test_env.jump_seconds(TIMELOCK_DELAY as i64).await;
+    assert!(test_env
+        .try_execute_queued_update(&admin, amm_index, None, 0, admin.pubkey())
+        .await
+        .unwrap()
+        .result
+        .is_err());
+    let amm_config: AmmConfig = test_env.fetch_account(amm_config_id).await;
+    assert_eq!(amm_config.trade_fee_rate, 3000);
+}
@@ -0,0 +1,353 @@
+use anchor_spl::token::TokenAccount;
+use gamma::curve::TradeDirection;
+use gamma::error::GammaError;
+use gamma::states::{
+    CollectFeeEvent, ConfigUpdate, MintEntryStatus, MintRegistryMode, PoolState,
+    RebindPoolConfigEvent, AMM_CONFIG_SEED,
+};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+fn amm_config_address(amm_index: u16) -> Pubkey {
+    Pubkey::find_program_address(
+        &[AMM_CONFIG_SEED.as_bytes(), &amm_index.to_be_bytes()],
+        &gamma::id(),
+    )
+    .0
+}
+
+async fn fund_user(test_env: &mut TestEnv, user: &Keypair) {
+    let token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, user)
+        .await;
+    test_env
+        .mint_base_tokens(token_0_account, 100000000000000, test_env.token_0_mint)
+        .await;
+    let token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, user)
+        .await;
+    test_env
+        .mint_base_tokens(token_1_account, 100000000000000, test_env.token_1_mint)
+        .await;
+}
+
+async fn setup_pool(test_env: &mut TestEnv, user: &Keypair) -> Pubkey {
+    fund_user(test_env, user).await;
+    let pool_id = test_env
+        .initialize_pool(
+            user,
+            0,
+            200000000,
+            100000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    test_env.jump_seconds(100).await;
+    pool_id
+}
+
+async fn token_balance(test_env: &mut TestEnv, owner: Pubkey, mint: Pubkey) -> u64 {
+    let token_account = test_env
+        .get_or_create_associated_token_account(owner, mint, &get_admin())
+        .await;
+    test_env
+        .fetch_account::<TokenAccount>(token_account)
+        .await
+        .amount
+}
+
+#[tokio::test]
+async fn should_settle_old_config_fees_and_keep_partner_fees_in_the_pool() {
+    let admin = get_admin();
+    let user = Keypair::new();
+    let depositor = Keypair::new();
+    let protocol_owner = Keypair::new();
+    let fund_owner = Keypair::new();
+    let mut test_env = TestEnv::new(vec![
+        admin.pubkey(),
+        user.pubkey(),
+        depositor.pubkey(),
+        protocol_owner.pubkey(),
+        fund_owner.pubkey(),
+    ])
+    .await;
+    test_env.create_config(&admin, 0, 3000, 2000, 50, 0).await;
+    test_env.create_config(&admin, 1, 3000, 2000, 50, 0).await;
+
+    // The old config is owned by other keys than the new one
+    test_env
+        .apply_amm_config_update(
+            &admin,
+            0,
+            ConfigUpdate::ProtocolOwner {
+                protocol_owner: protocol_owner.pubkey(),
+            },
+        )
+        .await;
+    test_env
+        .try_accept_role(&protocol_owner, 0)
+        .await
+        .unwrap_transaction();
+    test_env
+        .apply_amm_config_update(
+            &admin,
+            0,
+            ConfigUpdate::FundOwner {
+                fund_owner: fund_owner.pubkey(),
+            },
+        )
+        .await;
+    test_env
+        .try_accept_role(&fund_owner, 0)
+        .await
+        .unwrap_transaction();
+
+    let pool_id = setup_pool(&mut test_env, &user).await;
+    let partner_id = 0;
+    test_env
+        .create_partner(&admin, partner_id, "AssetDash", depositor.pubkey(), 500_000)
+        .await;
+    test_env.init_pool_partner(&user, pool_id, partner_id).await;
+    fund_user(&mut test_env, &depositor).await;
+    test_env
+        .init_user_pool_liquidity_with_partner(&depositor, pool_id, Some(partner_id))
+        .await;
+    test_env
+        .deposit(&depositor, pool_id, 0, 200000000, u64::MAX, u64::MAX)
+        .await;
+    for trade_direction in [TradeDirection::ZeroForOne, TradeDirection::OneForZero] {
+        test_env
+            .swap_base_input(&user, pool_id, 0, 100000000, 0, trade_direction)
+            .await;
+    }
+
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    let (protocol_fees_token_0, protocol_fees_token_1) =
+        pool_state.collectable_protocol_fees().unwrap();
+    let fund_fees_token_0 = pool_state.fund_fees_token_0;
+    let fund_fees_token_1 = pool_state.fund_fees_token_1;
+    let partner_fees_token_0 = pool_state.partner_fees_token_0;
+    let partner_fees_token_1 = pool_state.partner_fees_token_1;
+    assert!(protocol_fees_token_0 > 0 && protocol_fees_token_1 > 0);
+    assert!(fund_fees_token_0 > 0 && fund_fees_token_1 > 0);
+    assert!(partner_fees_token_0 > 0 && partner_fees_token_1 > 0);
+
+    // Only a fee manager can rebind
+    let result = test_env
+        .try_rebind_pool_config(&user, pool_id, 1, vec![])
+        .await;
+    assert_error!(result, GammaError::InvalidOwner);
+
+    let result = test_env
+        .try_rebind_pool_config(&admin, pool_id, 1, vec![])
+        .await;
+    result.unwrap_transaction();
+    let events: Vec<CollectFeeEvent> = decode_events(&result);
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].fee_type, 0);
+    assert_eq!(events[0].amount_0, protocol_fees_token_0);
+    assert_eq!(events[0].amount_1, protocol_fees_token_1);
+    assert_eq!(events[0].remaining_fees_token_0, partner_fees_token_0);
+    assert_eq!(events[0].remaining_fees_token_1, partner_fees_token_1);
+    assert_eq!(events[1].fee_type, 1);
+    assert_eq!(events[1].amount_0, fund_fees_token_0);
+    assert_eq!(events[1].amount_1, fund_fees_token_1);
+    let events: Vec<RebindPoolConfigEvent> = decode_events(&result);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].old_amm_config, amm_config_address(0));
+    assert_eq!(events[0].new_amm_config, amm_config_address(1));
+
+    // The fees accrued under the old config went to its owners
+    for (owner, amount_0, amount_1) in [
+        (
+            protocol_owner.pubkey(),
+            protocol_fees_token_0,
+            protocol_fees_token_1,
+        ),
+        (fund_owner.pubkey(), fund_fees_token_0, fund_fees_token_1),
+    ] {
+        assert_eq!(
+            token_balance(&mut test_env, owner, pool_state.token_0_mint).await,
+            amount_0
+        );
+        assert_eq!(
+            token_balance(&mut test_env, owner, pool_state.token_1_mint).await,
+            amount_1
+        );
+    }
+
+    // The partner fees are still in the pool and can be claimed
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.amm_config, amm_config_address(1));
+    assert_eq_with_copy!(pool_state.protocol_fees_token_0, partner_fees_token_0);
+    assert_eq_with_copy!(pool_state.protocol_fees_token_1, partner_fees_token_1);
+    assert_eq_with_copy!(pool_state.partner_fees_token_0, partner_fees_token_0);
+    assert_eq_with_copy!(pool_state.partner_fees_token_1, partner_fees_token_1);
+    assert_eq_with_copy!(pool_state.fund_fees_token_0, 0);
+    assert_eq_with_copy!(pool_state.fund_fees_token_1, 0);
+    test_env
+        .claim_partner_fees(&user, pool_id, partner_id, depositor.pubkey())
+        .await;
+}
+
+#[tokio::test]
+async fn should_reject_a_fee_increase_under_timelock() {
+    let admin = get_admin();
+    let user = Keypair::new();
+    let mut test_env = TestEnv::new(vec![admin.pubkey(), user.pubkey()]).await;
+    test_env.create_config(&admin, 0, 3000, 2000, 50, 0).await;
+    test_env.create_config(&admin, 1, 4000, 2000, 50, 0).await;
+    test_env.create_config(&admin, 2, 3000, 1000, 50, 0).await;
+    let pool_id = setup_pool(&mut test_env, &user).await;
+    test_env
+        .apply_amm_config_update(
+            &admin,
+            0,
+            ConfigUpdate::TimelockDelay {
+                timelock_delay: 3600,
+            },
+        )
+        .await;
+
+    // A higher fee would skip the delay of the old config
+    let result = test_env
+        .try_rebind_pool_config(&admin, pool_id, 1, vec![])
+        .await;
+    assert_error!(result, GammaError::TimelockRequired);
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.amm_config, amm_config_address(0));
+
+    // Same or lower fees take effect immediately under the same delay
+    test_env
+        .apply_amm_config_update(
+            &admin,
+            2,
+            ConfigUpdate::TimelockDelay {
+                timelock_delay: 3600,
+            },
+        )
+        .await;
+    test_env
+        .try_rebind_pool_config(&admin, pool_id, 2, vec![])
+        .await
+        .unwrap_transaction();
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.amm_config, amm_config_address(2));
+}
+
+#[tokio::test]
+async fn should_reject_a_fee_increase_through_a_config_with_a_shorter_timelock() {
+    let admin = get_admin();
+    let user = Keypair::new();
+    let mut test_env = TestEnv::new(vec![admin.pubkey(), user.pubkey()]).await;
+    test_env.create_config(&admin, 0, 3000, 2000, 50, 0).await;
+    test_env.create_config(&admin, 1, 4000, 2000, 50, 0).await;
+    test_env.create_config(&admin, 2, 3000, 1000, 50, 0).await;
+    let pool_id = setup_pool(&mut test_env, &user).await;
+    test_env
+        .apply_amm_config_update(
+            &admin,
+            0,
+            ConfigUpdate::TimelockDelay {
+                timelock_delay: 3600,
+            },
+        )
+        .await;
+
+    // Config 2 has no delay, the pool could move on from it to the higher fee of config 1
+    let result = test_env
+        .try_rebind_pool_config(&admin, pool_id, 2, vec![])
+        .await;
+    assert_error!(result, GammaError::TimelockRequired);
+
+    // A longer delay is fine, it can't be used to skip the one of the old config
+    test_env
+        .apply_amm_config_update(
+            &admin,
+            2,
+            ConfigUpdate::TimelockDelay {
+                timelock_delay: 7200,
+            },
+        )
+        .await;
+    test_env
+        .try_rebind_pool_config(&admin, pool_id, 2, vec![])
+        .await
+        .unwrap_transaction();
+    let result = test_env
+        .try_rebind_pool_config(&admin, pool_id, 1, vec![])
+        .await;
+    assert_error!(result, GammaError::TimelockRequired);
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.amm_config, amm_config_address(2));
+}
+
+#[tokio::test]
+async fn should_enforce_the_mint_registry_of_the_new_config() {
+    let admin = get_admin();
+    let user = Keypair::new();
+    let mut test_env = TestEnv::new(vec![admin.pubkey(), user.pubkey()]).await;
+    test_env.create_config(&admin, 0, 3000, 2000, 50, 0).await;
+    test_env.create_config(&admin, 1, 3000, 2000, 50, 0).await;
+    let pool_id = setup_pool(&mut test_env, &user).await;
+    let new_amm_config = amm_config_address(1);
+    let token_0_mint = test_env.token_0_mint;
+    let token_1_mint = test_env.token_1_mint;
+    test_env
+        .try_init_mint_registry(&admin, 1, MintRegistryMode::Allowlist as u8)
+        .await
+        .unwrap_transaction();
+
+    // The registry accounts are required once the new config has a registry
+    let result = test_env
+        .try_rebind_pool_config(&admin, pool_id, 1, vec![])
+        .await;
+    assert_error!(result, GammaError::InvalidInput);
+    let result = test_env
+        .try_rebind_pool_config(
+            &admin,
+            pool_id,
+            1,
+            mint_registry_accounts(amm_config_address(0), token_0_mint, token_1_mint),
+        )
+        .await;
+    assert_error!(result, GammaError::InvalidInput);
+
+    // Both mints have to be allowed by the new config
+    test_env
+        .try_set_mint_entry(&admin, 1, token_0_mint, MintEntryStatus::Allowed as u8)
+        .await
+        .unwrap_transaction();
+    let result = test_env
+        .try_rebind_pool_config(
+            &admin,
+            pool_id,
+            1,
+            mint_registry_accounts(new_amm_config, token_0_mint, token_1_mint),
+        )
+        .await;
+    assert_error!(result, GammaError::MintNotAllowed);
+
+    test_env
+        .try_set_mint_entry(&admin, 1, token_1_mint, MintEntryStatus::Allowed as u8)
+        .await
+        .unwrap_transaction();
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.verified, 0);
+    test_env
+        .try_rebind_pool_config(
+            &admin,
+            pool_id,
+            1,
+            mint_registry_accounts(new_amm_config, token_0_mint, token_1_mint),
+        )
+        .await
+        .unwrap_transaction();
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.amm_config, new_amm_config);
+    assert_eq_with_copy!(pool_state.verified, 1);
+}
@@ -0,0 +1,115 @@
+use gamma::curve::TradeDirection;
+use gamma::states::PoolStatusBitIndex;
+use solana_program_test::tokio;
+use solana_sdk::{signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+#[tokio::test]
+async fn should_only_allow_withdrawals_and_swaps_towards_the_twap_in_reduce_only() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+
+    test_env
+        .create_config(&admin, amm_index, 3000, 2000, 50, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 100000000000000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 100000000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+
+    // Small swaps build observations around the initial price
+    for _ in 0..3 {
+        test_env.jump_seconds(20).await;
+        test_env
+            .swap_base_input(
+                &user,
+                pool_id,
+                amm_index,
+                1000,
+                0,
+                TradeDirection::ZeroForOne,
+            )
+            .await;
+    }
+    // A large sell moves the price of token_0 below the TWAP
+    test_env.jump_seconds(20).await;
+    test_env
+        .swap_base_input(
+            &user,
+            pool_id,
+            amm_index,
+            200000000,
+            0,
+            TradeDirection::ZeroForOne,
+        )
+        .await;
+
+    test_env
+        .update_pool(
+            &admin,
+            pool_id,
+            amm_index,
+            0,
+            1 << PoolStatusBitIndex::ReduceOnly as u8,
+        )
+        .await;
+    test_env.jump_seconds(20).await;
+
+    assert!(test_env
+        .try_deposit(&user, pool_id, amm_index, 1000000, u64::MAX, u64::MAX)
+        .await
+        .is_err());
+
+    // Selling token_0 moves the price further from the TWAP
+    assert!(test_env
+        .try_swap_base_input(
+            &user,
+            pool_id,
+            amm_index,
+            1000,
+            0,
+            TradeDirection::ZeroForOne,
+        )
+        .await
+        .is_err());
+
+    // Buying token_0 back moves the price towards the TWAP
+    test_env
+        .swap_base_input(
+            &user,
+            pool_id,
+            amm_index,
+            1000000,
+            0,
+            TradeDirection::OneForZero,
+        )
+        .await;
+
+    test_env
+        .withdraw(&user, pool_id, amm_index, 1000000, 0, 0)
+        .await;
+}
@@ -0,0 +1,294 @@
+use anchor_lang::Discriminator;
+use gamma::curve::TradeDirection;
+use gamma::states::{PoolState, UserPoolLiquidity, LEGACY_PARTNER_ID, USER_POOL_LIQUIDITY_SEED};
+use solana_program_test::tokio;
+use solana_sdk::{account::AccountSharedData, pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+/// Size of a UserPoolLiquidity before LP locks, fee tracking and the partner registry
+const LEGACY_USER_POOL_LIQUIDITY_LEN: usize = 8 + 32 * 2 + 16 * 5 + 32;
+/// Size of a PoolState before the price guardian, fee growth and partner fee tracking
+const LEGACY_POOL_STATE_LEN: usize = 8 + 10 * 32 + 5 + 7 * 8 + 16 * 4 + 23 * 8;
+/// Size of the trailing padding of the legacy PoolState, the new fields start there
+const LEGACY_POOL_STATE_PADDING_LEN: usize = 8 * 8;
+
+fn user_pool_liquidity_address(pool_id: Pubkey, user: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_id.to_bytes().as_ref(),
+            user.to_bytes().as_ref(),
+        ],
+        &gamma::id(),
+    )
+    .0
+}
+
+/// Rewrites a position in the layout it had when partners were an enum
+async fn downgrade_to_legacy_layout(
+    test_env: &mut TestEnv,
+    address: Pubkey,
+    legacy_partner: Option<u8>,
+) {
+    let mut account = test_env.get_account_info(address).await.unwrap().unwrap();
+    let position: UserPoolLiquidity = test_env.fetch_account(address).await;
+
+    let mut data = Vec::with_capacity(LEGACY_USER_POOL_LIQUIDITY_LEN);
+    data.extend_from_slice(&UserPoolLiquidity::DISCRIMINATOR);
+    data.extend_from_slice(position.user.as_ref());
+    data.extend_from_slice(position.pool_state.as_ref());
+    for amount in [
+        position.token_0_deposited,
+        position.token_1_deposited,
+        position.token_0_withdrawn,
+        position.token_1_withdrawn,
+        position.lp_tokens_owned,
+    ] {
+        data.extend_from_slice(&amount.to_le_bytes());
+    }
+    match legacy_partner {
+        Some(variant) => data.extend_from_slice(&[1, variant]),
+        None => data.push(0),
+    }
+    data.extend_from_slice(&position.first_investment_at.to_le_bytes());
+    data.resize(LEGACY_USER_POOL_LIQUIDITY_LEN, 0);
+
+    account.data = data;
+    test_env
+        .program_test_context
+        .set_account(&address, &AccountSharedData::from(account));
+}
+
+/// Rewrites a pool in the layout it had before the fields appended after the kamino amounts
+async fn downgrade_pool_to_legacy_layout(test_env: &mut TestEnv, address: Pubkey) {
+    let mut account = test_env.get_account_info(address).await.unwrap().unwrap();
+    account.data.truncate(LEGACY_POOL_STATE_LEN);
+    account.data[LEGACY_POOL_STATE_LEN - LEGACY_POOL_STATE_PADDING_LEN..].fill(0);
+    test_env
+        .program_test_context
+        .set_account(&address, &AccountSharedData::from(account));
+}
+
+#[tokio::test]
+async fn should_resize_legacy_positions_and_keep_them_usable() {
+    let user = Keypair::new();
+    let lp_depositor = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env =
+        TestEnv::new(vec![user.pubkey(), lp_depositor.pubkey(), admin.pubkey()]).await;
+
+    test_env
+        .create_config(&admin, amm_index, 3000, 2000, 50, 0)
+        .await;
+
+    for owner in [&user, &lp_depositor] {
+        let token_0_account = test_env
+            .get_or_create_associated_token_account(owner.pubkey(), test_env.token_0_mint, owner)
+            .await;
+        test_env
+            .mint_base_tokens(token_0_account, 100000000000000, test_env.token_0_mint)
+            .await;
+        let token_1_account = test_env
+            .get_or_create_associated_token_account(owner.pubkey(), test_env.token_1_mint, owner)
+            .await;
+        test_env
+            .mint_base_tokens(token_1_account, 100000000000000, test_env.token_1_mint)
+            .await;
+    }
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            200000000,
+            100000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    test_env.jump_seconds(100).await;
+
+    test_env
+        .init_user_pool_liquidity(&lp_depositor, pool_id)
+        .await;
+    let lp_amount = 100000000;
+    test_env
+        .deposit(
+            &lp_depositor,
+            pool_id,
+            amm_index,
+            lp_amount,
+            u64::MAX,
+            u64::MAX,
+        )
+        .await;
+
+    let depositor_position = user_pool_liquidity_address(pool_id, lp_depositor.pubkey());
+    let creator_position = user_pool_liquidity_address(pool_id, user.pubkey());
+    let depositor_before: UserPoolLiquidity = test_env.fetch_account(depositor_position).await;
+    let creator_before: UserPoolLiquidity = test_env.fetch_account(creator_position).await;
+    downgrade_to_legacy_layout(&mut test_env, depositor_position, None).await;
+    downgrade_to_legacy_layout(&mut test_env, creator_position, Some(0)).await;
+
+    // A position with the old layout can't be loaded until resized
+    assert!(test_env
+        .try_deposit(
+            &lp_depositor,
+            pool_id,
+            amm_index,
+            lp_amount,
+            u64::MAX,
+            u64::MAX,
+        )
+        .await
+        .is_err());
+
+    test_env
+        .resize_user_pool_liquidity(&admin, depositor_position)
+        .await;
+    test_env
+        .resize_user_pool_liquidity(&admin, creator_position)
+        .await;
+
+    let account = test_env
+        .get_account_info(depositor_position)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), UserPoolLiquidity::LEN);
+    let depositor_after: UserPoolLiquidity = test_env.fetch_account(depositor_position).await;
+    assert_eq!(depositor_after.user, depositor_before.user);
+    assert_eq!(depositor_after.pool_state, depositor_before.pool_state);
+    assert_eq!(
+        depositor_after.token_0_deposited,
+        depositor_before.token_0_deposited
+    );
+    assert_eq!(
+        depositor_after.token_1_deposited,
+        depositor_before.token_1_deposited
+    );
+    assert_eq!(depositor_after.lp_tokens_owned, lp_amount as u128);
+    assert_eq!(depositor_after.partner, None);
+    assert_eq!(
+        depositor_after.first_investment_at,
+        depositor_before.first_investment_at
+    );
+    assert_eq!(depositor_after.lp_tokens_locked, 0);
+
+    // The enum partner of a legacy position maps to the legacy partner id
+    let creator_after: UserPoolLiquidity = test_env.fetch_account(creator_position).await;
+    assert_eq!(creator_after.partner, Some(LEGACY_PARTNER_ID));
+    assert_eq!(
+        creator_after.lp_tokens_owned,
+        creator_before.lp_tokens_owned
+    );
+
+    test_env
+        .deposit(
+            &lp_depositor,
+            pool_id,
+            amm_index,
+            lp_amount,
+            u64::MAX,
+            u64::MAX,
+        )
+        .await;
+    let depositor_after: UserPoolLiquidity = test_env.fetch_account(depositor_position).await;
+    assert_eq!(depositor_after.lp_tokens_owned, 2 * lp_amount as u128);
+}
+
+#[tokio::test]
+async fn should_resize_legacy_pools_and_keep_them_usable() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+
+    test_env
+        .create_config(&admin, amm_index, 3000, 2000, 50, 0)
+        .await;
+
+    let token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(token_0_account, 100000000000000, test_env.token_0_mint)
+        .await;
+    let token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(token_1_account, 100000000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            200000000,
+            100000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    test_env.jump_seconds(100).await;
+
+    let pool_before: PoolState = test_env.fetch_account(pool_id).await;
+    downgrade_pool_to_legacy_layout(&mut test_env, pool_id).await;
+
+    // A pool with the old layout can't be loaded until resized
+    assert!(test_env
+        .try_swap_base_input(
+            &user,
+            pool_id,
+            amm_index,
+            1000000,
+            0,
+            TradeDirection::ZeroForOne,
+        )
+        .await
+        .is_err());
+
+    test_env.resize_pool_state(&admin, pool_id).await;
+
+    let account = test_env.get_account_info(pool_id).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), PoolState::LEN);
+    let pool_after: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_after.amm_config, pool_before.amm_config);
+    assert_eq_with_copy!(pool_after.token_0_vault, pool_before.token_0_vault);
+    assert_eq_with_copy!(pool_after.token_1_vault, pool_before.token_1_vault);
+    assert_eq_with_copy!(pool_after.lp_supply, pool_before.lp_supply);
+    assert_eq_with_copy!(
+        pool_after.token_0_vault_amount,
+        pool_before.token_0_vault_amount
+    );
+    assert_eq_with_copy!(
+        pool_after.token_1_vault_amount,
+        pool_before.token_1_vault_amount
+    );
+    assert_eq_with_copy!(pool_after.guardian_status, 0);
+    assert_eq_with_copy!(pool_after.reward_info_count, 0);
+
+    test_env
+        .swap_base_input(
+            &user,
+            pool_id,
+            amm_index,
+            1000000,
+            0,
+            TradeDirection::ZeroForOne,
+        )
+        .await;
+    let lp_amount = 100000000;
+    test_env
+        .deposit(&user, pool_id, amm_index, lp_amount, u64::MAX, u64::MAX)
+        .await;
+    test_env
+        .withdraw(&user, pool_id, amm_index, lp_amount, 0, 0)
+        .await;
+    let pool_after: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_after.lp_supply, pool_before.lp_supply);
+}
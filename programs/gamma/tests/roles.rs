@@ -0,0 +1,283 @@
+use gamma::error::GammaError;
+use gamma::states::{
+    AmmConfig, Capability, ConfigUpdate, CreateAmmConfigEvent, Roles, AMM_CONFIG_SEED,
+};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+fn amm_config_address(amm_index: u16) -> Pubkey {
+    Pubkey::find_program_address(
+        &[AMM_CONFIG_SEED.as_bytes(), &amm_index.to_be_bytes()],
+        &gamma::id(),
+    )
+    .0
+}
+
+#[tokio::test]
+async fn should_stop_trusting_the_hard_coded_admin_once_revoked() {
+    let admin = get_admin();
+    let new_admin = Keypair::new();
+    let mut test_env = TestEnv::new(vec![admin.pubkey(), new_admin.pubkey()]).await;
+
+    // Until the roles account is created the hard-coded admin is trusted
+    test_env
+        .try_create_config(&admin, 0, 3000, 2000, 50, 0)
+        .await
+        .unwrap();
+    assert!(test_env
+        .try_create_config(&new_admin, 1, 3000, 2000, 50, 0)
+        .await
+        .is_err());
+
+    test_env.init_roles(&admin).await;
+    let roles: Roles = test_env.fetch_account(roles_address()).await;
+    assert_eq!(roles.capabilities_of(admin.pubkey()), Capability::ALL_MASK);
+
+    test_env
+        .try_set_capabilities(&admin, new_admin.pubkey(), Capability::ALL_MASK)
+        .await
+        .unwrap();
+    test_env
+        .try_set_capabilities(&new_admin, admin.pubkey(), 0)
+        .await
+        .unwrap();
+
+    // The revoked admin key is not accepted anymore, the granted key is
+    assert!(test_env
+        .try_create_config(&admin, 1, 3000, 2000, 50, 0)
+        .await
+        .is_err());
+    assert!(test_env
+        .try_set_capabilities(&admin, admin.pubkey(), Capability::ALL_MASK)
+        .await
+        .is_err());
+    test_env
+        .try_create_config(&new_admin, 1, 3000, 2000, 50, 0)
+        .await
+        .unwrap();
+
+    // The last role admin can not be removed
+    assert!(test_env
+        .try_set_capabilities(&new_admin, new_admin.pubkey(), 0)
+        .await
+        .is_err());
+}
+
+async fn setup_pool(test_env: &mut TestEnv, user: &Keypair, admin: &Keypair) -> Pubkey {
+    test_env.create_config(admin, 0, 3000, 2000, 50, 0).await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 100000000000000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 100000000000000, test_env.token_1_mint)
+        .await;
+
+    test_env
+        .initialize_pool(
+            user,
+            0,
+            200000000,
+            100000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await
+}
+
+#[tokio::test]
+async fn should_gate_every_admin_path_on_capabilities_once_roles_exist() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let new_admin = Keypair::new();
+    let secondary_admin = Keypair::new();
+    let protocol_owner = Keypair::new();
+    let mut test_env = TestEnv::new(vec![
+        user.pubkey(),
+        admin.pubkey(),
+        new_admin.pubkey(),
+        secondary_admin.pubkey(),
+        protocol_owner.pubkey(),
+    ])
+    .await;
+    let pool_id = setup_pool(&mut test_env, &user, &admin).await;
+
+    test_env
+        .apply_amm_config_update(
+            &admin,
+            0,
+            ConfigUpdate::SecondaryAdmin {
+                secondary_admin: secondary_admin.pubkey(),
+            },
+        )
+        .await;
+    test_env
+        .try_accept_role(&secondary_admin, 0)
+        .await
+        .unwrap_transaction();
+    // Until the roles account is created the secondary admin changes the kamino share
+    test_env
+        .update_pool(&secondary_admin, pool_id, 0, 3, 0)
+        .await;
+
+    test_env.init_roles(&admin).await;
+    test_env
+        .try_set_capabilities(&admin, new_admin.pubkey(), Capability::ALL_MASK)
+        .await
+        .unwrap();
+    test_env
+        .try_set_capabilities(&new_admin, admin.pubkey(), 0)
+        .await
+        .unwrap();
+
+    // The revoked admin can't propose role transfers, change the timelock or the oracle mode
+    let result = test_env
+        .try_apply_amm_config_update(
+            &admin,
+            0,
+            ConfigUpdate::ProtocolOwner {
+                protocol_owner: protocol_owner.pubkey(),
+            },
+        )
+        .await;
+    assert_error!(result, GammaError::InvalidOwner);
+    let result = test_env
+        .try_apply_amm_config_update(&admin, 0, ConfigUpdate::TimelockDelay { timelock_delay: 0 })
+        .await;
+    assert_error!(result, GammaError::InvalidOwner);
+    let result = test_env.try_update_pool(&admin, pool_id, 0, 9, 1).await;
+    assert_error!(result, GammaError::InvalidOwner);
+    // Nor can the secondary admin without a capability
+    let result = test_env
+        .try_update_pool(&secondary_admin, pool_id, 0, 3, 0)
+        .await;
+    assert_error!(result, GammaError::InvalidOwner);
+
+    // The role admin proposes the transfer, the revoked admin can't cancel it
+    test_env
+        .apply_amm_config_update(
+            &new_admin,
+            0,
+            ConfigUpdate::ProtocolOwner {
+                protocol_owner: protocol_owner.pubkey(),
+            },
+        )
+        .await;
+    let result = test_env.try_cancel_role_transfer(&admin, 0).await;
+    assert_error!(result, GammaError::InvalidOwner);
+    test_env
+        .try_accept_role(&protocol_owner, 0)
+        .await
+        .unwrap_transaction();
+
+    // Fees are collected by the protocol owner or a fee collector, not the revoked admin
+    let result = test_env
+        .try_collect_protocol_fee(&admin, pool_id, admin.pubkey())
+        .await;
+    assert_error!(result, GammaError::InvalidOwner);
+    test_env
+        .try_collect_protocol_fee(&protocol_owner, pool_id, protocol_owner.pubkey())
+        .await
+        .unwrap_transaction();
+    test_env
+        .try_collect_protocol_fee(&new_admin, pool_id, protocol_owner.pubkey())
+        .await
+        .unwrap_transaction();
+
+    // Granting the capability lets the secondary admin through again
+    test_env
+        .try_set_capabilities(
+            &new_admin,
+            secondary_admin.pubkey(),
+            Capability::KaminoShareManager.mask(),
+        )
+        .await
+        .unwrap();
+    test_env
+        .update_pool(&secondary_admin, pool_id, 0, 3, 0)
+        .await;
+}
+
+#[tokio::test]
+async fn should_hand_the_fees_of_new_configs_to_the_rotated_keys() {
+    let admin = get_admin();
+    let new_admin = Keypair::new();
+    let config_creator = Keypair::new();
+    let fee_collector = Keypair::new();
+    let mut test_env = TestEnv::new(vec![
+        admin.pubkey(),
+        new_admin.pubkey(),
+        config_creator.pubkey(),
+        fee_collector.pubkey(),
+    ])
+    .await;
+
+    test_env.init_roles(&admin).await;
+    for (member, capabilities) in [
+        (new_admin.pubkey(), Capability::ALL_MASK),
+        (config_creator.pubkey(), Capability::ConfigCreator.mask()),
+        (fee_collector.pubkey(), Capability::FeeCollector.mask()),
+    ] {
+        test_env
+            .try_set_capabilities(&admin, member, capabilities)
+            .await
+            .unwrap();
+    }
+    test_env
+        .try_set_capabilities(&new_admin, admin.pubkey(), 0)
+        .await
+        .unwrap();
+
+    // The role admin picks the fee owners of a new config, the rotated key instead of the old one
+    let result = test_env
+        .try_create_config_with_fee_owners(&new_admin, 0, new_admin.pubkey(), new_admin.pubkey())
+        .await;
+    result.unwrap_transaction();
+    let events: Vec<CreateAmmConfigEvent> = decode_events(&result);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].protocol_owner, new_admin.pubkey());
+    assert_eq!(events[0].fund_owner, new_admin.pubkey());
+    let amm_config: AmmConfig = test_env.fetch_account(amm_config_address(0)).await;
+    assert_eq!(amm_config.protocol_owner, new_admin.pubkey());
+    assert_eq!(amm_config.fund_owner, new_admin.pubkey());
+
+    // A config creator can't hand the fees to itself or to the revoked admin
+    for fee_owner in [config_creator.pubkey(), admin.pubkey()] {
+        let result = test_env
+            .try_create_config_with_fee_owners(&config_creator, 1, fee_owner, fee_owner)
+            .await;
+        assert_error!(result, GammaError::InvalidOwner);
+        let result = test_env
+            .try_create_config_with_fee_owners(
+                &config_creator,
+                1,
+                fee_collector.pubkey(),
+                fee_owner,
+            )
+            .await;
+        assert_error!(result, GammaError::InvalidOwner);
+    }
+
+    // Only to a fee collector
+    test_env
+        .try_create_config_with_fee_owners(
+            &config_creator,
+            1,
+            fee_collector.pubkey(),
+            fee_collector.pubkey(),
+        )
+        .await
+        .unwrap_transaction();
+    let amm_config: AmmConfig = test_env.fetch_account(amm_config_address(1)).await;
+    assert_eq!(amm_config.protocol_owner, fee_collector.pubkey());
+    assert_eq!(amm_config.fund_owner, fee_collector.pubkey());
+}
@@ -0,0 +1,138 @@
+use anchor_lang::prelude::Clock;
+use gamma::curve::TradeDirection;
+use gamma::states::{LpPriceHistory, LpPriceSnapshot, PoolState, Q32};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+async fn setup_pool(test_env: &mut TestEnv, user: &Keypair, admin: &Keypair) -> Pubkey {
+    test_env.create_config(admin, 0, 3000, 2000, 50, 0).await;
+
+    let token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, user)
+        .await;
+    test_env
+        .mint_base_tokens(token_0_account, 100000000000000, test_env.token_0_mint)
+        .await;
+    let token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, user)
+        .await;
+    test_env
+        .mint_base_tokens(token_1_account, 100000000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
+            user,
+            0,
+            200000000,
+            100000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    test_env.jump_seconds(100).await;
+    pool_id
+}
+
+async fn epoch_now(test_env: &mut TestEnv) -> u64 {
+    let clock: Clock = test_env
+        .program_test_context
+        .banks_client
+        .get_sysvar()
+        .await
+        .unwrap();
+    clock.epoch
+}
+
+async fn jump_to_next_epoch(test_env: &mut TestEnv) {
+    let epoch = epoch_now(test_env).await;
+    while epoch_now(test_env).await == epoch {
+        test_env.jump_days(1).await;
+    }
+}
+
+async fn ordered_snapshots(test_env: &mut TestEnv, pool_id: Pubkey) -> Vec<LpPriceSnapshot> {
+    let lp_price_history: LpPriceHistory = test_env
+        .fetch_account(lp_price_history_address(pool_id))
+        .await;
+    assert_eq_with_copy!(lp_price_history.pool_id, pool_id);
+    lp_price_history.ordered_snapshots()
+}
+
+/// Product of the token amounts per LP, it only grows with the fees left in the pool
+fn lp_value(snapshot: &LpPriceSnapshot) -> u128 {
+    snapshot.token_0_per_lp_x32 * snapshot.token_1_per_lp_x32
+}
+
+#[tokio::test]
+async fn should_snapshot_lp_price_once_per_pool_epoch() {
+    let admin = get_admin();
+    let user = Keypair::new();
+    let cranker = Keypair::new();
+    let mut test_env = TestEnv::new(vec![admin.pubkey(), user.pubkey(), cranker.pubkey()]).await;
+    let pool_id = setup_pool(&mut test_env, &user, &admin).await;
+    jump_to_next_epoch(&mut test_env).await;
+    test_env
+        .swap_base_input(&user, pool_id, 0, 10000000, 0, TradeDirection::ZeroForOne)
+        .await;
+
+    // Anyone can crank, the first snapshot creates the history
+    test_env
+        .try_snapshot_lp_price(&cranker, pool_id)
+        .await
+        .unwrap_transaction();
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    let snapshots = ordered_snapshots(&mut test_env, pool_id).await;
+    assert_eq!(snapshots.len(), 1);
+    let first = snapshots[0];
+    assert_eq_with_copy!(first.epoch, pool_state.recent_epoch);
+    assert_eq_with_copy!(first.epoch, epoch_now(&mut test_env).await);
+    assert_eq_with_copy!(
+        first.token_0_per_lp_x32,
+        u128::from(pool_state.token_0_vault_amount) * Q32 / u128::from(pool_state.lp_supply)
+    );
+    assert_eq_with_copy!(
+        first.token_1_per_lp_x32,
+        u128::from(pool_state.token_1_vault_amount) * Q32 / u128::from(pool_state.lp_supply)
+    );
+
+    // Later snapshots in the same epoch are no-ops, even after the reserves moved
+    test_env
+        .swap_base_input(&user, pool_id, 0, 10000000, 0, TradeDirection::ZeroForOne)
+        .await;
+    test_env
+        .try_snapshot_lp_price(&cranker, pool_id)
+        .await
+        .unwrap_transaction();
+    let snapshots = ordered_snapshots(&mut test_env, pool_id).await;
+    assert_eq!(snapshots.len(), 1);
+    assert_eq_with_copy!(snapshots[0].token_0_per_lp_x32, first.token_0_per_lp_x32);
+
+    // A new epoch is only snapshotted once the pool is updated in it
+    jump_to_next_epoch(&mut test_env).await;
+    test_env
+        .try_snapshot_lp_price(&cranker, pool_id)
+        .await
+        .unwrap_transaction();
+    assert_eq!(ordered_snapshots(&mut test_env, pool_id).await.len(), 1);
+
+    test_env
+        .swap_base_input(&user, pool_id, 0, 10000000, 0, TradeDirection::OneForZero)
+        .await;
+    test_env
+        .try_snapshot_lp_price(&cranker, pool_id)
+        .await
+        .unwrap_transaction();
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    let snapshots = ordered_snapshots(&mut test_env, pool_id).await;
+    assert_eq!(snapshots.len(), 2);
+    assert_eq_with_copy!(snapshots[0].epoch, first.epoch);
+    assert_eq_with_copy!(snapshots[1].epoch, pool_state.recent_epoch);
+    assert!({ snapshots[1].epoch } > { first.epoch });
+    assert!({ snapshots[1].block_timestamp } > { first.block_timestamp });
+    // The trade fees left in the pool grow the value of the LP
+    assert!(lp_value(&snapshots[1]) > lp_value(&first));
+}
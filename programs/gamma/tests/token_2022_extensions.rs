@@ -0,0 +1,285 @@
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{default_account_state, transfer_hook, ExtensionType},
+    state::AccountState,
+};
+use anchor_spl::token_interface::TokenAccount;
+use gamma::curve::TradeDirection;
+use gamma::error::GammaError;
+use gamma::states::PoolState;
+use solana_program_test::{processor, tokio};
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    hash::hash,
+    instruction::AccountMeta,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::Keypair,
+    signer::Signer,
+};
+mod utils;
+
+use utils::*;
+
+/// Transfers of this amount are rejected by the transfer hook
+const REJECTED_AMOUNT: u64 = 1_000_013;
+
+fn process_transfer_hook(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    // The execute instruction is an 8 bytes discriminator followed by the amount
+    let amount = instruction_data
+        .get(8..16)
+        .map(|amount| u64::from_le_bytes(amount.try_into().unwrap()))
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    if amount == REJECTED_AMOUNT {
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(())
+}
+
+/// Creates the validation account of the hook for the mint, without extra account metas
+fn set_empty_validation_account(
+    test_env: &mut TestEnv,
+    hook_program_id: Pubkey,
+    mint: Pubkey,
+) -> Pubkey {
+    let address =
+        Pubkey::find_program_address(&[b"extra-account-metas", mint.as_ref()], &hook_program_id).0;
+    // A TLV entry of the execute instruction holding an empty list
+    let discriminator = &hash(b"spl-transfer-hook-interface:execute").to_bytes()[..8];
+    let data = [discriminator, &4u32.to_le_bytes(), &0u32.to_le_bytes()].concat();
+    test_env.program_test_context.set_account(
+        &address,
+        &AccountSharedData::from(Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: hook_program_id,
+            ..Default::default()
+        }),
+    );
+    address
+}
+
+async fn fund_user(test_env: &mut TestEnv, user: &Keypair) {
+    for mint in [test_env.token_0_mint, test_env.token_1_mint] {
+        let token_account = test_env
+            .get_or_create_associated_token_account(user.pubkey(), mint, user)
+            .await;
+        test_env
+            .mint_base_tokens(token_account, 100000000000000, mint)
+            .await;
+    }
+}
+
+#[tokio::test]
+async fn should_swap_transfer_hook_mints_with_the_hook_accounts() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let hook_program_id = Pubkey::new_unique();
+    let mut test_env = TestEnv::new_with_config(
+        vec![user.pubkey(), admin.pubkey()],
+        vec![ProgramInfo {
+            program_name: "transfer_hook".to_string(),
+            program_id: hook_program_id,
+            process_instruction: processor!(process_transfer_hook),
+        }],
+    )
+    .await;
+    test_env
+        .use_token_2022_mints(&[ExtensionType::TransferHook], |mint| {
+            vec![transfer_hook::instruction::initialize(
+                &spl_token_2022::id(),
+                mint,
+                None,
+                Some(hook_program_id),
+            )
+            .unwrap()]
+        })
+        .await;
+    let mut hook_accounts = vec![AccountMeta::new_readonly(hook_program_id, false)];
+    for mint in [test_env.token_0_mint, test_env.token_1_mint] {
+        let validation_account = set_empty_validation_account(&mut test_env, hook_program_id, mint);
+        hook_accounts.push(AccountMeta::new_readonly(validation_account, false));
+    }
+
+    test_env
+        .create_config(&admin, amm_index, 3000, 2000, 50, 0)
+        .await;
+    fund_user(&mut test_env, &user).await;
+
+    let (pool_id, mut instruction) = test_env
+        .initialize_pool_instruction(
+            &user,
+            amm_index,
+            1000000000,
+            1000000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    // The hook program has to be passed for the transfers into the vaults
+    let transaction = get_signed_transaction(
+        &mut test_env.program_test_context,
+        &[instruction.clone()],
+        &user,
+    )
+    .await;
+    assert!(test_env
+        .program_test_context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .is_err());
+    instruction.accounts.extend(hook_accounts.clone());
+    let transaction =
+        get_signed_transaction(&mut test_env.program_test_context, &[instruction], &user).await;
+    test_env
+        .program_test_context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    // The hook accounts follow the fixed remaining accounts of the swap
+    test_env.jump_seconds(100).await;
+    let mut remaining_accounts = swap_remaining_accounts(pool_id);
+    remaining_accounts.extend(hook_accounts);
+    test_env
+        .try_swap_base_input_with_remaining_accounts(
+            &user,
+            pool_id,
+            amm_index,
+            1000000,
+            0,
+            TradeDirection::ZeroForOne,
+            remaining_accounts.clone(),
+        )
+        .await
+        .unwrap();
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    let token_0_vault: TokenAccount = test_env.fetch_account(pool_state.token_0_vault).await;
+    assert_eq!(token_0_vault.amount, 1000000000 + 1000000);
+
+    // The hook runs on every transfer of the swap
+    assert!(test_env
+        .try_swap_base_input_with_remaining_accounts(
+            &user,
+            pool_id,
+            amm_index,
+            REJECTED_AMOUNT,
+            0,
+            TradeDirection::ZeroForOne,
+            remaining_accounts,
+        )
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn should_thaw_the_vaults_of_frozen_by_default_mints_with_the_freeze_authority() {
+    let user = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![user.pubkey(), admin.pubkey()]).await;
+    test_env
+        .use_token_2022_mints(&[ExtensionType::DefaultAccountState], |mint| {
+            vec![
+                default_account_state::instruction::initialize_default_account_state(
+                    &spl_token_2022::id(),
+                    mint,
+                    &AccountState::Frozen,
+                )
+                .unwrap(),
+            ]
+        })
+        .await;
+
+    test_env
+        .create_config(&admin, amm_index, 3000, 2000, 50, 0)
+        .await;
+    for mint in [test_env.token_0_mint, test_env.token_1_mint] {
+        let token_account = test_env
+            .get_or_create_associated_token_account(user.pubkey(), mint, &user)
+            .await;
+        let thaw_instruction = spl_token_2022::instruction::thaw_account(
+            &spl_token_2022::id(),
+            &token_account,
+            &mint,
+            &test_env.mint_authority.pubkey(),
+            &[],
+        )
+        .unwrap();
+        let transaction = get_signed_transaction(
+            &mut test_env.program_test_context,
+            &[thaw_instruction],
+            &test_env.mint_authority,
+        )
+        .await;
+        test_env
+            .program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+    fund_user(&mut test_env, &user).await;
+
+    let (pool_id, mut instruction) = test_env
+        .initialize_pool_instruction(
+            &user,
+            amm_index,
+            200000000,
+            100000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    let transaction = get_signed_transaction(
+        &mut test_env.program_test_context,
+        &[instruction.clone()],
+        &user,
+    )
+    .await;
+    let result = test_env
+        .program_test_context
+        .banks_client
+        .process_transaction_with_metadata(transaction)
+        .await;
+    assert_error!(result, GammaError::FrozenVault);
+
+    // The freeze authority signs as a remaining account
+    instruction.accounts.push(AccountMeta::new_readonly(
+        test_env.mint_authority.pubkey(),
+        true,
+    ));
+    let transaction = get_signed_transaction_with_different_payer(
+        &mut test_env.program_test_context,
+        &[instruction],
+        &test_env.mint_authority,
+        &user,
+    )
+    .await;
+    test_env
+        .program_test_context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    for (vault, amount) in [
+        (pool_state.token_0_vault, 200000000),
+        (pool_state.token_1_vault, 100000000),
+    ] {
+        let vault: TokenAccount = test_env.fetch_account(vault).await;
+        assert_eq!(vault.state, AccountState::Initialized);
+        assert_eq!(vault.amount, amount);
+    }
+}
@@ -0,0 +1,257 @@
+use gamma::{
+    states::{UserPoolLiquidity, UserRewardInfo, USER_POOL_LIQUIDITY_SEED},
+    REWARD_INFO_SEED, USER_REWARD_INFO_SEED,
+};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+fn user_pool_liquidity_key(pool_id: Pubkey, user: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            USER_POOL_LIQUIDITY_SEED.as_bytes(),
+            pool_id.to_bytes().as_ref(),
+            user.to_bytes().as_ref(),
+        ],
+        &gamma::id(),
+    )
+    .0
+}
+
+fn user_reward_info_key(reward_info: Pubkey, user: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            USER_REWARD_INFO_SEED.as_bytes(),
+            reward_info.to_bytes().as_ref(),
+            user.to_bytes().as_ref(),
+        ],
+        &gamma::id(),
+    )
+    .0
+}
+
+#[tokio::test]
+async fn received_lp_does_not_earn_past_rewards() {
+    let user = Keypair::new();
+    let user2 = Keypair::new();
+    let reward_provider = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![
+        user.pubkey(),
+        user2.pubkey(),
+        admin.pubkey(),
+        reward_provider.pubkey(),
+    ])
+    .await;
+    test_env
+        .create_config(&admin, amm_index, 100, 20, 5, 0)
+        .await;
+
+    let user_token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_0_account, 100000, test_env.token_0_mint)
+        .await;
+    let user_token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, &user)
+        .await;
+    test_env
+        .mint_base_tokens(user_token_1_account, 100000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            1000,
+            2000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    // An empty position opened long before it receives any LP
+    test_env.init_user_pool_liquidity(&user2, pool_id).await;
+
+    let reward_mint = Keypair::new();
+    test_env
+        .create_token_mint(&reward_mint, &test_env.mint_authority.pubkey(), 9)
+        .await;
+    let reward_provider_token_account = test_env
+        .get_or_create_associated_token_account(
+            reward_provider.pubkey(),
+            reward_mint.pubkey(),
+            &reward_provider,
+        )
+        .await;
+    let reward_amount = 1000000000;
+    test_env
+        .mint_base_tokens(
+            reward_provider_token_account,
+            reward_amount,
+            reward_mint.pubkey(),
+        )
+        .await;
+
+    let timestamp_now = test_env.timestamp_now().await as u64;
+    let start_time = timestamp_now + 10;
+    let end_time = timestamp_now + 3000;
+    test_env
+        .create_rewards(
+            &reward_provider,
+            pool_id,
+            start_time,
+            end_time,
+            reward_mint.pubkey(),
+            reward_amount,
+        )
+        .await;
+    let (reward_info_key, _) = Pubkey::find_program_address(
+        &[
+            REWARD_INFO_SEED.as_bytes(),
+            pool_id.to_bytes().as_ref(),
+            &start_time.to_le_bytes(),
+            reward_mint.pubkey().to_bytes().as_ref(),
+        ],
+        &gamma::id(),
+    );
+
+    test_env.jump_seconds(20).await;
+    test_env
+        .calculate_rewards(&user, pool_id, reward_info_key)
+        .await;
+    let user_reward_info: UserRewardInfo = test_env
+        .fetch_account(user_reward_info_key(reward_info_key, user.pubkey()))
+        .await;
+    let user_rewards_before = user_reward_info.total_rewards;
+
+    test_env.jump_seconds(1000).await;
+    let user_pool_liquidity: UserPoolLiquidity = test_env
+        .fetch_account(user_pool_liquidity_key(pool_id, user.pubkey()))
+        .await;
+    let lp_amount = (user_pool_liquidity.lp_tokens_owned / 2) as u64;
+    // The sender is settled at its balance before the transfer
+    test_env
+        .transfer_liquidity(
+            &user,
+            user2.pubkey(),
+            pool_id,
+            lp_amount,
+            &[reward_info_key],
+        )
+        .await;
+    let user_reward_info: UserRewardInfo = test_env
+        .fetch_account(user_reward_info_key(reward_info_key, user.pubkey()))
+        .await;
+    let user_rewards_at_transfer = user_reward_info.total_rewards;
+    assert!(user_rewards_at_transfer > user_rewards_before);
+
+    let user2_pool_liquidity: UserPoolLiquidity = test_env
+        .fetch_account(user_pool_liquidity_key(pool_id, user2.pubkey()))
+        .await;
+    assert_eq!(user2_pool_liquidity.lp_tokens_owned, u128::from(lp_amount));
+
+    // The receiving position had no rewards calculated yet, it only earns from the transfer on
+    test_env
+        .calculate_rewards(&user2, pool_id, reward_info_key)
+        .await;
+    let user2_reward_info: UserRewardInfo = test_env
+        .fetch_account(user_reward_info_key(reward_info_key, user2.pubkey()))
+        .await;
+    assert!(user2_reward_info.total_rewards < user_rewards_at_transfer / 100);
+
+    // Moving LP back without settlement accounts does not credit the past period either, the
+    // receiving owner agrees to it by signing
+    test_env.jump_seconds(100).await;
+    test_env
+        .try_transfer_liquidity(&user2, user.pubkey(), Some(&user), pool_id, lp_amount, &[])
+        .await
+        .unwrap();
+    test_env
+        .calculate_rewards(&user, pool_id, reward_info_key)
+        .await;
+    let user_reward_info: UserRewardInfo = test_env
+        .fetch_account(user_reward_info_key(reward_info_key, user.pubkey()))
+        .await;
+    assert!(
+        user_reward_info.total_rewards - user_rewards_at_transfer < user_rewards_at_transfer / 100
+    );
+}
+
+#[tokio::test]
+async fn third_party_can_not_reset_the_reward_clock_of_a_position() {
+    let user = Keypair::new();
+    let user2 = Keypair::new();
+    let admin = get_admin();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![user.pubkey(), user2.pubkey(), admin.pubkey()]).await;
+    test_env
+        .create_config(&admin, amm_index, 100, 20, 5, 0)
+        .await;
+
+    for mint in [test_env.token_0_mint, test_env.token_1_mint] {
+        let token_account = test_env
+            .get_or_create_associated_token_account(user.pubkey(), mint, &user)
+            .await;
+        test_env.mint_base_tokens(token_account, 100000, mint).await;
+    }
+    let pool_id = test_env
+        .initialize_pool(
+            &user,
+            amm_index,
+            1000,
+            2000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+
+    let user_pool_liquidity: UserPoolLiquidity = test_env
+        .fetch_account(user_pool_liquidity_key(pool_id, user.pubkey()))
+        .await;
+    let lp_amount = (user_pool_liquidity.lp_tokens_owned / 2) as u64;
+    test_env
+        .transfer_liquidity(&user, user2.pubkey(), pool_id, lp_amount, &[])
+        .await;
+
+    // Sending LP to a position holding LP would forfeit its unsettled rewards
+    test_env.jump_seconds(1000).await;
+    let user_pool_liquidity: UserPoolLiquidity = test_env
+        .fetch_account(user_pool_liquidity_key(pool_id, user.pubkey()))
+        .await;
+    assert!(test_env
+        .try_transfer_liquidity(&user2, user.pubkey(), None, pool_id, 1, &[])
+        .await
+        .is_err());
+    let user_pool_liquidity_after: UserPoolLiquidity = test_env
+        .fetch_account(user_pool_liquidity_key(pool_id, user.pubkey()))
+        .await;
+    assert_eq!(
+        user_pool_liquidity_after.first_investment_at,
+        user_pool_liquidity.first_investment_at
+    );
+    assert_eq!(
+        user_pool_liquidity_after.lp_tokens_owned,
+        user_pool_liquidity.lp_tokens_owned
+    );
+
+    // With the signature of the receiving owner the LP is moved and its clock reset
+    test_env
+        .try_transfer_liquidity(&user2, user.pubkey(), Some(&user), pool_id, 1, &[])
+        .await
+        .unwrap();
+    let user_pool_liquidity_after: UserPoolLiquidity = test_env
+        .fetch_account(user_pool_liquidity_key(pool_id, user.pubkey()))
+        .await;
+    assert_eq!(
+        user_pool_liquidity_after.lp_tokens_owned,
+        user_pool_liquidity.lp_tokens_owned + 1
+    );
+    assert_eq!(
+        user_pool_liquidity_after.first_investment_at,
+        test_env.timestamp_now().await as u64
+    );
+}
@@ -0,0 +1,168 @@
+use gamma::error::GammaError;
+use gamma::states::{AmmConfig, ConfigRole, ConfigUpdate, RoleTransferEvent, AMM_CONFIG_SEED};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+fn amm_config_address(amm_index: u16) -> Pubkey {
+    Pubkey::find_program_address(
+        &[AMM_CONFIG_SEED.as_bytes(), &amm_index.to_be_bytes()],
+        &gamma::id(),
+    )
+    .0
+}
+
+#[tokio::test]
+async fn should_only_accept_role_by_the_proposed_key() {
+    let admin = get_admin();
+    let protocol_owner = Keypair::new();
+    let other = Keypair::new();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![
+        admin.pubkey(),
+        protocol_owner.pubkey(),
+        other.pubkey(),
+    ])
+    .await;
+    test_env
+        .create_config(&admin, amm_index, 3000, 2000, 50, 0)
+        .await;
+    let amm_config_id = amm_config_address(amm_index);
+    let old_protocol_owner = test_env
+        .fetch_account::<AmmConfig>(amm_config_id)
+        .await
+        .protocol_owner;
+
+    // Nothing to accept before a proposal
+    let result = test_env.try_accept_role(&protocol_owner, amm_index).await;
+    assert_error!(result, GammaError::InvalidOwner);
+
+    let result = test_env
+        .try_apply_amm_config_update(
+            &admin,
+            amm_index,
+            ConfigUpdate::ProtocolOwner {
+                protocol_owner: protocol_owner.pubkey(),
+            },
+        )
+        .await;
+    result.unwrap_transaction();
+    let events: Vec<RoleTransferEvent> = decode_events(&result);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].amm_config, amm_config_id);
+    assert_eq!(events[0].authority, admin.pubkey());
+    assert_eq!(events[0].role, ConfigRole::ProtocolOwner as u8);
+    assert_eq!(events[0].current_key, old_protocol_owner);
+    assert_eq!(events[0].pending_key, protocol_owner.pubkey());
+    assert_eq!(events[0].action, 0);
+
+    // The proposal does not move the role, only the proposed key can accept it
+    let amm_config: AmmConfig = test_env.fetch_account(amm_config_id).await;
+    assert_eq!(amm_config.protocol_owner, old_protocol_owner);
+    let result = test_env.try_accept_role(&other, amm_index).await;
+    assert_error!(result, GammaError::InvalidOwner);
+    let result = test_env.try_accept_role(&admin, amm_index).await;
+    assert_error!(result, GammaError::InvalidOwner);
+
+    let result = test_env.try_accept_role(&protocol_owner, amm_index).await;
+    result.unwrap_transaction();
+    let events: Vec<RoleTransferEvent> = decode_events(&result);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].authority, protocol_owner.pubkey());
+    assert_eq!(events[0].role, ConfigRole::ProtocolOwner as u8);
+    assert_eq!(events[0].current_key, old_protocol_owner);
+    assert_eq!(events[0].pending_key, protocol_owner.pubkey());
+    assert_eq!(events[0].action, 1);
+
+    let amm_config: AmmConfig = test_env.fetch_account(amm_config_id).await;
+    assert_eq!(amm_config.protocol_owner, protocol_owner.pubkey());
+    assert_eq!(amm_config.pending_role, 0);
+    assert_eq!(amm_config.pending_key, Pubkey::default());
+}
+
+#[tokio::test]
+async fn should_reject_a_second_proposal_until_the_pending_one_is_cancelled() {
+    let admin = get_admin();
+    let fund_owner = Keypair::new();
+    let secondary_admin = Keypair::new();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![
+        admin.pubkey(),
+        fund_owner.pubkey(),
+        secondary_admin.pubkey(),
+    ])
+    .await;
+    test_env
+        .create_config(&admin, amm_index, 3000, 2000, 50, 0)
+        .await;
+    let amm_config_id = amm_config_address(amm_index);
+
+    let result = test_env.try_cancel_role_transfer(&admin, amm_index).await;
+    assert_error!(result, GammaError::NoPendingRoleTransfer);
+
+    test_env
+        .apply_amm_config_update(
+            &admin,
+            amm_index,
+            ConfigUpdate::FundOwner {
+                fund_owner: fund_owner.pubkey(),
+            },
+        )
+        .await;
+
+    // A proposal for any role is rejected while another one is pending
+    let result = test_env
+        .try_apply_amm_config_update(
+            &admin,
+            amm_index,
+            ConfigUpdate::SecondaryAdmin {
+                secondary_admin: secondary_admin.pubkey(),
+            },
+        )
+        .await;
+    assert_error!(result, GammaError::RoleTransferPending);
+    let result = test_env
+        .try_update_amm_config(&admin, amm_index, 4, 0, Some(secondary_admin.pubkey()))
+        .await;
+    assert_error!(result, GammaError::RoleTransferPending);
+    let amm_config: AmmConfig = test_env.fetch_account(amm_config_id).await;
+    assert_eq!(amm_config.pending_role, ConfigRole::FundOwner as u8);
+    assert_eq!(amm_config.pending_key, fund_owner.pubkey());
+
+    // Only a role admin can cancel it
+    let result = test_env
+        .try_cancel_role_transfer(&fund_owner, amm_index)
+        .await;
+    assert_error!(result, GammaError::InvalidOwner);
+    let result = test_env.try_cancel_role_transfer(&admin, amm_index).await;
+    result.unwrap_transaction();
+    let events: Vec<RoleTransferEvent> = decode_events(&result);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].authority, admin.pubkey());
+    assert_eq!(events[0].role, ConfigRole::FundOwner as u8);
+    assert_eq!(events[0].current_key, amm_config.fund_owner);
+    assert_eq!(events[0].pending_key, fund_owner.pubkey());
+    assert_eq!(events[0].action, 2);
+
+    // The cancelled key can't accept anymore, a new proposal goes through
+    let result = test_env.try_accept_role(&fund_owner, amm_index).await;
+    assert_error!(result, GammaError::InvalidOwner);
+    test_env
+        .apply_amm_config_update(
+            &admin,
+            amm_index,
+            ConfigUpdate::SecondaryAdmin {
+                secondary_admin: secondary_admin.pubkey(),
+            },
+        )
+        .await;
+    test_env
+        .try_accept_role(&secondary_admin, amm_index)
+        .await
+        .unwrap_transaction();
+    let amm_config: AmmConfig = test_env.fetch_account(amm_config_id).await;
+    assert_eq!(amm_config.secondary_admin, secondary_admin.pubkey());
+    assert_ne!(amm_config.fund_owner, fund_owner.pubkey());
+}
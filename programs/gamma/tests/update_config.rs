@@ -0,0 +1,141 @@
+use gamma::error::GammaError;
+use gamma::fees::FEE_RATE_DENOMINATOR_VALUE;
+use gamma::states::{AmmConfig, ConfigRole, ConfigUpdate, AMM_CONFIG_SEED};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+fn amm_config_address(amm_index: u16) -> Pubkey {
+    Pubkey::find_program_address(
+        &[AMM_CONFIG_SEED.as_bytes(), &amm_index.to_be_bytes()],
+        &gamma::id(),
+    )
+    .0
+}
+
+#[tokio::test]
+async fn should_map_legacy_params_to_config_updates() {
+    let admin = get_admin();
+    let new_key = Keypair::new();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![admin.pubkey()]).await;
+    test_env
+        .create_config(&admin, amm_index, 3000, 2000, 50, 0)
+        .await;
+    let amm_config_id = amm_config_address(amm_index);
+
+    for (param, value) in [(0, 4000), (1, 3000), (2, 100), (5, 10), (6, 1), (7, 3600)] {
+        test_env
+            .try_update_amm_config(&admin, amm_index, param, value, None)
+            .await
+            .unwrap_transaction();
+    }
+    let amm_config: AmmConfig = test_env.fetch_account(amm_config_id).await;
+    assert_eq!(amm_config.trade_fee_rate, 4000);
+    assert_eq!(amm_config.protocol_fee_rate, 3000);
+    assert_eq!(amm_config.fund_fee_rate, 100);
+    assert_eq!(amm_config.create_pool_fee, 10);
+    assert!(amm_config.disable_create_pool);
+    assert_eq!(amm_config.max_open_time, 3600);
+
+    // The role params read the key from the remaining accounts and only propose it
+    for (param, role) in [
+        (3, ConfigRole::ProtocolOwner),
+        (4, ConfigRole::FundOwner),
+        (8, ConfigRole::SecondaryAdmin),
+    ] {
+        let result = test_env
+            .try_update_amm_config(&admin, amm_index, param, 0, None)
+            .await;
+        assert_error!(result, GammaError::InvalidInput);
+
+        test_env
+            .try_update_amm_config(&admin, amm_index, param, 0, Some(new_key.pubkey()))
+            .await
+            .unwrap_transaction();
+        let amm_config: AmmConfig = test_env.fetch_account(amm_config_id).await;
+        assert_eq!(amm_config.pending_role, role as u8);
+        assert_eq!(amm_config.pending_key, new_key.pubkey());
+        assert_ne!(amm_config.role_key(role), new_key.pubkey());
+        test_env
+            .try_cancel_role_transfer(&admin, amm_index)
+            .await
+            .unwrap_transaction();
+    }
+
+    // Params without an update are rejected
+    let result = test_env
+        .try_update_amm_config(&admin, amm_index, 9, 0, None)
+        .await;
+    assert_error!(result, GammaError::InvalidInput);
+}
+
+#[tokio::test]
+async fn should_apply_typed_config_updates_within_bounds() {
+    let admin = get_admin();
+    let user = Keypair::new();
+    let amm_index = 0;
+    let mut test_env = TestEnv::new(vec![admin.pubkey(), user.pubkey()]).await;
+    test_env
+        .create_config(&admin, amm_index, 3000, 2000, 50, 0)
+        .await;
+    let amm_config_id = amm_config_address(amm_index);
+
+    test_env
+        .apply_amm_config_update(
+            &admin,
+            amm_index,
+            ConfigUpdate::TradeFeeRate {
+                trade_fee_rate: FEE_RATE_DENOMINATOR_VALUE,
+            },
+        )
+        .await;
+    test_env
+        .apply_amm_config_update(
+            &admin,
+            amm_index,
+            ConfigUpdate::CreatorLpLockDuration {
+                creator_lp_lock_duration: 86400,
+            },
+        )
+        .await;
+    let amm_config: AmmConfig = test_env.fetch_account(amm_config_id).await;
+    assert_eq!(amm_config.trade_fee_rate, FEE_RATE_DENOMINATOR_VALUE);
+    assert_eq!(amm_config.creator_lp_lock_duration, 86400);
+
+    let result = test_env
+        .try_apply_amm_config_update(
+            &admin,
+            amm_index,
+            ConfigUpdate::TradeFeeRate {
+                trade_fee_rate: FEE_RATE_DENOMINATOR_VALUE + 1,
+            },
+        )
+        .await;
+    assert_error!(result, GammaError::InvalidFee);
+    // The protocol and fund fees share the fee they are taken from
+    let result = test_env
+        .try_apply_amm_config_update(
+            &admin,
+            amm_index,
+            ConfigUpdate::ProtocolFeeRate {
+                protocol_fee_rate: FEE_RATE_DENOMINATOR_VALUE,
+            },
+        )
+        .await;
+    assert_error!(result, GammaError::InvalidFee);
+
+    // Only the admin, or a key with the capability of the update once roles exist
+    let result = test_env
+        .try_apply_amm_config_update(
+            &user,
+            amm_index,
+            ConfigUpdate::MaxOpenTime {
+                max_open_time: 3600,
+            },
+        )
+        .await;
+    assert_error!(result, GammaError::InvalidOwner);
+}
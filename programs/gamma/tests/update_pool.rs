@@ -0,0 +1,186 @@
+use gamma::error::GammaError;
+use gamma::fees::{FEE_RATE_DENOMINATOR_VALUE, MAX_SHARED_WITH_KAMINO_RATE};
+use gamma::states::{OracleUpdateMode, PoolState, PoolUpdate, MAX_GUARDIAN_COOLDOWN};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+mod utils;
+
+use utils::*;
+
+async fn setup_pool(test_env: &mut TestEnv, user: &Keypair, admin: &Keypair) -> Pubkey {
+    test_env.create_config(admin, 0, 3000, 2000, 50, 0).await;
+
+    let token_0_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_0_mint, user)
+        .await;
+    test_env
+        .mint_base_tokens(token_0_account, 100000000000000, test_env.token_0_mint)
+        .await;
+    let token_1_account = test_env
+        .get_or_create_associated_token_account(user.pubkey(), test_env.token_1_mint, user)
+        .await;
+    test_env
+        .mint_base_tokens(token_1_account, 100000000000000, test_env.token_1_mint)
+        .await;
+
+    let pool_id = test_env
+        .initialize_pool(
+            user,
+            0,
+            200000000,
+            100000000,
+            0,
+            gamma::create_pool_fee_reveiver::id(),
+        )
+        .await;
+    test_env.jump_seconds(100).await;
+    pool_id
+}
+
+#[tokio::test]
+async fn should_map_legacy_params_to_pool_updates() {
+    let admin = get_admin();
+    let user = Keypair::new();
+    let mut test_env = TestEnv::new(vec![admin.pubkey(), user.pubkey()]).await;
+    let pool_id = setup_pool(&mut test_env, &user, &admin).await;
+
+    for (param, value) in [
+        (0, 1 << 2),
+        (1, 50000),
+        (2, 200000),
+        (3, MAX_SHARED_WITH_KAMINO_RATE),
+        (4, 1000),
+        (6, 100000),
+        (7, 600),
+        (9, OracleUpdateMode::PostSwapPerSlot as u64),
+        (10, 1),
+    ] {
+        test_env.update_pool(&admin, pool_id, 0, param, value).await;
+    }
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.status, 1 << 2);
+    assert_eq_with_copy!(pool_state.max_trade_fee_rate, 50000);
+    assert_eq_with_copy!(pool_state.volatility_factor, 200000);
+    assert_eq_with_copy!(pool_state.max_shared_token0, MAX_SHARED_WITH_KAMINO_RATE);
+    assert_eq_with_copy!(pool_state.max_shared_token1, 1000);
+    assert_eq_with_copy!(pool_state.guardian_deviation_threshold, 100000);
+    assert_eq_with_copy!(pool_state.guardian_cooldown, 600);
+    assert_eq_with_copy!(
+        pool_state.oracle_update_mode,
+        OracleUpdateMode::PostSwapPerSlot as u8
+    );
+    assert_eq_with_copy!(pool_state.verified, 1);
+
+    test_env.jump_seconds(100).await;
+    test_env.update_pool(&admin, pool_id, 0, 5, 0).await;
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.open_time, test_env.timestamp_now().await as u64);
+
+    // Values that don't fit the field of the update and params without one are rejected
+    let result = test_env.try_update_pool(&admin, pool_id, 0, 0, 256).await;
+    assert_error!(result, GammaError::InvalidInput);
+    let result = test_env
+        .try_update_pool(&admin, pool_id, 0, 7, u64::from(u32::MAX) + 1)
+        .await;
+    assert_error!(result, GammaError::InvalidInput);
+    let result = test_env.try_update_pool(&admin, pool_id, 0, 11, 0).await;
+    assert_error!(result, GammaError::InvalidInput);
+}
+
+#[tokio::test]
+async fn should_apply_typed_pool_updates_within_bounds() {
+    let admin = get_admin();
+    let user = Keypair::new();
+    let mut test_env = TestEnv::new(vec![admin.pubkey(), user.pubkey()]).await;
+    let pool_id = setup_pool(&mut test_env, &user, &admin).await;
+
+    test_env
+        .try_apply_pool_update(
+            &admin,
+            pool_id,
+            0,
+            PoolUpdate::VolatilityFactor {
+                volatility_factor: FEE_RATE_DENOMINATOR_VALUE,
+            },
+        )
+        .await
+        .unwrap_transaction();
+    test_env
+        .try_apply_pool_update(
+            &admin,
+            pool_id,
+            0,
+            PoolUpdate::GuardianCooldown {
+                guardian_cooldown: MAX_GUARDIAN_COOLDOWN as u32,
+            },
+        )
+        .await
+        .unwrap_transaction();
+    let pool_state: PoolState = test_env.fetch_account(pool_id).await;
+    assert_eq_with_copy!(pool_state.volatility_factor, FEE_RATE_DENOMINATOR_VALUE);
+    assert_eq_with_copy!(pool_state.guardian_cooldown as u64, MAX_GUARDIAN_COOLDOWN);
+
+    let result = test_env
+        .try_apply_pool_update(
+            &admin,
+            pool_id,
+            0,
+            PoolUpdate::VolatilityFactor {
+                volatility_factor: FEE_RATE_DENOMINATOR_VALUE + 1,
+            },
+        )
+        .await;
+    assert_error!(result, GammaError::InvalidInput);
+    let result = test_env
+        .try_apply_pool_update(
+            &admin,
+            pool_id,
+            0,
+            PoolUpdate::GuardianCooldown {
+                guardian_cooldown: MAX_GUARDIAN_COOLDOWN as u32 + 1,
+            },
+        )
+        .await;
+    assert_error!(result, GammaError::InvalidInput);
+    let result = test_env
+        .try_apply_pool_update(
+            &admin,
+            pool_id,
+            0,
+            PoolUpdate::MaxTradeFeeRate {
+                max_trade_fee_rate: FEE_RATE_DENOMINATOR_VALUE,
+            },
+        )
+        .await;
+    assert_error!(result, GammaError::InvalidFee);
+    let result = test_env
+        .try_apply_pool_update(
+            &admin,
+            pool_id,
+            0,
+            PoolUpdate::MaxSharedToken0 {
+                max_shared_token0: MAX_SHARED_WITH_KAMINO_RATE + 1,
+            },
+        )
+        .await;
+    assert_error!(result, GammaError::InvalidInput);
+
+    // The legacy params go through the same bounds
+    let result = test_env
+        .try_update_pool(&admin, pool_id, 0, 2, FEE_RATE_DENOMINATOR_VALUE + 1)
+        .await;
+    assert_error!(result, GammaError::InvalidInput);
+
+    // Only the admin, or a key with the capability of the update once roles exist
+    let result = test_env
+        .try_apply_pool_update(
+            &user,
+            pool_id,
+            0,
+            PoolUpdate::VolatilityFactor {
+                volatility_factor: 1,
+            },
+        )
+        .await;
+    assert_error!(result, GammaError::InvalidOwner);
+}
@@ -1,9 +1,10 @@
 use anchor_lang::AccountDeserialize;
 use anyhow::{anyhow, Context, Result};
+use gamma::curve::TradeDirection;
 use gamma::states::{ObservationState, PoolStatusBitIndex};
 use jupiter_amm_interface::{
     try_get_account_data, AccountMap, Amm, AmmContext, KeyedAccount, Quote, QuoteParams,
-    SwapAndAccountMetas, SwapParams,
+    SwapAndAccountMetas, SwapMode, SwapParams,
 };
 use rust_decimal::prelude::FromPrimitive;
 use spl_token_2022::extension::BaseStateWithExtensions;
@@ -163,6 +164,10 @@ impl Amm for Gamma {
 
     fn quote(&self, quote_params: &QuoteParams) -> Result<Quote> {
         if !self.pool_state.get_status_by_bit(PoolStatusBitIndex::Swap)
+            || (quote_params.swap_mode == SwapMode::ExactOut
+                && !self
+                    .pool_state
+                    .get_status_by_bit(PoolStatusBitIndex::SwapBaseOutput))
             || (self.timestamp.load(std::sync::atomic::Ordering::Relaxed) as u64)
                 < self.pool_state.open_time
         {
@@ -172,6 +177,24 @@ impl Amm for Gamma {
         let amm_config = self.amm_config.as_ref().context("Missing AmmConfig")?;
 
         let zero_for_one: bool = quote_params.input_mint == self.pool_state.token_0_mint;
+        let trade_direction = if zero_for_one {
+            TradeDirection::ZeroForOne
+        } else {
+            TradeDirection::OneForZero
+        };
+        let guardian_halted_until = self.pool_state.guardian_halted_until;
+        let guardian_halt_expired = guardian_halted_until != 0
+            && self.timestamp.load(std::sync::atomic::Ordering::Relaxed) as u64
+                >= guardian_halted_until;
+        let direction_bit = 1u8 << (PoolStatusBitIndex::swap_direction(trade_direction) as u8);
+        let guardian_status = if guardian_halt_expired {
+            0
+        } else {
+            self.pool_state.guardian_status
+        };
+        if (self.pool_state.status | guardian_status) & direction_bit != 0 {
+            return Err(anyhow!("Swaps in this direction are halted"));
+        }
 
         if self.token_mints_and_token_programs.is_none() {
             return Err(anyhow!("Missing token mints and token programs"));
@@ -206,57 +229,113 @@ impl Amm for Gamma {
 
         let amount = quote_params.amount;
         let epoch = self.epoch.load(std::sync::atomic::Ordering::Relaxed);
-
-        let actual_amount_in = if let Some(transfer_fee_config) = source_mint_transfer_fee_config {
-            amount.saturating_sub(
-                transfer_fee_config
-                    .calculate_epoch_fee(epoch, amount)
-                    .context("Fee calculation failure")?,
-            )
-        } else {
-            amount
-        };
-        if actual_amount_in == 0 {
-            return Err(anyhow!("Amount too low"));
-        }
+        let timestamp = self.timestamp.load(std::sync::atomic::Ordering::Relaxed) as u64;
+        let observation_state = self
+            .observation_state
+            .as_ref()
+            .context("Missing observation state")?;
 
         // Calculate the trade amounts
         let (total_token_0_amount, total_token_1_amount) =
             vault_amount_without_fee(&self.pool_state)?;
+        let (total_input_token_amount, total_output_token_amount) = if zero_for_one {
+            (total_token_0_amount, total_token_1_amount)
+        } else {
+            (total_token_1_amount, total_token_0_amount)
+        };
 
-        let result = gamma::curve::CurveCalculator::swap_base_input(
-            actual_amount_in.into(),
-            if zero_for_one {
-                total_token_0_amount.into()
-            } else {
-                total_token_1_amount.into()
-            },
-            if zero_for_one {
-                total_token_1_amount.into()
-            } else {
-                total_token_0_amount.into()
-            },
-            &amm_config,
-            &self.pool_state,
-            self.timestamp.load(std::sync::atomic::Ordering::Relaxed) as u64,
-            self.observation_state
-                .as_ref()
-                .context("Missing observation state")?,
-            false,
-        )
-        .context("swap failed")?;
-
-        let amount_out: u64 = result.destination_amount_swapped.try_into()?;
-        let actual_amount_out =
-            if let Some(transfer_fee_config) = destination_mint_transfer_fee_config {
-                amount_out.saturating_sub(
-                    transfer_fee_config
-                        .calculate_epoch_fee(epoch, amount_out)
-                        .context("Fee calculation failure")?,
+        let (result, actual_amount_in, actual_amount_out) = match quote_params.swap_mode {
+            SwapMode::ExactIn => {
+                let actual_amount_in =
+                    if let Some(transfer_fee_config) = source_mint_transfer_fee_config {
+                        amount.saturating_sub(
+                            transfer_fee_config
+                                .calculate_epoch_fee(epoch, amount)
+                                .context("Fee calculation failure")?,
+                        )
+                    } else {
+                        amount
+                    };
+                if actual_amount_in == 0 {
+                    return Err(anyhow!("Amount too low"));
+                }
+
+                let result = gamma::curve::CurveCalculator::swap_base_input(
+                    actual_amount_in.into(),
+                    total_input_token_amount.into(),
+                    total_output_token_amount.into(),
+                    &amm_config,
+                    &self.pool_state,
+                    timestamp,
+                    observation_state,
+                    false,
                 )
-            } else {
-                amount_out
-            };
+                .context("swap failed")?;
+
+                let amount_out: u64 = result.destination_amount_swapped.try_into()?;
+                let actual_amount_out =
+                    if let Some(transfer_fee_config) = destination_mint_transfer_fee_config {
+                        amount_out.saturating_sub(
+                            transfer_fee_config
+                                .calculate_epoch_fee(epoch, amount_out)
+                                .context("Fee calculation failure")?,
+                        )
+                    } else {
+                        amount_out
+                    };
+                (result, actual_amount_in, actual_amount_out)
+            }
+            SwapMode::ExactOut => {
+                if amount == 0 {
+                    return Err(anyhow!("Amount too low"));
+                }
+                // The vault sends the output plus its transfer fee
+                let amount_out =
+                    if let Some(transfer_fee_config) = destination_mint_transfer_fee_config {
+                        amount
+                            .checked_add(
+                                transfer_fee_config
+                                    .calculate_inverse_epoch_fee(epoch, amount)
+                                    .context("Fee calculation failure")?,
+                            )
+                            .ok_or(anyhow!("Math overflow"))?
+                    } else {
+                        amount
+                    };
+
+                let result = gamma::curve::CurveCalculator::swap_base_output(
+                    amount_out.into(),
+                    total_input_token_amount.into(),
+                    total_output_token_amount.into(),
+                    &amm_config,
+                    &self.pool_state,
+                    timestamp,
+                    observation_state,
+                    false,
+                )
+                .context("swap failed")?;
+
+                // The user sends the input plus its transfer fee
+                let amount_in: u64 = result.source_amount_swapped.try_into()?;
+                let actual_amount_in =
+                    if let Some(transfer_fee_config) = source_mint_transfer_fee_config {
+                        amount_in
+                            .checked_add(
+                                transfer_fee_config
+                                    .calculate_inverse_epoch_fee(epoch, amount_in)
+                                    .context("Fee calculation failure")?,
+                            )
+                            .ok_or(anyhow!("Math overflow"))?
+                    } else {
+                        amount_in
+                    };
+                (result, actual_amount_in, amount)
+            }
+        };
+
+        self.pool_state
+            .check_reduce_only(observation_state, trade_direction, &result, timestamp)
+            .map_err(|_| anyhow!("Swap is not allowed in reduce-only mode"))?;
 
         Ok(Quote {
             in_amount: actual_amount_in,
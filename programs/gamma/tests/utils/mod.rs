@@ -1,27 +1,36 @@
 #![allow(dead_code)]
 pub mod jupiter;
 
-use anchor_spl::associated_token::get_associated_token_address;
-use anchor_spl::token::TokenAccount;
-use anchor_spl::token_2022::spl_token_2022;
+use anchor_spl::associated_token::{
+    get_associated_token_address, get_associated_token_address_with_program_id,
+};
+use anchor_spl::token_2022::spl_token_2022::{self, extension::ExtensionType};
+use anchor_spl::token_interface::TokenAccount;
 use gamma::curve::TradeDirection;
 use gamma::states::{
-    ObservationState, AMM_CONFIG_SEED, OBSERVATION_NUM, OBSERVATION_SEED, POOL_LP_MINT_SEED,
-    POOL_SEED, POOL_VAULT_SEED, USER_POOL_LIQUIDITY_SEED,
+    AmmConfig, ConfigUpdate, ObservationState, PoolPartner, PoolState, PoolStatistics, PoolUpdate,
+    QueuedAction, RewardInfo, UserPoolLiquidity, AMM_CONFIG_SEED, LP_PRICE_HISTORY_SEED,
+    MINT_ENTRY_SEED, MINT_REGISTRY_SEED, OBSERVATION_NUM, OBSERVATION_SEED, PARTNER_SEED,
+    POOL_LP_MINT_SEED, POOL_SEED, POOL_VAULT_SEED, POSITION_OPERATOR_SEED, QUEUED_UPDATE_SEED,
+    ROLES_SEED, USER_POOL_LIQUIDITY_SEED,
 };
 use gamma::{AUTH_SEED, REWARD_INFO_SEED, REWARD_VAULT_SEED, USER_REWARD_INFO_SEED};
 use solana_program_runtime::invoke_context::BuiltinFunctionWithContext;
-use solana_sdk::instruction::Instruction;
+use solana_sdk::instruction::{AccountMeta, Instruction};
 use solana_sdk::program_option::COption;
 use solana_sdk::program_pack::Pack;
 
 use anchor_lang::prelude::{Clock, Pubkey, Rent};
-use anchor_lang::{system_program, AccountDeserialize, InstructionData, ToAccountMetas};
+use anchor_lang::{
+    system_program, AccountDeserialize, AnchorDeserialize, Discriminator, InstructionData,
+    ToAccountMetas,
+};
+use base64::Engine;
 use solana_program_test::{
     BanksClientError, BanksTransactionResultWithMetadata, ProgramTest, ProgramTestBanksClientExt,
     ProgramTestContext,
 };
-use solana_sdk::account::Account;
+use solana_sdk::account::{Account, AccountSharedData};
 use solana_sdk::signature::Keypair;
 use solana_sdk::signer::Signer;
 use solana_sdk::transaction::Transaction;
@@ -87,6 +96,162 @@ pub fn get_admin() -> Keypair {
     Keypair::from_bytes(&TEST_ADMIN_KEYPAIR).unwrap()
 }
 
+pub fn partner_address(partner_id: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[PARTNER_SEED.as_bytes(), partner_id.to_le_bytes().as_ref()],
+        &gamma::ID,
+    )
+    .0
+}
+
+pub fn roles_address() -> Pubkey {
+    Pubkey::find_program_address(&[ROLES_SEED.as_bytes()], &gamma::ID).0
+}
+
+pub fn queued_update_address(amm_config: Pubkey, id: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            QUEUED_UPDATE_SEED.as_bytes(),
+            amm_config.as_ref(),
+            &id.to_le_bytes(),
+        ],
+        &gamma::id(),
+    )
+    .0
+}
+
+pub fn mint_registry_address(amm_config: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[MINT_REGISTRY_SEED.as_bytes(), amm_config.as_ref()],
+        &gamma::id(),
+    )
+    .0
+}
+
+pub fn mint_entry_address(amm_config: Pubkey, mint: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            MINT_ENTRY_SEED.as_bytes(),
+            amm_config.as_ref(),
+            mint.as_ref(),
+        ],
+        &gamma::id(),
+    )
+    .0
+}
+
+/// The registry and the entries of both mints, read by `check_pool_mints`
+pub fn mint_registry_accounts(
+    amm_config: Pubkey,
+    token_0_mint: Pubkey,
+    token_1_mint: Pubkey,
+) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(mint_registry_address(amm_config), false),
+        AccountMeta::new_readonly(mint_entry_address(amm_config, token_0_mint), false),
+        AccountMeta::new_readonly(mint_entry_address(amm_config, token_1_mint), false),
+    ]
+}
+
+pub fn lp_price_history_address(pool_id: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[LP_PRICE_HISTORY_SEED.as_bytes(), pool_id.as_ref()],
+        &gamma::id(),
+    )
+    .0
+}
+
+pub fn reward_info_address(pool_id: Pubkey, start_time: u64, reward_mint: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            REWARD_INFO_SEED.as_bytes(),
+            pool_id.as_ref(),
+            &start_time.to_le_bytes(),
+            reward_mint.as_ref(),
+        ],
+        &gamma::id(),
+    )
+    .0
+}
+
+pub fn lp_mint_address(pool_id: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[POOL_LP_MINT_SEED.as_bytes(), pool_id.as_ref()],
+        &gamma::id(),
+    )
+    .0
+}
+
+/// The PoolPartner of the position if it has a partner, followed by the reward_info and
+/// user_reward_info of `owner` for every reward to settle
+pub fn lp_wrap_remaining_accounts(
+    pool_id: Pubkey,
+    owner: Pubkey,
+    partner_id: Option<u64>,
+    reward_infos: &[Pubkey],
+) -> Vec<AccountMeta> {
+    let mut remaining_accounts = vec![];
+    if let Some(partner_id) = partner_id {
+        remaining_accounts.push(AccountMeta::new(
+            PoolPartner::find_address(pool_id, partner_id),
+            false,
+        ));
+    }
+    for reward_info in reward_infos {
+        let user_reward_info = Pubkey::find_program_address(
+            &[
+                USER_REWARD_INFO_SEED.as_bytes(),
+                reward_info.as_ref(),
+                owner.as_ref(),
+            ],
+            &gamma::id(),
+        )
+        .0;
+        remaining_accounts.extend([
+            AccountMeta::new_readonly(*reward_info, false),
+            AccountMeta::new(user_reward_info, false),
+        ]);
+    }
+    remaining_accounts
+}
+
+pub fn position_operator_address(pool_id: Pubkey, owner: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            POSITION_OPERATOR_SEED.as_bytes(),
+            pool_id.to_bytes().as_ref(),
+            owner.to_bytes().as_ref(),
+        ],
+        &gamma::ID,
+    )
+    .0
+}
+
+/// The PositionOperator account expected in the remaining accounts when an operator signs for the owner
+pub fn position_operator_accounts(
+    pool_id: Pubkey,
+    owner: Pubkey,
+    signer: Pubkey,
+) -> Vec<AccountMeta> {
+    if signer == owner {
+        return vec![];
+    }
+    vec![AccountMeta::new_readonly(
+        position_operator_address(pool_id, owner),
+        false,
+    )]
+}
+
+/// Remaining accounts of a swap without segmenter or referral
+pub fn swap_remaining_accounts(pool_id: Pubkey) -> Vec<AccountMeta> {
+    let mut remaining_accounts = vec![AccountMeta::new_readonly(gamma::id(), false); 4];
+    remaining_accounts.push(AccountMeta::new(
+        PoolStatistics::find_address(pool_id),
+        false,
+    ));
+    remaining_accounts
+}
+
 pub fn get_instruction<AnchorInstruction, AnchorAccounts>(
     data: AnchorInstruction,
     accounts: AnchorAccounts,
@@ -192,6 +357,26 @@ macro_rules! assert_eq_with_copy {
     };
 }
 
+/// Events of type `T` emitted by the transaction, decoded from its `Program data:` logs
+pub fn decode_events<T: Discriminator + AnchorDeserialize>(
+    result: &ProcessTransactionResult,
+) -> Vec<T> {
+    let metadata = result
+        .as_ref()
+        .expect("Failed to get transaction return data")
+        .metadata
+        .as_ref()
+        .expect("Failed to get transaction metadata");
+    metadata
+        .log_messages
+        .iter()
+        .filter_map(|log| log.strip_prefix("Program data: "))
+        .filter_map(|data| base64::engine::general_purpose::STANDARD.decode(data).ok())
+        .filter(|data| data.starts_with(&T::DISCRIMINATOR))
+        .map(|data| T::deserialize(&mut &data[T::DISCRIMINATOR.len()..]).unwrap())
+        .collect()
+}
+
 pub const INITIAL_ACCOUNT_LAMPORTS: u64 = 10_000_000_000_000;
 
 pub fn get_current_price_token_0_price(observation: ObservationState) -> u128 {
@@ -233,6 +418,10 @@ impl TestEnv {
         let mut program_test = ProgramTest::new("gamma", gamma::id(), None);
 
         for program in programs {
+            // Builtin processors are ignored when `test-sbf` prefers BPF programs
+            if program.process_instruction.is_some() {
+                program_test.prefer_bpf(false);
+            }
             program_test.add_program(
                 &program.program_name,
                 program.program_id,
@@ -327,6 +516,10 @@ impl TestEnv {
         let mut program_test = ProgramTest::new("gamma", gamma::id(), None);
 
         for program in programs {
+            // Builtin processors are ignored when `test-sbf` prefers BPF programs
+            if program.process_instruction.is_some() {
+                program_test.prefer_bpf(false);
+            }
             program_test.add_program(
                 &program.program_name,
                 program.program_id,
@@ -471,6 +664,86 @@ impl TestEnv {
             .expect("Failed to create token mint");
     }
 
+    /// `extension_instructions` initialize the extensions of the mint before the mint itself,
+    /// the mint authority is also its freeze authority
+    pub async fn create_token_2022_mint(
+        &mut self,
+        token_mint: &Keypair,
+        decimals: u8,
+        extensions: &[ExtensionType],
+        extension_instructions: Vec<Instruction>,
+    ) {
+        let space =
+            ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(extensions)
+                .unwrap();
+        let mut instructions = vec![solana_sdk::system_instruction::create_account(
+            &get_wallet().pubkey(),
+            &token_mint.pubkey(),
+            Rent::default().minimum_balance(space),
+            space as u64,
+            &spl_token_2022::id(),
+        )];
+        instructions.extend(extension_instructions);
+        instructions.push(
+            spl_token_2022::instruction::initialize_mint2(
+                &spl_token_2022::id(),
+                &token_mint.pubkey(),
+                &self.mint_authority.pubkey(),
+                Some(&self.mint_authority.pubkey()),
+                decimals,
+            )
+            .unwrap(),
+        );
+
+        let latest_blockhash = self
+            .program_test_context
+            .banks_client
+            .get_latest_blockhash()
+            .await
+            .unwrap();
+        self.program_test_context
+            .banks_client
+            .process_transaction(Transaction::new_signed_with_payer(
+                &instructions,
+                Some(&get_wallet().pubkey()),
+                &[&get_wallet(), token_mint],
+                latest_blockhash,
+            ))
+            .await
+            .expect("Failed to create token-2022 mint");
+    }
+
+    /// Replaces both pool mints with token-2022 mints,
+    /// `extension_instructions` returns the extension instructions of a mint
+    pub async fn use_token_2022_mints(
+        &mut self,
+        extensions: &[ExtensionType],
+        extension_instructions: impl Fn(&Pubkey) -> Vec<Instruction>,
+    ) {
+        let mut mints = [Keypair::new(), Keypair::new()];
+        mints.sort_by_key(|mint| mint.pubkey());
+        for mint in mints.iter() {
+            self.create_token_2022_mint(
+                mint,
+                6,
+                extensions,
+                extension_instructions(&mint.pubkey()),
+            )
+            .await;
+        }
+        self.token_0_mint = mints[0].pubkey();
+        self.token_1_mint = mints[1].pubkey();
+    }
+
+    /// The spl token program or token program 2022 owning the mint
+    pub async fn token_program_of(&mut self, mint: Pubkey) -> Pubkey {
+        self.get_account_info(mint)
+            .await
+            .unwrap()
+            .expect("Mint not found")
+            .owner
+    }
+
     pub async fn create_token_account(
         &mut self,
         account: &Keypair,
@@ -529,12 +802,14 @@ impl TestEnv {
             .get_latest_blockhash()
             .await
             .unwrap();
-        let associated_token_account = get_associated_token_address(account, mint);
+        let token_program = self.token_program_of(*mint).await;
+        let associated_token_account =
+            get_associated_token_address_with_program_id(account, mint, &token_program);
         let ix = spl_associated_token_account::instruction::create_associated_token_account(
             &payer.pubkey(),
             account,
             mint,
-            &anchor_spl::token::ID,
+            &token_program,
         );
 
         self.program_test_context
@@ -557,7 +832,9 @@ impl TestEnv {
         mint: Pubkey,
         payer: &Keypair,
     ) -> Pubkey {
-        let associated_token_account = get_associated_token_address(&account, &mint);
+        let token_program = self.token_program_of(mint).await;
+        let associated_token_account =
+            get_associated_token_address_with_program_id(&account, &mint, &token_program);
 
         let existing_account: Result<TokenAccount, BanksClientError> =
             self.try_fetch_account(associated_token_account).await;
@@ -609,8 +886,9 @@ impl TestEnv {
         amount: u64,
         token_mint: Pubkey,
     ) {
-        let mint_ix = anchor_spl::token::spl_token::instruction::mint_to(
-            &anchor_spl::token::spl_token::id(),
+        let token_program = self.token_program_of(token_mint).await;
+        let mint_ix = spl_token_2022::instruction::mint_to(
+            &token_program,
             &token_mint,
             &token_account,
             &self.mint_authority.pubkey(),
@@ -703,6 +981,27 @@ impl TestEnv {
         fund_fee_rate: u64,
         create_pool_fee: u64,
     ) {
+        self.try_create_config(
+            user,
+            amm_index,
+            trade_fee_rate,
+            protocol_fee_rate,
+            fund_fee_rate,
+            create_pool_fee,
+        )
+        .await
+        .unwrap();
+    }
+
+    pub async fn try_create_config(
+        &mut self,
+        user: &Keypair,
+        amm_index: u16,
+        trade_fee_rate: u64,
+        protocol_fee_rate: u64,
+        fund_fee_rate: u64,
+        create_pool_fee: u64,
+    ) -> Result<(), BanksClientError> {
         let (amm_config_key, __bump) = Pubkey::find_program_address(
             &[AMM_CONFIG_SEED.as_bytes(), &amm_index.to_be_bytes()],
             &gamma::ID,
@@ -711,6 +1010,7 @@ impl TestEnv {
         let accounts = gamma::accounts::CreateAmmConfig {
             owner: user.pubkey(),
             amm_config: amm_config_key,
+            roles: roles_address(),
             system_program: system_program::ID,
         };
 
@@ -722,6 +1022,8 @@ impl TestEnv {
             fund_fee_rate,
             create_pool_fee,
             max_open_time,
+            protocol_owner: user.pubkey(),
+            fund_owner: user.pubkey(),
         };
 
         let transaction = self
@@ -732,347 +1034,1844 @@ impl TestEnv {
             .banks_client
             .process_transaction(transaction)
             .await
-            .unwrap();
     }
 
-    pub async fn initialize_pool(
+    /// Creates a config with the default rates of the tests paying its fees to the given owners
+    pub async fn try_create_config_with_fee_owners(
         &mut self,
         user: &Keypair,
-        amm_config_index: u16,
-        init_amount_0: u64,
-        init_amount_1: u64,
-        open_time: u64,
-        create_pool_fee: Pubkey,
-    ) -> Pubkey {
+        amm_index: u16,
+        protocol_owner: Pubkey,
+        fund_owner: Pubkey,
+    ) -> ProcessTransactionResult {
         let (amm_config_key, __bump) = Pubkey::find_program_address(
-            &[AMM_CONFIG_SEED.as_bytes(), &amm_config_index.to_be_bytes()],
-            &gamma::ID,
-        );
-
-        let pool_account_key = Pubkey::find_program_address(
-            &[
-                POOL_SEED.as_bytes(),
-                amm_config_key.to_bytes().as_ref(),
-                self.token_0_mint.to_bytes().as_ref(),
-                self.token_1_mint.to_bytes().as_ref(),
-            ],
-            &gamma::ID,
-        )
-        .0;
-        let (authority, __bump) = Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::ID);
-        let (token_0_vault, __bump) = Pubkey::find_program_address(
-            &[
-                POOL_VAULT_SEED.as_bytes(),
-                pool_account_key.to_bytes().as_ref(),
-                self.token_0_mint.to_bytes().as_ref(),
-            ],
-            &gamma::ID,
-        );
-        let (token_1_vault, __bump) = Pubkey::find_program_address(
-            &[
-                POOL_VAULT_SEED.as_bytes(),
-                pool_account_key.to_bytes().as_ref(),
-                self.token_1_mint.to_bytes().as_ref(),
-            ],
-            &gamma::ID,
-        );
-        let (_lp_mint_key, __bump) = Pubkey::find_program_address(
-            &[
-                POOL_LP_MINT_SEED.as_bytes(),
-                pool_account_key.to_bytes().as_ref(),
-            ],
-            &gamma::ID,
-        );
-        let (observation_key, __bump) = Pubkey::find_program_address(
-            &[
-                OBSERVATION_SEED.as_bytes(),
-                pool_account_key.to_bytes().as_ref(),
-            ],
+            &[AMM_CONFIG_SEED.as_bytes(), &amm_index.to_be_bytes()],
             &gamma::ID,
         );
-        let user_token_0_account = self
-            .get_or_create_associated_token_account(user.pubkey(), self.token_0_mint.clone(), &user)
-            .await;
-
-        let user_token_1_account = self
-            .get_or_create_associated_token_account(user.pubkey(), self.token_1_mint.clone(), &user)
-            .await;
-        let user_pool_liquidity = Pubkey::find_program_address(
-            &[
-                USER_POOL_LIQUIDITY_SEED.as_bytes(),
-                pool_account_key.to_bytes().as_ref(),
-                user.pubkey().to_bytes().as_ref(),
-            ],
-            &gamma::ID,
-        )
-        .0;
 
-        let accounts = gamma::accounts::Initialize {
-            creator: user.pubkey(),
+        let accounts = gamma::accounts::CreateAmmConfig {
+            owner: user.pubkey(),
             amm_config: amm_config_key,
-            authority,
-            pool_state: pool_account_key,
-            user_pool_liquidity,
-            token_0_mint: self.token_0_mint,
-            token_1_mint: self.token_1_mint,
-            creator_token_0: user_token_0_account,
-            creator_token_1: user_token_1_account,
-            token_0_vault,
-            token_1_vault,
-            create_pool_fee: create_pool_fee,
-            observation_state: observation_key,
-            token_program: spl_token::id(),
-            token_0_program: spl_token::id(),
-            token_1_program: spl_token::id(),
-            associated_token_program: spl_associated_token_account::id(),
+            roles: roles_address(),
             system_program: system_program::ID,
-            rent: sysvar::rent::id(),
         };
-
-        let data = gamma::instruction::Initialize {
-            init_amount_0,
-            init_amount_1,
-            open_time,
-            max_trade_fee_rate: 0,
-            volatility_factor: 0,
+        let data = gamma::instruction::CreateAmmConfig {
+            index: amm_index,
+            trade_fee_rate: 3000,
+            protocol_fee_rate: 2000,
+            fund_fee_rate: 50,
+            create_pool_fee: 0,
+            max_open_time: 60 * 60 * 24 * 5,
+            protocol_owner,
+            fund_owner,
         };
 
         let transaction = self
             .encode_instruction_and_sign_transaction(data, accounts, user)
             .await;
 
+        self.program_test_context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+    }
+
+    pub async fn init_roles(&mut self, admin: &Keypair) {
+        let accounts = gamma::accounts::InitRoles {
+            owner: admin.pubkey(),
+            roles: roles_address(),
+            system_program: system_program::ID,
+        };
+        let data = gamma::instruction::InitRoles {};
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, admin)
+            .await;
+
         self.program_test_context
             .banks_client
             .process_transaction(transaction)
             .await
             .unwrap();
+    }
+
+    pub async fn try_set_capabilities(
+        &mut self,
+        authority: &Keypair,
+        member: Pubkey,
+        capabilities: u32,
+    ) -> Result<(), BanksClientError> {
+        let accounts = gamma::accounts::SetCapabilities {
+            authority: authority.pubkey(),
+            roles: roles_address(),
+        };
+        let data = gamma::instruction::SetCapabilities {
+            member,
+            capabilities,
+        };
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, authority)
+            .await;
 
-        pool_account_key
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
     }
 
-    pub async fn deposit(
+    pub async fn apply_amm_config_update(
         &mut self,
-        user: &Keypair,
-        pool_id: Pubkey,
-        amm_config_index: u16,
-        lp_token_amount: u64,
-        maximum_token_0_amount: u64,
-        maximum_token_1_amount: u64,
+        authority: &Keypair,
+        amm_index: u16,
+        update: ConfigUpdate,
     ) {
+        self.try_apply_amm_config_update(authority, amm_index, update)
+            .await
+            .unwrap_transaction();
+    }
+
+    /// Updates the config through the numeric `param` dispatch, `key` is passed as the remaining
+    /// account read by the role params
+    pub async fn try_update_amm_config(
+        &mut self,
+        authority: &Keypair,
+        amm_index: u16,
+        param: u16,
+        value: u64,
+        key: Option<Pubkey>,
+    ) -> ProcessTransactionResult {
         let (amm_config_key, __bump) = Pubkey::find_program_address(
-            &[AMM_CONFIG_SEED.as_bytes(), &amm_config_index.to_be_bytes()],
+            &[AMM_CONFIG_SEED.as_bytes(), &amm_index.to_be_bytes()],
             &gamma::ID,
         );
 
-        let (pool_account_key, __bump) = Pubkey::find_program_address(
-            &[
-                POOL_SEED.as_bytes(),
-                amm_config_key.to_bytes().as_ref(),
-                self.token_0_mint.to_bytes().as_ref(),
-                self.token_1_mint.to_bytes().as_ref(),
-            ],
-            &gamma::ID,
-        );
+        let accounts = gamma::accounts::UpdateAmmConfig {
+            owner: authority.pubkey(),
+            amm_config: amm_config_key,
+            roles: roles_address(),
+        };
+        let data = gamma::instruction::UpdateAmmConfig { param, value };
 
-        let (token_0_vault, __bump) = Pubkey::find_program_address(
-            &[
-                POOL_VAULT_SEED.as_bytes(),
-                pool_account_key.to_bytes().as_ref(),
-                self.token_0_mint.to_bytes().as_ref(),
-            ],
+        let mut instruction = get_instruction(data, accounts);
+        if let Some(key) = key {
+            instruction
+                .accounts
+                .push(AccountMeta::new_readonly(key, false));
+        }
+        let transaction =
+            get_signed_transaction(&mut self.program_test_context, &[instruction], authority).await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+    }
+
+    pub async fn try_apply_amm_config_update(
+        &mut self,
+        authority: &Keypair,
+        amm_index: u16,
+        update: ConfigUpdate,
+    ) -> ProcessTransactionResult {
+        let (amm_config_key, __bump) = Pubkey::find_program_address(
+            &[AMM_CONFIG_SEED.as_bytes(), &amm_index.to_be_bytes()],
             &gamma::ID,
         );
-        let (token_1_vault, __bump) = Pubkey::find_program_address(
-            &[
-                POOL_VAULT_SEED.as_bytes(),
+
+        let accounts = gamma::accounts::UpdateAmmConfig {
+            owner: authority.pubkey(),
+            amm_config: amm_config_key,
+            roles: roles_address(),
+        };
+        let data = gamma::instruction::ApplyAmmConfigUpdate { update };
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, authority)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+    }
+
+    pub async fn try_queue_update(
+        &mut self,
+        authority: &Keypair,
+        amm_index: u16,
+        pool_id: Option<Pubkey>,
+        id: u64,
+        action: QueuedAction,
+    ) -> ProcessTransactionResult {
+        let (amm_config_key, __bump) = Pubkey::find_program_address(
+            &[AMM_CONFIG_SEED.as_bytes(), &amm_index.to_be_bytes()],
+            &gamma::ID,
+        );
+
+        let accounts = gamma::accounts::QueueUpdate {
+            authority: authority.pubkey(),
+            amm_config: amm_config_key,
+            pool_state: pool_id,
+            queued_update: queued_update_address(amm_config_key, id),
+            roles: roles_address(),
+            system_program: system_program::ID,
+        };
+        let data = gamma::instruction::QueueUpdate { id, action };
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, authority)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+    }
+
+    pub async fn try_execute_queued_update(
+        &mut self,
+        executor: &Keypair,
+        amm_index: u16,
+        pool_id: Option<Pubkey>,
+        id: u64,
+        proposer: Pubkey,
+    ) -> ProcessTransactionResult {
+        let (amm_config_key, __bump) = Pubkey::find_program_address(
+            &[AMM_CONFIG_SEED.as_bytes(), &amm_index.to_be_bytes()],
+            &gamma::ID,
+        );
+
+        let accounts = gamma::accounts::ExecuteQueuedUpdate {
+            executor: executor.pubkey(),
+            proposer,
+            queued_update: queued_update_address(amm_config_key, id),
+            amm_config: amm_config_key,
+            pool_state: pool_id,
+            roles: roles_address(),
+        };
+        let data = gamma::instruction::ExecuteQueuedUpdate {};
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, executor)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+    }
+
+    pub async fn try_cancel_queued_update(
+        &mut self,
+        authority: &Keypair,
+        amm_index: u16,
+        id: u64,
+        proposer: Pubkey,
+    ) -> ProcessTransactionResult {
+        let (amm_config_key, __bump) = Pubkey::find_program_address(
+            &[AMM_CONFIG_SEED.as_bytes(), &amm_index.to_be_bytes()],
+            &gamma::ID,
+        );
+
+        let accounts = gamma::accounts::CancelQueuedUpdate {
+            authority: authority.pubkey(),
+            proposer,
+            queued_update: queued_update_address(amm_config_key, id),
+            roles: roles_address(),
+        };
+        let data = gamma::instruction::CancelQueuedUpdate {};
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, authority)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+    }
+
+    pub async fn try_accept_role(
+        &mut self,
+        new_owner: &Keypair,
+        amm_index: u16,
+    ) -> ProcessTransactionResult {
+        let (amm_config_key, __bump) = Pubkey::find_program_address(
+            &[AMM_CONFIG_SEED.as_bytes(), &amm_index.to_be_bytes()],
+            &gamma::ID,
+        );
+
+        let accounts = gamma::accounts::AcceptRole {
+            new_owner: new_owner.pubkey(),
+            amm_config: amm_config_key,
+        };
+        let data = gamma::instruction::AcceptRole {};
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, new_owner)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+    }
+
+    pub async fn try_cancel_role_transfer(
+        &mut self,
+        owner: &Keypair,
+        amm_index: u16,
+    ) -> ProcessTransactionResult {
+        let (amm_config_key, __bump) = Pubkey::find_program_address(
+            &[AMM_CONFIG_SEED.as_bytes(), &amm_index.to_be_bytes()],
+            &gamma::ID,
+        );
+
+        let accounts = gamma::accounts::CancelRoleTransfer {
+            owner: owner.pubkey(),
+            amm_config: amm_config_key,
+            roles: roles_address(),
+        };
+        let data = gamma::instruction::CancelRoleTransfer {};
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, owner)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+    }
+
+    pub async fn try_init_mint_registry(
+        &mut self,
+        authority: &Keypair,
+        amm_index: u16,
+        mode: u8,
+    ) -> ProcessTransactionResult {
+        let (amm_config_key, __bump) = Pubkey::find_program_address(
+            &[AMM_CONFIG_SEED.as_bytes(), &amm_index.to_be_bytes()],
+            &gamma::ID,
+        );
+
+        let accounts = gamma::accounts::InitMintRegistry {
+            authority: authority.pubkey(),
+            amm_config: amm_config_key,
+            mint_registry: mint_registry_address(amm_config_key),
+            roles: roles_address(),
+            system_program: system_program::ID,
+        };
+        let data = gamma::instruction::InitMintRegistry { mode };
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, authority)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+    }
+
+    pub async fn try_set_mint_entry(
+        &mut self,
+        authority: &Keypair,
+        amm_index: u16,
+        mint: Pubkey,
+        status: u8,
+    ) -> ProcessTransactionResult {
+        let (amm_config_key, __bump) = Pubkey::find_program_address(
+            &[AMM_CONFIG_SEED.as_bytes(), &amm_index.to_be_bytes()],
+            &gamma::ID,
+        );
+
+        let accounts = gamma::accounts::SetMintEntry {
+            authority: authority.pubkey(),
+            amm_config: amm_config_key,
+            mint_entry: mint_entry_address(amm_config_key, mint),
+            roles: roles_address(),
+            system_program: system_program::ID,
+        };
+        let data = gamma::instruction::SetMintEntry { mint, status };
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, authority)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+    }
+
+    /// Pays the fees of the old config to the associated token accounts of its owners,
+    /// `remaining_accounts` are passed after the named accounts
+    pub async fn try_rebind_pool_config(
+        &mut self,
+        owner: &Keypair,
+        pool_id: Pubkey,
+        new_amm_index: u16,
+        remaining_accounts: Vec<AccountMeta>,
+    ) -> ProcessTransactionResult {
+        let pool_state: PoolState = self.fetch_account(pool_id).await;
+        let old_amm_config: AmmConfig = self.fetch_account(pool_state.amm_config).await;
+        let (new_amm_config, __bump) = Pubkey::find_program_address(
+            &[AMM_CONFIG_SEED.as_bytes(), &new_amm_index.to_be_bytes()],
+            &gamma::ID,
+        );
+        let (authority, __bump) =
+            Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
+        let protocol_owner_token_0 = self
+            .get_or_create_associated_token_account(
+                old_amm_config.protocol_owner,
+                pool_state.token_0_mint,
+                owner,
+            )
+            .await;
+        let protocol_owner_token_1 = self
+            .get_or_create_associated_token_account(
+                old_amm_config.protocol_owner,
+                pool_state.token_1_mint,
+                owner,
+            )
+            .await;
+        let fund_owner_token_0 = self
+            .get_or_create_associated_token_account(
+                old_amm_config.fund_owner,
+                pool_state.token_0_mint,
+                owner,
+            )
+            .await;
+        let fund_owner_token_1 = self
+            .get_or_create_associated_token_account(
+                old_amm_config.fund_owner,
+                pool_state.token_1_mint,
+                owner,
+            )
+            .await;
+
+        let accounts = gamma::accounts::RebindPoolConfig {
+            owner: owner.pubkey(),
+            authority,
+            pool_state: pool_id,
+            old_amm_config: pool_state.amm_config,
+            new_amm_config,
+            token_0_vault: pool_state.token_0_vault,
+            token_1_vault: pool_state.token_1_vault,
+            vault_0_mint: pool_state.token_0_mint,
+            vault_1_mint: pool_state.token_1_mint,
+            protocol_owner_token_0,
+            protocol_owner_token_1,
+            fund_owner_token_0,
+            fund_owner_token_1,
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+            roles: roles_address(),
+        };
+        let data = gamma::instruction::RebindPoolConfig {};
+
+        let mut instruction = get_instruction(data, accounts);
+        instruction.accounts.extend(remaining_accounts);
+        let transaction =
+            get_signed_transaction(&mut self.program_test_context, &[instruction], owner).await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+    }
+
+    pub async fn update_pool(
+        &mut self,
+        authority: &Keypair,
+        pool_id: Pubkey,
+        amm_config_index: u16,
+        param: u32,
+        value: u64,
+    ) {
+        self.try_update_pool(authority, pool_id, amm_config_index, param, value)
+            .await
+            .unwrap_transaction();
+    }
+
+    pub async fn try_update_pool(
+        &mut self,
+        authority: &Keypair,
+        pool_id: Pubkey,
+        amm_config_index: u16,
+        param: u32,
+        value: u64,
+    ) -> ProcessTransactionResult {
+        let (amm_config_key, __bump) = Pubkey::find_program_address(
+            &[AMM_CONFIG_SEED.as_bytes(), &amm_config_index.to_be_bytes()],
+            &gamma::ID,
+        );
+
+        let accounts = gamma::accounts::UpdatePool {
+            authority: authority.pubkey(),
+            pool_state: pool_id,
+            amm_config: amm_config_key,
+            roles: roles_address(),
+        };
+        let data = gamma::instruction::UpdatePool { param, value };
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, authority)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+    }
+
+    pub async fn try_apply_pool_update(
+        &mut self,
+        authority: &Keypair,
+        pool_id: Pubkey,
+        amm_config_index: u16,
+        update: PoolUpdate,
+    ) -> ProcessTransactionResult {
+        let (amm_config_key, __bump) = Pubkey::find_program_address(
+            &[AMM_CONFIG_SEED.as_bytes(), &amm_config_index.to_be_bytes()],
+            &gamma::ID,
+        );
+
+        let accounts = gamma::accounts::UpdatePool {
+            authority: authority.pubkey(),
+            pool_state: pool_id,
+            amm_config: amm_config_key,
+            roles: roles_address(),
+        };
+        let data = gamma::instruction::ApplyPoolUpdate { update };
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, authority)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+    }
+
+    pub async fn initialize_pool(
+        &mut self,
+        user: &Keypair,
+        amm_config_index: u16,
+        init_amount_0: u64,
+        init_amount_1: u64,
+        open_time: u64,
+        create_pool_fee: Pubkey,
+    ) -> Pubkey {
+        self.try_initialize_pool(
+            user,
+            amm_config_index,
+            init_amount_0,
+            init_amount_1,
+            open_time,
+            create_pool_fee,
+        )
+        .await
+        .unwrap()
+    }
+
+    pub async fn try_initialize_pool(
+        &mut self,
+        user: &Keypair,
+        amm_config_index: u16,
+        init_amount_0: u64,
+        init_amount_1: u64,
+        open_time: u64,
+        create_pool_fee: Pubkey,
+    ) -> Result<Pubkey, BanksClientError> {
+        let (pool_id, instruction) = self
+            .initialize_pool_instruction(
+                user,
+                amm_config_index,
+                init_amount_0,
+                init_amount_1,
+                open_time,
+                create_pool_fee,
+            )
+            .await;
+        let transaction =
+            get_signed_transaction(&mut self.program_test_context, &[instruction], user).await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await?;
+
+        Ok(pool_id)
+    }
+
+    /// Also creates the token accounts of the user, returns the pool address
+    pub async fn initialize_pool_instruction(
+        &mut self,
+        user: &Keypair,
+        amm_config_index: u16,
+        init_amount_0: u64,
+        init_amount_1: u64,
+        open_time: u64,
+        create_pool_fee: Pubkey,
+    ) -> (Pubkey, Instruction) {
+        let (amm_config_key, __bump) = Pubkey::find_program_address(
+            &[AMM_CONFIG_SEED.as_bytes(), &amm_config_index.to_be_bytes()],
+            &gamma::ID,
+        );
+
+        let pool_account_key = Pubkey::find_program_address(
+            &[
+                POOL_SEED.as_bytes(),
+                amm_config_key.to_bytes().as_ref(),
+                self.token_0_mint.to_bytes().as_ref(),
+                self.token_1_mint.to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        )
+        .0;
+        let (authority, __bump) = Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::ID);
+        let (token_0_vault, __bump) = Pubkey::find_program_address(
+            &[
+                POOL_VAULT_SEED.as_bytes(),
+                pool_account_key.to_bytes().as_ref(),
+                self.token_0_mint.to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
+        let (token_1_vault, __bump) = Pubkey::find_program_address(
+            &[
+                POOL_VAULT_SEED.as_bytes(),
+                pool_account_key.to_bytes().as_ref(),
+                self.token_1_mint.to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
+        let (_lp_mint_key, __bump) = Pubkey::find_program_address(
+            &[
+                POOL_LP_MINT_SEED.as_bytes(),
+                pool_account_key.to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
+        let (observation_key, __bump) = Pubkey::find_program_address(
+            &[
+                OBSERVATION_SEED.as_bytes(),
+                pool_account_key.to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
+        let user_token_0_account = self
+            .get_or_create_associated_token_account(user.pubkey(), self.token_0_mint.clone(), &user)
+            .await;
+
+        let user_token_1_account = self
+            .get_or_create_associated_token_account(user.pubkey(), self.token_1_mint.clone(), &user)
+            .await;
+        let user_pool_liquidity = Pubkey::find_program_address(
+            &[
+                USER_POOL_LIQUIDITY_SEED.as_bytes(),
+                pool_account_key.to_bytes().as_ref(),
+                user.pubkey().to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        )
+        .0;
+        let token_0_program = self.token_program_of(self.token_0_mint).await;
+        let token_1_program = self.token_program_of(self.token_1_mint).await;
+
+        let accounts = gamma::accounts::Initialize {
+            creator: user.pubkey(),
+            amm_config: amm_config_key,
+            authority,
+            pool_state: pool_account_key,
+            user_pool_liquidity,
+            token_0_mint: self.token_0_mint,
+            token_1_mint: self.token_1_mint,
+            creator_token_0: user_token_0_account,
+            creator_token_1: user_token_1_account,
+            token_0_vault,
+            token_1_vault,
+            create_pool_fee: create_pool_fee,
+            observation_state: observation_key,
+            token_program: spl_token::id(),
+            token_0_program,
+            token_1_program,
+            associated_token_program: spl_associated_token_account::id(),
+            system_program: system_program::ID,
+            rent: sysvar::rent::id(),
+        };
+
+        let data = gamma::instruction::Initialize {
+            init_amount_0,
+            init_amount_1,
+            open_time,
+            max_trade_fee_rate: 0,
+            volatility_factor: 0,
+        };
+
+        (pool_account_key, get_instruction(data, accounts))
+    }
+
+    pub async fn deposit(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+        amm_config_index: u16,
+        lp_token_amount: u64,
+        maximum_token_0_amount: u64,
+        maximum_token_1_amount: u64,
+    ) {
+        self.try_deposit(
+            user,
+            pool_id,
+            amm_config_index,
+            lp_token_amount,
+            maximum_token_0_amount,
+            maximum_token_1_amount,
+        )
+        .await
+        .unwrap();
+    }
+
+    pub async fn try_deposit(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+        amm_config_index: u16,
+        lp_token_amount: u64,
+        maximum_token_0_amount: u64,
+        maximum_token_1_amount: u64,
+    ) -> Result<(), BanksClientError> {
+        self.try_deposit_for(
+            user,
+            user.pubkey(),
+            pool_id,
+            amm_config_index,
+            lp_token_amount,
+            maximum_token_0_amount,
+            maximum_token_1_amount,
+        )
+        .await
+    }
+
+    /// Deposits the tokens of the signer into the position of the owner
+    pub async fn try_deposit_for(
+        &mut self,
+        signer: &Keypair,
+        owner: Pubkey,
+        pool_id: Pubkey,
+        amm_config_index: u16,
+        lp_token_amount: u64,
+        maximum_token_0_amount: u64,
+        maximum_token_1_amount: u64,
+    ) -> Result<(), BanksClientError> {
+        let (amm_config_key, __bump) = Pubkey::find_program_address(
+            &[AMM_CONFIG_SEED.as_bytes(), &amm_config_index.to_be_bytes()],
+            &gamma::ID,
+        );
+
+        let (pool_account_key, __bump) = Pubkey::find_program_address(
+            &[
+                POOL_SEED.as_bytes(),
+                amm_config_key.to_bytes().as_ref(),
+                self.token_0_mint.to_bytes().as_ref(),
+                self.token_1_mint.to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
+
+        let (token_0_vault, __bump) = Pubkey::find_program_address(
+            &[
+                POOL_VAULT_SEED.as_bytes(),
+                pool_account_key.to_bytes().as_ref(),
+                self.token_0_mint.to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
+        let (token_1_vault, __bump) = Pubkey::find_program_address(
+            &[
+                POOL_VAULT_SEED.as_bytes(),
+                pool_account_key.to_bytes().as_ref(),
+                self.token_1_mint.to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
+
+        let user_token_0_account = self
+            .get_or_create_associated_token_account(
+                signer.pubkey(),
+                self.token_0_mint.clone(),
+                signer,
+            )
+            .await;
+
+        let user_token_1_account = self
+            .get_or_create_associated_token_account(
+                signer.pubkey(),
+                self.token_1_mint.clone(),
+                signer,
+            )
+            .await;
+
+        let (authority, __bump) =
+            Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
+
+        let user_pool_liquidity = Pubkey::find_program_address(
+            &[
+                USER_POOL_LIQUIDITY_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                owner.to_bytes().as_ref(),
+            ],
+            &gamma::id(),
+        )
+        .0;
+
+        let accounts = gamma::accounts::Deposit {
+            owner: signer.pubkey(),
+            authority,
+            pool_state: pool_id,
+            user_pool_liquidity,
+            // owner_lp_token: user_token_lp_account,
+            token_0_account: user_token_0_account,
+            token_1_account: user_token_1_account,
+            token_0_vault,
+            token_1_vault,
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+            vault_0_mint: self.token_0_mint,
+            vault_1_mint: self.token_1_mint,
+        };
+
+        let data = gamma::instruction::Deposit {
+            lp_token_amount,
+            maximum_token_0_amount,
+            maximum_token_1_amount,
+        };
+
+        let mut instruction = get_instruction(data, accounts);
+        instruction.accounts.extend(
+            self.pool_partner_accounts(pool_id, user_pool_liquidity)
+                .await,
+        );
+        instruction
+            .accounts
+            .extend(position_operator_accounts(pool_id, owner, signer.pubkey()));
+        let transaction =
+            get_signed_transaction(&mut self.program_test_context, &[instruction], signer).await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    pub async fn withdraw(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+        amm_config_index: u16,
+        lp_token_amount: u64,
+        minimum_token_0_amount: u64,
+        minimum_token_1_amount: u64,
+    ) {
+        self.try_withdraw_for(
+            user,
+            user.pubkey(),
+            pool_id,
+            amm_config_index,
+            lp_token_amount,
+            minimum_token_0_amount,
+            minimum_token_1_amount,
+        )
+        .await
+        .unwrap();
+    }
+
+    /// Withdraws from the position of the owner into the token accounts of the owner
+    pub async fn try_withdraw_for(
+        &mut self,
+        signer: &Keypair,
+        owner: Pubkey,
+        pool_id: Pubkey,
+        amm_config_index: u16,
+        lp_token_amount: u64,
+        minimum_token_0_amount: u64,
+        minimum_token_1_amount: u64,
+    ) -> Result<(), BanksClientError> {
+        let (amm_config_key, __bump) = Pubkey::find_program_address(
+            &[AMM_CONFIG_SEED.as_bytes(), &amm_config_index.to_be_bytes()],
+            &gamma::ID,
+        );
+
+        let (pool_account_key, __bump) = Pubkey::find_program_address(
+            &[
+                POOL_SEED.as_bytes(),
+                amm_config_key.to_bytes().as_ref(),
+                self.token_0_mint.to_bytes().as_ref(),
+                self.token_1_mint.to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
+
+        let (token_0_vault, __bump) = Pubkey::find_program_address(
+            &[
+                POOL_VAULT_SEED.as_bytes(),
+                pool_account_key.to_bytes().as_ref(),
+                self.token_0_mint.to_bytes().as_ref(),
+            ],
+            &gamma::ID,
+        );
+        let (token_1_vault, __bump) = Pubkey::find_program_address(
+            &[
+                POOL_VAULT_SEED.as_bytes(),
                 pool_account_key.to_bytes().as_ref(),
                 self.token_1_mint.to_bytes().as_ref(),
             ],
-            &gamma::ID,
+            &gamma::ID,
+        );
+        let (authority, __bump) =
+            Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
+        let user_pool_liquidity = Pubkey::find_program_address(
+            &[
+                USER_POOL_LIQUIDITY_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                owner.to_bytes().as_ref(),
+            ],
+            &gamma::id(),
+        )
+        .0;
+
+        let user_token_0_account: Pubkey = self
+            .get_or_create_associated_token_account(owner, self.token_0_mint.clone(), signer)
+            .await;
+
+        let user_token_1_account = self
+            .get_or_create_associated_token_account(owner, self.token_1_mint.clone(), signer)
+            .await;
+
+        let accounts = gamma::accounts::Withdraw {
+            owner: signer.pubkey(),
+            authority,
+            pool_state: pool_id,
+            user_pool_liquidity,
+            token_0_account: user_token_0_account,
+            token_1_account: user_token_1_account,
+            token_0_vault,
+            token_1_vault,
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+            vault_0_mint: self.token_0_mint,
+            vault_1_mint: self.token_1_mint,
+            memo_program: spl_memo::id(),
+            instruction_sysvar_account: sysvar::instructions::id(),
+            kamino_program: solana_sdk::pubkey!("KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD"),
+        };
+
+        let data = gamma::instruction::Withdraw {
+            lp_token_amount,
+            minimum_token_0_amount,
+            minimum_token_1_amount,
+        };
+
+        let mut instruction = get_instruction(data, accounts);
+        instruction.accounts.extend(
+            self.pool_partner_accounts(pool_id, user_pool_liquidity)
+                .await,
+        );
+        instruction
+            .accounts
+            .extend(position_operator_accounts(pool_id, owner, signer.pubkey()));
+        let transaction =
+            get_signed_transaction(&mut self.program_test_context, &[instruction], signer).await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    pub async fn init_user_pool_liquidity(&mut self, user: &Keypair, pool_id: Pubkey) {
+        self.init_user_pool_liquidity_with_partner(user, pool_id, None)
+            .await;
+    }
+
+    pub async fn init_user_pool_liquidity_with_partner(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+        partner_id: Option<u64>,
+    ) {
+        let user_pool_liquidity = Pubkey::find_program_address(
+            &[
+                USER_POOL_LIQUIDITY_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                user.pubkey().to_bytes().as_ref(),
+            ],
+            &gamma::id(),
+        )
+        .0;
+
+        let accounts: gamma::accounts::InitUserPoolLiquidity =
+            gamma::accounts::InitUserPoolLiquidity {
+                user: user.pubkey(),
+                pool_state: pool_id,
+                user_pool_liquidity,
+                partner: partner_id.map(partner_address),
+                system_program: system_program::ID,
+            };
+
+        let data = gamma::instruction::InitUserPoolLiquidity { partner: None };
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, user)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+
+    pub async fn resize_user_pool_liquidity(
+        &mut self,
+        payer: &Keypair,
+        user_pool_liquidity: Pubkey,
+    ) {
+        let accounts = gamma::accounts::ResizeUserPoolLiquidity {
+            payer: payer.pubkey(),
+            user_pool_liquidity,
+            system_program: system_program::ID,
+        };
+
+        let data = gamma::instruction::ResizeUserPoolLiquidity {};
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, payer)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+
+    pub async fn resize_pool_state(&mut self, payer: &Keypair, pool_state: Pubkey) {
+        let accounts = gamma::accounts::ResizePoolState {
+            payer: payer.pubkey(),
+            pool_state,
+            system_program: system_program::ID,
+        };
+
+        let data = gamma::instruction::ResizePoolState {};
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, payer)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+
+    /// Overwrites the pool, to put it in a state only older versions of the program leave
+    pub async fn set_pool_state(&mut self, pool_id: Pubkey, pool_state: &PoolState) {
+        let mut account = self.get_account_info(pool_id).await.unwrap().unwrap();
+        account.data[8..8 + std::mem::size_of::<PoolState>()]
+            .copy_from_slice(bytemuck::bytes_of(pool_state));
+        self.program_test_context
+            .set_account(&pool_id, &AccountSharedData::from(account));
+    }
+
+    /// Counts the rewards of a pool created before they were counted, the reward infos are
+    /// passed sorted
+    pub async fn try_migrate_reward_info_count(
+        &mut self,
+        authority: &Keypair,
+        pool_id: Pubkey,
+        reward_infos: Vec<Pubkey>,
+    ) -> ProcessTransactionResult {
+        let accounts = gamma::accounts::MigrateRewardInfoCount {
+            authority: authority.pubkey(),
+            pool_state: pool_id,
+            roles: roles_address(),
+        };
+        let data = gamma::instruction::MigrateRewardInfoCount {};
+
+        let mut reward_infos = reward_infos;
+        reward_infos.sort();
+        let mut instruction = get_instruction(data, accounts);
+        instruction.accounts.extend(
+            reward_infos
+                .into_iter()
+                .map(|reward_info| AccountMeta::new_readonly(reward_info, false)),
+        );
+        let transaction =
+            get_signed_transaction(&mut self.program_test_context, &[instruction], authority).await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+    }
+
+    /// The PoolPartner account expected in the remaining accounts when the position has a partner,
+    /// left out like older clients do while the pool has no PoolPartner
+    pub async fn pool_partner_accounts(
+        &mut self,
+        pool_id: Pubkey,
+        user_pool_liquidity: Pubkey,
+    ) -> Vec<AccountMeta> {
+        let pool_state: Result<PoolState, _> = self.try_fetch_account(pool_id).await;
+        if pool_state.is_ok_and(|pool_state| pool_state.pool_partners_created == 0) {
+            return vec![];
+        }
+        let user_pool_liquidity: Result<UserPoolLiquidity, _> =
+            self.try_fetch_account(user_pool_liquidity).await;
+        match user_pool_liquidity
+            .ok()
+            .and_then(|position| position.partner)
+        {
+            Some(partner_id) => vec![AccountMeta::new(
+                PoolPartner::find_address(pool_id, partner_id),
+                false,
+            )],
+            None => vec![],
+        }
+    }
+
+    pub async fn create_partner(
+        &mut self,
+        admin: &Keypair,
+        id: u64,
+        name: &str,
+        payout_wallet: Pubkey,
+        revenue_share_rate: u64,
+    ) -> Pubkey {
+        let partner = partner_address(id);
+        let accounts = gamma::accounts::CreatePartner {
+            authority: admin.pubkey(),
+            partner,
+            roles: roles_address(),
+            system_program: system_program::ID,
+        };
+        let data = gamma::instruction::CreatePartner {
+            id,
+            name: name.to_string(),
+            payout_wallet,
+            revenue_share_rate,
+        };
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, admin)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+        partner
+    }
+
+    pub async fn update_partner(
+        &mut self,
+        admin: &Keypair,
+        id: u64,
+        name: &str,
+        payout_wallet: Pubkey,
+        revenue_share_rate: u64,
+    ) {
+        let accounts = gamma::accounts::UpdatePartner {
+            authority: admin.pubkey(),
+            partner: partner_address(id),
+            roles: roles_address(),
+        };
+        let data = gamma::instruction::UpdatePartner {
+            name: name.to_string(),
+            payout_wallet,
+            revenue_share_rate,
+        };
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, admin)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+
+    pub async fn init_pool_partner(
+        &mut self,
+        payer: &Keypair,
+        pool_id: Pubkey,
+        partner_id: u64,
+    ) -> Pubkey {
+        let pool_partner = PoolPartner::find_address(pool_id, partner_id);
+        let accounts = gamma::accounts::InitPoolPartner {
+            payer: payer.pubkey(),
+            pool_state: pool_id,
+            partner: partner_address(partner_id),
+            pool_partner,
+            system_program: system_program::ID,
+        };
+        let data = gamma::instruction::InitPoolPartner {};
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, payer)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+        pool_partner
+    }
+
+    pub async fn set_position_partner(
+        &mut self,
+        owner: &Keypair,
+        pool_id: Pubkey,
+        old_partner_id: Option<u64>,
+        new_partner_id: Option<u64>,
+    ) {
+        let user_pool_liquidity = Pubkey::find_program_address(
+            &[
+                USER_POOL_LIQUIDITY_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                owner.pubkey().to_bytes().as_ref(),
+            ],
+            &gamma::id(),
+        )
+        .0;
+
+        let accounts = gamma::accounts::SetPositionPartner {
+            owner: owner.pubkey(),
+            pool_state: pool_id,
+            user_pool_liquidity,
+            partner: new_partner_id.map(partner_address),
+            partner_signer: None,
+            pool_partner: old_partner_id
+                .map(|partner_id| PoolPartner::find_address(pool_id, partner_id)),
+            new_pool_partner: new_partner_id
+                .map(|partner_id| PoolPartner::find_address(pool_id, partner_id)),
+        };
+        let data = gamma::instruction::SetPositionPartner {};
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, owner)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+
+    pub async fn set_position_operator(
+        &mut self,
+        owner: &Keypair,
+        pool_id: Pubkey,
+        operator: Pubkey,
+        permissions: u32,
+        expires_at: u64,
+    ) -> Pubkey {
+        let user_pool_liquidity = Pubkey::find_program_address(
+            &[
+                USER_POOL_LIQUIDITY_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                owner.pubkey().to_bytes().as_ref(),
+            ],
+            &gamma::id(),
+        )
+        .0;
+        let position_operator = position_operator_address(pool_id, owner.pubkey());
+
+        let accounts = gamma::accounts::SetPositionOperator {
+            owner: owner.pubkey(),
+            pool_state: pool_id,
+            user_pool_liquidity,
+            position_operator,
+            system_program: system_program::ID,
+        };
+        let data = gamma::instruction::SetPositionOperator {
+            operator,
+            permissions,
+            expires_at,
+        };
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, owner)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+        position_operator
+    }
+
+    pub async fn init_lp_mint(&mut self, payer: &Keypair, pool_id: Pubkey) -> Pubkey {
+        let (lp_mint, __bump) = Pubkey::find_program_address(
+            &[POOL_LP_MINT_SEED.as_bytes(), pool_id.to_bytes().as_ref()],
+            &gamma::id(),
         );
+        let (authority, __bump) =
+            Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
+
+        let accounts = gamma::accounts::InitLpMint {
+            payer: payer.pubkey(),
+            authority,
+            pool_state: pool_id,
+            lp_mint,
+            token_program: spl_token_2022::id(),
+            roles: roles_address(),
+            system_program: system_program::ID,
+        };
+        let data = gamma::instruction::InitLpMint {};
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, payer)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+        lp_mint
+    }
+
+    /// Closes the pool, reward_infos being every RewardInfo of the pool
+    pub async fn try_close_pool(
+        &mut self,
+        owner: &Keypair,
+        pool_id: Pubkey,
+        reward_infos: Vec<Pubkey>,
+    ) -> Result<(), BanksClientError> {
+        let pool_state: PoolState = self.fetch_account(pool_id).await;
+        let amm_config: AmmConfig = self.fetch_account(pool_state.amm_config).await;
+        let protocol_owner_token_0 = self
+            .get_or_create_associated_token_account(
+                amm_config.protocol_owner,
+                pool_state.token_0_mint,
+                owner,
+            )
+            .await;
+        let protocol_owner_token_1 = self
+            .get_or_create_associated_token_account(
+                amm_config.protocol_owner,
+                pool_state.token_1_mint,
+                owner,
+            )
+            .await;
+        let (authority, __bump) =
+            Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
+
+        let accounts = gamma::accounts::ClosePool {
+            owner: owner.pubkey(),
+            authority,
+            pool_state: pool_id,
+            amm_config: pool_state.amm_config,
+            observation_state: pool_state.observation_key,
+            token_0_vault: pool_state.token_0_vault,
+            token_1_vault: pool_state.token_1_vault,
+            vault_0_mint: pool_state.token_0_mint,
+            vault_1_mint: pool_state.token_1_mint,
+            protocol_owner_token_0,
+            protocol_owner_token_1,
+            rent_receiver: pool_state.pool_creator,
+            lp_mint: (pool_state.lp_mint_enabled == 1).then_some(pool_state.lp_mint),
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+            roles: roles_address(),
+        };
+        let data = gamma::instruction::ClosePool {};
+
+        let mut reward_infos = reward_infos;
+        reward_infos.sort();
+        let mut instruction = get_instruction(data, accounts);
+        for reward_info_key in reward_infos {
+            let reward_info: RewardInfo = self.fetch_account(reward_info_key).await;
+            let (reward_vault, __bump) = Pubkey::find_program_address(
+                &[
+                    REWARD_VAULT_SEED.as_bytes(),
+                    reward_info_key.to_bytes().as_ref(),
+                ],
+                &gamma::id(),
+            );
+            let creator_token_account = self
+                .get_or_create_associated_token_account(
+                    reward_info.rewarded_by,
+                    reward_info.mint,
+                    owner,
+                )
+                .await;
+            instruction.accounts.extend([
+                AccountMeta::new_readonly(reward_info_key, false),
+                AccountMeta::new(reward_vault, false),
+                AccountMeta::new(reward_info.mint, false),
+                AccountMeta::new(creator_token_account, false),
+                AccountMeta::new(reward_info.rewarded_by, false),
+            ]);
+        }
+        let transaction =
+            get_signed_transaction(&mut self.program_test_context, &[instruction], owner).await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    pub async fn try_close_user_pool_liquidity(
+        &mut self,
+        owner: &Keypair,
+        pool_id: Pubkey,
+        reward_infos: Vec<Pubkey>,
+    ) -> Result<(), BanksClientError> {
+        let user_pool_liquidity = Pubkey::find_program_address(
+            &[
+                USER_POOL_LIQUIDITY_SEED.as_bytes(),
+                pool_id.to_bytes().as_ref(),
+                owner.pubkey().to_bytes().as_ref(),
+            ],
+            &gamma::id(),
+        )
+        .0;
+
+        let accounts = gamma::accounts::CloseUserPoolLiquidity {
+            owner: owner.pubkey(),
+            pool_state: pool_id,
+            user_pool_liquidity,
+            position_operator: position_operator_address(pool_id, owner.pubkey()),
+        };
+        let data = gamma::instruction::CloseUserPoolLiquidity {};
+
+        let mut instruction = get_instruction(data, accounts);
+        let mut reward_infos = reward_infos;
+        reward_infos.sort();
+        for reward_info in reward_infos {
+            let (user_reward_info, __bump) = Pubkey::find_program_address(
+                &[
+                    USER_REWARD_INFO_SEED.as_bytes(),
+                    reward_info.to_bytes().as_ref(),
+                    owner.pubkey().to_bytes().as_ref(),
+                ],
+                &gamma::id(),
+            );
+            instruction.accounts.extend([
+                AccountMeta::new_readonly(reward_info, false),
+                AccountMeta::new_readonly(user_reward_info, false),
+            ]);
+        }
+        let transaction =
+            get_signed_transaction(&mut self.program_test_context, &[instruction], owner).await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    /// Moves LP from the position of owner to the one of new_owner, settling the rewards of
+    /// the given reward infos first
+    pub async fn transfer_liquidity(
+        &mut self,
+        owner: &Keypair,
+        new_owner: Pubkey,
+        pool_id: Pubkey,
+        lp_amount: u64,
+        reward_infos: &[Pubkey],
+    ) {
+        self.try_transfer_liquidity(owner, new_owner, None, pool_id, lp_amount, reward_infos)
+            .await
+            .unwrap();
+    }
+
+    /// `new_owner_signer` signs for the receiving wallet, needed when its position holds LP
+    pub async fn try_transfer_liquidity(
+        &mut self,
+        owner: &Keypair,
+        new_owner: Pubkey,
+        new_owner_signer: Option<&Keypair>,
+        pool_id: Pubkey,
+        lp_amount: u64,
+        reward_infos: &[Pubkey],
+    ) -> Result<(), BanksClientError> {
+        let user_pool_liquidity_of = |user: Pubkey| {
+            Pubkey::find_program_address(
+                &[
+                    USER_POOL_LIQUIDITY_SEED.as_bytes(),
+                    pool_id.to_bytes().as_ref(),
+                    user.to_bytes().as_ref(),
+                ],
+                &gamma::id(),
+            )
+            .0
+        };
+        let user_reward_info_of = |reward_info: &Pubkey, user: Pubkey| {
+            Pubkey::find_program_address(
+                &[
+                    USER_REWARD_INFO_SEED.as_bytes(),
+                    reward_info.to_bytes().as_ref(),
+                    user.to_bytes().as_ref(),
+                ],
+                &gamma::id(),
+            )
+            .0
+        };
+
+        let accounts = gamma::accounts::TransferLiquidity {
+            owner: owner.pubkey(),
+            new_owner,
+            pool_state: pool_id,
+            user_pool_liquidity: user_pool_liquidity_of(owner.pubkey()),
+            new_user_pool_liquidity: user_pool_liquidity_of(new_owner),
+            pool_partner: None,
+            new_pool_partner: None,
+            system_program: system_program::ID,
+        };
+        let data = gamma::instruction::TransferLiquidity { lp_amount };
+
+        let mut instruction = get_instruction(data, accounts);
+        for reward_info in reward_infos {
+            instruction.accounts.extend([
+                AccountMeta::new_readonly(*reward_info, false),
+                AccountMeta::new(user_reward_info_of(reward_info, owner.pubkey()), false),
+                AccountMeta::new(user_reward_info_of(reward_info, new_owner), false),
+            ]);
+        }
+        let transaction = match new_owner_signer {
+            Some(new_owner_signer) => {
+                for account in instruction.accounts.iter_mut() {
+                    if account.pubkey == new_owner {
+                        account.is_signer = true;
+                    }
+                }
+                get_signed_transaction_with_different_payer(
+                    &mut self.program_test_context,
+                    &[instruction],
+                    new_owner_signer,
+                    owner,
+                )
+                .await
+            }
+            None => {
+                get_signed_transaction(&mut self.program_test_context, &[instruction], owner).await
+            }
+        };
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
 
-        let user_token_0_account = self
-            .get_or_create_associated_token_account(user.pubkey(), self.token_0_mint.clone(), &user)
-            .await;
+    pub async fn try_snapshot_lp_price(
+        &mut self,
+        payer: &Keypair,
+        pool_id: Pubkey,
+    ) -> ProcessTransactionResult {
+        let accounts = gamma::accounts::SnapshotLpPrice {
+            payer: payer.pubkey(),
+            pool_state: pool_id,
+            lp_price_history: lp_price_history_address(pool_id),
+            system_program: system_program::ID,
+        };
+        let data = gamma::instruction::SnapshotLpPrice {};
 
-        let user_token_1_account = self
-            .get_or_create_associated_token_account(user.pubkey(), self.token_1_mint.clone(), &user)
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, payer)
             .await;
 
+        self.program_test_context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+    }
+
+    pub async fn try_init_lp_mint(
+        &mut self,
+        payer: &Keypair,
+        pool_id: Pubkey,
+    ) -> ProcessTransactionResult {
         let (authority, __bump) =
             Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
 
+        let accounts = gamma::accounts::InitLpMint {
+            payer: payer.pubkey(),
+            authority,
+            pool_state: pool_id,
+            lp_mint: lp_mint_address(pool_id),
+            token_program: spl_token_2022::id(),
+            roles: roles_address(),
+            system_program: system_program::ID,
+        };
+        let data = gamma::instruction::InitLpMint {};
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, payer)
+            .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+    }
+
+    /// Mints the LP tokens into `lp_token_account`, which can belong to anyone
+    pub async fn try_wrap_lp(
+        &mut self,
+        owner: &Keypair,
+        pool_id: Pubkey,
+        lp_amount: u64,
+        lp_token_account: Pubkey,
+        partner_id: Option<u64>,
+        reward_infos: &[Pubkey],
+    ) -> ProcessTransactionResult {
+        let (authority, __bump) =
+            Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
         let user_pool_liquidity = Pubkey::find_program_address(
             &[
                 USER_POOL_LIQUIDITY_SEED.as_bytes(),
-                pool_id.to_bytes().as_ref(),
-                user.pubkey().to_bytes().as_ref(),
+                pool_id.as_ref(),
+                owner.pubkey().as_ref(),
             ],
             &gamma::id(),
         )
         .0;
 
-        let accounts = gamma::accounts::Deposit {
-            owner: user.pubkey(),
+        let accounts = gamma::accounts::WrapLp {
+            owner: owner.pubkey(),
             authority,
             pool_state: pool_id,
             user_pool_liquidity,
-            // owner_lp_token: user_token_lp_account,
-            token_0_account: user_token_0_account,
-            token_1_account: user_token_1_account,
-            token_0_vault,
-            token_1_vault,
-            token_program: spl_token::id(),
-            token_program_2022: spl_token_2022::id(),
-            vault_0_mint: self.token_0_mint,
-            vault_1_mint: self.token_1_mint,
-        };
-
-        let data = gamma::instruction::Deposit {
-            lp_token_amount,
-            maximum_token_0_amount,
-            maximum_token_1_amount,
+            lp_mint: lp_mint_address(pool_id),
+            lp_token_account,
+            token_program: spl_token_2022::id(),
         };
-
-        let transaction = self
-            .encode_instruction_and_sign_transaction(data, accounts, user)
-            .await;
+        let data = gamma::instruction::WrapLp { lp_amount };
+
+        let mut instruction = get_instruction(data, accounts);
+        instruction.accounts.extend(lp_wrap_remaining_accounts(
+            pool_id,
+            owner.pubkey(),
+            partner_id,
+            reward_infos,
+        ));
+        let transaction =
+            get_signed_transaction(&mut self.program_test_context, &[instruction], owner).await;
 
         self.program_test_context
             .banks_client
-            .process_transaction(transaction)
+            .process_transaction_with_metadata(transaction)
             .await
-            .unwrap();
     }
 
-    pub async fn withdraw(
+    /// Burns the LP tokens of the associated token account of owner
+    pub async fn try_unwrap_lp(
         &mut self,
-        user: &Keypair,
+        owner: &Keypair,
         pool_id: Pubkey,
-        amm_config_index: u16,
-        lp_token_amount: u64,
-        minimum_token_0_amount: u64,
-        minimum_token_1_amount: u64,
-    ) {
-        let (amm_config_key, __bump) = Pubkey::find_program_address(
-            &[AMM_CONFIG_SEED.as_bytes(), &amm_config_index.to_be_bytes()],
-            &gamma::ID,
-        );
-
-        let (pool_account_key, __bump) = Pubkey::find_program_address(
-            &[
-                POOL_SEED.as_bytes(),
-                amm_config_key.to_bytes().as_ref(),
-                self.token_0_mint.to_bytes().as_ref(),
-                self.token_1_mint.to_bytes().as_ref(),
-            ],
-            &gamma::ID,
-        );
-
-        let (token_0_vault, __bump) = Pubkey::find_program_address(
-            &[
-                POOL_VAULT_SEED.as_bytes(),
-                pool_account_key.to_bytes().as_ref(),
-                self.token_0_mint.to_bytes().as_ref(),
-            ],
-            &gamma::ID,
-        );
-        let (token_1_vault, __bump) = Pubkey::find_program_address(
-            &[
-                POOL_VAULT_SEED.as_bytes(),
-                pool_account_key.to_bytes().as_ref(),
-                self.token_1_mint.to_bytes().as_ref(),
-            ],
-            &gamma::ID,
-        );
-        let (authority, __bump) =
-            Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
+        lp_amount: u64,
+        partner_id: Option<u64>,
+        reward_infos: &[Pubkey],
+    ) -> ProcessTransactionResult {
+        let lp_mint = lp_mint_address(pool_id);
         let user_pool_liquidity = Pubkey::find_program_address(
             &[
                 USER_POOL_LIQUIDITY_SEED.as_bytes(),
-                pool_id.to_bytes().as_ref(),
-                user.pubkey().to_bytes().as_ref(),
+                pool_id.as_ref(),
+                owner.pubkey().as_ref(),
             ],
             &gamma::id(),
         )
         .0;
 
-        let user_token_0_account: Pubkey = self
-            .get_or_create_associated_token_account(user.pubkey(), self.token_0_mint.clone(), &user)
-            .await;
+        let accounts = gamma::accounts::UnwrapLp {
+            owner: owner.pubkey(),
+            pool_state: pool_id,
+            user_pool_liquidity,
+            lp_mint,
+            lp_token_account: get_associated_token_address_with_program_id(
+                &owner.pubkey(),
+                &lp_mint,
+                &spl_token_2022::id(),
+            ),
+            token_program: spl_token_2022::id(),
+        };
+        let data = gamma::instruction::UnwrapLp { lp_amount };
+
+        let mut instruction = get_instruction(data, accounts);
+        instruction.accounts.extend(lp_wrap_remaining_accounts(
+            pool_id,
+            owner.pubkey(),
+            partner_id,
+            reward_infos,
+        ));
+        let transaction =
+            get_signed_transaction(&mut self.program_test_context, &[instruction], owner).await;
 
-        let user_token_1_account = self
-            .get_or_create_associated_token_account(user.pubkey(), self.token_1_mint.clone(), &user)
+        self.program_test_context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+    }
+
+    /// Collects the protocol fees of the pool into the associated token accounts of recipient
+    pub async fn try_collect_protocol_fee(
+        &mut self,
+        owner: &Keypair,
+        pool_id: Pubkey,
+        recipient: Pubkey,
+    ) -> ProcessTransactionResult {
+        let pool_state: PoolState = self.fetch_account(pool_id).await;
+        let recipient_token_0_account = self
+            .get_or_create_associated_token_account(recipient, pool_state.token_0_mint, owner)
             .await;
+        let recipient_token_1_account = self
+            .get_or_create_associated_token_account(recipient, pool_state.token_1_mint, owner)
+            .await;
+        let (authority, __bump) =
+            Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
 
-        let accounts = gamma::accounts::Withdraw {
-            owner: user.pubkey(),
+        let accounts = gamma::accounts::CollectProtocolFee {
+            owner: owner.pubkey(),
             authority,
             pool_state: pool_id,
-            user_pool_liquidity,
-            token_0_account: user_token_0_account,
-            token_1_account: user_token_1_account,
-            token_0_vault,
-            token_1_vault,
+            amm_config: pool_state.amm_config,
+            token_0_vault: pool_state.token_0_vault,
+            token_1_vault: pool_state.token_1_vault,
+            vault_0_mint: pool_state.token_0_mint,
+            vault_1_mint: pool_state.token_1_mint,
+            recipient_token_0_account,
+            recipient_token_1_account,
             token_program: spl_token::id(),
             token_program_2022: spl_token_2022::id(),
-            vault_0_mint: self.token_0_mint,
-            vault_1_mint: self.token_1_mint,
-            memo_program: spl_memo::id(),
-            instruction_sysvar_account: sysvar::instructions::id(),
-            kamino_program: solana_sdk::pubkey!("KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD"),
+            roles: roles_address(),
         };
-
-        let data = gamma::instruction::Withdraw {
-            lp_token_amount,
-            minimum_token_0_amount,
-            minimum_token_1_amount,
+        let data = gamma::instruction::CollectProtocolFee {
+            amount_0_requested: u64::MAX,
+            amount_1_requested: u64::MAX,
         };
 
         let transaction = self
-            .encode_instruction_and_sign_transaction(data, accounts, user)
+            .encode_instruction_and_sign_transaction(data, accounts, owner)
             .await;
 
         self.program_test_context
             .banks_client
-            .process_transaction(transaction)
+            .process_transaction_with_metadata(transaction)
             .await
-            .unwrap();
     }
 
-    pub async fn init_user_pool_liquidity(&mut self, user: &Keypair, pool_id: Pubkey) {
-        self.init_user_pool_liquidity_with_partner(user, pool_id, None)
+    pub async fn claim_partner_fees(
+        &mut self,
+        payer: &Keypair,
+        pool_id: Pubkey,
+        partner_id: u64,
+        payout_wallet: Pubkey,
+    ) {
+        let pool_state: gamma::states::PoolState = self.fetch_account(pool_id).await;
+        let (authority, __bump) =
+            Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
+
+        let accounts = gamma::accounts::ClaimPartnerFees {
+            payer: payer.pubkey(),
+            authority,
+            pool_state: pool_id,
+            partner: partner_address(partner_id),
+            pool_partner: PoolPartner::find_address(pool_id, partner_id),
+            token_0_vault: pool_state.token_0_vault,
+            token_1_vault: pool_state.token_1_vault,
+            vault_0_mint: pool_state.token_0_mint,
+            vault_1_mint: pool_state.token_1_mint,
+            payout_token_0_account: get_associated_token_address(
+                &payout_wallet,
+                &pool_state.token_0_mint,
+            ),
+            payout_token_1_account: get_associated_token_address(
+                &payout_wallet,
+                &pool_state.token_1_mint,
+            ),
+            token_program: spl_token::id(),
+            token_program_2022: spl_token_2022::id(),
+        };
+        let data = gamma::instruction::ClaimPartnerFees {};
+
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, payer)
             .await;
+
+        self.program_test_context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
     }
 
-    pub async fn init_user_pool_liquidity_with_partner(
+    /// Value of one LP unit of the pool, read from the return data of a simulated get_lp_price
+    pub async fn try_get_lp_price(
         &mut self,
-        user: &Keypair,
+        payer: &Keypair,
         pool_id: Pubkey,
-        partner: Option<String>,
-    ) {
-        let user_pool_liquidity = Pubkey::find_program_address(
-            &[
-                USER_POOL_LIQUIDITY_SEED.as_bytes(),
-                pool_id.to_bytes().as_ref(),
-                user.pubkey().to_bytes().as_ref(),
-            ],
-            &gamma::id(),
-        )
-        .0;
+    ) -> Result<gamma::instructions::LpPrice, BanksClientError> {
+        let (observation_state, __bump) = Pubkey::find_program_address(
+            &[OBSERVATION_SEED.as_bytes(), pool_id.to_bytes().as_ref()],
+            &gamma::ID,
+        );
+        let accounts = gamma::accounts::GetLpPrice {
+            pool_state: pool_id,
+            observation_state,
+        };
+        let data = gamma::instruction::GetLpPrice {};
 
-        let accounts: gamma::accounts::InitUserPoolLiquidity =
-            gamma::accounts::InitUserPoolLiquidity {
-                user: user.pubkey(),
-                pool_state: pool_id,
-                user_pool_liquidity,
-                system_program: system_program::ID,
-            };
+        let transaction = self
+            .encode_instruction_and_sign_transaction(data, accounts, payer)
+            .await;
+
+        let simulation = self
+            .program_test_context
+            .banks_client
+            .simulate_transaction(transaction)
+            .await?;
+        if let Some(Err(error)) = simulation.result {
+            return Err(BanksClientError::TransactionError(error));
+        }
+        let return_data = simulation
+            .simulation_details
+            .and_then(|details| details.return_data)
+            .ok_or(BanksClientError::ClientError("Missing return data"))?;
+        gamma::instructions::LpPrice::deserialize(&mut return_data.data.as_ref())
+            .map_err(|_| BanksClientError::ClientError("Failed to deserialize return data"))
+    }
 
-        let data = gamma::instruction::InitUserPoolLiquidity { partner };
+    pub async fn init_pool_statistics(&mut self, payer: &Keypair, pool_id: Pubkey) -> Pubkey {
+        let pool_statistics = PoolStatistics::find_address(pool_id);
+        let accounts = gamma::accounts::InitPoolStatistics {
+            payer: payer.pubkey(),
+            pool_state: pool_id,
+            pool_statistics,
+            system_program: system_program::ID,
+        };
+        let data = gamma::instruction::InitPoolStatistics {};
 
         let transaction = self
-            .encode_instruction_and_sign_transaction(data, accounts, user)
+            .encode_instruction_and_sign_transaction(data, accounts, payer)
             .await;
 
         self.program_test_context
@@ -1080,6 +2879,7 @@ impl TestEnv {
             .process_transaction(transaction)
             .await
             .unwrap();
+        pool_statistics
     }
 
     pub async fn swap_base_input(
@@ -1091,6 +2891,49 @@ impl TestEnv {
         minimum_amount_out: u64,
         trade_direction: TradeDirection,
     ) {
+        self.try_swap_base_input(
+            user,
+            pool_id,
+            amm_config_index,
+            amount_in,
+            minimum_amount_out,
+            trade_direction,
+        )
+        .await
+        .unwrap();
+    }
+
+    pub async fn try_swap_base_input(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+        amm_config_index: u16,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        trade_direction: TradeDirection,
+    ) -> Result<(), BanksClientError> {
+        self.try_swap_base_input_with_remaining_accounts(
+            user,
+            pool_id,
+            amm_config_index,
+            amount_in,
+            minimum_amount_out,
+            trade_direction,
+            swap_remaining_accounts(pool_id),
+        )
+        .await
+    }
+
+    pub async fn try_swap_base_input_with_remaining_accounts(
+        &mut self,
+        user: &Keypair,
+        pool_id: Pubkey,
+        amm_config_index: u16,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        trade_direction: TradeDirection,
+        remaining_accounts: Vec<AccountMeta>,
+    ) -> Result<(), BanksClientError> {
         let (authority, __bump) =
             Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
         let (amm_config_key, __bump) = Pubkey::find_program_address(
@@ -1127,6 +2970,9 @@ impl TestEnv {
             &gamma::ID,
         );
 
+        let token_0_program = self.token_program_of(self.token_0_mint).await;
+        let token_1_program = self.token_program_of(self.token_1_mint).await;
+
         let (
             input_token_account,
             output_token_account,
@@ -1144,8 +2990,8 @@ impl TestEnv {
                 self.token_1_mint,
                 token_0_vault,
                 token_1_vault,
-                spl_token::id(),
-                spl_token::id(),
+                token_0_program,
+                token_1_program,
             ),
             TradeDirection::OneForZero => (
                 user_token_1_account,
@@ -1154,8 +3000,8 @@ impl TestEnv {
                 self.token_0_mint,
                 token_1_vault,
                 token_0_vault,
-                spl_token::id(),
-                spl_token::id(),
+                token_1_program,
+                token_0_program,
             ),
         };
 
@@ -1180,15 +3026,15 @@ impl TestEnv {
             minimum_amount_out,
         };
 
-        let transaction = self
-            .encode_instruction_and_sign_transaction(data, accounts, user)
-            .await;
+        let mut instruction = get_instruction(data, accounts);
+        instruction.accounts.extend(remaining_accounts);
+        let transaction =
+            get_signed_transaction(&mut self.program_test_context, &[instruction], user).await;
 
         self.program_test_context
             .banks_client
             .process_transaction(transaction)
             .await
-            .unwrap();
     }
 
     pub async fn swap_base_output(
@@ -1236,6 +3082,9 @@ impl TestEnv {
             &gamma::ID,
         );
 
+        let token_0_program = self.token_program_of(self.token_0_mint).await;
+        let token_1_program = self.token_program_of(self.token_1_mint).await;
+
         let (
             input_token_account,
             output_token_account,
@@ -1253,8 +3102,8 @@ impl TestEnv {
                 self.token_1_mint,
                 token_0_vault,
                 token_1_vault,
-                spl_token::id(),
-                spl_token::id(),
+                token_0_program,
+                token_1_program,
             ),
             TradeDirection::OneForZero => (
                 user_token_1_account,
@@ -1263,8 +3112,8 @@ impl TestEnv {
                 self.token_0_mint,
                 token_1_vault,
                 token_0_vault,
-                spl_token::id(),
-                spl_token::id(),
+                token_1_program,
+                token_0_program,
             ),
         };
 
@@ -1289,9 +3138,12 @@ impl TestEnv {
             max_amount_in,
         };
 
-        let transaction = self
-            .encode_instruction_and_sign_transaction(data, accounts, user)
-            .await;
+        let mut instruction = get_instruction(data, accounts);
+        instruction
+            .accounts
+            .extend(swap_remaining_accounts(pool_id));
+        let transaction =
+            get_signed_transaction(&mut self.program_test_context, &[instruction], user).await;
 
         self.program_test_context
             .banks_client
@@ -1407,6 +3259,7 @@ impl TestEnv {
             user_pool_liquidity,
             pool_state: pool_id,
             reward_info: reward_info_key,
+            roles: roles_address(),
             system_program: system_program::ID,
         };
 
@@ -1430,6 +3283,20 @@ impl TestEnv {
         reward_info_key: Pubkey,
         reward_mint: Pubkey,
     ) {
+        self.try_claim_rewards_for(user, user.pubkey(), pool_id, reward_info_key, reward_mint)
+            .await
+            .unwrap();
+    }
+
+    /// Claims the rewards of the owner into the reward token account of the owner
+    pub async fn try_claim_rewards_for(
+        &mut self,
+        signer: &Keypair,
+        owner: Pubkey,
+        pool_id: Pubkey,
+        reward_info_key: Pubkey,
+        reward_mint: Pubkey,
+    ) -> Result<(), BanksClientError> {
         let (authority, __bump) =
             Pubkey::find_program_address(&[AUTH_SEED.as_bytes()], &gamma::id());
 
@@ -1437,7 +3304,7 @@ impl TestEnv {
             &[
                 USER_REWARD_INFO_SEED.as_bytes(),
                 reward_info_key.to_bytes().as_ref(),
-                user.pubkey().to_bytes().as_ref(),
+                owner.to_bytes().as_ref(),
             ],
             &gamma::id(),
         );
@@ -1451,11 +3318,11 @@ impl TestEnv {
         );
 
         let user_token_account = self
-            .get_or_create_associated_token_account(user.pubkey(), reward_mint, user)
+            .get_or_create_associated_token_account(owner, reward_mint, signer)
             .await;
 
         let accounts = gamma::accounts::ClaimRewards {
-            user: user.pubkey(),
+            user: signer.pubkey(),
             user_reward_info: user_reward_info_key,
             authority,
             pool_state: pool_id,
@@ -1470,14 +3337,16 @@ impl TestEnv {
 
         let data = gamma::instruction::ClaimRewards {};
 
-        let transaction = self
-            .encode_instruction_and_sign_transaction(data, accounts, user)
-            .await;
+        let mut instruction = get_instruction(data, accounts);
+        instruction
+            .accounts
+            .extend(position_operator_accounts(pool_id, owner, signer.pubkey()));
+        let transaction =
+            get_signed_transaction(&mut self.program_test_context, &[instruction], signer).await;
 
         self.program_test_context
             .banks_client
             .process_transaction(transaction)
             .await
-            .unwrap();
     }
 }